@@ -91,46 +91,96 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
                     }
                 };
             } else if attr.path.is_ident("persist") {
-                match attr.parse_meta() {
-                    Ok(syn::Meta::NameValue(syn::MetaNameValue {
-                        lit: syn::Lit::Str(s),
-                        ..
-                    })) => {
-                        if processed_attribute {
+                // This can either be a plain `#[persist = "key"]` attribute, or a
+                // `#[persist(key = "key", skip_presets)]` list where `skip_presets` is optional
+                let (key, skip_presets) =
+                    match attr.parse_meta() {
+                        Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                            lit: syn::Lit::Str(s),
+                            ..
+                        })) => (s, false),
+                        Ok(syn::Meta::List(syn::MetaList {
+                            nested: nested_attrs,
+                            ..
+                        })) => {
+                            let mut key: Option<syn::LitStr> = None;
+                            let mut skip_presets = false;
+                            for nested_attr in nested_attrs {
+                                match nested_attr {
+                                    syn::NestedMeta::Meta(syn::Meta::Path(p))
+                                        if p.is_ident("skip_presets") =>
+                                    {
+                                        skip_presets = true;
+                                    }
+                                    syn::NestedMeta::Meta(syn::Meta::NameValue(
+                                        syn::MetaNameValue {
+                                            path,
+                                            lit: syn::Lit::Str(s),
+                                            ..
+                                        },
+                                    )) if path.is_ident("key") => {
+                                        key = Some(s.clone());
+                                    }
+                                    _ => return syn::Error::new(
+                                        nested_attr.span(),
+                                        "Unknown attribute. See the Params trait documentation \
+                                         for more information.",
+                                    )
+                                    .to_compile_error()
+                                    .into(),
+                                }
+                            }
+
+                            match key {
+                                Some(key) => (key, skip_presets),
+                                None => {
+                                    return syn::Error::new(
+                                        attr.span(),
+                                        "The persist attribute is missing a key: \
+                                     #[persist(key = \"foo_bar\", skip_presets)]",
+                                    )
+                                    .to_compile_error()
+                                    .into()
+                                }
+                            }
+                        }
+                        _ => {
                             return syn::Error::new(
                                 attr.span(),
-                                "Duplicate or incompatible attribute found",
+                                "The persist attribute should be a key-value pair with a string \
+                             argument: #[persist = \"foo_bar\"], or a list in the following \
+                             format: #[persist(key = \"foo_bar\", [skip_presets])]",
                             )
                             .to_compile_error()
-                            .into();
+                            .into()
                         }
+                    };
 
-                        if persistent_fields.iter().any(|p| p.key == s) {
-                            return syn::Error::new(
-                                field.span(),
-                                "Multiple persistent fields with the same key found",
-                            )
-                            .to_compile_error()
-                            .into();
-                        }
+                if processed_attribute {
+                    return syn::Error::new(
+                        attr.span(),
+                        "Duplicate or incompatible attribute found",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
 
-                        persistent_fields.push(PersistentField {
-                            key: s,
-                            field: field_name.clone(),
-                        });
+                if persistent_fields.iter().any(|p| p.key == key) {
+                    return syn::Error::new(
+                        field.span(),
+                        "Multiple persistent fields with the same key found",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
 
-                        processed_attribute = true;
-                    }
-                    _ => {
-                        return syn::Error::new(
-                            attr.span(),
-                            "The persist attribute should be a key-value pair with a string \
-                             argument: #[persist = \"foo_bar\"]",
-                        )
-                        .to_compile_error()
-                        .into()
-                    }
-                };
+                persistent_fields.push(PersistentField {
+                    key,
+                    field: field_name.clone(),
+                    skip_presets,
+                });
+
+                processed_attribute = true;
             } else if attr.path.is_ident("nested") {
                 // This one is more complicated. Supports an `array` attribute, an `id_prefix =
                 // "foo"` attribute, and a `group = "group name"` attribute. All are optional, and
@@ -265,6 +315,57 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
         }
     };
 
+    // Fields marked with `skip_presets` are not stored in presets. The same prefixes and suffixes
+    // used for serializing the fields are also applied to the keys from nested objects.
+    let preset_excluded_fields_tokens = {
+        let preset_excluded_self_keys = persistent_fields
+            .iter()
+            .filter(|persistent_field| persistent_field.skip_presets)
+            .map(|persistent_field| &persistent_field.key);
+        let preset_excluded_nested_tokens = params
+            .iter()
+            .filter_map(|p| match p {
+                Param::Single { .. } => None,
+                Param::Nested(nested) => Some(nested),
+            })
+            .map(|nested| match nested {
+                NestedParams::Inline { field, .. } => quote! {
+                    excluded.extend(self.#field.preset_excluded_fields());
+                },
+                NestedParams::Prefixed {
+                    field, id_prefix, ..
+                } => quote! {
+                    excluded.extend(
+                        self.#field
+                            .preset_excluded_fields()
+                            .into_iter()
+                            .map(|key| format!("{}_{}", #id_prefix, key)),
+                    );
+                },
+                NestedParams::Array { field, .. } => quote! {
+                    for (field_idx, field) in self.#field.iter().enumerate() {
+                        let idx = field_idx + 1;
+                        excluded.extend(
+                            field
+                                .preset_excluded_fields()
+                                .into_iter()
+                                .map(|key| format!("{}_{}", key, idx)),
+                        );
+                    }
+                },
+            });
+
+        quote! {
+            #[allow(unused_mut)]
+            let mut excluded = Vec::new();
+            #(excluded.push(String::from(#preset_excluded_self_keys));)*
+
+            #(#preset_excluded_nested_tokens)*
+
+            excluded
+        }
+    };
+
     let (serialize_fields_tokens, deserialize_fields_tokens) = {
         // Like with `param_map()`, we'll try to do the serialization for this struct and then
         // recursively call the child parameter structs. We don't know anything about the actual
@@ -273,7 +374,7 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
         let (serialize_fields_self_tokens, deserialize_fields_match_self_tokens): (Vec<_>, Vec<_>) =
             persistent_fields
                 .into_iter()
-                .map(|PersistentField { field, key }| {
+                .map(|PersistentField { field, key, .. }| {
                     (
                         quote! {
                             match ::nih_plug::params::persist::PersistentField::map(
@@ -425,6 +526,10 @@ pub fn derive_params(input: TokenStream) -> TokenStream {
             fn deserialize_fields(&self, serialized: &::std::collections::BTreeMap<String, String>) {
                 #deserialize_fields_tokens
             }
+
+            fn preset_excluded_fields(&self) -> Vec<String> {
+                #preset_excluded_fields_tokens
+            }
         }
     }
     .into()
@@ -466,6 +571,8 @@ struct PersistentField {
     field: syn::Ident,
     /// The field's unique key.
     key: syn::LitStr,
+    /// Whether the field should be left out when saving or loading presets.
+    skip_presets: bool,
 }
 
 /// A field containing another object whose parameters and persistent fields should be added to this
@@ -15,6 +15,25 @@ struct ArrayWrapperParams {
     pub inners: [InnerParams; 3],
 }
 
+#[derive(Params, Default)]
+struct PresetExcludedParams {
+    #[persist = "kept"]
+    pub kept: Mutex<u32>,
+    #[persist(key = "skipped", skip_presets)]
+    pub skipped: Mutex<u32>,
+
+    #[nested(id_prefix = "foo")]
+    pub inner: PresetExcludedInnerParams,
+    #[nested(array)]
+    pub inners: [PresetExcludedInnerParams; 2],
+}
+
+#[derive(Params, Default)]
+struct PresetExcludedInnerParams {
+    #[persist(key = "bar", skip_presets)]
+    pub bar: Mutex<u32>,
+}
+
 #[derive(Default)]
 struct InnerParams {
     /// The value `deserialize()` has been called with so we can check that the prefix has been
@@ -135,4 +154,29 @@ mod persist {
             }
         }
     }
+
+    mod skip_presets {
+        use super::super::*;
+
+        #[test]
+        fn preset_excluded_fields() {
+            let params = PresetExcludedParams::default();
+
+            assert_eq!(
+                params.preset_excluded_fields(),
+                ["skipped", "foo_bar", "bar_1", "bar_2"]
+            );
+        }
+
+        #[test]
+        fn still_serialized() {
+            let params = PresetExcludedParams::default();
+
+            // Excluding these fields from presets is up to the wrapper
+            let serialized = params.serialize_fields();
+            assert_eq!(serialized.len(), 5);
+            assert!(serialized.contains_key("kept"));
+            assert!(serialized.contains_key("skipped"));
+        }
+    }
 }
@@ -5,7 +5,9 @@ use baseview::{Size, WindowHandle, WindowOpenOptions, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
 use egui::Context;
 use egui_baseview::EguiWindow;
-use nih_plug::prelude::{Editor, GuiContext, ParamSetter, ParentWindowHandle};
+use nih_plug::prelude::{
+    Editor, EditorResizeConstraints, GuiContext, ParamSetter, ParentWindowHandle,
+};
 use parking_lot::RwLock;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -118,6 +120,26 @@ where
     fn param_values_changed(&self) {
         // Same
     }
+
+    fn resize_constraints(&self) -> Option<EditorResizeConstraints> {
+        // TODO: `egui-baseview` does not let us resize an open window yet, so for now the host can
+        //       only change the size while the editor is closed. Hosts should not offer resizing
+        //       an open editor since those requests would be refused anyway.
+        if self.egui_state.is_open() {
+            None
+        } else {
+            self.egui_state.resize_constraints
+        }
+    }
+
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        if self.egui_state.resize_constraints.is_none() || self.egui_state.is_open() {
+            return false;
+        }
+
+        self.egui_state.size.store((width, height));
+        true
+    }
 }
 
 /// The window handle used for [`EguiEditor`].
@@ -8,7 +8,7 @@
 use crossbeam::atomic::AtomicCell;
 use egui::Context;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, ParamSetter};
+use nih_plug::prelude::{Editor, EditorResizeConstraints, ParamSetter};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -66,6 +66,9 @@ pub struct EguiState {
     /// The window's size in logical pixels before applying `scale_factor`.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     size: AtomicCell<(u32, u32)>,
+    /// If set, then the host is allowed to resize the editor within these constraints.
+    #[serde(skip)]
+    resize_constraints: Option<EditorResizeConstraints>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -73,7 +76,11 @@ pub struct EguiState {
 
 impl<'a> PersistentField<'a, EguiState> for Arc<EguiState> {
     fn set(&self, new_value: EguiState) {
-        self.size.store(new_value.size.load());
+        let size = new_value.size.load();
+        self.size.store(match self.resize_constraints {
+            Some(constraints) => constraints.constrain(size),
+            None => size,
+        });
     }
 
     fn map<F, R>(&self, f: F) -> R
@@ -90,6 +97,26 @@ impl EguiState {
     pub fn from_size(width: u32, height: u32) -> Arc<EguiState> {
         Arc::new(EguiState {
             size: AtomicCell::new((width, height)),
+            resize_constraints: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`from_size()`][Self::from_size()], but this also allows the host to resize the
+    /// editor within `constraints`. The new size is stored in this state object, so it will be
+    /// persisted if this object is stored in a `#[persist = "key"]` field.
+    ///
+    /// The window cannot yet be resized while it is open, so the host can only change the editor's
+    /// size before the editor gets opened. Most hosts do this to restore the last editor size. The
+    /// editor is not reported as resizable while it is open.
+    pub fn from_size_resizable(
+        width: u32,
+        height: u32,
+        constraints: EditorResizeConstraints,
+    ) -> Arc<EguiState> {
+        Arc::new(EguiState {
+            size: AtomicCell::new(constraints.constrain((width, height))),
+            resize_constraints: Some(constraints),
             open: AtomicBool::new(false),
         })
     }
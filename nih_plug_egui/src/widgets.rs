@@ -7,6 +7,8 @@
 
 pub mod generic_ui;
 mod param_slider;
+mod snapshot_switcher;
 pub mod util;
 
 pub use param_slider::ParamSlider;
+pub use snapshot_switcher::SnapshotSwitcher;
@@ -57,7 +57,19 @@ pub fn create(
                 }
 
                 ui.label(unsafe { param_ptr.name() });
-                unsafe { widget.add_widget_raw(ui, &param_ptr, setter) };
+                if flags.contains(ParamFlags::OUTPUT) {
+                    // Output parameters are set by the plugin, so these are drawn as meters
+                    let normalized_value = unsafe { param_ptr.unmodulated_normalized_value() };
+                    ui.add(
+                        egui::ProgressBar::new(normalized_value)
+                            .desired_width(100.0)
+                            .text(unsafe {
+                                param_ptr.normalized_value_to_string(normalized_value, true)
+                            }),
+                    );
+                } else {
+                    unsafe { widget.add_widget_raw(ui, &param_ptr, setter) };
+                }
 
                 first_widget = false;
             }
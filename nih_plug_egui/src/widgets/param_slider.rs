@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use egui::{vec2, Key, Response, Sense, Stroke, TextEdit, TextStyle, Ui, Vec2, Widget, WidgetText};
+use egui::{
+    vec2, Key, Rect, Response, Sense, Stroke, TextEdit, TextStyle, Ui, Vec2, Widget, WidgetText,
+};
 use lazy_static::lazy_static;
 use nih_plug::prelude::{Param, ParamSetter};
 use parking_lot::Mutex;
@@ -19,7 +21,8 @@ lazy_static! {
 
 /// A slider widget similar to [`egui::widgets::Slider`] that knows about NIH-plug parameters ranges
 /// and can get values for it. The slider supports double click and control click to reset,
-/// shift+drag for granular dragging, text value entry by clicking on the value text.
+/// shift+drag for granular dragging, text value entry by clicking on the value text. Right clicking
+/// the slider opens a context menu for MIDI learn.
 ///
 /// TODO: Vertical orientation
 /// TODO: Check below for more input methods that should be added
@@ -32,6 +35,7 @@ pub struct ParamSlider<'a, P: Param> {
     setter: &'a ParamSetter<'a>,
 
     draw_value: bool,
+    draw_indication: bool,
     slider_width: Option<f32>,
 
     /// Will be set in the `ui()` function so we can request keyboard input focus on Alt+click.
@@ -47,6 +51,7 @@ impl<'a, P: Param> ParamSlider<'a, P> {
             setter,
 
             draw_value: true,
+            draw_indication: false,
             slider_width: None,
 
             keyboard_focus_id: None,
@@ -59,6 +64,15 @@ impl<'a, P: Param> ParamSlider<'a, P> {
         self
     }
 
+    /// Draw the host's indication for the parameter on the slider. Parameters that are mapped to a
+    /// hardware controller get a border in the mapping's color, and automated parameters get a
+    /// small marker in the top left corner colored according to the automation state. See
+    /// [`Param::indication()`].
+    pub fn with_indication(mut self) -> Self {
+        self.draw_indication = true;
+        self
+    }
+
     /// Set a custom width for the slider.
     pub fn with_width(mut self, width: f32) -> Self {
         self.slider_width = Some(width);
@@ -174,6 +188,28 @@ impl<'a, P: Param> ParamSlider<'a, P> {
         ui.memory().data.insert_temp(*DRAG_AMOUNT_MEMORY_ID, amount);
     }
 
+    /// Add a context menu with MIDI learn options to the slider.
+    fn midi_learn_menu(&self, response: Response) -> Response {
+        response.context_menu(|ui| {
+            if self.setter.is_midi_learning(self.param) {
+                if ui.button("Cancel MIDI learn").clicked() {
+                    self.setter.cancel_midi_learn();
+                    ui.close_menu();
+                }
+            } else if ui.button("MIDI learn").clicked() {
+                self.setter.start_midi_learn(self.param);
+                ui.close_menu();
+            }
+
+            if self.setter.midi_mapping(self.param).is_some()
+                && ui.button("Forget MIDI mapping").clicked()
+            {
+                self.setter.forget_midi_mapping(self.param);
+                ui.close_menu();
+            }
+        })
+    }
+
     fn slider_ui(&self, ui: &mut Ui, response: &mut Response) {
         // Handle user input
         // TODO: Optionally (since it can be annoying) add scrolling behind a builder option
@@ -238,6 +274,28 @@ impl<'a, P: Param> ParamSlider<'a, P> {
                 0.0,
                 Stroke::new(1.0, ui.visuals().widgets.active.bg_fill),
             );
+
+            if self.draw_indication {
+                self.indication_ui(ui, response.rect);
+            }
+        }
+    }
+
+    fn indication_ui(&self, ui: &mut Ui, rect: Rect) {
+        let indication = self.param.indication();
+        if indication.mapped {
+            let mapping_color = indication
+                .mapping_color
+                .map_or(ui.visuals().selection.stroke.color, util::indication_color);
+            ui.painter()
+                .rect_stroke(rect, 0.0, Stroke::new(2.0, mapping_color));
+        }
+
+        if let Some(automation_color) =
+            util::automation_color(indication.automation, indication.automation_color)
+        {
+            let marker_rect = Rect::from_min_size(rect.min, Vec2::splat(rect.height() / 3.0));
+            ui.painter().rect_filled(marker_rect, 0.0, automation_color);
         }
     }
 
@@ -339,6 +397,7 @@ impl<P: Param> Widget for ParamSlider<'_, P> {
                 .inner;
 
             self.slider_ui(ui, &mut response);
+            let response = self.midi_learn_menu(response);
             if self.draw_value {
                 self.value_ui(ui);
             }
@@ -0,0 +1,54 @@
+use egui::{Response, Ui, Widget};
+use nih_plug::prelude::{ParamSetter, Params, Snapshots};
+
+/// A row of toggle buttons for switching between a [`Snapshots`] object's slots, for A/B
+/// comparisons. Clicking a slot stores the current parameter values in the active slot and recalls
+/// the clicked slot's values through the [`ParamSetter`], so the host sees the changes as regular
+/// parameter gestures.
+#[must_use = "You should put this widget in an ui with `ui.add(widget);`"]
+pub struct SnapshotSwitcher<'a> {
+    snapshots: &'a Snapshots,
+    params: &'a dyn Params,
+    setter: &'a ParamSetter<'a>,
+}
+
+impl<'a> SnapshotSwitcher<'a> {
+    /// Create a switcher for the slots in `snapshots`. `params` should be the plugin's parameters
+    /// object.
+    pub fn new(
+        snapshots: &'a Snapshots,
+        params: &'a dyn Params,
+        setter: &'a ParamSetter<'a>,
+    ) -> Self {
+        Self {
+            snapshots,
+            params,
+            setter,
+        }
+    }
+}
+
+impl Widget for SnapshotSwitcher<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.horizontal(|ui| {
+            let active_slot = self.snapshots.active_slot();
+            let mut response: Option<Response> = None;
+            for idx in 0..self.snapshots.num_slots() {
+                let slot_response =
+                    ui.selectable_label(idx == active_slot, Snapshots::slot_name(idx));
+                if slot_response.clicked() && idx != active_slot {
+                    self.snapshots.select(idx, self.params).apply(self.setter);
+                }
+
+                response = Some(match response {
+                    Some(response) => response | slot_response,
+                    None => slot_response,
+                });
+            }
+
+            // There's always at least one slot
+            response.unwrap()
+        })
+        .inner
+    }
+}
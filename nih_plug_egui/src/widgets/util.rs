@@ -1,6 +1,7 @@
 //! Utilities for creating these widgets.
 
 use egui::Color32;
+use nih_plug::prelude::{AutomationState, IndicationColor};
 
 /// Additively modify the hue, saturation, and lightness [0, 1] values of a color.
 pub fn add_hsv(color: Color32, h: f32, s: f32, v: f32) -> Color32 {
@@ -19,3 +20,26 @@ pub fn scale_hsv(color: Color32, h: f32, s: f32, v: f32) -> Color32 {
     hsv.v *= v;
     hsv.into()
 }
+
+/// Convert a color from a parameter's [`ParamIndication`][nih_plug::prelude::ParamIndication] to
+/// an egui color.
+pub fn indication_color(color: IndicationColor) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.red, color.green, color.blue, color.alpha)
+}
+
+/// The color used to draw a parameter's automation state. This uses the host's color if it
+/// provided one, and `None` is returned when the parameter isn't automated.
+pub fn automation_color(
+    automation: AutomationState,
+    host_color: Option<IndicationColor>,
+) -> Option<Color32> {
+    let default_color = match automation {
+        AutomationState::None => return None,
+        AutomationState::Present => Color32::from_rgb(128, 128, 128),
+        AutomationState::Playing => Color32::from_rgb(64, 192, 64),
+        AutomationState::Recording => Color32::from_rgb(224, 48, 48),
+        AutomationState::Overriding => Color32::from_rgb(232, 160, 32),
+    };
+
+    Some(host_color.map_or(default_color, indication_color))
+}
@@ -4,7 +4,7 @@ use baseview::{WindowOpenOptions, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
 pub use iced_baseview::*;
-use nih_plug::prelude::{Editor, GuiContext, ParentWindowHandle};
+use nih_plug::prelude::{Editor, EditorResizeConstraints, GuiContext, ParentWindowHandle};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
@@ -122,6 +122,34 @@ impl<E: IcedEditor> Editor for IcedEditorWrapper<E> {
     fn param_values_changed(&self) {
         let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
     }
+
+    fn param_indication_changed(&self, _id: &str) {
+        let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
+    }
+
+    fn track_info_changed(&self) {
+        let _ = self.parameter_updates_sender.try_send(ParameterUpdate);
+    }
+
+    fn resize_constraints(&self) -> Option<EditorResizeConstraints> {
+        // TODO: `iced_baseview` does not let us resize an open window yet, so for now the host can
+        //       only change the size while the editor is closed. Hosts should not offer resizing
+        //       an open editor since those requests would be refused anyway.
+        if self.iced_state.is_open() {
+            None
+        } else {
+            self.iced_state.resize_constraints
+        }
+    }
+
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        if self.iced_state.resize_constraints.is_none() || self.iced_state.is_open() {
+            return false;
+        }
+
+        self.iced_state.size.store((width, height));
+        true
+    }
 }
 
 /// The window handle used for [`IcedEditorWrapper`].
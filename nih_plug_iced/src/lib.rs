@@ -93,7 +93,7 @@ use baseview::WindowScalePolicy;
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, GuiContext};
+use nih_plug::prelude::{Editor, EditorResizeConstraints, GuiContext};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -228,6 +228,9 @@ pub trait IcedEditor: 'static + Send + Sync + Sized {
                 context.raw_set_parameter_normalized(p, v)
             },
             ParamMessage::EndSetParameter(p) => unsafe { context.raw_end_set_parameter(p) },
+            ParamMessage::StartMidiLearn(p) => unsafe { context.raw_start_midi_learn(p) },
+            ParamMessage::CancelMidiLearn => context.cancel_midi_learn(),
+            ParamMessage::ForgetMidiMapping(p) => unsafe { context.raw_set_midi_mapping(p, None) },
         }
     }
 }
@@ -238,6 +241,9 @@ pub struct IcedState {
     /// The window's size in logical pixels before applying `scale_factor`.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     size: AtomicCell<(u32, u32)>,
+    /// If set, then the host is allowed to resize the editor within these constraints.
+    #[serde(skip)]
+    resize_constraints: Option<EditorResizeConstraints>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -245,7 +251,11 @@ pub struct IcedState {
 
 impl<'a> PersistentField<'a, IcedState> for Arc<IcedState> {
     fn set(&self, new_value: IcedState) {
-        self.size.store(new_value.size.load());
+        let size = new_value.size.load();
+        self.size.store(match self.resize_constraints {
+            Some(constraints) => constraints.constrain(size),
+            None => size,
+        });
     }
 
     fn map<F, R>(&self, f: F) -> R
@@ -262,6 +272,26 @@ impl IcedState {
     pub fn from_size(width: u32, height: u32) -> Arc<IcedState> {
         Arc::new(IcedState {
             size: AtomicCell::new((width, height)),
+            resize_constraints: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`from_size()`][Self::from_size()], but this also allows the host to resize the
+    /// editor within `constraints`. The new size is stored in this state object, so it will be
+    /// persisted if this object is stored in a `#[persist = "key"]` field.
+    ///
+    /// The window cannot yet be resized while it is open, so the host can only change the editor's
+    /// size before the editor gets opened. Most hosts do this to restore the last editor size. The
+    /// editor is not reported as resizable while it is open.
+    pub fn from_size_resizable(
+        width: u32,
+        height: u32,
+        constraints: EditorResizeConstraints,
+    ) -> Arc<IcedState> {
+        Arc::new(IcedState {
+            size: AtomicCell::new(constraints.constrain((width, height))),
+            resize_constraints: Some(constraints),
             open: AtomicBool::new(false),
         })
     }
@@ -10,10 +10,12 @@ use nih_plug::prelude::ParamPtr;
 pub mod generic_ui;
 pub mod param_slider;
 pub mod peak_meter;
+pub mod snapshot_switcher;
 pub mod util;
 
 pub use param_slider::ParamSlider;
 pub use peak_meter::PeakMeter;
+pub use snapshot_switcher::SnapshotSwitcher;
 
 /// A message to update a parameter value. Since NIH-plug manages the parameters, interacting with
 /// parameter values with iced works a little different from updating any other state. This main
@@ -29,4 +31,10 @@ pub enum ParamMessage {
     SetParameterNormalized(ParamPtr, f32),
     /// End an automation gesture for a parameter.
     EndSetParameter(ParamPtr),
+    /// Map the next incoming MIDI controller to this parameter.
+    StartMidiLearn(ParamPtr),
+    /// Stop waiting for a MIDI controller to learn.
+    CancelMidiLearn,
+    /// Remove the parameter's MIDI mapping.
+    ForgetMidiMapping(ParamPtr),
 }
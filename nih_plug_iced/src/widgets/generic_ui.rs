@@ -14,7 +14,7 @@ use crate::backend::Renderer;
 use crate::text::Renderer as TextRenderer;
 use crate::{
     alignment, event, layout, renderer, widget, Alignment, Clipboard, Element, Event, Layout,
-    Length, Point, Rectangle, Row, Scrollable, Shell, Space, Text, Widget,
+    Length, Point, ProgressBar, Rectangle, Row, Scrollable, Shell, Space, Text, Widget,
 };
 
 /// A widget that can be used to create a generic UI with. This is used in conjuction with empty
@@ -182,6 +182,18 @@ where
             let widget_state: &'a mut W::State =
                 unsafe { &mut *(widget_state.get_mut(&param_ptr).unwrap() as *mut _) };
 
+            // Output parameters are set by the plugin, so these are drawn as meters
+            let param_widget: Element<'a, ParamMessage> = if flags.contains(ParamFlags::OUTPUT) {
+                ProgressBar::new(0.0..=1.0, unsafe {
+                    param_ptr.unmodulated_normalized_value()
+                })
+                .width(Length::Units(180))
+                .height(Length::Units(30))
+                .into()
+            } else {
+                unsafe { W::into_widget_element_raw(&param_ptr, widget_state) }
+            };
+
             // Show the label next to the parameter for better use of the space
             let mut row = Row::new()
                 .width(Length::Fill)
@@ -194,7 +206,7 @@ where
                         .horizontal_alignment(alignment::Horizontal::Right)
                         .vertical_alignment(alignment::Vertical::Center),
                 )
-                .push(unsafe { W::into_widget_element_raw(&param_ptr, widget_state) });
+                .push(param_widget);
             if self.pad_scrollbar {
                 // There's already spacing applied, so this element doesn't actually need to hae any
                 // size of its own
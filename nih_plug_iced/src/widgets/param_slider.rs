@@ -22,6 +22,8 @@ const GRANULAR_DRAG_MULTIPLIER: f32 = 0.1;
 
 /// The thickness of this widget's borders.
 const BORDER_WIDTH: f32 = 1.0;
+/// The thickness of the border drawn when the parameter is mapped to a controller in the host.
+const MAPPING_BORDER_WIDTH: f32 = 2.0;
 
 /// A slider that integrates with NIH-plug's [`Param`] types.
 ///
@@ -36,6 +38,7 @@ pub struct ParamSlider<'a, P: Param> {
     width: Length,
     text_size: Option<u16>,
     font: Font,
+    draw_indication: bool,
 }
 
 /// State for a [`ParamSlider`].
@@ -109,6 +112,7 @@ impl<'a, P: Param> ParamSlider<'a, P> {
             height: Length::Units(30),
             text_size: None,
             font: <Renderer as TextRenderer>::Font::default(),
+            draw_indication: false,
         }
     }
 
@@ -136,6 +140,15 @@ impl<'a, P: Param> ParamSlider<'a, P> {
         self
     }
 
+    /// Sets whether the [`ParamSlider`] should draw the host's indication for the parameter.
+    /// Parameters that are mapped to a hardware controller get a border in the mapping's color,
+    /// and automated parameters get a small marker in the top left corner colored according to the
+    /// automation state. See [`Param::indication()`].
+    pub fn draw_indication(mut self, draw_indication: bool) -> Self {
+        self.draw_indication = draw_indication;
+        self
+    }
+
     /// Create a temporary [`TextInput`] hooked up to [`State::text_input_value`] and outputting
     /// [`TextInputMessage`] messages and do something with it. This can be used to
     fn with_text_input<T, R, F>(&self, layout: Layout, renderer: R, current_value: &str, f: F) -> T
@@ -545,6 +558,42 @@ impl<'a, P: Param> Widget<ParamMessage, Renderer> for ParamSlider<'a, P> {
                 });
             });
         }
+
+        if self.draw_indication {
+            let indication = self.param.indication();
+            if indication.mapped {
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds,
+                        border_color: indication
+                            .mapping_color
+                            .map_or(Color::from_rgb8(80, 140, 220), util::indication_color),
+                        border_width: MAPPING_BORDER_WIDTH,
+                        border_radius: 0.0,
+                    },
+                    Color::TRANSPARENT,
+                );
+            }
+
+            if let Some(automation_color) =
+                util::automation_color(indication.automation, indication.automation_color)
+            {
+                let marker_size = bounds.height / 3.0;
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: Rectangle {
+                            width: marker_size,
+                            height: marker_size,
+                            ..bounds
+                        },
+                        border_color: Color::TRANSPARENT,
+                        border_width: 0.0,
+                        border_radius: 0.0,
+                    },
+                    automation_color,
+                );
+            }
+        }
     }
 }
 
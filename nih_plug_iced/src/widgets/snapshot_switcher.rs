@@ -0,0 +1,233 @@
+//! A row of toggle buttons for switching between snapshot slots.
+
+use nih_plug::prelude::{Params, Snapshots};
+
+use crate::backend::Renderer;
+use crate::renderer::Renderer as GraphicsRenderer;
+use crate::text::Renderer as TextRenderer;
+use crate::{
+    alignment, event, layout, mouse, renderer, text, touch, Clipboard, Color, Element, Event, Font,
+    Layout, Length, Point, Rectangle, Shell, Size, Widget,
+};
+
+use super::ParamMessage;
+
+/// The thickness of this widget's borders.
+const BORDER_WIDTH: f32 = 1.0;
+
+/// A row of toggle buttons for switching between a [`Snapshots`] object's slots, for A/B
+/// comparisons. Clicking a slot stores the current parameter values in the active slot and recalls
+/// the clicked slot's values as regular parameter gestures.
+///
+/// TODO: There are currently no styling options at all
+pub struct SnapshotSwitcher<'a> {
+    snapshots: &'a Snapshots,
+    params: &'a dyn Params,
+
+    height: Length,
+    width: Length,
+    text_size: Option<u16>,
+    font: Font,
+}
+
+impl<'a> SnapshotSwitcher<'a> {
+    /// Creates a new [`SnapshotSwitcher`] for the slots in `snapshots`. `params` should be the
+    /// plugin's parameters object.
+    pub fn new(snapshots: &'a Snapshots, params: &'a dyn Params) -> Self {
+        Self {
+            snapshots,
+            params,
+
+            width: Length::Units(30 * snapshots.num_slots() as u16),
+            height: Length::Units(30),
+            text_size: None,
+            font: <Renderer as TextRenderer>::Font::default(),
+        }
+    }
+
+    /// Sets the width of the [`SnapshotSwitcher`].
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the height of the [`SnapshotSwitcher`].
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the text size of the [`SnapshotSwitcher`].
+    pub fn text_size(mut self, size: u16) -> Self {
+        self.text_size = Some(size);
+        self
+    }
+
+    /// Sets the font of the [`SnapshotSwitcher`].
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// The bounds of slot `idx` within the widget's bounds.
+    fn slot_bounds(&self, bounds: Rectangle, idx: usize) -> Rectangle {
+        let slot_width = bounds.width / self.snapshots.num_slots() as f32;
+
+        Rectangle {
+            x: bounds.x + (slot_width * idx as f32),
+            width: slot_width,
+            ..bounds
+        }
+    }
+}
+
+impl<'a> Widget<ParamMessage, Renderer> for SnapshotSwitcher<'a> {
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(&self, _renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        let limits = limits.width(self.width).height(self.height);
+        let size = limits.resolve(Size::ZERO);
+
+        layout::Node::new(size)
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, ParamMessage>,
+    ) -> event::Status {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+            | Event::Touch(touch::Event::FingerPressed { .. }) => {
+                let bounds = layout.bounds();
+                let clicked_slot = (0..self.snapshots.num_slots())
+                    .find(|idx| self.slot_bounds(bounds, *idx).contains(cursor_position));
+                match clicked_slot {
+                    Some(idx) => {
+                        if idx != self.snapshots.active_slot() {
+                            // All changes are part of a single gesture so they can be undone at
+                            // once
+                            let changes = self.snapshots.select(idx, self.params);
+                            for (param_ptr, _) in changes.iter() {
+                                shell.publish(ParamMessage::BeginSetParameter(param_ptr));
+                            }
+                            for (param_ptr, normalized) in changes.iter() {
+                                shell.publish(ParamMessage::SetParameterNormalized(
+                                    param_ptr, normalized,
+                                ));
+                            }
+                            for (param_ptr, _) in changes.iter() {
+                                shell.publish(ParamMessage::EndSetParameter(param_ptr));
+                            }
+                        }
+
+                        event::Status::Captured
+                    }
+                    None => event::Status::Ignored,
+                }
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let bounds = layout.bounds();
+        let is_mouse_over = bounds.contains(cursor_position);
+
+        if is_mouse_over {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let active_slot = self.snapshots.active_slot();
+        let text_size = self.text_size.unwrap_or_else(|| renderer.default_size()) as f32;
+        for idx in 0..self.snapshots.num_slots() {
+            let slot_bounds = self.slot_bounds(bounds, idx);
+            let is_mouse_over = slot_bounds.contains(cursor_position);
+
+            // The active slot is filled, and other slots show a different background color when the
+            // mouse is hovering over them to indicate that they're interactive
+            let (background_color, text_color) = if idx == active_slot {
+                (
+                    Color::from_rgb8(196, 196, 196),
+                    Color::from_rgb8(80, 80, 80),
+                )
+            } else if is_mouse_over {
+                (Color::new(0.5, 0.5, 0.5, 0.1), style.text_color)
+            } else {
+                (Color::TRANSPARENT, style.text_color)
+            };
+
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: slot_bounds,
+                    border_color: Color::BLACK,
+                    border_width: BORDER_WIDTH,
+                    border_radius: 0.0,
+                },
+                background_color,
+            );
+
+            renderer.fill_text(text::Text {
+                content: &Snapshots::slot_name(idx),
+                font: self.font,
+                size: text_size,
+                bounds: Rectangle {
+                    x: slot_bounds.center_x(),
+                    y: slot_bounds.center_y(),
+                    ..slot_bounds
+                },
+                color: text_color,
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Center,
+            });
+        }
+    }
+}
+
+impl<'a> SnapshotSwitcher<'a> {
+    /// Convert this [`SnapshotSwitcher`] into an [`Element`] with the correct message. You should
+    /// have a variant on your own message type that wraps around [`ParamMessage`] so you can
+    /// forward those messages to
+    /// [`IcedEditor::handle_param_message()`][crate::IcedEditor::handle_param_message()].
+    pub fn map<Message, F>(self, f: F) -> Element<'a, Message>
+    where
+        Message: 'static,
+        F: Fn(ParamMessage) -> Message + 'static,
+    {
+        Element::from(self).map(f)
+    }
+}
+
+impl<'a> From<SnapshotSwitcher<'a>> for Element<'a, ParamMessage> {
+    fn from(widget: SnapshotSwitcher<'a>) -> Self {
+        Element::new(widget)
+    }
+}
@@ -1,6 +1,8 @@
 //! Utilities for creating these widgets.
 
-use crate::Rectangle;
+use nih_plug::prelude::{AutomationState, IndicationColor};
+
+use crate::{Color, Rectangle};
 
 /// Remap a `[0, 1]` value to an x-coordinate within this rectangle. The value will be clamped to
 /// `[0, 1]` if it isn't already in that range.
@@ -25,3 +27,31 @@ pub fn remap_rect_x_coordinate(rect: &Rectangle, x_coord: f32) -> f32 {
 pub fn remap_rect_y_coordinate(rect: &Rectangle, y_coord: f32) -> f32 {
     ((y_coord - rect.y) / rect.height).clamp(0.0, 1.0)
 }
+
+/// Convert a color from a parameter's [`ParamIndication`][nih_plug::prelude::ParamIndication] to
+/// an iced color.
+pub fn indication_color(color: IndicationColor) -> Color {
+    Color::from_rgba8(
+        color.red,
+        color.green,
+        color.blue,
+        color.alpha as f32 / 255.0,
+    )
+}
+
+/// The color used to draw a parameter's automation state. This uses the host's color if it
+/// provided one, and `None` is returned when the parameter isn't automated.
+pub fn automation_color(
+    automation: AutomationState,
+    host_color: Option<IndicationColor>,
+) -> Option<Color> {
+    let default_color = match automation {
+        AutomationState::None => return None,
+        AutomationState::Present => Color::from_rgb8(128, 128, 128),
+        AutomationState::Playing => Color::from_rgb8(64, 192, 64),
+        AutomationState::Recording => Color::from_rgb8(224, 48, 48),
+        AutomationState::Overriding => Color::from_rgb8(232, 160, 32),
+    };
+
+    Some(host_color.map_or(default_color, indication_color))
+}
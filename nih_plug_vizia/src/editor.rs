@@ -2,13 +2,13 @@
 
 use baseview::{WindowHandle, WindowScalePolicy};
 use crossbeam::atomic::AtomicCell;
-use nih_plug::prelude::{Editor, GuiContext, ParentWindowHandle};
+use nih_plug::prelude::{Editor, EditorResizeConstraints, GuiContext, ParentWindowHandle};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use vizia::context::backend::TextConfig;
 use vizia::prelude::*;
 
-use crate::widgets::RawParamEvent;
+use crate::widgets::{RawParamEvent, WindowModelEvent};
 use crate::{assets, widgets, ViziaState, ViziaTheming};
 
 /// An [`Editor`] implementation that calls an vizia draw loop.
@@ -29,6 +29,9 @@ pub(crate) struct ViziaEditor {
     /// to compute a property in an event handler. Like when positioning an element based on the
     /// display value's width.
     pub(crate) emit_parameters_changed_event: Arc<AtomicBool>,
+    /// A new user scale factor set when the host resizes the editor while it is open. This is
+    /// applied to the window during the next idle callback.
+    pub(crate) pending_user_scale_factor: Arc<AtomicCell<Option<f64>>>,
 }
 
 impl Editor for ViziaEditor {
@@ -44,6 +47,8 @@ impl Editor for ViziaEditor {
         let (unscaled_width, unscaled_height) = vizia_state.inner_logical_size();
         let system_scaling_factor = self.scaling_factor.load();
         let user_scale_factor = vizia_state.user_scale_factor();
+        // This scale factor is already included in `user_scale_factor`
+        self.pending_user_scale_factor.store(None);
 
         let mut application = Application::new(move |cx| {
             // Set some default styles to match the iced integration
@@ -93,6 +98,7 @@ impl Editor for ViziaEditor {
         })
         .on_idle({
             let emit_parameters_changed_event = self.emit_parameters_changed_event.clone();
+            let pending_user_scale_factor = self.pending_user_scale_factor.clone();
             move |cx| {
                 if emit_parameters_changed_event
                     .compare_exchange(true, false, Ordering::AcqRel, Ordering::Relaxed)
@@ -103,6 +109,13 @@ impl Editor for ViziaEditor {
                             .propagate(Propagation::Subtree),
                     );
                 }
+
+                if let Some(scale_factor) = pending_user_scale_factor.take() {
+                    cx.emit_custom(
+                        Event::new(WindowModelEvent::SetUserScaleFactor(scale_factor))
+                            .propagate(Propagation::Subtree),
+                    );
+                }
             }
         });
 
@@ -156,6 +169,55 @@ impl Editor for ViziaEditor {
         self.emit_parameters_changed_event
             .store(true, Ordering::Relaxed);
     }
+
+    fn param_indication_changed(&self, _id: &str) {
+        self.emit_parameters_changed_event
+            .store(true, Ordering::Relaxed);
+    }
+
+    fn track_info_changed(&self) {
+        self.emit_parameters_changed_event
+            .store(true, Ordering::Relaxed);
+    }
+
+    fn resize_constraints(&self) -> Option<EditorResizeConstraints> {
+        let scale_factor_range = self.vizia_state.host_resize_scale_factor_range.as_ref()?;
+        let (inner_width, inner_height) = self.vizia_state.inner_logical_size();
+        let scale_size = |scale_factor: f64| {
+            (
+                (inner_width as f64 * scale_factor).round() as u32,
+                (inner_height as f64 * scale_factor).round() as u32,
+            )
+        };
+
+        // The editor's contents can only be scaled uniformly
+        Some(EditorResizeConstraints {
+            min_size: scale_size(*scale_factor_range.start()),
+            max_size: Some(scale_size(*scale_factor_range.end())),
+            aspect_ratio: Some((inner_width, inner_height)),
+        })
+    }
+
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        let scale_factor_range = match &self.vizia_state.host_resize_scale_factor_range {
+            Some(range) => range,
+            None => return false,
+        };
+        let (inner_width, inner_height) = self.vizia_state.inner_logical_size();
+        if inner_width == 0 || inner_height == 0 {
+            return false;
+        }
+
+        let scale_factor = (width as f64 / inner_width as f64)
+            .min(height as f64 / inner_height as f64)
+            .clamp(*scale_factor_range.start(), *scale_factor_range.end());
+        self.vizia_state.scale_factor.store(scale_factor);
+        if self.vizia_state.is_open() {
+            self.pending_user_scale_factor.store(Some(scale_factor));
+        }
+
+        true
+    }
 }
 
 /// The window handle used for [`ViziaEditor`].
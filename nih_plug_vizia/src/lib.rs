@@ -8,6 +8,7 @@ use nih_plug::params::persist::PersistentField;
 use nih_plug::prelude::{Editor, GuiContext};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use vizia::prelude::*;
@@ -62,6 +63,7 @@ where
         scaling_factor: AtomicCell::new(Some(1.0)),
 
         emit_parameters_changed_event: Arc::new(AtomicBool::new(false)),
+        pending_user_scale_factor: Arc::new(AtomicCell::new(None)),
     }))
 }
 
@@ -94,6 +96,10 @@ pub struct ViziaState {
     /// This can be used to allow GUIs to be scaled uniformly.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
     scale_factor: AtomicCell<f64>,
+    /// If set, then the host is allowed to resize the editor. Since the editor's size is computed
+    /// by `size_fn`, this is done by changing the user scale factor within this range.
+    #[serde(skip)]
+    host_resize_scale_factor_range: Option<RangeInclusive<f64>>,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
@@ -111,6 +117,10 @@ impl Debug for ViziaState {
         f.debug_struct("ViziaState")
             .field("size_fn", &format!("<fn> ({}, {})", width, height))
             .field("scale_factor", &self.scale_factor)
+            .field(
+                "host_resize_scale_factor_range",
+                &self.host_resize_scale_factor_range,
+            )
             .field("open", &self.open)
             .finish()
     }
@@ -138,6 +148,7 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(1.0),
+            host_resize_scale_factor_range: None,
             open: AtomicBool::new(false),
         })
     }
@@ -152,6 +163,25 @@ impl ViziaState {
         Arc::new(ViziaState {
             size_fn: Box::new(size_fn),
             scale_factor: AtomicCell::new(default_scale_factor),
+            host_resize_scale_factor_range: None,
+            open: AtomicBool::new(false),
+        })
+    }
+
+    /// The same as [`new_with_default_scale_factor()`][Self::new_with_default_scale_factor()], but
+    /// this also allows the host to resize the editor. The editor keeps the aspect ratio of the
+    /// size returned by `size_fn`, and resizing it changes the user scale factor to a value within
+    /// `scale_factor_range`. Like when the user scale factor is changed from within the GUI, the
+    /// new scale factor is stored in this state object.
+    pub fn new_resizable(
+        size_fn: impl Fn() -> (u32, u32) + Send + Sync + 'static,
+        default_scale_factor: f64,
+        scale_factor_range: RangeInclusive<f64>,
+    ) -> Arc<ViziaState> {
+        Arc::new(ViziaState {
+            size_fn: Box::new(size_fn),
+            scale_factor: AtomicCell::new(default_scale_factor),
+            host_resize_scale_factor_range: Some(scale_factor_range),
             open: AtomicBool::new(false),
         })
     }
@@ -19,6 +19,7 @@ mod param_button;
 mod param_slider;
 mod peak_meter;
 mod resize_handle;
+mod snapshot_switcher;
 pub mod util;
 
 pub use generic_ui::GenericUi;
@@ -26,6 +27,7 @@ pub use param_button::{ParamButton, ParamButtonExt};
 pub use param_slider::{ParamSlider, ParamSliderExt, ParamSliderStyle};
 pub use peak_meter::PeakMeter;
 pub use resize_handle::ResizeHandle;
+pub use snapshot_switcher::SnapshotSwitcher;
 
 /// Register the default theme for the widgets exported by this module. This is automatically called
 /// for you when using [`create_vizia_editor()`][super::create_vizia_editor()].
@@ -51,6 +53,13 @@ pub enum ParamEvent<'a, P: Param> {
     SetParameterNormalized(&'a P, f32),
     /// End an automation gesture for a parameter.
     EndSetParameter(&'a P),
+    /// Map the next incoming MIDI controller to this parameter. See
+    /// [`ParamSetter::start_midi_learn()`][nih_plug::prelude::ParamSetter::start_midi_learn()].
+    StartMidiLearn(&'a P),
+    /// Stop waiting for a MIDI controller to learn.
+    CancelMidiLearn,
+    /// Remove the parameter's MIDI mapping.
+    ForgetMidiMapping(&'a P),
 }
 
 /// The same as [`ParamEvent`], but type erased.
@@ -63,11 +72,25 @@ pub enum RawParamEvent {
     SetParameterNormalized(ParamPtr, f32),
     /// End an automation gesture for a parameter.
     EndSetParameter(ParamPtr),
+    /// Map the next incoming MIDI controller to this parameter.
+    StartMidiLearn(ParamPtr),
+    /// Stop waiting for a MIDI controller to learn.
+    CancelMidiLearn,
+    /// Remove the parameter's MIDI mapping.
+    ForgetMidiMapping(ParamPtr),
     /// Sent by the wrapper to indicate that one or more parameter values have changed. Useful when
     /// using properties based on a parameter's value that are computed inside of an event handler.
     ParametersChanged,
 }
 
+/// Events sent by the editor to the [`WindowModel`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum WindowModelEvent {
+    /// The host has resized the editor. The new user scale factor has already been stored in the
+    /// `ViziaState`, and it still needs to be applied to the window.
+    SetUserScaleFactor(f64),
+}
+
 /// Handles parameter updates for VIZIA GUIs. Registered in
 /// [`ViziaEditor::spawn()`][super::ViziaEditor::spawn()].
 pub(crate) struct ParamModel {
@@ -98,6 +121,11 @@ impl Model for ParamModel {
                 self.context.raw_set_parameter_normalized(p, v)
             },
             RawParamEvent::EndSetParameter(p) => unsafe { self.context.raw_end_set_parameter(p) },
+            RawParamEvent::StartMidiLearn(p) => unsafe { self.context.raw_start_midi_learn(p) },
+            RawParamEvent::CancelMidiLearn => self.context.cancel_midi_learn(),
+            RawParamEvent::ForgetMidiMapping(p) => unsafe {
+                self.context.raw_set_midi_mapping(p, None)
+            },
             // This can be used by widgets to be notified when parameter values have changed
             RawParamEvent::ParametersChanged => (),
         });
@@ -106,6 +134,15 @@ impl Model for ParamModel {
 
 impl Model for WindowModel {
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // Resizes initiated by the host don't need to be sent back to the host. Since the new scale
+        // factor has already been stored in the `ViziaState`, the `GeometryChanged` event below
+        // won't cause another resize request.
+        event.map(|window_model_event, _| match *window_model_event {
+            WindowModelEvent::SetUserScaleFactor(scale_factor) => {
+                cx.set_user_scale_factor(scale_factor)
+            }
+        });
+
         // This gets fired whenever the inner window gets resized
         event.map(|window_event, _| {
             if let WindowEvent::GeometryChanged { .. } = window_event {
@@ -161,6 +198,9 @@ impl<P: Param> From<ParamEvent<'_, P>> for RawParamEvent {
                 RawParamEvent::SetParameterNormalized(p.as_ptr(), v)
             }
             ParamEvent::EndSetParameter(p) => RawParamEvent::EndSetParameter(p.as_ptr()),
+            ParamEvent::StartMidiLearn(p) => RawParamEvent::StartMidiLearn(p.as_ptr()),
+            ParamEvent::CancelMidiLearn => RawParamEvent::CancelMidiLearn,
+            ParamEvent::ForgetMidiMapping(p) => RawParamEvent::ForgetMidiMapping(p.as_ptr()),
         }
     }
 }
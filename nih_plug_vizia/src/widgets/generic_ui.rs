@@ -87,6 +87,10 @@ impl GenericUi {
             }
         }
         .set_style(match unsafe { param_ptr.step_count() } {
+            // Output parameters are drawn as a meter, and the slider won't respond to user input
+            _ if unsafe { param_ptr.flags() }.contains(ParamFlags::OUTPUT) => {
+                ParamSliderStyle::FromLeft
+            }
             // This looks nice for boolean values, but it's too crowded for anything beyond
             // that without making the widget wider
             Some(step_count) if step_count <= 1 => {
@@ -223,6 +223,21 @@ impl ParamWidgetBase {
         cx.emit(RawParamEvent::EndSetParameter(self.param_ptr));
     }
 
+    /// Map the next MIDI controller the plugin receives to this parameter.
+    pub fn start_midi_learn(&self, cx: &mut EventContext) {
+        cx.emit(RawParamEvent::StartMidiLearn(self.param_ptr));
+    }
+
+    /// Stop waiting for a MIDI controller to learn.
+    pub fn cancel_midi_learn(&self, cx: &mut EventContext) {
+        cx.emit(RawParamEvent::CancelMidiLearn);
+    }
+
+    /// Remove this parameter's MIDI mapping.
+    pub fn forget_midi_mapping(&self, cx: &mut EventContext) {
+        cx.emit(RawParamEvent::ForgetMidiMapping(self.param_ptr));
+    }
+
     param_ptr_forward!(pub fn name(&self) -> &str);
     param_ptr_forward!(pub fn unit(&self) -> &'static str);
     param_ptr_forward!(pub fn poly_modulation_id(&self) -> Option<u32>);
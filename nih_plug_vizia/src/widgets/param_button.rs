@@ -4,6 +4,7 @@ use nih_plug::prelude::Param;
 use vizia::prelude::*;
 
 use super::param_base::ParamWidgetBase;
+use super::util;
 
 /// A toggleable button that integrates with NIH-plug's [`Param`] types. Only makes sense with
 /// [`BoolParam`][nih_plug::prelude::BoolParam]s. Clicking on the button will toggle between the
@@ -18,6 +19,8 @@ pub struct ParamButton {
     use_scroll_wheel: bool,
     /// A specific label to use instead of displaying the parameter's value.
     label_override: Option<String>,
+    /// Whether to draw the host's indication for the parameter on top of the button.
+    draw_indication: bool,
 
     /// The number of (fractional) scrolled lines that have not yet been turned into parameter
     /// change events. This is needed to support trackpads with smooth scrolling.
@@ -43,17 +46,25 @@ impl ParamButton {
 
             use_scroll_wheel: true,
             label_override: None,
+            draw_indication: false,
 
             scrolled_lines: 0.0,
         }
         .build(
             cx,
             ParamWidgetBase::build_view(params.clone(), params_to_param, move |cx, param_data| {
+                let indication_lens = param_data.make_lens(|param| param.indication());
+
                 Binding::new(cx, Self::label_override, move |cx, label_override| {
                     match label_override.get(cx) {
                         Some(label_override) => Label::new(cx, &label_override),
                         None => Label::new(cx, param_data.param().name()),
                     };
+                });
+                Binding::new(cx, Self::draw_indication, move |cx, draw_indication| {
+                    if draw_indication.get(cx) {
+                        util::indication_view(cx, indication_lens.clone());
+                    }
                 })
             }),
         )
@@ -130,6 +141,10 @@ pub trait ParamButtonExt {
     /// Change the label used for the button. If this is not set, then the parameter's name will be
     /// used.
     fn with_label(self, value: impl Into<String>) -> Self;
+
+    /// Draw the host's indication for the parameter on top of the button. See
+    /// [`ParamSliderExt::with_indication()`][super::ParamSliderExt::with_indication()].
+    fn with_indication(self) -> Self;
 }
 
 impl ParamButtonExt for Handle<'_, ParamButton> {
@@ -146,4 +161,8 @@ impl ParamButtonExt for Handle<'_, ParamButton> {
             param_button.label_override = Some(value.into())
         })
     }
+
+    fn with_indication(self) -> Self {
+        self.modify(|param_button: &mut ParamButton| param_button.draw_indication = true)
+    }
 }
@@ -1,6 +1,6 @@
 //! A slider that integrates with NIH-plug's [`Param`] types.
 
-use nih_plug::prelude::Param;
+use nih_plug::prelude::{Param, ParamFlags};
 use vizia::prelude::*;
 
 use super::param_base::ParamWidgetBase;
@@ -37,6 +37,8 @@ pub struct ParamSlider {
     style: ParamSliderStyle,
     /// A specific label to use instead of displaying the parameter's value.
     label_override: Option<String>,
+    /// Whether to draw the host's indication for the parameter on top of the slider.
+    draw_indication: bool,
 }
 
 /// How the [`ParamSlider`] should display its values. Set this using
@@ -110,10 +112,13 @@ impl ParamSlider {
             scrolled_lines: 0.0,
             style: ParamSliderStyle::Centered,
             label_override: None,
+            draw_indication: false,
         }
         .build(
             cx,
             ParamWidgetBase::build_view(params, params_to_param, move |cx, param_data| {
+                let indication_lens = param_data.make_lens(|param| param.indication());
+
                 Binding::new(cx, ParamSlider::style, move |cx, style| {
                     let style = style.get(cx);
 
@@ -199,6 +204,16 @@ impl ParamSlider {
                         },
                     );
                 });
+
+                Binding::new(
+                    cx,
+                    ParamSlider::draw_indication,
+                    move |cx, draw_indication| {
+                        if draw_indication.get(cx) {
+                            util::indication_view(cx, indication_lens.clone());
+                        }
+                    },
+                );
             }),
         )
     }
@@ -437,6 +452,11 @@ impl View for ParamSlider {
     }
 
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // Output parameters are set by the plugin, so they are only displayed
+        if self.param_base.flags().contains(ParamFlags::OUTPUT) {
+            return;
+        }
+
         event.map(|param_slider_event, meta| match param_slider_event {
             ParamSliderEvent::CancelTextInput => {
                 self.text_input_active = false;
@@ -626,6 +646,12 @@ pub trait ParamSliderExt {
     /// Manually set a fixed label for the slider instead of displaying the current value. This is
     /// currently not reactive.
     fn with_label(self, value: impl Into<String>) -> Self;
+
+    /// Draw the host's indication for the parameter on top of the slider. Parameters that are
+    /// mapped to a hardware controller get a border in the mapping's color, and automated
+    /// parameters get a small marker in the top left corner colored according to the automation
+    /// state. See [`Param::indication()`].
+    fn with_indication(self) -> Self;
 }
 
 impl ParamSliderExt for Handle<'_, ParamSlider> {
@@ -642,4 +668,8 @@ impl ParamSliderExt for Handle<'_, ParamSlider> {
             param_slider.label_override = Some(value.into())
         })
     }
+
+    fn with_indication(self) -> Self {
+        self.modify(|param_slider: &mut ParamSlider| param_slider.draw_indication = true)
+    }
 }
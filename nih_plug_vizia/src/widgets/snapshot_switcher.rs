@@ -0,0 +1,131 @@
+//! A row of toggle buttons for switching between snapshot slots.
+
+use nih_plug::prelude::{Params, SnapshotChanges, Snapshots};
+use std::sync::Arc;
+use vizia::prelude::*;
+
+use super::RawParamEvent;
+
+/// A row of toggle buttons for switching between a [`Snapshots`] object's slots, for A/B
+/// comparisons. Clicking a slot stores the current parameter values in the active slot and recalls
+/// the clicked slot's values as regular parameter gestures. The active slot has the `:checked`
+/// pseudoclass.
+#[derive(Lens)]
+pub struct SnapshotSwitcher {
+    /// Selects a slot and returns the changes needed to recall it. This captures the parameters
+    /// object so the widget doesn't need to be generic over it.
+    select: Box<dyn Fn(usize) -> SnapshotChanges>,
+    snapshots: Arc<Snapshots>,
+
+    /// A copy of the active slot, so the slots' `:checked` pseudoclasses can be updated. This is
+    /// resynchronized whenever the parameters change, since loading a state may also change the
+    /// active slot.
+    active_slot: usize,
+}
+
+/// A single slot in the [`SnapshotSwitcher`].
+struct SnapshotSlot {
+    idx: usize,
+}
+
+enum SnapshotSwitcherEvent {
+    Select(usize),
+}
+
+impl SnapshotSwitcher {
+    /// Creates a new [`SnapshotSwitcher`]. `params` should be a lens to the plugin's parameters
+    /// object, and `params_to_snapshots` should return the [`Snapshots`] stored in that object.
+    pub fn new<L, PsRef, Ps, FMap>(
+        cx: &mut Context,
+        params: L,
+        params_to_snapshots: FMap,
+    ) -> Handle<Self>
+    where
+        L: Lens<Target = PsRef>,
+        PsRef: AsRef<Ps> + Clone + 'static,
+        Ps: Params + 'static,
+        FMap: Fn(&Ps) -> &Arc<Snapshots> + Copy + 'static,
+    {
+        let params = params.get(cx);
+        let snapshots = params_to_snapshots(params.as_ref()).clone();
+        let num_slots = snapshots.num_slots();
+        let active_slot = snapshots.active_slot();
+
+        Self {
+            select: Box::new(move |idx| {
+                let params = params.as_ref();
+                params_to_snapshots(params).select(idx, params)
+            }),
+            snapshots,
+
+            active_slot,
+        }
+        .build(cx, move |cx| {
+            for idx in 0..num_slots {
+                SnapshotSlot { idx }
+                    .build(cx, move |cx| {
+                        Label::new(cx, &Snapshots::slot_name(idx));
+                    })
+                    .checked(
+                        SnapshotSwitcher::active_slot.map(move |active_slot| *active_slot == idx),
+                    );
+            }
+        })
+    }
+}
+
+impl View for SnapshotSwitcher {
+    fn element(&self) -> Option<&'static str> {
+        Some("snapshot-switcher")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(
+            |snapshot_switcher_event, meta| match *snapshot_switcher_event {
+                SnapshotSwitcherEvent::Select(idx) => {
+                    if idx != self.active_slot {
+                        // All changes are part of a single gesture so they can be undone at once
+                        let changes = (self.select)(idx);
+                        for (param_ptr, _) in changes.iter() {
+                            cx.emit(RawParamEvent::BeginSetParameter(param_ptr));
+                        }
+                        for (param_ptr, normalized) in changes.iter() {
+                            cx.emit(RawParamEvent::SetParameterNormalized(param_ptr, normalized));
+                        }
+                        for (param_ptr, _) in changes.iter() {
+                            cx.emit(RawParamEvent::EndSetParameter(param_ptr));
+                        }
+
+                        self.active_slot = idx;
+                    }
+
+                    meta.consume();
+                }
+            },
+        );
+
+        event.map(|param_event: &RawParamEvent, _| {
+            if let RawParamEvent::ParametersChanged = param_event {
+                self.active_slot = self.snapshots.active_slot();
+            }
+        });
+    }
+}
+
+impl View for SnapshotSlot {
+    fn element(&self) -> Option<&'static str> {
+        Some("snapshot-slot")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match window_event {
+            WindowEvent::MouseDown(MouseButton::Left)
+            | WindowEvent::MouseDoubleClick(MouseButton::Left)
+            | WindowEvent::MouseTripleClick(MouseButton::Left) => {
+                cx.emit(SnapshotSwitcherEvent::Select(self.idx));
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+}
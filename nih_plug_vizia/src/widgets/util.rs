@@ -1,5 +1,6 @@
 //! Utilities for writing VIZIA widgets.
 
+use nih_plug::prelude::{AutomationState, IndicationColor, ParamIndication};
 use vizia::prelude::*;
 
 /// An extension trait for [`Modifiers`] that adds platform-independent getters.
@@ -91,3 +92,61 @@ pub fn remap_current_entity_y_coordinate(cx: &EventContext, y_coord: f32) -> f32
     let height = cx.cache.get_height(cx.current()) - (border_width * 2.0);
     ((y_coord - y_pos) / height).clamp(0.0, 1.0)
 }
+
+/// Convert a color from a parameter's [`ParamIndication`] to a VIZIA color.
+pub fn indication_color(color: IndicationColor) -> Color {
+    Color::rgba(color.red, color.green, color.blue, color.alpha)
+}
+
+/// The color used to draw a parameter's automation state. This uses the host's color if it
+/// provided one, and `None` is returned when the parameter isn't automated.
+pub fn automation_color(
+    automation: AutomationState,
+    host_color: Option<IndicationColor>,
+) -> Option<Color> {
+    let default_color = match automation {
+        AutomationState::None => return None,
+        AutomationState::Present => Color::rgb(128, 128, 128),
+        AutomationState::Playing => Color::rgb(64, 192, 64),
+        AutomationState::Recording => Color::rgb(224, 48, 48),
+        AutomationState::Overriding => Color::rgb(232, 160, 32),
+    };
+
+    Some(host_color.map_or(default_color, indication_color))
+}
+
+/// Draw a parameter's host indication on top of a parameter widget. Parameters that are mapped to
+/// a hardware controller get a border in the mapping's color, and automated parameters get a small
+/// marker in the top left corner colored according to the automation state. The sizes of these
+/// elements can be changed using the `indication__mapping` and `indication__automation` classes.
+pub(crate) fn indication_view(
+    cx: &mut Context,
+    indication_lens: impl Lens<Target = ParamIndication>,
+) {
+    Element::new(cx)
+        .class("indication__mapping")
+        .position_type(PositionType::SelfDirected)
+        .width(Stretch(1.0))
+        .height(Stretch(1.0))
+        .visibility(indication_lens.clone().map(|indication| indication.mapped))
+        .border_color(indication_lens.clone().map(|indication| {
+            // This color is used when the host doesn't provide its own color
+            indication
+                .mapping_color
+                .map_or(Color::rgb(80, 140, 220), indication_color)
+        }))
+        .hoverable(false);
+
+    Element::new(cx)
+        .class("indication__automation")
+        .position_type(PositionType::SelfDirected)
+        .visibility(
+            indication_lens
+                .clone()
+                .map(|indication| indication.automation.is_automated()),
+        )
+        .background_color(indication_lens.map(|indication| {
+            automation_color(indication.automation, indication.automation_color).unwrap_or_default()
+        }))
+        .hoverable(false);
+}
@@ -182,6 +182,7 @@ impl Plugin for BuffrGlitch {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.buffr-glitch";
 
     // We'll only do stereo for now
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
@@ -300,6 +300,7 @@ impl Plugin for Crisp {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.crisp";
 
     // We'll add a SIMD version in a bit which only supports stereo
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
@@ -120,12 +120,14 @@ impl FirCrossover {
     /// The split bands will be written to `band_outputs`. The main output should be cleared
     /// separately. For efficiency's sake this processes an entire channel at once to minimize the
     /// number of FFT operations needed. Since this process delays the signal by `FFT_INPUT_SIZE`
-    /// samples, the latency should be reported to the host.
+    /// samples, the latency should be reported to the host. Bands that are not marked as active in
+    /// `active_bands` are neither filtered nor written to.
     pub fn process(
         &mut self,
         num_bands: usize,
         main_input: &[f32],
         mut band_outputs: [&mut &mut [f32]; NUM_BANDS],
+        active_bands: [bool; NUM_BANDS],
         channel_idx: usize,
     ) {
         nih_debug_assert!(main_input.len() == band_outputs[0].len());
@@ -148,10 +150,12 @@ impl FirCrossover {
                     .copy_from_slice(
                         &main_input[current_sample_idx..current_sample_idx + process_num_samples],
                     );
-                for (band_output, band_output_buffers) in band_outputs
+                for ((band_output, band_output_buffers), _) in band_outputs
                     .iter_mut()
                     .zip(self.band_output_buffers.iter())
+                    .zip(active_bands)
                     .take(num_bands)
+                    .filter(|(_, band_active)| *band_active)
                 {
                     band_output[current_sample_idx..current_sample_idx + process_num_samples]
                         .copy_from_slice(
@@ -185,12 +189,20 @@ impl FirCrossover {
                 // to be able to modify the input, we need to make a copy of this first:
                 let input_fft = *self.complex_scratch_buffer;
 
-                for (band_output_buffers, band_filter) in self
+                for ((band_output_buffers, band_filter), band_active) in self
                     .band_output_buffers
                     .iter_mut()
                     .zip(self.band_filters.iter_mut())
+                    .zip(active_bands)
                     .take(num_bands)
                 {
+                    // Inactive bands are cleared so they don't output stale data when they get
+                    // activated again
+                    if !band_active {
+                        band_output_buffers[channel_idx].fill(0.0);
+                        continue;
+                    }
+
                     band_filter.process(
                         &input_fft,
                         &mut band_output_buffers[channel_idx],
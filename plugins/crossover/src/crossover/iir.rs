@@ -33,6 +33,9 @@ pub struct IirCrossover {
     crossovers: [Crossover; NUM_BANDS - 1],
     /// Used to compensate the earlier bands for the phase shift introduced in the higher bands.
     all_passes: AllPassCascade,
+    /// The `active_bands` from the last `.process()` call. Filters that were skipped there have
+    /// stale state, so they're reset before they're used again.
+    active_bands: [bool; NUM_BANDS],
 }
 
 /// The type of IIR crossover to use.
@@ -84,17 +87,21 @@ impl IirCrossover {
             mode,
             crossovers: Default::default(),
             all_passes: Default::default(),
+            active_bands: [false; NUM_BANDS],
         }
     }
 
     /// Split the signal into bands using the crossovers previously configured through `.update()`.
     /// The split bands will be written to `band_outputs`. `main_io` is not written to, and should
-    /// be cleared separately.
+    /// be cleared separately. Bands that are not marked as active in `active_bands` are not written
+    /// to, and the crossovers for the highest bands are skipped entirely if none of those bands are
+    /// active. Skipped filters are reset when their bands become active again.
     pub fn process(
         &mut self,
         num_bands: usize,
         main_io: &ChannelSamples,
         mut band_outputs: [ChannelSamples; NUM_BANDS],
+        active_bands: [bool; NUM_BANDS],
     ) {
         nih_debug_assert!(num_bands >= 2);
         nih_debug_assert!(num_bands <= NUM_BANDS);
@@ -102,6 +109,8 @@ impl IirCrossover {
         // be unsound
         assert!(main_io.len() == 2);
 
+        let previous_active_bands = std::mem::replace(&mut self.active_bands, active_bands);
+
         let mut samples: f32x2 = unsafe { main_io.to_simd_unchecked() };
         match self.mode {
             IirCrossoverType::LinkwitzRiley24 => {
@@ -112,18 +121,38 @@ impl IirCrossover {
                     .take(num_bands - 1)
                     .enumerate()
                 {
+                    // If none of the remaining bands are routed anywhere then we don't need to
+                    // split the signal any further
+                    if !active_bands[crossover_idx..num_bands].contains(&true) {
+                        return;
+                    }
+
+                    // Filters that were skipped during the last call would otherwise continue from
+                    // stale state, causing clicks
+                    if !previous_active_bands[crossover_idx..num_bands].contains(&true) {
+                        crossover.reset();
+                    }
+
                     let (lp_samples, hp_samples) = crossover.process_lr24(samples);
+                    if active_bands[crossover_idx] {
+                        if !previous_active_bands[crossover_idx] {
+                            self.all_passes.reset_band(crossover_idx);
+                        }
 
-                    // The low-pass result needs to have the same phase shift applied to it that
-                    // higher bands would get
-                    let lp_samples = self.all_passes.compensate_lr24(lp_samples, crossover_idx);
+                        // The low-pass result needs to have the same phase shift applied to it
+                        // that higher bands would get
+                        let lp_samples = self.all_passes.compensate_lr24(lp_samples, crossover_idx);
+
+                        unsafe { band_channel_samples.from_simd_unchecked(lp_samples) };
+                    }
 
-                    unsafe { band_channel_samples.from_simd_unchecked(lp_samples) };
                     samples = hp_samples;
                 }
 
                 // And the final high-passed result should be written to the last band
-                unsafe { band_outputs[num_bands - 1].from_simd_unchecked(samples) };
+                if active_bands[num_bands - 1] {
+                    unsafe { band_outputs[num_bands - 1].from_simd_unchecked(samples) };
+                }
             }
         }
     }
@@ -262,6 +291,13 @@ impl AllPassCascade {
         }
     }
 
+    /// Reset the internal filter state for a single band.
+    pub fn reset_band(&mut self, band_idx: usize) {
+        for filter in &mut self.ap_filters[band_idx] {
+            filter.reset();
+        }
+    }
+
     /// Reset the internal filter state.
     pub fn reset(&mut self) {
         for filters in &mut self.ap_filters {
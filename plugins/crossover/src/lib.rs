@@ -30,8 +30,7 @@ mod crossover;
 /// The number of channels this plugin supports. Hard capped at 2 for SIMD reasons.
 pub const NUM_CHANNELS: u32 = 2;
 
-/// The number of bands. Not used directly here, but this avoids hardcoding some constants in the
-/// crossover implementations.
+/// The number of bands. This avoids hardcoding some constants in the crossover implementations.
 pub const NUM_BANDS: usize = 5;
 
 const MIN_CROSSOVER_FREQUENCY: f32 = 40.0;
@@ -165,6 +164,7 @@ impl Plugin for Crossover {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.crossover";
 
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
         main_input_channels: NonZeroU32::new(NUM_CHANNELS),
@@ -183,6 +183,7 @@ impl Plugin for Crossover {
             aux_inputs: &[],
             aux_outputs: &["Band 1", "Band 2", "Band 3", "Band 4", "Band 5"],
         },
+        ..AudioIOLayout::const_default()
     }];
 
     type SysExMessage = ();
@@ -249,6 +250,7 @@ impl Crossover {
     /// sample. The closure receives an input sample and it should write the output samples for each
     /// band to the array.
     fn process_iir(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers) {
+        let active_bands = active_bands(aux);
         let aux_outputs = &mut aux.outputs;
         let (band_1_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
         let (band_2_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
@@ -292,6 +294,7 @@ impl Crossover {
                 self.params.num_bands.value() as usize,
                 &main_channel_samples,
                 bands,
+                active_bands,
             );
 
             // The main output should be silent as the signal is already evenly split over the other
@@ -310,6 +313,7 @@ impl Crossover {
             self.update_filters(buffer.samples() as u32);
         }
 
+        let active_bands = active_bands(aux);
         let aux_outputs = &mut aux.outputs;
         let (band_1_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
         let (band_2_buffer, aux_outputs) = aux_outputs.split_first_mut().unwrap();
@@ -332,6 +336,7 @@ impl Crossover {
                 self.params.num_bands.value() as usize,
                 main_io,
                 band_outputs,
+                active_bands,
                 channel_idx,
             );
 
@@ -381,6 +386,17 @@ impl Crossover {
     }
 }
 
+/// Which of the bands' outputs are routed somewhere. Hosts can deactivate the auxiliary outputs for
+/// bands that aren't connected to anything, and those bands then don't need to be computed.
+fn active_bands(aux: &AuxiliaryBuffers) -> [bool; NUM_BANDS] {
+    let mut active_bands = [true; NUM_BANDS];
+    for (band_active, output_active) in active_bands.iter_mut().zip(aux.outputs_active) {
+        *band_active = *output_active;
+    }
+
+    active_bands
+}
+
 impl ClapPlugin for Crossover {
     const CLAP_ID: &'static str = "nl.robbertvanderhelm.crossover";
     const CLAP_DESCRIPTION: Option<&'static str> =
@@ -113,6 +113,7 @@ impl Plugin for Diopser {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.diopser";
 
     // The SIMD version only supports stereo
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
@@ -143,7 +144,7 @@ impl Plugin for Diopser {
         )
     }
 
-    fn filter_state(state: &mut PluginState) {
+    fn filter_state(state: &mut PluginState, _context: StateContext) {
         // Safe-mode is enabled by default, so to avoid changing the behavior we'll keep it disabled
         // for older presets
         if semver::Version::parse(&state.version)
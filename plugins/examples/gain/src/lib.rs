@@ -119,6 +119,8 @@ impl Plugin for Gain {
     const EMAIL: &'static str = "info@example.com";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    // This identifies the plugin in preset files, so it should never change
+    const PRESET_ID: &'static str = "com.moist-plugins-gmbh.gain";
 
     // The first audio IO layout is used as the default. The other layouts may be selected either
     // explicitly or automatically by the host or the user depending on the plugin API/backend.
@@ -134,6 +136,9 @@ impl Plugin for Gain {
             // are generated as needed. This layout will be called 'Stereo', while the other one is
             // given the name 'Mono' based no the number of input and output channels.
             names: PortNames::const_default(),
+            // Surround and ambisonic ports need to specify their channel layout here. Ports with
+            // one or two channels are assumed to be mono or stereo ports.
+            channel_layouts: PortChannelLayouts::const_default(),
         },
         AudioIOLayout {
             main_input_channels: NonZeroU32::new(1),
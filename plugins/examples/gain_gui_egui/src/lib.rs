@@ -77,6 +77,7 @@ impl Plugin for Gain {
     const EMAIL: &'static str = "info@example.com";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "com.moist-plugins-gmbh-egui.gain-gui";
 
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
         AudioIOLayout {
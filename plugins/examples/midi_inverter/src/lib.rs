@@ -25,6 +25,7 @@ impl Plugin for MidiInverter {
     const EMAIL: &'static str = "info@example.com";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "com.moist-plugins-gmbh.midi-inverter";
 
     // This plugin doesn't have any audio IO
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[];
@@ -84,8 +85,8 @@ impl Plugin for MidiInverter {
                 } => context.send_event(NoteEvent::Choke {
                     timing,
                     voice_id,
-                    channel: 15 - channel,
-                    note: 127 - note,
+                    channel: channel.map(|channel| 15 - channel),
+                    note: note.map(|note| 127 - note),
                 }),
                 NoteEvent::PolyPressure {
                     timing,
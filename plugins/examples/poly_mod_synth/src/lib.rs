@@ -10,8 +10,8 @@ const NUM_VOICES: u32 = 16;
 const MAX_BLOCK_SIZE: usize = 64;
 
 // Polyphonic modulation works by assigning integer IDs to parameters. Pattern matching on these in
-// `PolyModulation` and `MonoAutomation` events makes it possible to easily link these events to the
-// correct parameter.
+// `PolyModulation`, `KeyModulation`, and `MonoAutomation` events makes it possible to easily link
+// these events to the correct parameter.
 const GAIN_POLY_MOD_ID: u32 = 0;
 
 /// A simple polyphonic synthesizer with support for CLAP's polyphonic modulation. See
@@ -27,6 +27,10 @@ struct PolyModSynth {
     /// The next internal voice ID, used only to figure out the oldest voice for voice stealing.
     /// This is incremented by one each time a voice is created.
     next_internal_voice_id: u64,
+    /// The normalized gain offsets from per-key modulation, indexed by `[channel][note]`. Unlike
+    /// voice ID based modulation this also applies to notes that start after the modulation was
+    /// sent, so it needs to be stored separately from the voices.
+    key_gain_offsets: [[f32; 128]; 16],
 }
 
 #[derive(Params)]
@@ -87,6 +91,7 @@ impl Default for PolyModSynth {
             // `[None; N]` requires the `Some(T)` to be `Copy`able
             voices: [0; NUM_VOICES as usize].map(|_| None),
             next_internal_voice_id: 0,
+            key_gain_offsets: [[0.0; 128]; 16],
         }
     }
 }
@@ -147,6 +152,7 @@ impl Plugin for PolyModSynth {
     const EMAIL: &'static str = "info@example.com";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "com.moist-plugins-gmbh.poly-mod-synth";
 
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
         main_input_channels: NonZeroU32::new(2),
@@ -174,6 +180,7 @@ impl Plugin for PolyModSynth {
 
         self.voices.fill(None);
         self.next_internal_voice_id = 0;
+        self.key_gain_offsets = [[0.0; 128]; 16];
     }
 
     fn process(
@@ -224,12 +231,26 @@ impl Plugin for PolyModSynth {
                                 amp_envelope.reset(0.0);
                                 amp_envelope.set_target(sample_rate, 1.0);
 
+                                // Per-key modulation that was sent before the note started also
+                                // applies to the new voice
+                                let key_gain_offset =
+                                    self.key_gain_offsets[channel as usize][note as usize];
+                                let voice_gain = if key_gain_offset != 0.0 {
+                                    let smoother = self.params.gain.smoothed.clone();
+                                    smoother
+                                        .reset(self.params.gain.preview_modulated(key_gain_offset));
+                                    Some((key_gain_offset, smoother))
+                                } else {
+                                    None
+                                };
+
                                 let voice =
                                     self.start_voice(context, timing, voice_id, channel, note);
                                 voice.velocity_sqrt = velocity.sqrt();
                                 voice.phase = initial_phase;
                                 voice.phase_delta = util::midi_note_to_freq(note) / sample_rate;
                                 voice.amp_envelope = amp_envelope;
+                                voice.voice_gain = voice_gain;
                             }
                             NoteEvent::NoteOff {
                                 timing: _,
@@ -304,6 +325,71 @@ impl Plugin for PolyModSynth {
                                     }
                                 }
                             }
+                            NoteEvent::KeyModulation {
+                                timing: _,
+                                channel,
+                                note,
+                                poly_modulation_id,
+                                normalized_offset,
+                            } => {
+                                // Per-key modulation targets all voices with a matching channel
+                                // and key instead of a single voice. `None` values act as
+                                // wildcards. This is only sent because `supports_key_modulation`
+                                // is enabled in the `PolyModulationConfig`.
+                                let matches = |voice_channel: u8, voice_note: u8| {
+                                    channel.map_or(true, |channel| channel == voice_channel)
+                                        && note.map_or(true, |note| note == voice_note)
+                                };
+
+                                match poly_modulation_id {
+                                    GAIN_POLY_MOD_ID => {
+                                        // The offset is stored so it also applies to new notes
+                                        for (channel_idx, offsets) in
+                                            self.key_gain_offsets.iter_mut().enumerate()
+                                        {
+                                            for (note_idx, offset) in offsets.iter_mut().enumerate()
+                                            {
+                                                if matches(channel_idx as u8, note_idx as u8) {
+                                                    *offset = normalized_offset;
+                                                }
+                                            }
+                                        }
+
+                                        // And it's applied to the matching active voices the same
+                                        // way as for `PolyModulation` events
+                                        let target_plain_value =
+                                            self.params.gain.preview_modulated(normalized_offset);
+                                        for voice in self
+                                            .voices
+                                            .iter_mut()
+                                            .filter_map(|v| v.as_mut())
+                                            .filter(|voice| matches(voice.channel, voice.note))
+                                        {
+                                            let (offset, smoother) =
+                                                voice.voice_gain.get_or_insert_with(|| {
+                                                    (
+                                                        normalized_offset,
+                                                        self.params.gain.smoothed.clone(),
+                                                    )
+                                                });
+                                            *offset = normalized_offset;
+
+                                            if voice.internal_voice_id
+                                                >= this_sample_internal_voice_id_start
+                                            {
+                                                smoother.reset(target_plain_value);
+                                            } else {
+                                                smoother
+                                                    .set_target(sample_rate, target_plain_value);
+                                            }
+                                        }
+                                    }
+                                    n => nih_debug_assert_failure!(
+                                        "Key modulation sent for unknown poly modulation ID {}",
+                                        n
+                                    ),
+                                }
+                            }
                             NoteEvent::MonoAutomation {
                                 timing: _,
                                 poly_modulation_id,
@@ -544,14 +630,14 @@ impl PolyModSynth {
 
     /// Immediately terminate one or more voice, removing it from the pool and informing the host
     /// that the voice has ended. If `voice_id` is not provided, then this will terminate all
-    /// matching voices.
+    /// matching voices. A missing channel or note matches any channel or note.
     fn choke_voices(
         &mut self,
         context: &mut impl ProcessContext<Self>,
         sample_offset: u32,
         voice_id: Option<i32>,
-        channel: u8,
-        note: u8,
+        channel: Option<u8>,
+        note: Option<u8>,
     ) {
         for voice in self.voices.iter_mut() {
             match voice {
@@ -561,14 +647,16 @@ impl PolyModSynth {
                     note: candidate_note,
                     ..
                 }) if voice_id == Some(*candidate_voice_id)
-                    || (channel == *candidate_channel && note == *candidate_note) =>
+                    || (voice_id.is_none()
+                        && channel.map_or(true, |channel| channel == *candidate_channel)
+                        && note.map_or(true, |note| note == *candidate_note)) =>
                 {
                     context.send_event(NoteEvent::VoiceTerminated {
                         timing: sample_offset,
                         // Notice how we always send the terminated voice ID here
                         voice_id: Some(*candidate_voice_id),
-                        channel,
-                        note,
+                        channel: *candidate_channel,
+                        note: *candidate_note,
                     });
                     *voice = None;
 
@@ -608,6 +696,9 @@ impl ClapPlugin for PolyModSynth {
         max_voice_capacity: NUM_VOICES,
         // This enables voice stacking in Bitwig.
         supports_overlapping_voices: true,
+        // This lets Bitwig's per-key modulators modulate the gain. These are sent as
+        // `KeyModulation` events.
+        supports_key_modulation: true,
     });
 }
 
@@ -50,6 +50,7 @@ impl Plugin for SysEx {
     const EMAIL: &'static str = "info@example.com";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "com.moist-plugins-gmbh.sysex";
 
     // This plugin doesn't have any audio IO
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[];
@@ -164,6 +164,7 @@ impl Plugin for PubertySimulator {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.puberty-simulator";
 
     // We'll only do stereo for simplicity's sake
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
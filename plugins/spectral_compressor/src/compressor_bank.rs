@@ -577,6 +577,15 @@ impl CompressorBank {
         self.update_sidechain_spectra(sc_buffer, channel_idx);
     }
 
+    /// Set the sidechain frequency spectrum magnitudes to zero. This is used instead of
+    /// [`process_sidechain()`][Self::process_sidechain()] when the host has deactivated the
+    /// sidechain input, which has the same effect as processing a silent sidechain signal.
+    pub fn clear_sidechain(&mut self) {
+        for magnitudes in self.sidechain_spectrum_magnitudes.iter_mut() {
+            magnitudes.fill(0.0);
+        }
+    }
+
     /// Update the envelope followers based on the bin magnetudes.
     fn update_envelopes(
         &mut self,
@@ -257,6 +257,7 @@ impl Plugin for SpectralCompressor {
     const EMAIL: &'static str = "mail@robbertvanderhelm.nl";
 
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    const PRESET_ID: &'static str = "nl.robbertvanderhelm.spectral-compressor";
 
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
         AudioIOLayout {
@@ -390,6 +391,9 @@ impl Plugin for SpectralCompressor {
         // This is mixed in later with latency compensation applied
         self.dry_wet_mixer.write_dry(buffer);
 
+        // Hosts can deactivate the sidechain input when nothing is connected to it. The sidechain
+        // would be silent in that case, so there's no need to analyze it.
+        let sidechain_active = aux.inputs_active[0];
         match self.params.threshold.mode.value() {
             compressor_bank::ThresholdMode::Internal => self.stft.process_overlap_add(
                 buffer,
@@ -411,6 +415,31 @@ impl Plugin for SpectralCompressor {
                 },
             ),
             compressor_bank::ThresholdMode::SidechainMatch
+            | compressor_bank::ThresholdMode::SidechainCompress
+                if !sidechain_active =>
+            {
+                self.compressor_bank.clear_sidechain();
+                self.stft.process_overlap_add(
+                    buffer,
+                    overlap_times,
+                    |channel_idx, real_fft_buffer| {
+                        process_stft_main(
+                            channel_idx,
+                            real_fft_buffer,
+                            &mut self.complex_fft_buffer,
+                            fft_plan,
+                            &self.window_function,
+                            &self.params,
+                            &mut self.compressor_bank,
+                            input_gain,
+                            output_gain,
+                            overlap_times,
+                            first_non_dc_bin_idx,
+                        )
+                    },
+                )
+            }
+            compressor_bank::ThresholdMode::SidechainMatch
             | compressor_bank::ThresholdMode::SidechainCompress => {
                 self.stft.process_overlap_add_sidechain(
                     buffer,
@@ -30,6 +30,11 @@ pub struct AudioIOLayout {
     /// Optional names for the audio ports. Defining these can be useful for plugins with multiple
     /// output and input ports.
     pub names: PortNames,
+    /// Optional channel layouts for the audio ports. These are needed for surround and ambisonic
+    /// ports, since the channel counts alone don't say anything about the channels' meanings. Ports
+    /// without an explicit channel layout are treated as mono or stereo ports if they have one or
+    /// two channels.
+    pub channel_layouts: PortChannelLayouts,
 }
 
 /// Construct a `NonZeroU32` value at compile time. Equivalent to `NonZeroU32::new(n).unwrap()`.
@@ -40,15 +45,27 @@ pub const fn new_nonzero_u32(n: u32) -> NonZeroU32 {
     }
 }
 
-/// Contains auxiliary (sidechain) input and output buffers for a process call.
-pub struct AuxiliaryBuffers<'a> {
+/// Contains auxiliary (sidechain) input and output buffers for a process call. The sample type
+/// matches that of the main buffer, so this will contain `f64` buffers when the plugin is
+/// processing audio through [`Plugin::process_f64()`][crate::prelude::Plugin::process_f64()].
+pub struct AuxiliaryBuffers<'a, T = f32> {
     /// Buffers for all auxiliary (sidechain) inputs defined for this plugin. The data in these
     /// buffers can safely be overwritten. Auxiliary inputs can be defined using the
     /// [`AudioIOLayout::aux_input_ports`] field.
-    pub inputs: &'a mut [Buffer<'a>],
+    pub inputs: &'a mut [Buffer<'a, T>],
     /// Buffers for all auxiliary outputs defined for this plugin. Auxiliary outputs can be defined using the
     /// [`AudioIOLayout::aux_output_ports`] field.
-    pub outputs: &'a mut [Buffer<'a>],
+    pub outputs: &'a mut [Buffer<'a, T>],
+    /// Whether the host has activated each of the auxiliary inputs in [`inputs`][Self::inputs].
+    /// Hosts can deactivate ports that aren't connected to anything. Inactive inputs only contain
+    /// silence, so the plugin can skip any processing that depends on them. All ports are active
+    /// unless the host supports port activation, which is currently the case for CLAP's
+    /// `audio-ports-activation` extension and VST3's `IComponent::activateBus()`.
+    pub inputs_active: &'a [bool],
+    /// Whether the host has activated each of the auxiliary outputs in [`outputs`][Self::outputs].
+    /// The host discards anything written to an inactive output, so the plugin doesn't need to
+    /// compute those outputs.
+    pub outputs_active: &'a [bool],
 }
 
 /// Contains names for the ports defined in an `AudioIOLayout`. Setting these is optional, but it
@@ -74,6 +91,50 @@ pub struct PortNames {
     pub aux_outputs: &'static [&'static str],
 }
 
+/// Contains channel layouts for the ports defined in an `AudioIOLayout`. Each layout's channel
+/// count must match the port's channel count. The layout the host chose is passed to
+/// [`Plugin::initialize()`][crate::prelude::Plugin::initialize], and the resolved layout for each
+/// port can be queried using functions like [`AudioIOLayout::main_input_layout()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortChannelLayouts {
+    /// The channel layout for the main input port.
+    pub main_input: Option<ChannelLayout>,
+    /// The channel layout for the main output port.
+    pub main_output: Option<ChannelLayout>,
+    /// Channel layouts for auxiliary (sidechain) input ports. Ports that don't have an entry in
+    /// this slice don't have an explicit channel layout.
+    pub aux_inputs: &'static [Option<ChannelLayout>],
+    /// Channel layouts for auxiliary output ports. Ports that don't have an entry in this slice
+    /// don't have an explicit channel layout.
+    pub aux_outputs: &'static [Option<ChannelLayout>],
+}
+
+/// The meaning of an audio port's channels. The channel order for each layout is listed in the
+/// variant's documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// A single channel.
+    Mono,
+    /// Left and right channels.
+    Stereo,
+    /// 5.1 surround sound. The channels are ordered as left, right, center, LFE, left surround,
+    /// right surround.
+    Surround51,
+    /// 7.1.4 surround sound. The channels are ordered as left, right, center, LFE, left rear
+    /// surround, right rear surround, left side surround, right side surround, top front left, top
+    /// front right, top rear left, top rear right.
+    Surround714,
+    /// First order ambisonics using ACN channel ordering and SN3D normalization (AmbiX). This has
+    /// four channels.
+    AmbisonicFirstOrder,
+    /// Second order ambisonics using ACN channel ordering and SN3D normalization (AmbiX). This has
+    /// nine channels.
+    AmbisonicSecondOrder,
+    /// Third order ambisonics using ACN channel ordering and SN3D normalization (AmbiX). This has
+    /// sixteen channels.
+    AmbisonicThirdOrder,
+}
+
 /// Configuration for (the host's) audio buffers.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BufferConfig {
@@ -115,6 +176,7 @@ impl AudioIOLayout {
             aux_input_ports: &[],
             aux_output_ports: &[],
             names: PortNames::const_default(),
+            channel_layouts: PortChannelLayouts::const_default(),
         }
     }
 
@@ -187,6 +249,44 @@ impl AudioIOLayout {
             }
         }
     }
+
+    /// The channel layout for the main input port. Either taken from the `channel_layouts` field,
+    /// or derived from the channel count for mono and stereo ports. Returns `None` if the plugin
+    /// doesn't have a main input or if the layout is not known.
+    pub fn main_input_layout(&self) -> Option<ChannelLayout> {
+        self.main_input_channels
+            .and_then(|num_channels| port_layout(self.channel_layouts.main_input, num_channels))
+    }
+
+    /// The channel layout for the main output port. Either taken from the `channel_layouts` field,
+    /// or derived from the channel count for mono and stereo ports. Returns `None` if the plugin
+    /// doesn't have a main output or if the layout is not known.
+    pub fn main_output_layout(&self) -> Option<ChannelLayout> {
+        self.main_output_channels
+            .and_then(|num_channels| port_layout(self.channel_layouts.main_output, num_channels))
+    }
+
+    /// The channel layout for the auxiliary input port with the given index. Either taken from the
+    /// `channel_layouts` field, or derived from the channel count for mono and stereo ports.
+    pub fn aux_input_layout(&self, idx: usize) -> Option<ChannelLayout> {
+        self.aux_input_ports.get(idx).and_then(|num_channels| {
+            port_layout(
+                self.channel_layouts.aux_inputs.get(idx).copied().flatten(),
+                *num_channels,
+            )
+        })
+    }
+
+    /// The channel layout for the auxiliary output port with the given index. Either taken from
+    /// the `channel_layouts` field, or derived from the channel count for mono and stereo ports.
+    pub fn aux_output_layout(&self, idx: usize) -> Option<ChannelLayout> {
+        self.aux_output_ports.get(idx).and_then(|num_channels| {
+            port_layout(
+                self.channel_layouts.aux_outputs.get(idx).copied().flatten(),
+                *num_channels,
+            )
+        })
+    }
 }
 
 impl PortNames {
@@ -202,3 +302,64 @@ impl PortNames {
         }
     }
 }
+
+impl PortChannelLayouts {
+    /// [`PortChannelLayouts::default()`], but as a const function. Used when initializing
+    /// `Plugin::AUDIO_IO_LAYOUTS`. (<https://github.com/rust-lang/rust/issues/67792>)
+    pub const fn const_default() -> Self {
+        Self {
+            main_input: None,
+            main_output: None,
+            aux_inputs: &[],
+            aux_outputs: &[],
+        }
+    }
+}
+
+impl ChannelLayout {
+    /// The number of channels in this layout.
+    pub const fn num_channels(&self) -> u32 {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Surround51 => 6,
+            ChannelLayout::Surround714 => 12,
+            ChannelLayout::AmbisonicFirstOrder => 4,
+            ChannelLayout::AmbisonicSecondOrder => 9,
+            ChannelLayout::AmbisonicThirdOrder => 16,
+        }
+    }
+
+    /// The ambisonic order for ambisonic layouts, or `None` for speaker layouts.
+    pub const fn ambisonic_order(&self) -> Option<u32> {
+        match self {
+            ChannelLayout::AmbisonicFirstOrder => Some(1),
+            ChannelLayout::AmbisonicSecondOrder => Some(2),
+            ChannelLayout::AmbisonicThirdOrder => Some(3),
+            _ => None,
+        }
+    }
+
+    /// Whether this is one of the surround sound layouts.
+    pub const fn is_surround(&self) -> bool {
+        matches!(self, ChannelLayout::Surround51 | ChannelLayout::Surround714)
+    }
+}
+
+/// Resolve a port's channel layout. Ports without an explicit layout are only assumed to be mono or
+/// stereo if they have one or two channels.
+fn port_layout(layout: Option<ChannelLayout>, num_channels: NonZeroU32) -> Option<ChannelLayout> {
+    match (layout, num_channels.get()) {
+        (Some(layout), num_channels) => {
+            nih_debug_assert_eq!(
+                layout.num_channels(),
+                num_channels,
+                "The port's channel layout does not match its channel count"
+            );
+            Some(layout)
+        }
+        (None, 1) => Some(ChannelLayout::Mono),
+        (None, 2) => Some(ChannelLayout::Stereo),
+        (None, _) => None,
+    }
+}
@@ -13,10 +13,15 @@ pub use samples::{ChannelSamples, ChannelSamplesIter, SamplesIter};
 /// and efficiently iterate over the samples, or you can do your own thing using the raw audio
 /// buffers.
 ///
+/// The sample type defaults to `f32`. Plugins that opt into double precision processing through
+/// [`Plugin::F64_PROCESSING`][crate::prelude::Plugin::F64_PROCESSING] will receive a `Buffer<f64>`
+/// in [`Plugin::process_f64()`][crate::prelude::Plugin::process_f64()] instead. All of the iterator
+/// adapters work the same way for both sample types.
+///
 /// TODO: This lifetime makes zero sense because you're going to need unsafe lifetime casts to use
 ///       this either way. Maybe just get rid of it in favor for raw pointers.
 #[derive(Default)]
-pub struct Buffer<'a> {
+pub struct Buffer<'a, T = f32> {
     /// The number of samples contained within `output_slices`. This needs to be stored separately
     /// to be able to handle 0 channel IO for MIDI-only plugins.
     num_samples: usize,
@@ -28,10 +33,20 @@ pub struct Buffer<'a> {
     /// because this `Buffers` either cannot have the same lifetime as the separately stored output
     /// buffers, and it also cannot be stored in a field next to it because that would mean
     /// containing mutable references to data stored in a mutex.
-    output_slices: Vec<&'a mut [f32]>,
+    output_slices: Vec<&'a mut [T]>,
+
+    /// A bit mask indicating which channels the host marked as silent, with the least significant
+    /// bit corresponding to the first channel. Only the first 64 channels are tracked.
+    silence_mask: u64,
+    /// A bit mask indicating which channels the host marked as constant. Silent channels are also
+    /// constant.
+    constant_mask: u64,
+    /// A bit mask indicating which output channels the plugin marked as silent using
+    /// [`mark_silent()`][Self::mark_silent()]. This is reported back to the host after processing.
+    output_silence_mask: u64,
 }
 
-impl<'a> Buffer<'a> {
+impl<'a, T> Buffer<'a, T> {
     /// Returns the number of samples per channel in this buffer.
     #[inline]
     pub fn samples(&self) -> usize {
@@ -50,22 +65,52 @@ impl<'a> Buffer<'a> {
         self.num_samples == 0
     }
 
+    /// Returns true if the host indicated that this channel contains only zeroes. For the main
+    /// buffer and for auxiliary inputs this describes the input audio as it was passed to the
+    /// plugin, so plugins can skip expensive work like FFTs on silent sidechain inputs. Not all
+    /// hosts provide this information, so a channel can still be silent if this returns false.
+    /// This is always false for auxiliary outputs.
+    #[inline]
+    pub fn is_silent(&self, channel: usize) -> bool {
+        channel < 64 && self.silence_mask & (1 << channel) != 0
+    }
+
+    /// Returns true if the host indicated that every sample in this channel has the same value.
+    /// This is also true for [silent][Self::is_silent()] channels. Only CLAP hosts provide this
+    /// information for non-silent channels.
+    #[inline]
+    pub fn is_constant(&self, channel: usize) -> bool {
+        channel < 64 && self.constant_mask & (1 << channel) != 0
+    }
+
+    /// Tell the host that this output channel is silent after processing, allowing it to skip
+    /// processing further down the signal chain. The plugin must make sure the channel contains
+    /// only zeroes when it returns from the process function. This needs to be called again for
+    /// every process call. Only the first 64 channels can be marked as silent.
+    #[inline]
+    pub fn mark_silent(&mut self, channel: usize) {
+        nih_debug_assert!(channel < self.channels(), "Channel index out of bounds");
+        if channel < 64 {
+            self.output_silence_mask |= 1 << channel;
+        }
+    }
+
     /// Obtain the raw audio buffers.
     #[inline]
-    pub fn as_slice(&mut self) -> &mut [&'a mut [f32]] {
+    pub fn as_slice(&mut self) -> &mut [&'a mut [T]] {
         &mut self.output_slices
     }
 
     /// The same as [`as_slice()`][Self::as_slice()], but for a non-mutable reference. This is
     /// usually not needed.
     #[inline]
-    pub fn as_slice_immutable(&self) -> &[&'a mut [f32]] {
+    pub fn as_slice_immutable(&self) -> &[&'a mut [T]] {
         &self.output_slices
     }
 
     /// Iterate over the samples, returning a channel iterator for each sample.
     #[inline]
-    pub fn iter_samples<'slice>(&'slice mut self) -> SamplesIter<'slice, 'a> {
+    pub fn iter_samples<'slice>(&'slice mut self) -> SamplesIter<'slice, 'a, T> {
         SamplesIter {
             buffers: self.output_slices.as_mut_slice(),
             current_sample: 0,
@@ -99,7 +144,10 @@ impl<'a> Buffer<'a> {
     /// }
     /// ````
     #[inline]
-    pub fn iter_blocks<'slice>(&'slice mut self, max_block_size: usize) -> BlocksIter<'slice, 'a> {
+    pub fn iter_blocks<'slice>(
+        &'slice mut self,
+        max_block_size: usize,
+    ) -> BlocksIter<'slice, 'a, T> {
         BlocksIter {
             buffers: self.output_slices.as_mut_slice(),
             max_block_size,
@@ -111,7 +159,8 @@ impl<'a> Buffer<'a> {
     /// Set the slices in the raw output slice vector. This vector needs to be resized to match the
     /// number of output channels during the plugin's initialization. Then during audio processing,
     /// these slices should be updated to point to the plugin's audio buffers. The `num_samples`
-    /// argument should match the length of the inner slices.
+    /// argument should match the length of the inner slices. This also clears the buffer's silence
+    /// and constant flags.
     ///
     /// # Safety
     ///
@@ -121,9 +170,12 @@ impl<'a> Buffer<'a> {
     pub unsafe fn set_slices(
         &mut self,
         num_samples: usize,
-        update: impl FnOnce(&mut Vec<&'a mut [f32]>),
+        update: impl FnOnce(&mut Vec<&'a mut [T]>),
     ) {
         self.num_samples = num_samples;
+        self.silence_mask = 0;
+        self.constant_mask = 0;
+        self.output_silence_mask = 0;
         update(&mut self.output_slices);
 
         #[cfg(debug_assertions)]
@@ -131,6 +183,25 @@ impl<'a> Buffer<'a> {
             nih_debug_assert_eq!(slice.len(), num_samples);
         }
     }
+
+    /// Set the silence and constant flags for this buffer's channels as reported by the host. This
+    /// needs to be called after [`set_slices()`][Self::set_slices()]. Silent channels are always
+    /// marked as constant.
+    pub(crate) fn set_channel_flags(&mut self, silence_mask: u64, constant_mask: u64) {
+        let channels_mask = match self.output_slices.len() {
+            num_channels if num_channels >= 64 => u64::MAX,
+            num_channels => (1 << num_channels) - 1,
+        };
+
+        self.silence_mask = silence_mask & channels_mask;
+        self.constant_mask = (constant_mask | silence_mask) & channels_mask;
+    }
+
+    /// The channels the plugin marked as silent during the last process call using
+    /// [`mark_silent()`][Self::mark_silent()].
+    pub(crate) fn output_silence_mask(&self) -> u64 {
+        self.output_silence_mask
+    }
 }
 
 #[cfg(any(miri, test))]
@@ -140,7 +211,7 @@ mod miri {
     #[test]
     fn repeated_access() {
         let mut real_buffers = vec![vec![0.0; 512]; 2];
-        let mut buffer = Buffer::default();
+        let mut buffer: Buffer = Buffer::default();
         unsafe {
             buffer.set_slices(512, |output_slices| {
                 let (first_channel, other_channels) = real_buffers.split_at_mut(1);
@@ -168,7 +239,7 @@ mod miri {
     #[test]
     fn repeated_slices() {
         let mut real_buffers = vec![vec![0.0; 512]; 2];
-        let mut buffer = Buffer::default();
+        let mut buffer: Buffer = Buffer::default();
         unsafe {
             buffer.set_slices(512, |output_slices| {
                 let (first_channel, other_channels) = real_buffers.split_at_mut(1);
@@ -198,4 +269,67 @@ mod miri {
             assert_eq!(real_buffers[0][i], 0.0);
         }
     }
+
+    #[test]
+    fn channel_flags() {
+        let mut real_buffers = vec![vec![0.0; 512]; 2];
+        let mut buffer: Buffer = Buffer::default();
+        unsafe {
+            buffer.set_slices(512, |output_slices| {
+                let (first_channel, other_channels) = real_buffers.split_at_mut(1);
+                *output_slices = vec![&mut first_channel[0], &mut other_channels[0]];
+            })
+        };
+
+        // Bits for channels that don't exist are ignored
+        buffer.set_channel_flags(0b101, 0b010);
+        assert!(buffer.is_silent(0));
+        assert!(buffer.is_constant(0));
+        assert!(!buffer.is_silent(1));
+        assert!(buffer.is_constant(1));
+        assert!(!buffer.is_silent(2));
+        assert!(!buffer.is_silent(100));
+
+        buffer.mark_silent(1);
+        assert_eq!(buffer.output_silence_mask(), 0b10);
+
+        // Setting new slices for the next block resets all flags
+        unsafe {
+            buffer.set_slices(256, |output_slices| {
+                let (first_channel, other_channels) = real_buffers.split_at_mut(1);
+                *output_slices = vec![&mut first_channel[0][..256], &mut other_channels[0][..256]];
+            })
+        };
+        assert!(!buffer.is_silent(0));
+        assert!(!buffer.is_constant(1));
+        assert_eq!(buffer.output_silence_mask(), 0);
+    }
+
+    #[test]
+    fn f64_access() {
+        let mut real_buffers = vec![vec![0.0f64; 512]; 2];
+        let mut buffer: Buffer<f64> = Buffer::default();
+        unsafe {
+            buffer.set_slices(512, |output_slices| {
+                let (first_channel, other_channels) = real_buffers.split_at_mut(1);
+                *output_slices = vec![&mut first_channel[0], &mut other_channels[0]];
+            })
+        };
+
+        for (_, block) in buffer.iter_blocks(128) {
+            for channel in block {
+                for sample in channel.iter_mut() {
+                    *sample += 0.5;
+                }
+            }
+        }
+
+        for samples in buffer.iter_samples() {
+            for sample in samples {
+                *sample *= 2.0;
+            }
+        }
+
+        assert!(real_buffers.iter().flatten().all(|sample| *sample == 1.0));
+    }
 }
@@ -3,45 +3,45 @@
 use std::marker::PhantomData;
 
 #[cfg(feature = "simd")]
-use std::simd::{LaneCount, Simd, SupportedLaneCount};
+use std::simd::{LaneCount, Simd, SimdElement, SupportedLaneCount};
 
 use super::SamplesIter;
 
 /// An iterator over all samples in the buffer, slicing over the sample-dimension with a maximum
 /// size of `max_block_size`. See [`Buffer::iter_blocks()`][super::Buffer::iter_blocks()]. Yields
 /// both the block and the offset from the start of the buffer.
-pub struct BlocksIter<'slice, 'sample: 'slice> {
+pub struct BlocksIter<'slice, 'sample: 'slice, T = f32> {
     /// The raw output buffers.
-    pub(super) buffers: *mut [&'sample mut [f32]],
+    pub(super) buffers: *mut [&'sample mut [T]],
     pub(super) max_block_size: usize,
     pub(super) current_block_start: usize,
-    pub(super) _marker: PhantomData<&'slice mut [&'sample mut [f32]]>,
+    pub(super) _marker: PhantomData<&'slice mut [&'sample mut [T]]>,
 }
 
 /// A block yielded by [`BlocksIter`]. Can be iterated over once or multiple times, and also
 /// supports direct access to the block's samples if needed.
-pub struct Block<'slice, 'sample: 'slice> {
+pub struct Block<'slice, 'sample: 'slice, T = f32> {
     /// The raw output buffers.
-    pub(self) buffers: *mut [&'sample mut [f32]],
+    pub(self) buffers: *mut [&'sample mut [T]],
     pub(self) current_block_start: usize,
     /// The index of the last sample in the block plus one.
     pub(self) current_block_end: usize,
-    pub(self) _marker: PhantomData<&'slice mut [&'sample mut [f32]]>,
+    pub(self) _marker: PhantomData<&'slice mut [&'sample mut [T]]>,
 }
 
 /// An iterator over all channels in a block yielded by [`Block`], returning an entire channel slice
 /// at a time.
-pub struct BlockChannelsIter<'slice, 'sample: 'slice> {
+pub struct BlockChannelsIter<'slice, 'sample: 'slice, T = f32> {
     /// The raw output buffers.
-    pub(self) buffers: *mut [&'sample mut [f32]],
+    pub(self) buffers: *mut [&'sample mut [T]],
     pub(self) current_block_start: usize,
     pub(self) current_block_end: usize,
     pub(self) current_channel: usize,
-    pub(self) _marker: PhantomData<&'slice mut [&'sample mut [f32]]>,
+    pub(self) _marker: PhantomData<&'slice mut [&'sample mut [T]]>,
 }
 
-impl<'slice, 'sample> Iterator for BlocksIter<'slice, 'sample> {
-    type Item = (usize, Block<'slice, 'sample>);
+impl<'slice, 'sample, T> Iterator for BlocksIter<'slice, 'sample, T> {
+    type Item = (usize, Block<'slice, 'sample, T>);
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -74,9 +74,9 @@ impl<'slice, 'sample> Iterator for BlocksIter<'slice, 'sample> {
     }
 }
 
-impl<'slice, 'sample> IntoIterator for Block<'slice, 'sample> {
-    type Item = &'sample mut [f32];
-    type IntoIter = BlockChannelsIter<'slice, 'sample>;
+impl<'slice, 'sample, T> IntoIterator for Block<'slice, 'sample, T> {
+    type Item = &'sample mut [T];
+    type IntoIter = BlockChannelsIter<'slice, 'sample, T>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
@@ -90,8 +90,8 @@ impl<'slice, 'sample> IntoIterator for Block<'slice, 'sample> {
     }
 }
 
-impl<'slice, 'sample> Iterator for BlockChannelsIter<'slice, 'sample> {
-    type Item = &'sample mut [f32];
+impl<'slice, 'sample, T> Iterator for BlockChannelsIter<'slice, 'sample, T> {
+    type Item = &'sample mut [T];
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -121,10 +121,10 @@ impl<'slice, 'sample> Iterator for BlockChannelsIter<'slice, 'sample> {
     }
 }
 
-impl ExactSizeIterator for BlocksIter<'_, '_> {}
-impl ExactSizeIterator for BlockChannelsIter<'_, '_> {}
+impl<T> ExactSizeIterator for BlocksIter<'_, '_, T> {}
+impl<T> ExactSizeIterator for BlockChannelsIter<'_, '_, T> {}
 
-impl<'slice, 'sample> Block<'slice, 'sample> {
+impl<'slice, 'sample, T> Block<'slice, 'sample, T> {
     /// Get the number of samples per channel in the block.
     #[inline]
     pub fn samples(&self) -> usize {
@@ -141,7 +141,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     /// you don't need to use this function as [`Block`] already implements [`Iterator`]. You can
     /// also use the direct accessor functions on this block instead.
     #[inline]
-    pub fn iter_mut(&mut self) -> BlockChannelsIter<'slice, 'sample> {
+    pub fn iter_mut(&mut self) -> BlockChannelsIter<'slice, 'sample, T> {
         BlockChannelsIter {
             buffers: self.buffers,
             current_block_start: self.current_block_start,
@@ -155,7 +155,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     /// [`Buffer::iter_samples()`][super::Buffer::iter_samples()] but for a smaller block instead of
     /// the entire buffer
     #[inline]
-    pub fn iter_samples(&mut self) -> SamplesIter<'slice, 'sample> {
+    pub fn iter_samples(&mut self) -> SamplesIter<'slice, 'sample, T> {
         SamplesIter {
             buffers: self.buffers,
             current_sample: self.current_block_start,
@@ -167,7 +167,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     /// Access a channel by index. Useful when you would otherwise iterate over this [`Block`]
     /// multiple times.
     #[inline]
-    pub fn get(&self, channel_index: usize) -> Option<&[f32]> {
+    pub fn get(&self, channel_index: usize) -> Option<&[T]> {
         // SAFETY: The block bound has already been checked
         unsafe {
             Some(
@@ -184,7 +184,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     ///
     /// `channel_index` must be in the range `0..Self::len()`.
     #[inline]
-    pub unsafe fn get_unchecked(&self, channel_index: usize) -> &[f32] {
+    pub unsafe fn get_unchecked(&self, channel_index: usize) -> &[T] {
         (*self.buffers)
             .get_unchecked(channel_index)
             .get_unchecked(self.current_block_start..self.current_block_end)
@@ -193,7 +193,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     /// Access a mutable channel by index. Useful when you would otherwise iterate over this
     /// [`Block`] multiple times.
     #[inline]
-    pub fn get_mut(&mut self, channel_index: usize) -> Option<&mut [f32]> {
+    pub fn get_mut(&mut self, channel_index: usize) -> Option<&mut [T]> {
         // SAFETY: The block bound has already been checked
         unsafe {
             Some(
@@ -210,7 +210,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     ///
     /// `channel_index` must be in the range `0..Self::len()`.
     #[inline]
-    pub unsafe fn get_unchecked_mut(&mut self, channel_index: usize) -> &mut [f32] {
+    pub unsafe fn get_unchecked_mut(&mut self, channel_index: usize) -> &mut [T] {
         (*self.buffers)
             .get_unchecked_mut(channel_index)
             .get_unchecked_mut(self.current_block_start..self.current_block_end)
@@ -223,11 +223,9 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     /// Returns a `None` value if `sample_index` is out of bounds.
     #[cfg(feature = "simd")]
     #[inline]
-    pub fn to_channel_simd<const LANES: usize>(
-        &self,
-        sample_index: usize,
-    ) -> Option<Simd<f32, LANES>>
+    pub fn to_channel_simd<const LANES: usize>(&self, sample_index: usize) -> Option<Simd<T, LANES>>
     where
+        T: SimdElement + Default,
         LaneCount<LANES>: SupportedLaneCount,
     {
         if sample_index > self.samples() {
@@ -235,7 +233,7 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
         }
 
         let used_lanes = self.samples().max(LANES);
-        let mut values = [0.0; LANES];
+        let mut values = [T::default(); LANES];
         for (channel_idx, value) in values.iter_mut().enumerate().take(used_lanes) {
             *value = unsafe {
                 *(*self.buffers)
@@ -258,11 +256,12 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     pub unsafe fn to_channel_simd_unchecked<const LANES: usize>(
         &self,
         sample_index: usize,
-    ) -> Simd<f32, LANES>
+    ) -> Simd<T, LANES>
     where
+        T: SimdElement + Default,
         LaneCount<LANES>: SupportedLaneCount,
     {
-        let mut values = [0.0; LANES];
+        let mut values = [T::default(); LANES];
         for (channel_idx, value) in values.iter_mut().enumerate() {
             *value = *(*self.buffers)
                 .get_unchecked(channel_idx)
@@ -283,9 +282,10 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     pub fn from_channel_simd<const LANES: usize>(
         &mut self,
         sample_index: usize,
-        vector: Simd<f32, LANES>,
+        vector: Simd<T, LANES>,
     ) -> bool
     where
+        T: SimdElement + Default,
         LaneCount<LANES>: SupportedLaneCount,
     {
         if sample_index > self.samples() {
@@ -318,8 +318,9 @@ impl<'slice, 'sample> Block<'slice, 'sample> {
     pub unsafe fn from_channel_simd_unchecked<const LANES: usize>(
         &mut self,
         sample_index: usize,
-        vector: Simd<f32, LANES>,
+        vector: Simd<T, LANES>,
     ) where
+        T: SimdElement + Default,
         LaneCount<LANES>: SupportedLaneCount,
     {
         let values = vector.to_array();
@@ -15,6 +15,67 @@ pub enum PluginApi {
     Vst3,
 }
 
+/// Information about the host the plugin is running in. This can be queried using
+/// [`InitContext::host_info()`][init::InitContext::host_info()],
+/// [`ProcessContext::host_info()`][process::ProcessContext::host_info()], and
+/// [`GuiContext::host_info()`][gui::GuiContext::host_info()], and it can be used to work around
+/// host-specific bugs. CLAP hosts provide all of these fields, although they may leave some of them
+/// empty. VST3 hosts only provide their name through `IHostApplication::getName()`. The standalone
+/// does not run in a host, so all fields are empty there.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HostInfo {
+    /// The host's name, e.g. `Bitwig Studio` or `REAPER`.
+    pub name: Option<String>,
+    /// The host's vendor. Only CLAP hosts provide this.
+    pub vendor: Option<String>,
+    /// The host's version. Only CLAP hosts provide this, and the format differs between hosts.
+    pub version: Option<String>,
+    /// The URL of the host's website. Only CLAP hosts provide this.
+    pub url: Option<String>,
+}
+
+/// Information about the track or mixer channel the plugin is inserted on, as provided by the host.
+/// This can be queried using [`InitContext::track_info()`][init::InitContext::track_info()] and
+/// [`GuiContext::track_info()`][gui::GuiContext::track_info()], and
+/// [`Editor::track_info_changed()`][crate::prelude::Editor::track_info_changed()] is called when
+/// it changes. CLAP hosts provide this through the track info extension, and VST3 hosts provide
+/// this through the channel context info interface. Hosts may leave out any of these fields.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// The track's name.
+    pub name: Option<String>,
+    /// The color the host uses to display the track.
+    pub color: Option<TrackColor>,
+    /// The number of audio channels on the track. VST3 hosts don't provide this.
+    pub channel_count: Option<u32>,
+    /// What kind of track the plugin is inserted on.
+    pub flags: TrackFlags,
+}
+
+/// A track's color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+bitflags::bitflags! {
+    /// Flags describing the kind of track the plugin is inserted on. Regular tracks don't have any
+    /// of these flags set.
+    #[repr(transparent)]
+    #[derive(Default)]
+    pub struct TrackFlags: u32 {
+        /// The plugin is inserted on a return or FX track.
+        const RETURN_TRACK = 1 << 0;
+        /// The plugin is inserted on a bus or group track.
+        const BUS = 1 << 1;
+        /// The plugin is inserted on the master track.
+        const MASTER = 1 << 2;
+    }
+}
+
 impl Display for PluginApi {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
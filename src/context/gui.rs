@@ -1,8 +1,10 @@
 //! A context passed to a plugin's editor.
 
+use std::os::raw::c_int;
 use std::sync::Arc;
 
-use super::PluginApi;
+use super::{HostInfo, PluginApi, TrackInfo};
+use crate::midi::learn::MidiMapping;
 use crate::params::internals::ParamPtr;
 use crate::params::Param;
 use crate::plugin::Plugin;
@@ -57,6 +59,41 @@ pub trait GuiContext: Send + Sync + 'static {
     /// mostly marked as unsafe for API reasons.
     unsafe fn raw_end_set_parameter(&self, param: ParamPtr);
 
+    /// Map the next incoming MIDI CC, pitch bend, or channel pressure message to a parameter.
+    /// Create a [`ParamSetter`] and use [`ParamSetter::start_midi_learn()`] instead for a safe,
+    /// user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_start_midi_learn(&self, param: ParamPtr);
+
+    /// Stop waiting for a MIDI controller to learn, if
+    /// [`raw_start_midi_learn()`][Self::raw_start_midi_learn()] was called before.
+    fn cancel_midi_learn(&self);
+
+    /// The parameter that's currently waiting for a MIDI controller to be learned, if any.
+    fn midi_learn_target(&self) -> Option<ParamPtr>;
+
+    /// Get a parameter's MIDI mapping. Create a [`ParamSetter`] and use
+    /// [`ParamSetter::midi_mapping()`] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_midi_mapping(&self, param: ParamPtr) -> Option<MidiMapping>;
+
+    /// Set or remove a parameter's MIDI mapping. Create a [`ParamSetter`] and use
+    /// [`ParamSetter::set_midi_mapping()`] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_set_midi_mapping(&self, param: ParamPtr, mapping: Option<MidiMapping>);
+
     /// Serialize the plugin's current state to a serde-serializable object. Useful for implementing
     /// preset handling within a plugin's GUI.
     fn get_state(&self) -> PluginState;
@@ -65,7 +102,95 @@ pub trait GuiContext: Send + Sync + 'static {
     /// until the state has been restored and a parameter value rescan has been requested from the
     /// host. If the plugin is currently processing audio, then the parameter values will be
     /// restored at the end of the current processing cycle.
+    ///
+    /// The state change is added to the plugin's undo history.
     fn set_state(&self, state: PluginState);
+
+    /// Undo the last change made through this context. Parameter gestures made through
+    /// [`raw_begin_set_parameter()`][Self::raw_begin_set_parameter()] and
+    /// [`raw_end_set_parameter()`][Self::raw_end_set_parameter()] are undone as a single
+    /// transaction, and overlapping gestures are undone together.
+    /// [`set_state()`][Self::set_state()] calls can also be undone. The parameters are restored
+    /// using regular gestures, so the host sees the changes. If the host supports CLAP's undo
+    /// extension, then the plugin's changes are part of the host's undo history and this asks the
+    /// host to undo its last change instead.
+    fn undo(&self);
+
+    /// Redo the last change undone with [`undo()`][Self::undo()].
+    fn redo(&self);
+
+    /// Whether there is a change that can be undone with [`undo()`][Self::undo()]. Useful for
+    /// disabling undo buttons.
+    fn can_undo(&self) -> bool;
+
+    /// Whether there is a change that can be redone with [`redo()`][Self::redo()].
+    fn can_redo(&self) -> bool;
+
+    /// Inform the host that the names returned from
+    /// [`Plugin::note_names()`][crate::prelude::Plugin::note_names()] have changed, for instance
+    /// because the user loaded a different sample kit from the plugin's GUI.
+    fn note_names_changed(&self);
+
+    /// Get information about the track the plugin is inserted on, such as the track's name and
+    /// color. Returns `None` if the host doesn't provide this information.
+    /// [`Editor::track_info_changed()`][crate::prelude::Editor::track_info_changed()] is called
+    /// when this changes.
+    fn track_info(&self) -> Option<TrackInfo>;
+
+    /// Get information about the host the plugin is running in, such as the host's name and
+    /// version. See [`HostInfo`] for the fields each plugin API provides.
+    fn host_info(&self) -> &HostInfo;
+
+    /// Register a timer with the host that calls `callback` on the GUI thread roughly every
+    /// `period_ms` milliseconds. This can be used to poll meters or other data from the GUI
+    /// without spawning a separate thread. Returns `None` if the host doesn't support timers, in
+    /// which case the editor will need to fall back to its own event loop.
+    ///
+    /// This uses CLAP's `timer-support` extension and VST3's `IRunLoop` on Linux. With VST3 this
+    /// is only available while the editor is open. All timers are unregistered automatically when
+    /// the editor is closed.
+    fn register_timer(&self, period_ms: u32, callback: Box<dyn FnMut() + Send>) -> Option<TimerId>;
+
+    /// Unregister a timer that was previously registered with
+    /// [`register_timer()`][Self::register_timer()].
+    fn unregister_timer(&self, timer_id: TimerId);
+
+    /// Watch a file descriptor on the host's GUI thread. `callback` is called with the events that
+    /// occurred whenever the file descriptor is ready for one of the events in `flags`. Only one
+    /// watch can be registered per file descriptor, and the file descriptor must stay open until
+    /// it has been unregistered. Returns `false` if the host doesn't support this.
+    ///
+    /// This uses CLAP's `posix-fd-support` extension and VST3's `IRunLoop` on Linux. VST3 hosts
+    /// only watch file descriptors for [`FdFlags::READ`] events. All file descriptor watches are
+    /// unregistered automatically when the editor is closed.
+    fn register_fd(
+        &self,
+        fd: c_int,
+        flags: FdFlags,
+        callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool;
+
+    /// Stop watching a file descriptor that was previously registered with
+    /// [`register_fd()`][Self::register_fd()].
+    fn unregister_fd(&self, fd: c_int);
+}
+
+/// A timer registered through [`GuiContext::register_timer()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(pub(crate) u32);
+
+bitflags::bitflags! {
+    /// The events to watch a file descriptor for in [`GuiContext::register_fd()`], and the events
+    /// that occurred when the callback gets called.
+    #[repr(transparent)]
+    pub struct FdFlags: u32 {
+        /// The file descriptor can be read from.
+        const READ = 1 << 0;
+        /// The file descriptor can be written to.
+        const WRITE = 1 << 1;
+        /// An error occurred on the file descriptor.
+        const ERROR = 1 << 2;
+    }
 }
 
 /// An way to run background tasks from the plugin's GUI, equivalent to the
@@ -183,4 +308,45 @@ impl<'a> ParamSetter<'a> {
     pub fn end_set_parameter<P: Param>(&self, param: &P) {
         unsafe { self.raw_context.raw_end_set_parameter(param.as_ptr()) };
     }
+
+    /// Map the next MIDI CC, pitch bend, or channel pressure message the plugin receives to this
+    /// parameter. The learned mapping replaces the parameter's existing mapping, and any other
+    /// parameter mapped to the same controller loses its mapping. MIDI mappings are only applied
+    /// when the plugin's [`MIDI_INPUT`][crate::prelude::Plugin::MIDI_INPUT] is set to
+    /// [`MidiConfig::MidiCCs`][crate::prelude::MidiConfig::MidiCCs] or higher. Mapped messages
+    /// are not passed on to the plugin.
+    pub fn start_midi_learn<P: Param>(&self, param: &P) {
+        unsafe { self.raw_context.raw_start_midi_learn(param.as_ptr()) };
+    }
+
+    /// Stop waiting for a MIDI controller after calling
+    /// [`start_midi_learn()`][Self::start_midi_learn()].
+    pub fn cancel_midi_learn(&self) {
+        self.raw_context.cancel_midi_learn();
+    }
+
+    /// Whether this parameter is waiting for a MIDI controller to be learned.
+    pub fn is_midi_learning<P: Param>(&self, param: &P) -> bool {
+        self.raw_context.midi_learn_target() == Some(param.as_ptr())
+    }
+
+    /// Get the parameter's MIDI mapping, if it has one.
+    pub fn midi_mapping<P: Param>(&self, param: &P) -> Option<MidiMapping> {
+        unsafe { self.raw_context.raw_midi_mapping(param.as_ptr()) }
+    }
+
+    /// Set the parameter's MIDI mapping directly, for instance to change the range or curve of a
+    /// learned mapping. MIDI mappings are stored as part of the plugin's state, but they are not
+    /// included in presets.
+    pub fn set_midi_mapping<P: Param>(&self, param: &P, mapping: MidiMapping) {
+        unsafe {
+            self.raw_context
+                .raw_set_midi_mapping(param.as_ptr(), Some(mapping))
+        };
+    }
+
+    /// Remove the parameter's MIDI mapping.
+    pub fn forget_midi_mapping<P: Param>(&self, param: &P) {
+        unsafe { self.raw_context.raw_set_midi_mapping(param.as_ptr(), None) };
+    }
 }
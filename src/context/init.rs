@@ -1,6 +1,6 @@
 //! A context passed during plugin initialization.
 
-use super::PluginApi;
+use super::{HostInfo, PluginApi, TrackInfo};
 use crate::plugin::Plugin;
 
 /// Callbacks the plugin can make while it is being initialized. This is passed to the plugin during
@@ -34,4 +34,17 @@ pub trait InitContext<P: Plugin> {
     /// runtime allows the host to better optimize polyphonic modulation, or to switch to strictly
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
+
+    /// Inform the host that the names returned from
+    /// [`Plugin::note_names()`][crate::prelude::Plugin::note_names()] have changed. The host will
+    /// then query the new names from the main thread.
+    fn note_names_changed(&self);
+
+    /// Get information about the track the plugin is inserted on, such as the track's name and
+    /// color. Returns `None` if the host doesn't provide this information.
+    fn track_info(&self) -> Option<TrackInfo>;
+
+    /// Get information about the host the plugin is running in, such as the host's name and
+    /// version. See [`HostInfo`] for the fields each plugin API provides.
+    fn host_info(&self) -> &HostInfo;
 }
@@ -1,7 +1,9 @@
 //! A context passed during the process function.
 
-use super::PluginApi;
+use super::{HostInfo, PluginApi};
 use crate::midi::PluginNoteEvent;
+use crate::params::internals::ParamPtr;
+use crate::params::Param;
 use crate::plugin::Plugin;
 
 /// Contains both context data and callbacks the plugin can use during processing. Most notably this
@@ -38,7 +40,13 @@ pub trait ProcessContext<P: Plugin> {
     /// your task executor.
     fn execute_gui(&self, task: P::BackgroundTask);
 
-    /// Get information about the current transport position and status.
+    /// Get information about the current transport position and status at the start of the
+    /// current (sub)block. When a CLAP host changes the transport in the middle of a buffer, for
+    /// instance because the tempo is being automated or because playback jumped back to the start
+    /// of a loop, then the buffer is split up at that point and the rest of the buffer is processed
+    /// with the new transport information. This happens regardless of
+    /// [`Plugin::SAMPLE_ACCURATE_AUTOMATION`][crate::prelude::Plugin::SAMPLE_ACCURATE_AUTOMATION].
+    /// VST3 hosts only provide transport information at the start of every buffer.
     fn transport(&self) -> &Transport;
 
     /// Returns the next note event, if there is one. Use [`NoteEvent::timing()`] to get the event's
@@ -92,10 +100,102 @@ pub trait ProcessContext<P: Plugin> {
     /// monophonic modulation when dropping the capacity down to 1.
     fn set_current_voice_capacity(&self, capacity: u32);
 
-    // TODO: Add this, this works similar to [GuiContext::set_parameter] but it adds the parameter
-    //       change to a queue (or directly to the VST3 plugin's parameter output queues) instead of
-    //       using main thread host automation (and all the locks involved there).
-    // fn set_parameter<P: Param>(&self, param: &P, value: P::Plain);
+    /// Inform the host that the names returned from
+    /// [`Plugin::note_names()`][crate::prelude::Plugin::note_names()] have changed. The host will
+    /// then query the new names from the main thread. This is realtime-safe.
+    fn note_names_changed(&self);
+
+    /// Run `task` once for every index in `0..num_tasks`, possibly in parallel. This returns once
+    /// all tasks have finished. When the plugin is used in a CLAP host that supports the
+    /// `thread-pool` extension, the tasks are spread out over the host's audio worker threads.
+    /// Otherwise, or if the host refuses the request, the tasks are run one after another on the
+    /// calling thread. This can be used to, for instance, process every channel's FFT in parallel.
+    ///
+    /// This may only be called from the plugin's process function, and `task` must be
+    /// realtime-safe as it may run on the host's audio threads. Calls cannot be nested.
+    fn execute_parallel(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) {
+        for task_idx in 0..num_tasks {
+            task(task_idx);
+        }
+    }
+
+    /// Get information about the host the plugin is running in, such as the host's name and
+    /// version. See [`HostInfo`] for the fields each plugin API provides. This is realtime-safe.
+    fn host_info(&self) -> &HostInfo;
+
+    /// Inform the host that a parameter will be automated from the audio thread. Use
+    /// [`begin_set_parameter()`][Self::begin_set_parameter()] instead for a safe, user friendly
+    /// API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_begin_set_parameter(&mut self, param: ParamPtr);
+
+    /// Change a parameter's value from the audio thread using an already normalized value. Use
+    /// [`set_parameter()`][Self::set_parameter()] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32);
+
+    /// Inform the host that a parameter is no longer being automated from the audio thread. Use
+    /// [`end_set_parameter()`][Self::end_set_parameter()] instead for a safe, user friendly API.
+    ///
+    /// # Safety
+    ///
+    /// The implementing function still needs to check if `param` actually exists. This function is
+    /// mostly marked as unsafe for API reasons.
+    unsafe fn raw_end_set_parameter(&mut self, param: ParamPtr);
+
+    /// Inform the host that you will start automating a parameter from the audio thread. This
+    /// needs to be called before calling [`set_parameter()`][Self::set_parameter()] for the
+    /// specified parameter so the host can record the change as automation. The gesture may span
+    /// multiple process calls. This works the same as
+    /// [`ParamSetter::begin_set_parameter()`][crate::prelude::ParamSetter::begin_set_parameter()],
+    /// except that it is realtime-safe.
+    ///
+    /// VST3 has no notion of gestures for parameter changes sent by the audio processor, so this
+    /// does not do anything there.
+    fn begin_set_parameter<Pa: Param>(&mut self, param: &Pa) {
+        unsafe { self.raw_begin_set_parameter(param.as_ptr()) };
+    }
+
+    /// Change a parameter's value from the audio thread and send that change to the host. This is
+    /// useful for things like automatic gain staging, MIDI learn, or morphing between presets when
+    /// those changes should also end up in the host's automation lanes. The change is sent to the
+    /// host at the start of the current block, and the parameter's actual value (and its smoother)
+    /// is only updated once the current process call or block has finished. This way parameter
+    /// values never change while the plugin is processing audio.
+    ///
+    /// This should be wrapped in calls to [`begin_set_parameter()`][Self::begin_set_parameter()]
+    /// and [`end_set_parameter()`][Self::end_set_parameter()], except for read-only output
+    /// parameters marked with [`ParamFlags::OUTPUT`][crate::prelude::ParamFlags::OUTPUT]. Those
+    /// can be set directly.
+    fn set_parameter<Pa: Param>(&mut self, param: &Pa, value: Pa::Plain) {
+        let normalized = param.preview_normalized(value);
+        unsafe { self.raw_set_parameter_normalized(param.as_ptr(), normalized) };
+    }
+
+    /// Set a parameter to an already normalized value. Works exactly the same as
+    /// [`set_parameter()`][Self::set_parameter()] and needs to follow the same rules.
+    ///
+    /// This does not perform any snapping. Consider converting the normalized value to a plain
+    /// value and setting that with [`set_parameter()`][Self::set_parameter()] instead so the
+    /// normalized value known to the host matches `param.normalized_value()`.
+    fn set_parameter_normalized<Pa: Param>(&mut self, param: &Pa, normalized: f32) {
+        unsafe { self.raw_set_parameter_normalized(param.as_ptr(), normalized) };
+    }
+
+    /// Inform the host that you are done automating a parameter from the audio thread. This needs
+    /// to be called after one or more [`set_parameter()`][Self::set_parameter()] calls for a
+    /// parameter so the host knows the automation gesture has finished.
+    fn end_set_parameter<Pa: Param>(&mut self, param: &Pa) {
+        unsafe { self.raw_end_set_parameter(param.as_ptr()) };
+    }
 }
 
 /// Information about the plugin's transport. Depending on the plugin API and the host not all
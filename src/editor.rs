@@ -68,11 +68,85 @@ pub trait Editor: Send {
     /// loaded.
     fn param_values_changed(&self);
 
+    /// Called whenever the host changes a parameter's controller mapping or automation state while
+    /// the editor is open. The new indication can be read using
+    /// [`Param::indication()`][crate::prelude::Param::indication()]. This is only supported by
+    /// CLAP. The default implementation does nothing.
+    #[allow(unused_variables)]
+    fn param_indication_changed(&self, id: &str) {}
+
+    /// Called whenever the host changes the information about the track the plugin is inserted on
+    /// while the editor is open, for instance when the user renames the track. The new information
+    /// can be queried using [`GuiContext::track_info()`][crate::prelude::GuiContext::track_info()].
+    /// The default implementation does nothing.
+    fn track_info_changed(&self) {}
+
+    /// Returns the constraints the host needs to respect when resizing the editor, or `None` if the
+    /// host is not allowed to resize the editor. This is the default. Like
+    /// [`size()`][Self::size()], all sizes are in logical pixels.
+    fn resize_constraints(&self) -> Option<EditorResizeConstraints> {
+        None
+    }
+
+    /// Called when the host wants to resize the editor to `width` and `height` in logical pixels.
+    /// This is only called when [`resize_constraints()`][Self::resize_constraints()] returns a
+    /// value, and the size has already been constrained using
+    /// [`EditorResizeConstraints::constrain()`]. This may be called both while the editor is open
+    /// and before it has been spawned. After this function returns `true`,
+    /// [`size()`][Self::size()] should return the new size. If the editor cannot be resized right
+    /// now, then this should return `false` and the host will keep using the old size.
+    #[allow(unused_variables)]
+    fn set_size(&self, width: u32, height: u32) -> bool {
+        false
+    }
+
     // TODO: Reconsider adding a tick function here for the Linux `IRunLoop`. To keep this platform
     //       and API agnostic, add a way to ask the GuiContext if the wrapper already provides a
     //       tick function. If it does not, then the Editor implementation must handle this by
     //       itself. This would also need an associated `PREFERRED_FRAME_RATE` constant.
-    // TODO: Host->Plugin resizing
+}
+
+/// Describes how the host is allowed to resize an editor. Returned from
+/// [`Editor::resize_constraints()`]. All sizes are in logical pixels, so before any DPI scaling is
+/// applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditorResizeConstraints {
+    /// The smallest size the editor can have as a `(width, height)` pair.
+    pub min_size: (u32, u32),
+    /// The largest size the editor can have as a `(width, height)` pair, if there is a limit.
+    pub max_size: Option<(u32, u32)>,
+    /// If set, then the editor's size must always have this `(width, height)` aspect ratio. For
+    /// instance, `Some((16, 9))`.
+    pub aspect_ratio: Option<(u32, u32)>,
+}
+
+impl EditorResizeConstraints {
+    /// Constrain a size requested by the host to these constraints. If the editor has a fixed
+    /// aspect ratio, then this returns the largest size with that aspect ratio that fits within the
+    /// requested size and the size limits.
+    pub fn constrain(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        let (min_width, min_height) = self.min_size;
+        let (max_width, max_height) = self.max_size.unwrap_or((u32::MAX, u32::MAX));
+        let clamp_width = |width: u32| width.max(min_width).min(max_width);
+        let clamp_height = |height: u32| height.max(min_height).min(max_height);
+
+        let (width, height) = (clamp_width(width), clamp_height(height));
+        match self.aspect_ratio {
+            Some((ratio_width, ratio_height))
+                if ratio_width > 0 && ratio_height > 0 && height > 0 =>
+            {
+                let ratio = ratio_width as f64 / ratio_height as f64;
+                let (width, height) = if width as f64 / height as f64 > ratio {
+                    ((height as f64 * ratio).round() as u32, height)
+                } else {
+                    (width, (width as f64 / ratio).round() as u32)
+                };
+
+                (clamp_width(width), clamp_height(height))
+            }
+            _ => (width, height),
+        }
+    }
 }
 
 /// A raw window handle for platform and GUI framework agnostic editors.
@@ -85,3 +159,34 @@ unsafe impl HasRawWindowHandle for ParentWindowHandle {
         self.handle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrain_size_limits() {
+        let constraints = EditorResizeConstraints {
+            min_size: (200, 100),
+            max_size: Some((800, 400)),
+            aspect_ratio: None,
+        };
+
+        assert_eq!(constraints.constrain((100, 50)), (200, 100));
+        assert_eq!(constraints.constrain((500, 300)), (500, 300));
+        assert_eq!(constraints.constrain((1000, 1000)), (800, 400));
+    }
+
+    #[test]
+    fn constrain_aspect_ratio() {
+        let constraints = EditorResizeConstraints {
+            min_size: (200, 100),
+            max_size: None,
+            aspect_ratio: Some((2, 1)),
+        };
+
+        assert_eq!(constraints.constrain((600, 200)), (400, 200));
+        assert_eq!(constraints.constrain((600, 400)), (600, 300));
+        assert_eq!(constraints.constrain((100, 100)), (200, 100));
+    }
+}
@@ -2,9 +2,13 @@
 
 use midi_consts::channel_event as midi;
 
+use self::mpe::MpeZones;
 use self::sysex::SysExMessage;
 use crate::plugin::Plugin;
 
+pub mod learn;
+pub mod mpe;
+pub mod note_names;
 pub mod sysex;
 
 pub use midi_consts::channel_event::control_change;
@@ -33,11 +37,26 @@ pub enum MidiConfig {
     /// involves adding 130*16 parameters to bind to the the 128 MIDI CCs, pitch bend, and channel
     /// pressure.
     MidiCCs,
+    /// The same as [`MidiCCs`][Self::MidiCCs], but the plugin's note input is treated as MIDI
+    /// Polyphonic Expression (MPE) using the specified zone layout. Pitch bend, CC74, and channel
+    /// pressure messages on the zones' member channels are translated to
+    /// [`NoteEvent::PolyTuning`], [`NoteEvent::PolyBrightness`], and [`NoteEvent::PolyPressure`]
+    /// events for the notes playing on those channels. See [`MpeZones`] for more information.
+    ///
+    /// This only affects note input. Using this for a plugin's MIDI output is the same as using
+    /// [`MidiCCs`][Self::MidiCCs].
+    Mpe(MpeZones),
 }
 
-// FIXME: Like the voice ID, channel and note number can also be omitted in CLAP. And instead of an
-//        Option, maybe this should use a dedicated type to more clearly indicate that missing
-//        values should be treated as wildcards.
+impl MidiConfig {
+    /// The MPE zone layout, if MPE is enabled.
+    pub fn mpe_zones(&self) -> Option<MpeZones> {
+        match self {
+            MidiConfig::Mpe(zones) => Some(*zones),
+            _ => None,
+        }
+    }
+}
 
 /// Event for (incoming) notes. The set of supported note events depends on the value of
 /// [`Plugin::MIDI_INPUT`][crate::prelude::Plugin::MIDI_INPUT]. Also check out the
@@ -84,15 +103,18 @@ pub enum NoteEvent<S> {
     /// A note choke event, available on [`MidiConfig::Basic`] and up. When the host sends this to
     /// the plugin, it indicates that a voice or all sound associated with a note should immediately
     /// stop playing.
+    ///
+    /// CLAP hosts may omit the channel and the note number. Missing values act as wildcards, so a
+    /// choke event without a voice ID, channel, and note number should stop all voices.
     Choke {
         timing: u32,
         /// A unique identifier for this note, if available. Using this to refer to a note is
         /// required when allowing overlapping voices for CLAP plugins.
         voice_id: Option<i32>,
-        /// The note's channel, in `0..16`.
-        channel: u8,
-        /// The note's MIDI key number, in `0..128`.
-        note: u8,
+        /// The note's channel, in `0..16`. `None` matches notes on any channel.
+        channel: Option<u8>,
+        /// The note's MIDI key number, in `0..128`. `None` matches all notes.
+        note: Option<u8>,
     },
 
     /// Sent by the plugin to the host to indicate that a voice has ended. This **needs** to be sent
@@ -158,6 +180,36 @@ pub enum NoteEvent<S> {
         /// The normalized offset value. See the event's docstring for more information.
         normalized_offset: f32,
     },
+    /// A polyphonic modulation event that targets notes by their channel and key instead of by
+    /// their voice ID, available on [`MidiConfig::Basic`] and up. This is sent by CLAP hosts that
+    /// support per-key or per-channel modulation, like Bitwig Studio's per-key modulators. This is
+    /// only sent if the plugin enabled `supports_key_modulation` in its
+    /// [`PolyModulationConfig`][crate::prelude::PolyModulationConfig]. Just like with
+    /// [`PolyModulation`][Self::PolyModulation], this will only be sent for parameters that were
+    /// decorated with the `.with_poly_modulation_id()` modifier, and the event contains a
+    /// normalized offset for the parameter's unmodulated value.
+    ///
+    /// Unlike `PolyModulation`, this modulation is not tied to a single voice. The offset applies
+    /// to all current voices matching the event's channel and note number, as well as to any
+    /// matching voices that start afterwards, until another `KeyModulation` event for the same
+    /// channel and key changes it. Missing values act as wildcards. An offset of zero clears the
+    /// modulation. Since these offsets are not tied to voices, the host does not need a
+    /// `VoiceTerminated` event to release them.
+    KeyModulation {
+        timing: u32,
+        /// The channel of the notes this modulation applies to, in `0..16`. `None` matches notes
+        /// on any channel.
+        channel: Option<u8>,
+        /// The MIDI key number of the notes this modulation applies to, in `0..128`. `None`
+        /// matches all notes.
+        note: Option<u8>,
+        /// The ID that was set for the modulated parameter using the `.with_poly_modulation_id()`
+        /// method.
+        poly_modulation_id: u32,
+        /// The normalized offset value. See the `PolyModulation` event's docstring for more
+        /// information.
+        normalized_offset: f32,
+    },
     /// A notification to inform the plugin that a polyphonically modulated parameter has received a
     /// new automation value. This is used in conjunction with the `PolyModulation` event. See that
     /// event's documentation for more details. The parameter's global value has already been
@@ -347,6 +399,7 @@ impl<S> NoteEvent<S> {
             NoteEvent::Choke { timing, .. } => *timing,
             NoteEvent::VoiceTerminated { timing, .. } => *timing,
             NoteEvent::PolyModulation { timing, .. } => *timing,
+            NoteEvent::KeyModulation { timing, .. } => *timing,
             NoteEvent::MonoAutomation { timing, .. } => *timing,
             NoteEvent::PolyPressure { timing, .. } => *timing,
             NoteEvent::PolyVolume { timing, .. } => *timing,
@@ -371,6 +424,7 @@ impl<S> NoteEvent<S> {
             NoteEvent::Choke { voice_id, .. } => *voice_id,
             NoteEvent::VoiceTerminated { voice_id, .. } => *voice_id,
             NoteEvent::PolyModulation { voice_id, .. } => Some(*voice_id),
+            NoteEvent::KeyModulation { .. } => None,
             NoteEvent::MonoAutomation { .. } => None,
             NoteEvent::PolyPressure { voice_id, .. } => *voice_id,
             NoteEvent::PolyVolume { voice_id, .. } => *voice_id,
@@ -387,14 +441,16 @@ impl<S> NoteEvent<S> {
         }
     }
 
-    /// Returns the event's channel, if it has any.
+    /// Returns the event's channel, if it has any. This also returns `None` for events that match
+    /// notes on any channel.
     pub fn channel(&self) -> Option<u8> {
         match self {
             NoteEvent::NoteOn { channel, .. } => Some(*channel),
             NoteEvent::NoteOff { channel, .. } => Some(*channel),
-            NoteEvent::Choke { channel, .. } => Some(*channel),
+            NoteEvent::Choke { channel, .. } => *channel,
             NoteEvent::VoiceTerminated { channel, .. } => Some(*channel),
             NoteEvent::PolyModulation { .. } => None,
+            NoteEvent::KeyModulation { channel, .. } => *channel,
             NoteEvent::MonoAutomation { .. } => None,
             NoteEvent::PolyPressure { channel, .. } => Some(*channel),
             NoteEvent::PolyVolume { channel, .. } => Some(*channel),
@@ -518,6 +574,95 @@ impl<S: SysExMessage> NoteEvent<S> {
         }
     }
 
+    /// Parse a MIDI 2.0 Universal MIDI Packet into a [`NoteEvent`]. Supports MIDI 1.0 and MIDI 2.0
+    /// channel voice messages. The message's group is ignored. Per-note pitch bend is converted to
+    /// [`NoteEvent::PolyTuning`] using MIDI 2.0's default per-note pitch bend range of 48
+    /// semitones. Will return `Err(status)` if the message is not supported, where `status` is the
+    /// message's status nibble.
+    pub(crate) fn from_midi2(timing: u32, ump: &[u32; 4]) -> Result<Self, u8> {
+        const PER_NOTE_PITCH_BEND_RANGE: f32 = 48.0;
+
+        let message_type = (ump[0] >> 28) as u8;
+        let status = ((ump[0] >> 20) & 0xf) as u8;
+        let channel = ((ump[0] >> 16) & 0xf) as u8;
+        let index = ((ump[0] >> 8) & 0x7f) as u8;
+        let data = ump[1];
+
+        match message_type {
+            // MIDI 1.0 channel voice messages wrapped in a single word packet
+            0x2 => NoteEvent::from_midi(
+                timing,
+                &[
+                    (ump[0] >> 16) as u8,
+                    (ump[0] >> 8) as u8 & 0x7f,
+                    ump[0] as u8 & 0x7f,
+                ],
+            )
+            .map_err(|_| status),
+            0x4 => match status << 4 {
+                midi::NOTE_ON => Ok(NoteEvent::NoteOn {
+                    timing,
+                    voice_id: None,
+                    channel,
+                    note: index,
+                    velocity: (data >> 16) as f32 / u16::MAX as f32,
+                }),
+                midi::NOTE_OFF => Ok(NoteEvent::NoteOff {
+                    timing,
+                    voice_id: None,
+                    channel,
+                    note: index,
+                    velocity: (data >> 16) as f32 / u16::MAX as f32,
+                }),
+                midi::POLYPHONIC_KEY_PRESSURE => Ok(NoteEvent::PolyPressure {
+                    timing,
+                    voice_id: None,
+                    channel,
+                    note: index,
+                    pressure: data as f32 / u32::MAX as f32,
+                }),
+                // Per-note pitch bend
+                0x60 => Ok(NoteEvent::PolyTuning {
+                    timing,
+                    voice_id: None,
+                    channel,
+                    note: index,
+                    tuning: (data as f64 / u32::MAX as f64 * 2.0 - 1.0) as f32
+                        * PER_NOTE_PITCH_BEND_RANGE,
+                }),
+                midi::CONTROL_CHANGE => Ok(NoteEvent::MidiCC {
+                    timing,
+                    channel,
+                    cc: index,
+                    value: data as f32 / u32::MAX as f32,
+                }),
+                midi::PROGRAM_CHANGE => Ok(NoteEvent::MidiProgramChange {
+                    timing,
+                    channel,
+                    program: ((data >> 24) & 0x7f) as u8,
+                }),
+                midi::CHANNEL_KEY_PRESSURE => Ok(NoteEvent::MidiChannelPressure {
+                    timing,
+                    channel,
+                    pressure: data as f32 / u32::MAX as f32,
+                }),
+                midi::PITCH_BEND_CHANGE => Ok(NoteEvent::MidiPitchBend {
+                    timing,
+                    channel,
+                    value: data as f32 / u32::MAX as f32,
+                }),
+                _ => {
+                    nih_trace!("Unhandled MIDI 2.0 channel voice message: {ump:08x?}");
+                    Err(status)
+                }
+            },
+            _ => {
+                nih_trace!("Unhandled MIDI 2.0 message type {message_type:#x}");
+                Err(status)
+            }
+        }
+    }
+
     /// Create a MIDI message from this note event. Returns `None` if this even does not have a
     /// direct MIDI equivalent. `PolyPressure` will be converted to polyphonic key pressure, but the
     /// other polyphonic note expression types will not be converted to MIDI CC messages.
@@ -610,6 +755,7 @@ impl<S: SysExMessage> NoteEvent<S> {
             NoteEvent::Choke { .. }
             | NoteEvent::VoiceTerminated { .. }
             | NoteEvent::PolyModulation { .. }
+            | NoteEvent::KeyModulation { .. }
             | NoteEvent::MonoAutomation { .. }
             | NoteEvent::PolyVolume { .. }
             | NoteEvent::PolyPan { .. }
@@ -622,27 +768,39 @@ impl<S: SysExMessage> NoteEvent<S> {
 
     /// Subtract a sample offset from this event's timing, needed to compensate for the block
     /// splitting in the VST3 wrapper implementation because all events have to be read upfront.
-    #[cfg_attr(not(feature = "vst3"), allow(dead_code))]
     pub(crate) fn subtract_timing(&mut self, samples: u32) {
+        *self.timing_mut() -= samples;
+    }
+
+    /// Add a sample offset to this event's timing. This is the inverse of
+    /// [`subtract_timing()`][Self::subtract_timing()], and it's used to move output events from a
+    /// split block back to the start of the entire buffer.
+    pub(crate) fn add_timing(&mut self, samples: u32) {
+        *self.timing_mut() += samples;
+    }
+
+    /// A mutable reference to this event's timing field.
+    fn timing_mut(&mut self) -> &mut u32 {
         match self {
-            NoteEvent::NoteOn { timing, .. } => *timing -= samples,
-            NoteEvent::NoteOff { timing, .. } => *timing -= samples,
-            NoteEvent::Choke { timing, .. } => *timing -= samples,
-            NoteEvent::VoiceTerminated { timing, .. } => *timing -= samples,
-            NoteEvent::PolyModulation { timing, .. } => *timing -= samples,
-            NoteEvent::MonoAutomation { timing, .. } => *timing -= samples,
-            NoteEvent::PolyPressure { timing, .. } => *timing -= samples,
-            NoteEvent::PolyVolume { timing, .. } => *timing -= samples,
-            NoteEvent::PolyPan { timing, .. } => *timing -= samples,
-            NoteEvent::PolyTuning { timing, .. } => *timing -= samples,
-            NoteEvent::PolyVibrato { timing, .. } => *timing -= samples,
-            NoteEvent::PolyExpression { timing, .. } => *timing -= samples,
-            NoteEvent::PolyBrightness { timing, .. } => *timing -= samples,
-            NoteEvent::MidiChannelPressure { timing, .. } => *timing -= samples,
-            NoteEvent::MidiPitchBend { timing, .. } => *timing -= samples,
-            NoteEvent::MidiCC { timing, .. } => *timing -= samples,
-            NoteEvent::MidiProgramChange { timing, .. } => *timing -= samples,
-            NoteEvent::MidiSysEx { timing, .. } => *timing -= samples,
+            NoteEvent::NoteOn { timing, .. } => timing,
+            NoteEvent::NoteOff { timing, .. } => timing,
+            NoteEvent::Choke { timing, .. } => timing,
+            NoteEvent::VoiceTerminated { timing, .. } => timing,
+            NoteEvent::PolyModulation { timing, .. } => timing,
+            NoteEvent::KeyModulation { timing, .. } => timing,
+            NoteEvent::MonoAutomation { timing, .. } => timing,
+            NoteEvent::PolyPressure { timing, .. } => timing,
+            NoteEvent::PolyVolume { timing, .. } => timing,
+            NoteEvent::PolyPan { timing, .. } => timing,
+            NoteEvent::PolyTuning { timing, .. } => timing,
+            NoteEvent::PolyVibrato { timing, .. } => timing,
+            NoteEvent::PolyExpression { timing, .. } => timing,
+            NoteEvent::PolyBrightness { timing, .. } => timing,
+            NoteEvent::MidiChannelPressure { timing, .. } => timing,
+            NoteEvent::MidiPitchBend { timing, .. } => timing,
+            NoteEvent::MidiCC { timing, .. } => timing,
+            NoteEvent::MidiProgramChange { timing, .. } => timing,
+            NoteEvent::MidiSysEx { timing, .. } => timing,
         }
     }
 }
@@ -748,6 +906,40 @@ mod tests {
         assert_eq!(roundtrip_basic_event(event), event);
     }
 
+    #[test]
+    fn test_midi2_parsing() {
+        // Note on for note 60 on channel 2 with full velocity, followed by a per-note pitch bend of
+        // a quarter of the range down
+        let note_on = NoteEvent::<()>::from_midi2(TIMING, &[0x4092_3c00, 0xffff_0000, 0, 0]);
+        assert_eq!(
+            note_on,
+            Ok(NoteEvent::NoteOn {
+                timing: TIMING,
+                voice_id: None,
+                channel: 2,
+                note: 60,
+                velocity: 1.0,
+            })
+        );
+
+        match NoteEvent::<()>::from_midi2(TIMING, &[0x4062_3c00, 0x4000_0000, 0, 0]) {
+            Ok(NoteEvent::PolyTuning {
+                channel: 2,
+                note: 60,
+                tuning,
+                ..
+            }) => assert!((tuning + 24.0).abs() < 1e-3),
+            result => panic!("Unexpected result: {result:?}"),
+        }
+
+        // MIDI 1.0 messages in a UMP are parsed like regular MIDI
+        let pitch_bend = NoteEvent::<()>::from_midi2(TIMING, &[0x20e3_0040, 0, 0, 0]);
+        assert_eq!(
+            pitch_bend,
+            NoteEvent::from_midi(TIMING, &[0xe3, 0x00, 0x40])
+        );
+    }
+
     mod sysex {
         use super::*;
 
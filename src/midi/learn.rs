@@ -0,0 +1,132 @@
+//! Mappings from MIDI controllers to parameters, used for MIDI learn. The wrappers apply these
+//! mappings to the incoming events before the plugin's process function gets to see them, and the
+//! mappings are stored as part of the plugin's state.
+
+use serde::{Deserialize, Serialize};
+
+use super::NoteEvent;
+
+/// A MIDI controller that can be mapped to a parameter. All channels are zero-indexed, so MIDI
+/// channel 1 is channel 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiMappingSource {
+    /// A MIDI control change on a specific channel.
+    Cc { channel: u8, cc: u8 },
+    /// Pitch bend on a specific channel.
+    PitchBend { channel: u8 },
+    /// Channel pressure (aftertouch) on a specific channel.
+    ChannelPressure { channel: u8 },
+}
+
+/// How a controller's value is mapped to a parameter's normalized value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiMappingCurve {
+    /// The controller's value is mapped linearly to the mapping's range.
+    #[default]
+    Linear,
+    /// The controller's `[0, 1]` value is raised to this power before it's mapped to the mapping's
+    /// range. Values above 1 give more resolution at the bottom of the range, and values below 1
+    /// give more resolution at the top of the range.
+    Power(f32),
+}
+
+/// A mapping from a MIDI controller to a parameter. These can be created by the user through MIDI
+/// learn, or they can be set directly through
+/// [`ParamSetter::set_midi_mapping()`][crate::prelude::ParamSetter::set_midi_mapping()].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    /// The controller that changes the parameter.
+    pub source: MidiMappingSource,
+    /// The parameter's normalized value when the controller is at its minimum value.
+    pub min: f32,
+    /// The parameter's normalized value when the controller is at its maximum value. This may be
+    /// lower than `min` to invert the mapping.
+    pub max: f32,
+    /// The curve applied to the controller's value.
+    #[serde(default)]
+    pub curve: MidiMappingCurve,
+}
+
+impl MidiMappingSource {
+    /// Get the controller and its normalized `[0, 1]` value from a note event, if the event is a
+    /// controller that can be mapped.
+    pub(crate) fn from_event<S>(event: &NoteEvent<S>) -> Option<(Self, f32)> {
+        match *event {
+            NoteEvent::MidiCC {
+                channel, cc, value, ..
+            } => Some((MidiMappingSource::Cc { channel, cc }, value)),
+            NoteEvent::MidiPitchBend { channel, value, .. } => {
+                Some((MidiMappingSource::PitchBend { channel }, value))
+            }
+            NoteEvent::MidiChannelPressure {
+                channel, pressure, ..
+            } => Some((MidiMappingSource::ChannelPressure { channel }, pressure)),
+            _ => None,
+        }
+    }
+}
+
+impl MidiMapping {
+    /// Map a controller to a parameter's entire range.
+    pub fn new(source: MidiMappingSource) -> Self {
+        Self {
+            source,
+            min: 0.0,
+            max: 1.0,
+            curve: MidiMappingCurve::Linear,
+        }
+    }
+
+    /// Only map the controller to part of the parameter's range. Both values are normalized
+    /// parameter values. Setting `min` higher than `max` inverts the mapping.
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Change the curve applied to the controller's value.
+    pub fn with_curve(mut self, curve: MidiMappingCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Compute the parameter's normalized value for a normalized `[0, 1]` controller value.
+    pub fn normalized_value(&self, midi_value: f32) -> f32 {
+        let midi_value = midi_value.clamp(0.0, 1.0);
+        let curved_value = match self.curve {
+            MidiMappingCurve::Linear => midi_value,
+            MidiMappingCurve::Power(exponent) => midi_value.powf(exponent),
+        };
+
+        (self.min + ((self.max - self.min) * curved_value)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_range() {
+        let mapping =
+            MidiMapping::new(MidiMappingSource::Cc { channel: 0, cc: 1 }).with_range(0.25, 0.75);
+
+        assert_eq!(mapping.normalized_value(0.0), 0.25);
+        assert_eq!(mapping.normalized_value(0.5), 0.5);
+        assert_eq!(mapping.normalized_value(1.0), 0.75);
+    }
+
+    #[test]
+    fn inverted_power_curve() {
+        let mapping = MidiMapping::new(MidiMappingSource::PitchBend { channel: 0 })
+            .with_range(1.0, 0.0)
+            .with_curve(MidiMappingCurve::Power(2.0));
+
+        assert_eq!(mapping.normalized_value(0.0), 1.0);
+        assert_eq!(mapping.normalized_value(0.5), 0.75);
+        assert_eq!(mapping.normalized_value(1.0), 0.0);
+    }
+}
@@ -0,0 +1,112 @@
+//! Zone configuration for MIDI Polyphonic Expression (MPE).
+
+/// The MPE zone layout used with [`MidiConfig::Mpe`][super::MidiConfig::Mpe]. MPE controllers send
+/// every note on its own _member channel_ so pitch bend, CC74 (brightness/timbre), and channel
+/// pressure messages can be applied to individual notes. The wrapper translates those messages to
+/// [`NoteEvent::PolyTuning`][super::NoteEvent::PolyTuning],
+/// [`NoteEvent::PolyBrightness`][super::NoteEvent::PolyBrightness], and
+/// [`NoteEvent::PolyPressure`][super::NoteEvent::PolyPressure] events for the notes playing on that
+/// channel. Messages on a zone's manager channel and on channels that are not part of any zone are
+/// passed through as is.
+///
+/// All channel numbers here follow the rest of NIH-plug and are zero-indexed. MIDI channel 1 is
+/// channel 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MpeZones {
+    /// The lower zone. This zone uses channel 0 as its manager channel, and the channels directly
+    /// above it as its member channels.
+    pub lower: Option<MpeZone>,
+    /// The upper zone. This zone uses channel 15 as its manager channel, and the channels directly
+    /// below it as its member channels. If the two zones overlap, then the lower zone takes
+    /// precedence.
+    pub upper: Option<MpeZone>,
+}
+
+/// A single MPE zone. See [`MpeZones`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MpeZone {
+    /// The number of member channels in this zone, in `1..=15`.
+    pub member_channels: u8,
+    /// The pitch bend range for the zone's member channels in semitones. Pitch bend messages on a
+    /// member channel are converted to tuning offsets in `[-pitch_bend_range, pitch_bend_range]`.
+    /// MPE's default is 48 semitones.
+    pub pitch_bend_range: u8,
+}
+
+impl MpeZones {
+    /// A single lower zone using all 15 remaining channels as member channels. This is what most
+    /// MPE controllers use by default.
+    pub const LOWER: Self = Self {
+        lower: Some(MpeZone::new(15)),
+        upper: None,
+    };
+
+    /// A single upper zone using all 15 remaining channels as member channels.
+    pub const UPPER: Self = Self {
+        lower: None,
+        upper: Some(MpeZone::new(15)),
+    };
+
+    /// Get the zone `channel` is a member channel of, if any. Returns `None` for manager channels
+    /// and for channels outside of the configured zones.
+    pub fn member_zone(&self, channel: u8) -> Option<&MpeZone> {
+        if let Some(lower) = &self.lower {
+            if (1..=lower.member_channels).contains(&channel) {
+                return Some(lower);
+            }
+        }
+
+        if let Some(upper) = &self.upper {
+            if channel < 15 && channel >= 15u8.saturating_sub(upper.member_channels) {
+                return Some(upper);
+            }
+        }
+
+        None
+    }
+}
+
+impl MpeZone {
+    /// A zone with `member_channels` member channels and MPE's default member channel pitch bend
+    /// range of 48 semitones.
+    pub const fn new(member_channels: u8) -> Self {
+        Self {
+            member_channels,
+            pitch_bend_range: 48,
+        }
+    }
+
+    /// Change the member channel pitch bend range in semitones.
+    pub const fn with_pitch_bend_range(mut self, pitch_bend_range: u8) -> Self {
+        self.pitch_bend_range = pitch_bend_range;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn member_channels() {
+        let zones = MpeZones {
+            lower: Some(MpeZone::new(5)),
+            upper: Some(MpeZone::new(3).with_pitch_bend_range(24)),
+        };
+
+        assert_eq!(zones.member_zone(0), None);
+        assert_eq!(zones.member_zone(1), Some(&MpeZone::new(5)));
+        assert_eq!(zones.member_zone(5), Some(&MpeZone::new(5)));
+        assert_eq!(zones.member_zone(6), None);
+        assert_eq!(zones.member_zone(11), None);
+        assert_eq!(
+            zones.member_zone(12).map(|zone| zone.pitch_bend_range),
+            Some(24)
+        );
+        assert_eq!(
+            zones.member_zone(14).map(|zone| zone.pitch_bend_range),
+            Some(24)
+        );
+        assert_eq!(zones.member_zone(15), None);
+    }
+}
@@ -0,0 +1,48 @@
+//! Names for individual keys, shown in the host's piano roll or drum editor.
+
+/// A name for a single key, returned from
+/// [`Plugin::note_names()`][crate::prelude::Plugin::note_names()]. Drum samplers can use these to
+/// name their pads, and instruments with keyswitches can use them to label their articulations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteName {
+    /// The channel this name applies to, in `0..16`. `None` means that the name applies to all
+    /// channels.
+    pub channel: Option<u8>,
+    /// The MIDI key number this name applies to, in `0..128`.
+    pub note: u8,
+    /// The key's name.
+    pub name: String,
+    /// Whether this key is a keyswitch. Keyswitches change the instrument's articulation instead of
+    /// playing a note. VST3 hosts receive these through `IKeyswitchController` in addition to the
+    /// regular note names.
+    pub keyswitch: bool,
+}
+
+impl NoteName {
+    /// Name the key `note` on all channels.
+    pub fn new(note: u8, name: impl Into<String>) -> Self {
+        Self {
+            channel: None,
+            note,
+            name: name.into(),
+            keyswitch: false,
+        }
+    }
+
+    /// Only apply this name to a single channel.
+    pub fn with_channel(mut self, channel: u8) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Mark this key as a keyswitch.
+    pub fn make_keyswitch(mut self) -> Self {
+        self.keyswitch = true;
+        self
+    }
+
+    /// Whether this name applies to notes on `channel`.
+    pub fn matches_channel(&self, channel: u8) -> bool {
+        self.channel.map_or(true, |c| c == channel)
+    }
+}
@@ -8,6 +8,7 @@ use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
 use std::sync::Arc;
 
+use self::indication::ParamIndication;
 use self::internals::ParamPtr;
 
 // The proc-macro for deriving `Params`
@@ -19,10 +20,12 @@ pub mod enums;
 mod float;
 mod integer;
 
+pub mod indication;
 pub mod internals;
 pub mod persist;
 pub mod range;
 pub mod smoothing;
+pub mod snapshots;
 
 pub use boolean::BoolParam;
 pub use enums::EnumParam;
@@ -50,6 +53,13 @@ bitflags::bitflags! {
         /// Don't show this parameter when generating a generic UI for the plugin using one of
         /// NIH-plug's generic UI widgets.
         const HIDE_IN_GENERIC_UI = 1 << 3;
+        /// Marks the parameter as a read-only output parameter. These parameters are set by the
+        /// plugin from its process function using
+        /// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()],
+        /// and hosts can display their values as meters. Useful for things like gain reduction or
+        /// a detected pitch. This implies `NON_AUTOMATABLE`, and output parameters are not stored
+        /// as part of the plugin's state.
+        const OUTPUT = 1 << 4;
     }
 }
 
@@ -186,6 +196,11 @@ pub trait Param: Display + Debug + sealed::Sealed {
     /// Flags to control the parameter's behavior. See [`ParamFlags`].
     fn flags(&self) -> ParamFlags;
 
+    /// The host's controller mapping and automation indication for this parameter. Editors can use
+    /// this to display the mapping's color or the parameter's automation state. See
+    /// [`ParamIndication`].
+    fn indication(&self) -> ParamIndication;
+
     /// Internal implementation detail for implementing [`Params`][Params]. This should
     /// not be used directly.
     fn as_ptr(&self) -> internals::ParamPtr;
@@ -230,6 +245,9 @@ pub(crate) trait ParamMut: Param {
     /// restoring a plugin so everything is in sync. In that case the smoother should completely
     /// reset to the current value.
     fn update_smoother(&self, sample_rate: f32, reset: bool);
+
+    /// Update the host's indication for this parameter.
+    fn set_indication(&self, indication: ParamIndication);
 }
 
 /// Describes a struct containing parameters and other persistent fields.
@@ -249,6 +267,13 @@ pub(crate) trait ParamMut: Param {
 /// with the `#[persist = "key"]` attribute containing types that can be serialized and deserialized
 /// with [Serde](https://serde.rs/).
 ///
+/// ## `#[persist(key = "key", skip_presets)]`
+///
+/// The same as `#[persist = "key"]`, but the field is not stored in or restored from presets. The
+/// field is still saved as part of the host's project. This is useful for things like an editor's
+/// size or an analyzer's settings that should not be baked into presets shared between users. See
+/// [`StateContext::Preset`][crate::prelude::StateContext::Preset].
+///
 /// ## `#[nested]`, `#[nested(group_name = "group name")]`
 ///
 /// Finally, the `Params` object may include parameters from other objects. Setting a group name is
@@ -311,6 +336,13 @@ pub unsafe trait Params: 'static + Send + Sync {
     /// [`persist::deserialize_field()`] under the hood.
     #[allow(unused_variables)]
     fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {}
+
+    /// The keys of all fields marked with `#[persist(key = "stable_name", skip_presets)]`, as used
+    /// in [`serialize_fields()`][Self::serialize_fields()]. These fields are removed from the
+    /// serialized fields when saving or loading a preset.
+    fn preset_excluded_fields(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// This may be useful when building generic UIs using nested `Params` objects.
@@ -326,4 +358,8 @@ unsafe impl<P: Params> Params for Arc<P> {
     fn deserialize_fields(&self, serialized: &BTreeMap<String, String>) {
         self.as_ref().deserialize_fields(serialized)
     }
+
+    fn preset_excluded_fields(&self) -> Vec<String> {
+        self.as_ref().preset_excluded_fields()
+    }
 }
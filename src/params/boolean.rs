@@ -1,10 +1,12 @@
 //! Simple boolean parameters.
 
 use atomic_float::AtomicF32;
+use crossbeam::atomic::AtomicCell;
 use std::fmt::{Debug, Display};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use super::indication::ParamIndication;
 use super::internals::ParamPtr;
 use super::{Param, ParamFlags, ParamMut};
 
@@ -29,6 +31,9 @@ pub struct BoolParam {
 
     /// Flags to control the parameter's behavior. See [`ParamFlags`].
     flags: ParamFlags,
+    /// The host's mapping and automation indication for this parameter. See
+    /// [`Param::indication()`].
+    indication: AtomicCell<ParamIndication>,
     /// Optional callback for listening to value changes. The argument passed to this function is
     /// the parameter's new value. This should not do anything expensive as it may be called
     /// multiple times in rapid succession, and it can be run from both the GUI and the audio
@@ -164,6 +169,10 @@ impl Param for BoolParam {
         self.flags
     }
 
+    fn indication(&self) -> ParamIndication {
+        self.indication.load()
+    }
+
     fn as_ptr(&self) -> ParamPtr {
         ParamPtr::BoolParam(self as *const BoolParam as *mut BoolParam)
     }
@@ -224,6 +233,10 @@ impl ParamMut for BoolParam {
     fn update_smoother(&self, _sample_rate: f32, _init: bool) {
         // Can't really smooth a binary parameter now can you
     }
+
+    fn set_indication(&self, indication: ParamIndication) {
+        self.indication.store(indication);
+    }
 }
 
 impl BoolParam {
@@ -239,6 +252,7 @@ impl BoolParam {
             default,
 
             flags: ParamFlags::default(),
+            indication: AtomicCell::new(ParamIndication::default()),
             value_changed: None,
 
             name: name.into(),
@@ -332,4 +346,14 @@ impl BoolParam {
         self.flags.insert(ParamFlags::HIDE_IN_GENERIC_UI);
         self
     }
+
+    /// Mark this parameter as a read-only output parameter. The plugin can update the parameter's
+    /// value from its process function using
+    /// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()], and
+    /// hosts and NIH-plug's generic UIs will display the value as a meter. This implies
+    /// `NON_AUTOMATABLE`, and the parameter's value is not saved as part of the plugin's state.
+    pub fn make_output(mut self) -> Self {
+        self.flags.insert(ParamFlags::OUTPUT);
+        self
+    }
 }
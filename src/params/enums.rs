@@ -4,6 +4,7 @@ use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+use super::indication::ParamIndication;
 use super::internals::ParamPtr;
 use super::range::IntRange;
 use super::{IntParam, Param, ParamFlags, ParamMut};
@@ -205,6 +206,10 @@ impl<T: Enum + PartialEq> Param for EnumParam<T> {
         self.inner.flags()
     }
 
+    fn indication(&self) -> ParamIndication {
+        self.inner.indication()
+    }
+
     fn as_ptr(&self) -> ParamPtr {
         self.inner.as_ptr()
     }
@@ -292,6 +297,10 @@ impl Param for EnumParamInner {
         self.inner.flags()
     }
 
+    fn indication(&self) -> ParamIndication {
+        self.inner.indication()
+    }
+
     fn as_ptr(&self) -> ParamPtr {
         ParamPtr::EnumParam(self as *const EnumParamInner as *mut EnumParamInner)
     }
@@ -313,6 +322,10 @@ impl<T: Enum + PartialEq> ParamMut for EnumParam<T> {
     fn update_smoother(&self, sample_rate: f32, reset: bool) {
         self.inner.update_smoother(sample_rate, reset)
     }
+
+    fn set_indication(&self, indication: ParamIndication) {
+        self.inner.set_indication(indication)
+    }
 }
 
 impl ParamMut for EnumParamInner {
@@ -331,6 +344,10 @@ impl ParamMut for EnumParamInner {
     fn update_smoother(&self, sample_rate: f32, reset: bool) {
         self.inner.update_smoother(sample_rate, reset)
     }
+
+    fn set_indication(&self, indication: ParamIndication) {
+        self.inner.set_indication(indication)
+    }
 }
 
 impl<T: Enum + PartialEq + 'static> EnumParam<T> {
@@ -413,6 +430,16 @@ impl<T: Enum + PartialEq + 'static> EnumParam<T> {
         self.inner.inner = self.inner.inner.hide_in_generic_ui();
         self
     }
+
+    /// Mark this parameter as a read-only output parameter. The plugin can update the parameter's
+    /// value from its process function using
+    /// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()], and
+    /// hosts and NIH-plug's generic UIs will display the value as a meter. This implies
+    /// `NON_AUTOMATABLE`, and the parameter's value is not saved as part of the plugin's state.
+    pub fn make_output(mut self) -> Self {
+        self.inner.inner = self.inner.inner.make_output();
+        self
+    }
 }
 
 impl EnumParamInner {
@@ -446,4 +473,11 @@ impl EnumParamInner {
             None => false,
         }
     }
+
+    /// Whether this enum has a variant with this stable string identifier.
+    pub(crate) fn has_id(&self, id: &str) -> bool {
+        self.ids
+            .map(|ids| ids.iter().any(|candidate| *candidate == id))
+            .unwrap_or(false)
+    }
 }
@@ -1,10 +1,12 @@
 //! Continuous (or discrete, with a step size) floating point parameters.
 
 use atomic_float::AtomicF32;
+use crossbeam::atomic::AtomicCell;
 use std::fmt::{Debug, Display};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use super::indication::ParamIndication;
 use super::internals::ParamPtr;
 use super::range::FloatRange;
 use super::smoothing::{Smoother, SmoothingStyle};
@@ -35,6 +37,9 @@ pub struct FloatParam {
 
     /// Flags to control the parameter's behavior. See [`ParamFlags`].
     flags: ParamFlags,
+    /// The host's mapping and automation indication for this parameter. See
+    /// [`Param::indication()`].
+    indication: AtomicCell<ParamIndication>,
     /// Optional callback for listening to value changes. The argument passed to this function is
     /// the parameter's new **plain** value. This should not do anything expensive as it may be
     /// called multiple times in rapid succession.
@@ -198,6 +203,10 @@ impl Param for FloatParam {
         self.flags
     }
 
+    fn indication(&self) -> ParamIndication {
+        self.indication.load()
+    }
+
     fn as_ptr(&self) -> ParamPtr {
         ParamPtr::FloatParam(self as *const _ as *mut _)
     }
@@ -263,6 +272,10 @@ impl ParamMut for FloatParam {
                 .set_target(sample_rate, self.modulated_plain_value());
         }
     }
+
+    fn set_indication(&self, indication: ParamIndication) {
+        self.indication.store(indication);
+    }
 }
 
 impl FloatParam {
@@ -279,6 +292,7 @@ impl FloatParam {
             smoothed: Smoother::none(),
 
             flags: ParamFlags::default(),
+            indication: AtomicCell::new(ParamIndication::default()),
             value_changed: None,
 
             range,
@@ -410,6 +424,16 @@ impl FloatParam {
         self.flags.insert(ParamFlags::HIDE_IN_GENERIC_UI);
         self
     }
+
+    /// Mark this parameter as a read-only output parameter. The plugin can update the parameter's
+    /// value from its process function using
+    /// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()], and
+    /// hosts and NIH-plug's generic UIs will display the value as a meter. This implies
+    /// `NON_AUTOMATABLE`, and the parameter's value is not saved as part of the plugin's state.
+    pub fn make_output(mut self) -> Self {
+        self.flags.insert(ParamFlags::OUTPUT);
+        self
+    }
 }
 
 /// Calculate how many decimals to round to when displaying a floating point value with a specific
@@ -0,0 +1,73 @@
+//! Information the host provides about a parameter's controller mapping and automation state, so
+//! editors can display this next to the parameter.
+
+/// The host's indication for a parameter. This is currently only provided by CLAP hosts that
+/// implement the param indication extension, and it can be read from the editor using
+/// [`Param::indication()`][super::Param::indication()].
+/// [`Editor::param_indication_changed()`][crate::prelude::Editor::param_indication_changed()] is
+/// called when it changes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParamIndication {
+    /// Whether the parameter is mapped to a hardware controller or to some other control in the
+    /// host.
+    pub mapped: bool,
+    /// The color the host uses to display the mapping, if the host provided one.
+    pub mapping_color: Option<IndicationColor>,
+    /// The parameter's automation state.
+    pub automation: AutomationState,
+    /// The color the host uses to display the parameter's automation, if the host provided one.
+    pub automation_color: Option<IndicationColor>,
+}
+
+/// The automation state of a parameter in the host.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AutomationState {
+    /// The host doesn't have any automation for this parameter.
+    #[default]
+    None,
+    /// The host has automation for this parameter, but it isn't playing.
+    Present,
+    /// The host is playing back automation for this parameter.
+    Playing,
+    /// The host is recording automation for this parameter.
+    Recording,
+    /// The host should play back automation for this parameter, but the user has started adjusting
+    /// the parameter and is overriding the automation.
+    Overriding,
+}
+
+/// A color used for [`ParamIndication`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndicationColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl ParamIndication {
+    /// Whether the host has provided any indication for this parameter. Editors can use this to
+    /// skip drawing the indication altogether.
+    pub fn is_empty(&self) -> bool {
+        !self.mapped && self.automation == AutomationState::None
+    }
+}
+
+impl AutomationState {
+    /// Whether there is automation for this parameter, regardless of whether it's playing or not.
+    pub fn is_automated(&self) -> bool {
+        *self != AutomationState::None
+    }
+}
+
+impl IndicationColor {
+    /// Create a color from its 8-bit components.
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+}
@@ -1,10 +1,12 @@
 //! Stepped integer parameters.
 
 use atomic_float::AtomicF32;
+use crossbeam::atomic::AtomicCell;
 use std::fmt::{Debug, Display};
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 
+use super::indication::ParamIndication;
 use super::internals::ParamPtr;
 use super::range::IntRange;
 use super::smoothing::{Smoother, SmoothingStyle};
@@ -35,6 +37,9 @@ pub struct IntParam {
 
     /// Flags to control the parameter's behavior. See [`ParamFlags`].
     flags: ParamFlags,
+    /// The host's mapping and automation indication for this parameter. See
+    /// [`Param::indication()`].
+    indication: AtomicCell<ParamIndication>,
     /// Optional callback for listening to value changes. The argument passed to this function is
     /// the parameter's new **plain** value. This should not do anything expensive as it may be
     /// called multiple times in rapid succession.
@@ -178,6 +183,10 @@ impl Param for IntParam {
         self.flags
     }
 
+    fn indication(&self) -> ParamIndication {
+        self.indication.load()
+    }
+
     fn as_ptr(&self) -> ParamPtr {
         ParamPtr::IntParam(self as *const _ as *mut _)
     }
@@ -243,6 +252,10 @@ impl ParamMut for IntParam {
                 .set_target(sample_rate, self.modulated_plain_value());
         }
     }
+
+    fn set_indication(&self, indication: ParamIndication) {
+        self.indication.store(indication);
+    }
 }
 
 impl IntParam {
@@ -259,6 +272,7 @@ impl IntParam {
             smoothed: Smoother::none(),
 
             flags: ParamFlags::default(),
+            indication: AtomicCell::new(ParamIndication::default()),
             value_changed: None,
 
             range,
@@ -379,4 +393,14 @@ impl IntParam {
         self.flags.insert(ParamFlags::HIDE_IN_GENERIC_UI);
         self
     }
+
+    /// Mark this parameter as a read-only output parameter. The plugin can update the parameter's
+    /// value from its process function using
+    /// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()], and
+    /// hosts and NIH-plug's generic UIs will display the value as a meter. This implies
+    /// `NON_AUTOMATABLE`, and the parameter's value is not saved as part of the plugin's state.
+    pub fn make_output(mut self) -> Self {
+        self.flags.insert(ParamFlags::OUTPUT);
+        self
+    }
 }
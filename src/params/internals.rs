@@ -1,5 +1,6 @@
 //! Implementation details for the parameter management.
 
+use super::indication::ParamIndication;
 use super::{Param, ParamFlags, ParamMut};
 
 /// Internal pointers to parameters. This is an implementation detail used by the wrappers for type
@@ -73,10 +74,12 @@ impl ParamPtr {
     param_ptr_forward!(pub unsafe fn normalized_value_to_string(&self, normalized: f32, include_unit: bool) -> String);
     param_ptr_forward!(pub unsafe fn string_to_normalized_value(&self, string: &str) -> Option<f32>);
     param_ptr_forward!(pub unsafe fn flags(&self) -> ParamFlags);
+    param_ptr_forward!(pub unsafe fn indication(&self) -> ParamIndication);
 
     param_ptr_forward!(pub(crate) unsafe fn set_normalized_value(&self, normalized: f32) -> bool);
     param_ptr_forward!(pub(crate) unsafe fn modulate_value(&self, modulation_offset: f32) -> bool);
     param_ptr_forward!(pub(crate) unsafe fn update_smoother(&self, sample_rate: f32, reset: bool));
+    param_ptr_forward!(pub(crate) unsafe fn set_indication(&self, indication: ParamIndication));
 
     // These functions involve casts since the plugin formats only do floating point types, so we
     // can't generate them with the macro:
@@ -0,0 +1,411 @@
+//! Snapshot slots for A/B comparisons. A [`Snapshots`] object stores a number of parameter
+//! snapshots, one of which is the active slot. Selecting another slot stores the plugin's current
+//! parameter values in the active slot and then returns the changes needed to recall the selected
+//! slot. Snapshots can also be copied, swapped, and morphed between.
+//!
+//! None of these functions change any parameter values by themselves. Instead they return a
+//! [`SnapshotChanges`] object that should be applied using [`SnapshotChanges::apply()`] or the
+//! GUI framework's parameter events, so the changes are sent to the host as regular parameter
+//! gestures.
+//!
+//! ```ignore
+//! #[derive(Params)]
+//! struct MyParams {
+//!     #[persist = "snapshots"]
+//!     snapshots: Arc<Snapshots>,
+//!
+//!     #[id = "gain"]
+//!     gain: FloatParam,
+//! }
+//!
+//! // In the editor, `params` is an `Arc<MyParams>`
+//! params
+//!     .snapshots
+//!     .select(1, params.as_ref())
+//!     .apply(&setter);
+//! ```
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use super::internals::ParamPtr;
+use super::persist::PersistentField;
+use super::{ParamFlags, Params};
+use crate::context::gui::ParamSetter;
+
+/// The normalized values of all of a plugin's parameters at some point in time. Output parameters
+/// and the bypass parameter are not included.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The parameters' normalized values, indexed by parameter ID.
+    pub values: BTreeMap<String, f32>,
+}
+
+/// A number of snapshot slots, one of which is active. See the module's documentation for more
+/// information. This can be stored in a `#[persist = "..."]` field on the plugin's parameters
+/// struct to persist the snapshots as part of the plugin's state.
+pub struct Snapshots {
+    slots: Mutex<SnapshotSlots>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotSlots {
+    slots: Vec<Option<Snapshot>>,
+    active_slot: usize,
+}
+
+/// Parameter changes produced by [`Snapshots`]. These need to be applied to the plugin's
+/// parameters using [`apply()`][Self::apply()], or by sending the
+/// [`iter()`][Self::iter()]'s values through the GUI framework's parameter events.
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotChanges(Vec<(ParamPtr, f32)>);
+
+impl Snapshot {
+    /// Capture the current unmodulated values of `params`.
+    pub fn capture(params: &dyn Params) -> Self {
+        let values = params
+            .param_map()
+            .into_iter()
+            .filter(|(_, param_ptr, _)| unsafe { is_snapshot_param(*param_ptr) })
+            .map(|(param_id, param_ptr, _)| {
+                (param_id, unsafe {
+                    param_ptr.unmodulated_normalized_value()
+                })
+            })
+            .collect();
+
+        Self { values }
+    }
+
+    /// The changes needed to set `params` to the values from this snapshot. Parameters that
+    /// already have the snapshot's value and parameters that are not in the snapshot are left
+    /// alone.
+    pub fn changes(&self, params: &dyn Params) -> SnapshotChanges {
+        SnapshotChanges::from_values(params, |param_id, _| self.values.get(param_id).copied())
+    }
+}
+
+impl Snapshots {
+    /// Create an object with `num_slots` empty slots. The first slot is active. Use two slots for
+    /// regular A/B comparisons.
+    pub fn new(num_slots: usize) -> Arc<Self> {
+        Arc::new(Self::with_slots(num_slots))
+    }
+
+    fn with_slots(num_slots: usize) -> Self {
+        nih_debug_assert!(num_slots > 0, "Snapshots need at least one slot");
+
+        Self {
+            slots: Mutex::new(SnapshotSlots {
+                slots: vec![None; num_slots.max(1)],
+                active_slot: 0,
+            }),
+        }
+    }
+
+    /// A name for slot `idx`. The first 26 slots are named `A` through `Z`, and any slots after
+    /// that are numbered.
+    pub fn slot_name(idx: usize) -> String {
+        if idx < 26 {
+            String::from((b'A' + idx as u8) as char)
+        } else {
+            (idx + 1).to_string()
+        }
+    }
+
+    /// The number of slots.
+    pub fn num_slots(&self) -> usize {
+        self.slots.lock().slots.len()
+    }
+
+    /// The index of the active slot.
+    pub fn active_slot(&self) -> usize {
+        self.slots.lock().active_slot
+    }
+
+    /// Get the snapshot stored in slot `idx`, if there is one. The active slot's snapshot is only
+    /// updated when switching to another slot or when calling [`store()`][Self::store()].
+    pub fn snapshot(&self, idx: usize) -> Option<Snapshot> {
+        self.slots.lock().slots.get(idx).cloned().flatten()
+    }
+
+    /// Store the current parameter values in slot `idx`.
+    pub fn store(&self, idx: usize, params: &dyn Params) {
+        let snapshot = Snapshot::capture(params);
+        if let Some(slot) = self.slots.lock().slots.get_mut(idx) {
+            *slot = Some(snapshot);
+        } else {
+            nih_debug_assert_failure!("Snapshot slot {} does not exist", idx);
+        }
+    }
+
+    /// Remove the snapshot stored in slot `idx`.
+    pub fn clear(&self, idx: usize) {
+        if let Some(slot) = self.slots.lock().slots.get_mut(idx) {
+            *slot = None;
+        }
+    }
+
+    /// Make slot `idx` the active slot. The current parameter values are stored in the previously
+    /// active slot first. If the selected slot is empty, then it is initialized with the current
+    /// values so both slots start out the same. Returns the changes needed to recall the selected
+    /// slot.
+    pub fn select(&self, idx: usize, params: &dyn Params) -> SnapshotChanges {
+        let current = Snapshot::capture(params);
+        let mut slots = self.slots.lock();
+        if idx >= slots.slots.len() {
+            nih_debug_assert_failure!("Snapshot slot {} does not exist", idx);
+            return SnapshotChanges::default();
+        }
+
+        let active_slot = slots.active_slot;
+        slots.slots[active_slot] = Some(current.clone());
+        slots.active_slot = idx;
+
+        slots.slots[idx].get_or_insert(current).changes(params)
+    }
+
+    /// Copy slot `from` to slot `to`. If `from` is the active slot, then the current parameter
+    /// values are copied. If `to` is the active slot, then the returned changes recall the copied
+    /// snapshot.
+    pub fn copy(&self, from: usize, to: usize, params: &dyn Params) -> SnapshotChanges {
+        let mut slots = self.slots.lock();
+        if from >= slots.slots.len() || to >= slots.slots.len() {
+            nih_debug_assert_failure!("Snapshot slot {} or {} does not exist", from, to);
+            return SnapshotChanges::default();
+        }
+
+        if from == slots.active_slot {
+            slots.slots[from] = Some(Snapshot::capture(params));
+        }
+        slots.slots[to] = slots.slots[from].clone();
+
+        match &slots.slots[to] {
+            Some(snapshot) if to == slots.active_slot => snapshot.changes(params),
+            _ => SnapshotChanges::default(),
+        }
+    }
+
+    /// Swap the contents of slots `a` and `b`. The active slot stays the same, so if either slot
+    /// is active then the returned changes recall the snapshot that's been swapped into it.
+    pub fn swap(&self, a: usize, b: usize, params: &dyn Params) -> SnapshotChanges {
+        let mut slots = self.slots.lock();
+        if a >= slots.slots.len() || b >= slots.slots.len() {
+            nih_debug_assert_failure!("Snapshot slot {} or {} does not exist", a, b);
+            return SnapshotChanges::default();
+        }
+
+        let active_slot = slots.active_slot;
+        slots.slots[active_slot] = Some(Snapshot::capture(params));
+        slots.slots.swap(a, b);
+
+        match &slots.slots[active_slot] {
+            Some(snapshot) => snapshot.changes(params),
+            None => SnapshotChanges::default(),
+        }
+    }
+
+    /// Interpolate between the snapshots stored in slots `a` and `b`. An `amount` of 0.0 recalls
+    /// `a`, and 1.0 recalls `b`. Continuous parameters are interpolated in their normalized
+    /// ranges, and stepped parameters like integer, boolean, and enum parameters jump from `a`'s
+    /// value to `b`'s value halfway through. Returns no changes if either slot is empty.
+    ///
+    /// This uses the stored snapshots, so call [`store()`][Self::store()] first if one of the
+    /// slots is the active slot and the current values should be used. The active slot is not
+    /// changed.
+    pub fn morph(&self, a: usize, b: usize, amount: f32, params: &dyn Params) -> SnapshotChanges {
+        let (a, b) = match (self.snapshot(a), self.snapshot(b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return SnapshotChanges::default(),
+        };
+        let amount = amount.clamp(0.0, 1.0);
+
+        SnapshotChanges::from_values(params, |param_id, param_ptr| {
+            let a_value = *a.values.get(param_id)?;
+            let b_value = *b.values.get(param_id)?;
+            match unsafe { param_ptr.step_count() } {
+                None => Some(a_value + ((b_value - a_value) * amount)),
+                Some(_) if amount < 0.5 => Some(a_value),
+                Some(_) => Some(b_value),
+            }
+        })
+    }
+}
+
+impl Default for Snapshots {
+    fn default() -> Self {
+        Self::with_slots(2)
+    }
+}
+
+impl Serialize for Snapshots {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.slots.lock().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Snapshots {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            slots: Mutex::new(SnapshotSlots::deserialize(deserializer)?),
+        })
+    }
+}
+
+impl<'a> PersistentField<'a, Snapshots> for Arc<Snapshots> {
+    fn set(&self, new_value: Snapshots) {
+        // The number of slots is decided by the plugin, so restored states with a different
+        // number of slots are truncated or padded
+        let mut slots = self.slots.lock();
+        let num_slots = slots.slots.len();
+        *slots = new_value.slots.into_inner();
+        slots.slots.resize(num_slots, None);
+        if slots.active_slot >= num_slots {
+            slots.active_slot = 0;
+        }
+    }
+
+    fn map<F, R>(&self, f: F) -> R
+    where
+        F: Fn(&Snapshots) -> R,
+    {
+        f(self)
+    }
+}
+
+impl SnapshotChanges {
+    /// Compute the changes for every snapshot parameter in `params`. `value` returns the new
+    /// normalized value for a parameter, or `None` if the parameter should not be changed.
+    fn from_values(
+        params: &dyn Params,
+        mut value: impl FnMut(&str, ParamPtr) -> Option<f32>,
+    ) -> Self {
+        let changes = params
+            .param_map()
+            .into_iter()
+            .filter(|(_, param_ptr, _)| unsafe { is_snapshot_param(*param_ptr) })
+            .filter_map(|(param_id, param_ptr, _)| {
+                let normalized = value(&param_id, param_ptr)?.clamp(0.0, 1.0);
+                if normalized != unsafe { param_ptr.unmodulated_normalized_value() } {
+                    Some((param_ptr, normalized))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Self(changes)
+    }
+
+    /// Whether there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The changed parameters along with their new normalized values.
+    pub fn iter(&self) -> impl Iterator<Item = (ParamPtr, f32)> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Apply the changes through a [`ParamSetter`]. All changed parameters are part of a single
+    /// gesture, so the host and the plugin's undo history treat the changes as a single action.
+    pub fn apply(self, setter: &ParamSetter) {
+        let context = setter.raw_context;
+        unsafe {
+            for (param_ptr, _) in &self.0 {
+                context.raw_begin_set_parameter(*param_ptr);
+            }
+            for (param_ptr, normalized) in &self.0 {
+                context.raw_set_parameter_normalized(*param_ptr, *normalized);
+            }
+            for (param_ptr, _) in &self.0 {
+                context.raw_end_set_parameter(*param_ptr);
+            }
+        }
+    }
+}
+
+/// Whether a parameter should be part of a snapshot. Output parameters are set by the plugin, and
+/// recalling a snapshot should never toggle the plugin's bypass.
+///
+/// # Safety
+///
+/// `param_ptr` needs to point to a parameter that's still alive.
+unsafe fn is_snapshot_param(param_ptr: ParamPtr) -> bool {
+    !param_ptr
+        .flags()
+        .intersects(ParamFlags::OUTPUT | ParamFlags::BYPASS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::range::{FloatRange, IntRange};
+    use crate::params::{FloatParam, IntParam, Param, ParamMut};
+
+    struct TestParams {
+        gain: FloatParam,
+        mode: IntParam,
+    }
+
+    unsafe impl Params for TestParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            vec![
+                (String::from("gain"), self.gain.as_ptr(), String::new()),
+                (String::from("mode"), self.mode.as_ptr(), String::new()),
+            ]
+        }
+    }
+
+    fn test_params() -> TestParams {
+        TestParams {
+            gain: FloatParam::new("Gain", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            mode: IntParam::new("Mode", 0, IntRange::Linear { min: 0, max: 1 }),
+        }
+    }
+
+    #[test]
+    fn select_recalls_slots() {
+        let params = test_params();
+        let snapshots = Snapshots::new(2);
+
+        // Selecting an empty slot copies the current values
+        assert!(snapshots.select(1, &params).is_empty());
+        params.gain.set_normalized_value(0.75);
+
+        let changes = snapshots.select(0, &params);
+        assert_eq!(snapshots.active_slot(), 0);
+        assert_eq!(
+            changes.iter().collect::<Vec<_>>(),
+            [(params.gain.as_ptr(), 0.0)]
+        );
+        params.gain.set_normalized_value(0.0);
+
+        let changes = snapshots.select(1, &params);
+        assert_eq!(
+            changes.iter().collect::<Vec<_>>(),
+            [(params.gain.as_ptr(), 0.75)]
+        );
+    }
+
+    #[test]
+    fn morph_interpolates_continuous_params() {
+        let params = test_params();
+        let snapshots = Snapshots::new(2);
+        snapshots.store(0, &params);
+        params.gain.set_normalized_value(1.0);
+        params.mode.set_normalized_value(1.0);
+        snapshots.store(1, &params);
+
+        let changes = snapshots.morph(0, 1, 0.25, &params);
+        assert_eq!(
+            changes.iter().collect::<Vec<_>>(),
+            [(params.gain.as_ptr(), 0.25), (params.mode.as_ptr(), 0.0)]
+        );
+        assert!(snapshots.morph(1, 0, 0.0, &params).is_empty());
+        assert!(snapshots.morph(0, 2, 0.5, &params).is_empty());
+    }
+}
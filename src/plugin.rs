@@ -8,11 +8,14 @@ use crate::context::gui::AsyncExecutor;
 use crate::context::init::InitContext;
 use crate::context::process::ProcessContext;
 use crate::editor::Editor;
+use crate::midi::note_names::NoteName;
 use crate::midi::sysex::SysExMessage;
 use crate::midi::MidiConfig;
 use crate::params::Params;
 use crate::wrapper::clap::features::ClapFeature;
-use crate::wrapper::state::PluginState;
+use crate::wrapper::clap::remote_controls::ClapRemoteControls;
+use crate::wrapper::state::migration::StateMigrations;
+use crate::wrapper::state::{FactoryPreset, PluginState, StateContext};
 #[cfg(feature = "vst3")]
 pub use crate::wrapper::vst3::subcategories::Vst3SubCategory;
 
@@ -27,12 +30,6 @@ pub type TaskExecutor<P> = Box<dyn Fn(<P as Plugin>::BackgroundTask) + Send>;
 /// The main thing you need to do is define a `[Params]` struct containing all of your parameters.
 /// See the trait's documentation for more information on how to do that, or check out the examples.
 /// Most of the other functionality is optional and comes with default trait method implementations.
-///
-/// Some notable not yet implemented features include:
-///
-/// - MIDI2 for CLAP, note expressions, polyphonic modulation and MIDI1, and MIDI SysEx are already
-///   supported
-/// - Audio thread thread pools (with host integration in CLAP)
 #[allow(unused_variables)]
 pub trait Plugin: Default + Send + 'static {
     /// The plugin's name.
@@ -47,6 +44,11 @@ pub trait Plugin: Default + Send + 'static {
     /// Semver compatible version string (e.g. `0.0.1`). Hosts likely won't do anything with this,
     /// but just in case they do this should only contain decimals values and dots.
     const VERSION: &'static str;
+    /// A stable identifier for the plugin. This is stored in
+    /// [`PresetFile`][crate::prelude::PresetFile]s to detect presets that were saved for a
+    /// different plugin. Unlike [`NAME`][Self::NAME] this should never change, so something like
+    /// the plugin's CLAP ID is a good choice.
+    const PRESET_ID: &'static str;
 
     /// The plugin's supported audio IO layouts. The first config will be used as the default config
     /// if the host doesn't or can't select an alternative configuration. Because of that it's
@@ -55,7 +57,8 @@ pub trait Plugin: Default + Send + 'static {
     /// auxiliary input and output ports, if the plugin has any. If the slice is empty, then the
     /// plugin will not have any audio IO.
     ///
-    /// Both [`AudioIOLayout`] and [`PortNames`][crate::prelude::PortNames] have `.const_default()`
+    /// [`AudioIOLayout`], [`PortNames`][crate::prelude::PortNames], and
+    /// [`PortChannelLayouts`][crate::prelude::PortChannelLayouts] all have `.const_default()`
     /// functions for compile-time equivalents to `Default::default()`:
     ///
     /// ```
@@ -70,6 +73,26 @@ pub trait Plugin: Default + Send + 'static {
     /// }];
     /// ```
     ///
+    /// Surround and ambisonic ports need to have their
+    /// [`ChannelLayout`][crate::prelude::ChannelLayout] set explicitly, since the host can't tell
+    /// what the channels mean otherwise:
+    ///
+    /// ```
+    /// # use nih_plug::prelude::*;
+    /// const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
+    ///     main_input_channels: NonZeroU32::new(6),
+    ///     main_output_channels: NonZeroU32::new(6),
+    ///
+    ///     channel_layouts: PortChannelLayouts {
+    ///         main_input: Some(ChannelLayout::Surround51),
+    ///         main_output: Some(ChannelLayout::Surround51),
+    ///         ..PortChannelLayouts::const_default()
+    ///     },
+    ///
+    ///     ..AudioIOLayout::const_default()
+    /// }];
+    /// ```
+    ///
     /// # Note
     ///
     /// Some plugin hosts, like Ableton Live, don't support MIDI-only plugins and may refuse to load
@@ -87,7 +110,8 @@ pub trait Plugin: Default + Send + 'static {
     /// If enabled, the audio processing cycle may be split up into multiple smaller chunks if
     /// parameter values change occur in the middle of the buffer. Depending on the host these
     /// blocks may be as small as a single sample. Bitwig Studio sends at most one parameter change
-    /// every 64 samples.
+    /// every 64 samples. Buffers are always split on mid-buffer transport changes, see
+    /// [`ProcessContext::transport()`][crate::prelude::ProcessContext::transport()].
     const SAMPLE_ACCURATE_AUTOMATION: bool = false;
 
     /// If this is set to true, then the plugin will report itself as having a hard realtime
@@ -95,6 +119,14 @@ pub trait Plugin: Default + Send + 'static {
     /// to do offline processing.
     const HARD_REALTIME_ONLY: bool = false;
 
+    /// If this is set to true, then the plugin will advertise support for double precision (64-bit)
+    /// audio processing. When the host decides to use 64-bit buffers, the wrapper will call
+    /// [`process_f64()`][Self::process_f64()] instead of [`process()`][Self::process()], so the
+    /// plugin must also implement that function. Hosts are free to pick either precision, so
+    /// `process()` still needs to be implemented. CLAP and VST3 both support this. The standalone
+    /// target always uses single precision buffers.
+    const F64_PROCESSING: bool = false;
+
     /// The plugin's SysEx message type if it supports sending or receiving MIDI SysEx messages, or
     /// `()` if it does not. This type can be a struct or enum wrapping around one or more message
     /// types, and the [`SysExMessage`] trait is then used to convert between this type and basic
@@ -131,17 +163,66 @@ pub trait Plugin: Default + Send + 'static {
         None
     }
 
+    /// Migrations for state saved with older versions of the plugin. These are applied based on
+    /// the [`PluginState::version`] field just before a [`PluginState`] is loaded, before
+    /// [`filter_state()`][Self::filter_state()] is called. This can be used to rename parameters
+    /// and persistent fields, and to convert parameter values after changing a parameter's type.
+    /// See [`StateMigrations`] for more information. Keep in mind that host automation for renamed
+    /// parameters will still be broken.
+    ///
+    /// The [`TestHost`][crate::testing::TestHost]'s
+    /// [`set_state_json()`][crate::testing::TestHost::set_state_json()] function can be used to
+    /// check that old state still loads correctly.
+    fn state_migrations() -> StateMigrations {
+        StateMigrations::new()
+    }
+
     /// This function is always called just before a [`PluginState`] is loaded. This lets you
     /// directly modify old plugin state to perform migrations based on the [`PluginState::version`]
     /// field. Some examples of use cases for this are renaming parameter indices, remapping
     /// parameter values, and preserving old preset compatibility when introducing new parameters
     /// with default values that would otherwise change the sound of a preset. Keep in mind that
-    /// automation may still be broken in the first two use cases.
+    /// automation may still be broken in the first two use cases. The first two use cases are
+    /// better handled with [`state_migrations()`][Self::state_migrations()].
+    ///
+    /// `context` describes whether the state is loaded from the host's project, from a preset, or
+    /// to duplicate the plugin instance. See [`StateContext`] for more information.
     ///
     /// # Note
     ///
     /// This is an advanced feature that the vast majority of plugins won't need to implement.
-    fn filter_state(state: &mut PluginState) {}
+    fn filter_state(state: &mut PluginState, context: StateContext) {}
+
+    /// The plugin's factory presets. These are exposed to the host so they show up in its preset
+    /// browser. The VST3 wrapper publishes them as a program list, and the CLAP wrapper uses the
+    /// preset discovery factory and the preset load extension. Loading a preset works the same as
+    /// restoring a [`PluginState`], so [`filter_state()`][Self::filter_state()] is also called for
+    /// factory presets. This is queried once when the plugin gets loaded, and the CLAP wrapper may
+    /// also call this function without creating a plugin instance while indexing presets.
+    ///
+    /// An easy way to create these presets is to save a state object from the plugin's GUI using
+    /// [`GuiContext::get_state()`][crate::prelude::GuiContext::get_state()], and to embed the
+    /// resulting JSON in the plugin using `include_str!()` and `serde_json::from_str()`.
+    fn factory_presets() -> Vec<FactoryPreset> {
+        Vec::new()
+    }
+
+    /// Names for the keys the plugin responds to, like the pads of a drum sampler or the
+    /// keyswitches of a sampled instrument. Hosts show these in their piano roll or drum editor.
+    /// This is only used when [`MIDI_INPUT`][Self::MIDI_INPUT] is set to [`MidiConfig::Basic`] or
+    /// higher. The CLAP wrapper exposes these through the note name extension. VST3 only supports
+    /// pitch names for programs, so there the names for the first channel are attached to the
+    /// [factory presets][Self::factory_presets()], or to a program list containing a single program
+    /// if the plugin doesn't have any factory presets. Keyswitches are exposed through
+    /// `IKeyswitchController`.
+    ///
+    /// This is called from the main thread. The VST3 wrapper queries the names once when the plugin
+    /// gets created and caches them. If the names change at runtime, for instance because a
+    /// different sample kit has been loaded, then the plugin must call `note_names_changed()` on
+    /// any of its contexts so the host can query them again.
+    fn note_names(&self) -> Vec<NoteName> {
+        Vec::new()
+    }
 
     //
     // The following functions follow the lifetime of the plugin.
@@ -208,6 +289,30 @@ pub trait Plugin: Default + Send + 'static {
         context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus;
 
+    /// The same as [`process()`][Self::process()], but for double precision (64-bit) audio
+    /// buffers. This is only called when [`F64_PROCESSING`][Self::F64_PROCESSING] is set and the
+    /// host has opted into 64-bit processing. The host will use the same precision for every
+    /// process call between two [`initialize()`][Self::initialize()] calls, but a plugin should
+    /// not rely on this too much since parameter flushes may still happen in either precision
+    /// depending on the host.
+    ///
+    /// The default implementation returns an error. If you set `F64_PROCESSING`, then you need to
+    /// override this function. A common pattern is to make the plugin's DSP code generic over the
+    /// sample type and to call the same function from both `process()` and `process_f64()`.
+    fn process_f64(
+        &mut self,
+        buffer: &mut Buffer<f64>,
+        aux: &mut AuxiliaryBuffers<f64>,
+        context: &mut impl ProcessContext<Self>,
+    ) -> ProcessStatus {
+        nih_debug_assert_failure!(
+            "'Plugin::process_f64()' was called without being implemented, make sure to override \
+             it when setting 'Plugin::F64_PROCESSING'"
+        );
+
+        ProcessStatus::Error("64-bit processing is not implemented for this plugin")
+    }
+
     /// Called when the plugin is deactivated. The host will call
     /// [`initialize()`][Self::initialize()] again before the plugin resumes processing audio. These
     /// two functions will not be called when the host only temporarily stops processing audio. You
@@ -236,6 +341,12 @@ pub trait ClapPlugin: Plugin {
 
     /// If set, this informs the host about the plugin's capabilities for polyphonic modulation.
     const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = None;
+
+    /// Named pages of up to eight parameters that hosts can map to the knobs on a hardware
+    /// controller. By default these pages are generated from the parameter groups defined using
+    /// `#[nested(group = "...")]`. Use [`ClapRemoteControls::Pages`] to define the pages manually,
+    /// or [`ClapRemoteControls::None`] to not expose any pages.
+    const CLAP_REMOTE_CONTROLS: ClapRemoteControls = ClapRemoteControls::FromParamGroups;
 }
 
 /// Provides auxiliary metadata needed for a VST3 plugin.
@@ -306,4 +417,10 @@ pub struct PolyModulationConfig {
     /// different voice IDs. Bitwig Studio, for instance, can use this to do voice stacking. After
     /// enabling this, you should always prioritize using voice IDs to map note events to voices.
     pub supports_overlapping_voices: bool,
+    /// If set to `true`, then the host may also target polyphonic modulation at all notes with a
+    /// specific key and/or channel, like Bitwig Studio's per-key modulators do. The plugin then
+    /// receives these as [`NoteEvent::KeyModulation`][crate::prelude::NoteEvent::KeyModulation]
+    /// events, so it needs to handle those. Otherwise this modulation is applied to the
+    /// parameter's monophonic value.
+    pub supports_key_modulation: bool,
 }
@@ -14,26 +14,38 @@ pub use crate::formatters;
 pub use crate::util;
 
 pub use crate::audio_setup::{
-    new_nonzero_u32, AudioIOLayout, AuxiliaryBuffers, BufferConfig, PortNames, ProcessMode,
+    new_nonzero_u32, AudioIOLayout, AuxiliaryBuffers, BufferConfig, ChannelLayout,
+    PortChannelLayouts, PortNames, ProcessMode,
 };
 pub use crate::buffer::Buffer;
-pub use crate::context::gui::{AsyncExecutor, GuiContext, ParamSetter};
+pub use crate::context::gui::{AsyncExecutor, FdFlags, GuiContext, ParamSetter, TimerId};
 pub use crate::context::init::InitContext;
 pub use crate::context::process::ProcessContext;
+pub use crate::context::{HostInfo, TrackColor, TrackFlags, TrackInfo};
 // This also includes the derive macro
-pub use crate::editor::{Editor, ParentWindowHandle};
+pub use crate::editor::{Editor, EditorResizeConstraints, ParentWindowHandle};
+pub use crate::midi::learn::{MidiMapping, MidiMappingCurve, MidiMappingSource};
+pub use crate::midi::mpe::{MpeZone, MpeZones};
+pub use crate::midi::note_names::NoteName;
 pub use crate::midi::sysex::SysExMessage;
 pub use crate::midi::{control_change, MidiConfig, NoteEvent, PluginNoteEvent};
 pub use crate::params::enums::{Enum, EnumParam};
+pub use crate::params::indication::{AutomationState, IndicationColor, ParamIndication};
 pub use crate::params::internals::ParamPtr;
 pub use crate::params::range::{FloatRange, IntRange};
 pub use crate::params::smoothing::{Smoothable, Smoother, SmoothingStyle};
+pub use crate::params::snapshots::{Snapshot, SnapshotChanges, Snapshots};
 pub use crate::params::Params;
 pub use crate::params::{BoolParam, FloatParam, IntParam, Param, ParamFlags};
 #[cfg(feature = "vst3")]
 pub use crate::plugin::Vst3Plugin;
 pub use crate::plugin::{ClapPlugin, Plugin, PolyModulationConfig, ProcessStatus, TaskExecutor};
 pub use crate::wrapper::clap::features::ClapFeature;
-pub use crate::wrapper::state::PluginState;
+pub use crate::wrapper::clap::remote_controls::{ClapRemoteControls, ClapRemoteControlsPage};
+pub use crate::wrapper::state::migration::{StateMigration, StateMigrations};
+pub use crate::wrapper::state::preset::{
+    load_preset, save_preset, PresetError, PresetFile, PresetIssue,
+};
+pub use crate::wrapper::state::{FactoryPreset, ParamValue, PluginState, StateContext};
 #[cfg(feature = "vst3")]
 pub use crate::wrapper::vst3::subcategories::Vst3SubCategory;
@@ -0,0 +1,1289 @@
+//! An offline host for driving plugins from unit tests without needing a DAW. The [`TestHost`]
+//! instantiates a plugin, initializes it for a specific [`AudioIOLayout`] and [`BufferConfig`], and
+//! then lets you process scripted blocks of audio containing note events, parameter changes, and
+//! transport information. Parameter changes go through the same [`ParamPtr`] and smoothing code
+//! paths used by the actual plugin wrappers, and with
+//! [`Plugin::SAMPLE_ACCURATE_AUTOMATION`] enabled the buffer is split up at parameter changes just
+//! like the VST3 and CLAP wrappers would do. Transport changes always split up the buffer, just
+//! like in the CLAP wrapper.
+//!
+//! ```ignore
+//! let mut host = TestHost::<Gain>::with_default_layout(default_buffer_config()).unwrap();
+//! let output = host.process(
+//!     ProcessInput::new(512)
+//!         .with_main_input(vec![vec![1.0; 512]; 2])
+//!         .with_param_change(256, "gain", 0.0),
+//! );
+//!
+//! assert_eq!(output.main_output[0][0], 1.0);
+//! ```
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use crate::audio_setup::{AudioIOLayout, AuxiliaryBuffers, BufferConfig, ProcessMode};
+use crate::buffer::Buffer;
+use crate::context::process::Transport;
+use crate::context::HostInfo;
+use crate::midi::learn::MidiMapping;
+use crate::midi::{MidiConfig, PluginNoteEvent};
+use crate::params::internals::ParamPtr;
+use crate::params::Params;
+use crate::plugin::{Plugin, ProcessStatus, TaskExecutor};
+use crate::wrapper::state::{self, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::ProcessSample;
+use crate::wrapper::util::midi_learn::MidiLearn;
+use crate::wrapper::util::{clamp_input_event_timing, process_wrapper};
+
+mod context;
+
+use self::context::{TestInitContext, TestProcessContext};
+
+/// An offline host that drives a plugin through its initialization, reset, and processing
+/// functions. This can be used to write unit and regression tests for plugins. Everything happens
+/// on the calling thread, including background tasks scheduled by the plugin, so the results are
+/// fully deterministic.
+///
+/// The test host reports itself as [`PluginApi::Standalone`][crate::context::PluginApi::Standalone]
+/// to the plugin.
+pub struct TestHost<P: Plugin> {
+    /// The wrapped plugin instance.
+    plugin: P,
+    /// The plugin's background task executor. Tasks are executed immediately.
+    task_executor: TaskExecutor<P>,
+    /// The plugin's parameters. These are fetched once during initialization, just like in the
+    /// other wrappers.
+    params: Arc<dyn Params>,
+    /// A mapping from parameter string IDs to parameter pointers.
+    param_id_to_ptr: HashMap<String, ParamPtr>,
+
+    audio_io_layout: AudioIOLayout,
+    buffer_config: BufferConfig,
+    /// Whether each auxiliary input port is active. Inactive inputs are passed to the plugin as
+    /// silent buffers.
+    aux_inputs_active: Vec<bool>,
+    /// Whether each auxiliary output port is active.
+    aux_outputs_active: Vec<bool>,
+
+    /// The transport information used for the next process call. The position is advanced
+    /// automatically after every process call while the transport is playing.
+    transport: TestTransport,
+    /// The current latency in samples, as set by the plugin through the init and process contexts.
+    current_latency: Cell<u32>,
+    /// The host information reported to the plugin. This is empty by default.
+    host_info: HostInfo,
+    /// The plugin's MIDI mappings. Mapped MIDI messages are turned into parameter changes before
+    /// the plugin gets to see them, just like in the plugin wrappers.
+    midi_learn: MidiLearn,
+}
+
+/// Errors that may arise while using the [`TestHost`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestHostError {
+    /// The audio IO layout passed to [`TestHost::new()`] is not one of the plugin's
+    /// [`Plugin::AUDIO_IO_LAYOUTS`].
+    UnsupportedAudioIOLayout,
+    /// The plugin returned `false` during initialization.
+    InitializationFailed,
+    /// There is no parameter with this ID.
+    UnknownParameter(String),
+    /// The state object could not be loaded.
+    InvalidState,
+}
+
+/// The transport information sent to the plugin. Unlike [`Transport`], all of these fields can be
+/// set directly. The other positional information is reconstructed from the position in samples
+/// and the tempo by [`Transport`]'s getters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestTransport {
+    /// Whether the transport is currently running. The position is only advanced while playing.
+    pub playing: bool,
+    /// Whether recording is enabled in the project.
+    pub recording: bool,
+    /// The project's tempo in beats per minute.
+    pub tempo: Option<f64>,
+    /// The time signature's numerator.
+    pub time_sig_numerator: Option<i32>,
+    /// The time signature's denominator.
+    pub time_sig_denominator: Option<i32>,
+    /// The position in samples at the start of the next process call. For a [`TransportChange`]
+    /// this is the position at the sample the change occurs at.
+    pub pos_samples: i64,
+}
+
+/// A sample accurate parameter change for [`ProcessInput`], or a parameter change made by the
+/// plugin through
+/// [`ProcessContext::set_parameter()`][crate::prelude::ProcessContext::set_parameter()] in
+/// [`ProcessOutput`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamChange {
+    /// The sample index within the block at which the parameter changes.
+    pub timing: u32,
+    /// The parameter's string ID.
+    pub param_id: String,
+    /// The new normalized `[0, 1]` value for the parameter.
+    pub normalized_value: f32,
+}
+
+/// A change to the transport information in the middle of a block for [`ProcessInput`]. Like in the
+/// CLAP wrapper, the block is always split up at the change so the plugin receives the new
+/// transport information for the rest of the block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransportChange {
+    /// The sample index within the block at which the transport changes.
+    pub timing: u32,
+    /// The new transport information.
+    pub transport: TestTransport,
+}
+
+/// A single block of input for [`TestHost::process()`]. Create one with [`ProcessInput::new()`] and
+/// then use the `with_*()` builder methods to add audio, events, and parameter changes. The sample
+/// type is `f64` when using [`TestHost::process_f64()`].
+pub struct ProcessInput<P: Plugin, T = f32> {
+    /// The number of samples in this block. Must not exceed the buffer config's maximum buffer
+    /// size.
+    pub num_samples: usize,
+    /// The main input's channels, each containing `num_samples` samples. If this is left empty,
+    /// then the input will be silent.
+    pub main_input: Vec<Vec<T>>,
+    /// Channel data for the auxiliary input ports. Missing ports will be silent.
+    pub aux_inputs: Vec<Vec<Vec<T>>>,
+    /// Note events sent to the plugin. These don't need to be sorted.
+    pub events: Vec<PluginNoteEvent<P>>,
+    /// Parameter changes sent to the plugin. These don't need to be sorted.
+    pub param_changes: Vec<ParamChange>,
+    /// Transport changes within the block. These don't need to be sorted. The last change is kept
+    /// as the test host's transport information after the block has been processed.
+    pub transport_changes: Vec<TransportChange>,
+}
+
+/// The results of a single [`TestHost::process()`] or [`TestHost::process_f64()`] call.
+pub struct ProcessOutput<P: Plugin, T = f32> {
+    /// The main output's channels.
+    pub main_output: Vec<Vec<T>>,
+    /// Channel data for the auxiliary output ports.
+    pub aux_outputs: Vec<Vec<Vec<T>>>,
+    /// Note events output by the plugin. The timings are relative to the start of the entire block
+    /// even if the block was split up.
+    pub events: Vec<PluginNoteEvent<P>>,
+    /// Parameter changes made by the plugin from its process function. Like in the plugin
+    /// wrappers, these are timed at the start of the (sub)block they were made in, and they have
+    /// already been applied to the plugin's parameters at the end of that block.
+    pub param_changes: Vec<ParamChange>,
+    /// The status returned by the plugin for the last processed (sub)block. Processing stops early
+    /// if the plugin returned an error.
+    pub status: ProcessStatus,
+}
+
+/// Parameter changes and note events for a single process call, sorted by timing. This mirrors the
+/// `ProcessEvent` type from the VST3 wrapper.
+enum TestProcessEvent<P: Plugin> {
+    ParameterChange {
+        timing: u32,
+        param_ptr: ParamPtr,
+        normalized_value: f32,
+    },
+    TransportChange {
+        timing: u32,
+        transport: TestTransport,
+    },
+    NoteEvent(PluginNoteEvent<P>),
+}
+
+impl Default for TestTransport {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            recording: false,
+            tempo: Some(120.0),
+            time_sig_numerator: Some(4),
+            time_sig_denominator: Some(4),
+            pos_samples: 0,
+        }
+    }
+}
+
+impl<P: Plugin, T> ProcessInput<P, T> {
+    /// Create a block of input with `num_samples` samples of silence and no events.
+    pub fn new(num_samples: usize) -> Self {
+        Self {
+            num_samples,
+            main_input: Vec::new(),
+            aux_inputs: Vec::new(),
+            events: Vec::new(),
+            param_changes: Vec::new(),
+            transport_changes: Vec::new(),
+        }
+    }
+
+    /// Set the main input's channel data.
+    pub fn with_main_input(mut self, channels: Vec<Vec<T>>) -> Self {
+        self.main_input = channels;
+        self
+    }
+
+    /// Add channel data for the next auxiliary input port.
+    pub fn with_aux_input(mut self, channels: Vec<Vec<T>>) -> Self {
+        self.aux_inputs.push(channels);
+        self
+    }
+
+    /// Add a note event.
+    pub fn with_event(mut self, event: PluginNoteEvent<P>) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Add a parameter change at a specific sample index.
+    pub fn with_param_change(
+        mut self,
+        timing: u32,
+        param_id: impl Into<String>,
+        normalized_value: f32,
+    ) -> Self {
+        self.param_changes.push(ParamChange {
+            timing,
+            param_id: param_id.into(),
+            normalized_value,
+        });
+        self
+    }
+
+    /// Change the transport information at a specific sample index.
+    pub fn with_transport_change(mut self, timing: u32, transport: TestTransport) -> Self {
+        self.transport_changes
+            .push(TransportChange { timing, transport });
+        self
+    }
+}
+
+/// A 44.1 kHz realtime buffer config with a maximum buffer size of 512 samples. Useful for
+/// [`TestHost::new()`] when a test doesn't depend on a specific sample rate.
+pub fn default_buffer_config() -> BufferConfig {
+    BufferConfig {
+        sample_rate: 44_100.0,
+        min_buffer_size: None,
+        max_buffer_size: 512,
+        process_mode: ProcessMode::Realtime,
+    }
+}
+
+impl<P: Plugin> TestHost<P> {
+    /// Instantiate the plugin and initialize it with the given audio IO layout and buffer config.
+    /// The layout must be one of the plugin's [`Plugin::AUDIO_IO_LAYOUTS`]. Like in the plugin
+    /// wrappers, [`Plugin::reset()`] is called immediately after initializing the plugin.
+    pub fn new(
+        audio_io_layout: AudioIOLayout,
+        buffer_config: BufferConfig,
+    ) -> Result<Self, TestHostError> {
+        if !P::AUDIO_IO_LAYOUTS.is_empty() && !P::AUDIO_IO_LAYOUTS.contains(&audio_io_layout) {
+            return Err(TestHostError::UnsupportedAudioIOLayout);
+        }
+
+        let plugin = P::default();
+        let task_executor = plugin.task_executor();
+        let params = plugin.params();
+        let param_id_to_ptr = params
+            .param_map()
+            .into_iter()
+            .map(|(param_id, param_ptr, _)| (param_id, param_ptr))
+            .collect();
+
+        let mut host = Self {
+            plugin,
+            task_executor,
+            params,
+            param_id_to_ptr,
+
+            audio_io_layout,
+            buffer_config,
+            aux_inputs_active: vec![true; audio_io_layout.aux_input_ports.len()],
+            aux_outputs_active: vec![true; audio_io_layout.aux_output_ports.len()],
+
+            transport: TestTransport::default(),
+            current_latency: Cell::new(0),
+            host_info: HostInfo::default(),
+            midi_learn: MidiLearn::default(),
+        };
+
+        // Before initializing the plugin, make sure all smoothers are set the the default values
+        for param in host.param_id_to_ptr.values() {
+            unsafe { param.update_smoother(host.buffer_config.sample_rate, true) };
+        }
+
+        host.initialize()?;
+
+        Ok(host)
+    }
+
+    /// The same as [`new()`][Self::new()], but using the plugin's first audio IO layout. This is
+    /// also what the hosts use by default.
+    pub fn with_default_layout(buffer_config: BufferConfig) -> Result<Self, TestHostError> {
+        Self::new(
+            P::AUDIO_IO_LAYOUTS.first().copied().unwrap_or_default(),
+            buffer_config,
+        )
+    }
+
+    /// The wrapped plugin instance.
+    pub fn plugin(&self) -> &P {
+        &self.plugin
+    }
+
+    /// The wrapped plugin instance. Can be used to inspect or modify the plugin's internal state
+    /// between process calls.
+    pub fn plugin_mut(&mut self) -> &mut P {
+        &mut self.plugin
+    }
+
+    /// The plugin's parameters object.
+    pub fn params(&self) -> Arc<dyn Params> {
+        self.params.clone()
+    }
+
+    /// The audio IO layout the plugin was initialized with.
+    pub fn audio_io_layout(&self) -> &AudioIOLayout {
+        &self.audio_io_layout
+    }
+
+    /// The buffer config the plugin was initialized with.
+    pub fn buffer_config(&self) -> &BufferConfig {
+        &self.buffer_config
+    }
+
+    /// Activate or deactivate one of the plugin's auxiliary input ports, like a host would do when
+    /// nothing is connected to a sidechain input. Inactive inputs are passed to the plugin as
+    /// silent buffers, even if [`ProcessInput::with_aux_input()`] was used to provide data for
+    /// them. All ports are active by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the audio IO layout doesn't have an auxiliary input with this index.
+    pub fn set_aux_input_active(&mut self, port_idx: usize, active: bool) {
+        self.aux_inputs_active[port_idx] = active;
+    }
+
+    /// Activate or deactivate one of the plugin's auxiliary output ports. The plugin can skip
+    /// processing inactive outputs, so their contents in the [`ProcessOutput`] are unspecified. All
+    /// ports are active by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the audio IO layout doesn't have an auxiliary output with this index.
+    pub fn set_aux_output_active(&mut self, port_idx: usize, active: bool) {
+        self.aux_outputs_active[port_idx] = active;
+    }
+
+    /// Change the host information reported to the plugin through the init and process contexts.
+    /// This can be used to test host-specific workarounds. Since the plugin has already been
+    /// initialized at this point, this only affects future process calls and reinitializations.
+    pub fn set_host_info(&mut self, host_info: HostInfo) {
+        self.host_info = host_info;
+    }
+
+    /// The latency last reported by the plugin.
+    pub fn latency_samples(&self) -> u32 {
+        self.current_latency.get()
+    }
+
+    /// The transport information that will be used for the next process call.
+    pub fn transport(&self) -> &TestTransport {
+        &self.transport
+    }
+
+    /// Change the transport information for the next process calls.
+    pub fn transport_mut(&mut self) -> &mut TestTransport {
+        &mut self.transport
+    }
+
+    /// Get a parameter's current unmodulated normalized value.
+    pub fn param_normalized_value(&self, param_id: &str) -> Result<f32, TestHostError> {
+        match self.param_id_to_ptr.get(param_id) {
+            Some(param_ptr) => Ok(unsafe { param_ptr.unmodulated_normalized_value() }),
+            None => Err(TestHostError::UnknownParameter(param_id.to_owned())),
+        }
+    }
+
+    /// Immediately set a parameter to a new normalized value between process calls, the same way a
+    /// host would when the plugin is not processing audio. Use
+    /// [`ProcessInput::with_param_change()`] for sample accurate changes.
+    pub fn set_param_normalized_value(
+        &mut self,
+        param_id: &str,
+        normalized_value: f32,
+    ) -> Result<(), TestHostError> {
+        match self.param_id_to_ptr.get(param_id) {
+            Some(param_ptr) => {
+                self.set_normalized_value(*param_ptr, normalized_value);
+                Ok(())
+            }
+            None => Err(TestHostError::UnknownParameter(param_id.to_owned())),
+        }
+    }
+
+    /// Get a parameter's MIDI mapping, if it has one.
+    pub fn midi_mapping(&self, param_id: &str) -> Result<Option<MidiMapping>, TestHostError> {
+        match self.param_id_to_ptr.get(param_id) {
+            Some(param_ptr) => Ok(self.midi_learn.mapping(*param_ptr)),
+            None => Err(TestHostError::UnknownParameter(param_id.to_owned())),
+        }
+    }
+
+    /// Set or remove a parameter's MIDI mapping, like the plugin's editor would through
+    /// [`ParamSetter::set_midi_mapping()`][crate::prelude::ParamSetter::set_midi_mapping()].
+    pub fn set_midi_mapping(
+        &mut self,
+        param_id: &str,
+        mapping: Option<MidiMapping>,
+    ) -> Result<(), TestHostError> {
+        match self.param_id_to_ptr.get(param_id) {
+            Some(param_ptr) => {
+                self.midi_learn
+                    .set_mapping(*param_ptr, param_id.to_owned(), mapping);
+                Ok(())
+            }
+            None => Err(TestHostError::UnknownParameter(param_id.to_owned())),
+        }
+    }
+
+    /// Get the plugin's current state object, like the plugin wrappers do when the host saves a
+    /// project.
+    pub fn get_state(&self) -> PluginState {
+        self.get_state_for(StateContext::Project)
+    }
+
+    /// Get the plugin's current state object for a specific [`StateContext`].
+    pub fn get_state_for(&self, context: StateContext) -> PluginState {
+        unsafe {
+            state::serialize_object::<P>(
+                self.params.clone(),
+                self.param_id_to_ptr
+                    .iter()
+                    .map(|(param_id, param_ptr)| (param_id, *param_ptr)),
+                &self.midi_learn,
+                context,
+            )
+        }
+    }
+
+    /// Restore a state object and reinitialize the plugin, just like the plugin wrappers do when
+    /// the host loads a project.
+    pub fn set_state(&mut self, state: PluginState) -> Result<(), TestHostError> {
+        self.set_state_for(state, StateContext::Project)
+    }
+
+    /// Restore a state object for a specific [`StateContext`] and reinitialize the plugin.
+    pub fn set_state_for(
+        &mut self,
+        mut state: PluginState,
+        context: StateContext,
+    ) -> Result<(), TestHostError> {
+        let success = unsafe {
+            state::deserialize_object::<P>(
+                &mut state,
+                self.params.clone(),
+                |param_id| self.param_id_to_ptr.get(param_id).copied(),
+                &self.midi_learn,
+                Some(&self.buffer_config),
+                context,
+            )
+        };
+        if !success {
+            return Err(TestHostError::InvalidState);
+        }
+
+        self.initialize()
+    }
+
+    /// Parse a serialized state and apply the plugin's
+    /// [`state_migrations()`][Plugin::state_migrations()] and
+    /// [`filter_state()`][Plugin::filter_state()] to it, just like the plugin wrappers do before
+    /// loading a state. This can be used to check that state saved by an older version of the
+    /// plugin is migrated correctly. The state is not loaded. Returns an error if the state could
+    /// not be parsed, or if the migrated state contains a parameter the plugin doesn't have, which
+    /// usually means that a migration is missing.
+    pub fn migrate_state_json(&self, state: &[u8]) -> Result<PluginState, TestHostError> {
+        let state = unsafe { state::deserialize_json(state) }.ok_or(TestHostError::InvalidState)?;
+        self.migrate_state(state)
+    }
+
+    /// The part of [`migrate_state_json()`][Self::migrate_state_json()] that runs after the state
+    /// has been parsed.
+    fn migrate_state(&self, mut state: PluginState) -> Result<PluginState, TestHostError> {
+        P::state_migrations().apply(&mut state);
+        P::filter_state(&mut state, StateContext::Project);
+
+        match state
+            .params
+            .keys()
+            .chain(state.midi_mappings.keys())
+            .find(|param_id| !self.param_id_to_ptr.contains_key(*param_id))
+        {
+            Some(param_id) => Err(TestHostError::UnknownParameter(param_id.clone())),
+            None => Ok(state),
+        }
+    }
+
+    /// Load a serialized state, like the host would when opening a project saved with an older
+    /// version of the plugin. The state goes through the same checks as in
+    /// [`migrate_state_json()`][Self::migrate_state_json()] before it is loaded.
+    pub fn set_state_json(&mut self, state: &[u8]) -> Result<(), TestHostError> {
+        let state = unsafe { state::deserialize_json(state) }.ok_or(TestHostError::InvalidState)?;
+
+        // The migrations are applied again when the state is loaded, so the checks are performed
+        // on a copy
+        self.migrate_state(state.clone())?;
+        self.set_state(state)
+    }
+
+    /// Call [`Plugin::reset()`], like a host would when it stops and restarts processing.
+    pub fn reset(&mut self) {
+        process_wrapper(|| self.plugin.reset());
+    }
+
+    /// Call [`Plugin::deactivate()`]. The plugin should not be processed again afterwards.
+    pub fn deactivate(&mut self) {
+        self.plugin.deactivate();
+    }
+
+    /// Process a block of audio. The main input is copied to the main output before calling the
+    /// plugin's process function, just like in the plugin wrappers. If the plugin has
+    /// [`Plugin::SAMPLE_ACCURATE_AUTOMATION`] enabled, then the block will be split up at every
+    /// parameter change. Otherwise all parameter changes are applied before processing the block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is larger than the maximum buffer size, if the input audio does not
+    /// match the audio IO layout, or if a parameter change refers to an unknown parameter ID.
+    pub fn process(&mut self, input: ProcessInput<P>) -> ProcessOutput<P> {
+        self.process_impl(input)
+    }
+
+    /// The same as [`process()`][Self::process()], but this processes double precision audio
+    /// through [`Plugin::process_f64()`]. Like a host would, this should only be used with plugins
+    /// that set [`Plugin::F64_PROCESSING`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `process()`, and also when the plugin does not support
+    /// double precision processing.
+    pub fn process_f64(&mut self, input: ProcessInput<P, f64>) -> ProcessOutput<P, f64> {
+        assert!(
+            P::F64_PROCESSING,
+            "The plugin does not support 64-bit processing"
+        );
+
+        self.process_impl(input)
+    }
+
+    /// The implementation for [`process()`][Self::process()] and
+    /// [`process_f64()`][Self::process_f64()].
+    fn process_impl<T: ProcessSample>(&mut self, input: ProcessInput<P, T>) -> ProcessOutput<P, T> {
+        let ProcessInput {
+            num_samples,
+            main_input,
+            aux_inputs,
+            events,
+            param_changes,
+            transport_changes,
+        } = input;
+        assert!(
+            num_samples <= self.buffer_config.max_buffer_size as usize,
+            "The block size exceeds the maximum buffer size"
+        );
+        nih_debug_assert!(
+            events.is_empty() || P::MIDI_INPUT >= MidiConfig::Basic,
+            "Note events were sent to a plugin that does not accept note input"
+        );
+
+        // The main input is copied to the main output like in the other wrappers
+        let num_input_channels = self
+            .audio_io_layout
+            .main_input_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
+        let num_output_channels = self
+            .audio_io_layout
+            .main_output_channels
+            .map(NonZeroU32::get)
+            .unwrap_or_default() as usize;
+        assert!(
+            main_input.is_empty() || main_input.len() == num_input_channels,
+            "Expected {num_input_channels} main input channels, got {}",
+            main_input.len()
+        );
+        let mut main_output = vec![vec![T::default(); num_samples]; num_output_channels];
+        for (input_channel, output_channel) in main_input.iter().zip(main_output.iter_mut()) {
+            assert_eq!(input_channel.len(), num_samples);
+            output_channel.copy_from_slice(input_channel);
+        }
+
+        let mut aux_input_storage: Vec<Vec<Vec<T>>> = self
+            .audio_io_layout
+            .aux_input_ports
+            .iter()
+            .map(|num_channels| vec![vec![T::default(); num_samples]; num_channels.get() as usize])
+            .collect();
+        assert!(
+            aux_inputs.len() <= aux_input_storage.len(),
+            "More auxiliary inputs were provided than the audio IO layout defines"
+        );
+        for ((input_port, storage), active) in aux_inputs
+            .iter()
+            .zip(aux_input_storage.iter_mut())
+            .zip(self.aux_inputs_active.iter())
+        {
+            assert_eq!(input_port.len(), storage.len());
+            for (input_channel, channel_storage) in input_port.iter().zip(storage.iter_mut()) {
+                assert_eq!(input_channel.len(), num_samples);
+                // Inactive inputs are left silent
+                if *active {
+                    channel_storage.copy_from_slice(input_channel);
+                }
+            }
+        }
+        let mut aux_output_storage: Vec<Vec<Vec<T>>> = self
+            .audio_io_layout
+            .aux_output_ports
+            .iter()
+            .map(|num_channels| vec![vec![T::default(); num_samples]; num_channels.get() as usize])
+            .collect();
+
+        // Parameter and transport changes need to be ordered before note events at the same sample
+        // for the block splitting to work correctly, so this sort needs to be stable
+        let mut process_events: Vec<TestProcessEvent<P>> = param_changes
+            .into_iter()
+            .map(|change| TestProcessEvent::ParameterChange {
+                timing: clamp_input_event_timing(change.timing, num_samples as u32),
+                param_ptr: match self.param_id_to_ptr.get(&change.param_id) {
+                    Some(param_ptr) => *param_ptr,
+                    None => panic!("Unknown parameter ID '{}'", change.param_id),
+                },
+                normalized_value: change.normalized_value,
+            })
+            .collect();
+        process_events.extend(transport_changes.into_iter().map(|change| {
+            TestProcessEvent::TransportChange {
+                timing: clamp_input_event_timing(change.timing, num_samples as u32),
+                transport: change.transport,
+            }
+        }));
+        process_events.extend(events.into_iter().map(|mut event| {
+            let timing = clamp_input_event_timing(event.timing(), num_samples as u32);
+            event.subtract_timing(event.timing() - timing);
+            TestProcessEvent::NoteEvent(event)
+        }));
+        process_events.sort_by_key(|event| match event {
+            TestProcessEvent::ParameterChange { timing, .. } => *timing,
+            TestProcessEvent::TransportChange { timing, .. } => *timing,
+            TestProcessEvent::NoteEvent(event) => event.timing(),
+        });
+
+        let mut output_events = Vec::new();
+        let mut output_param_changes = Vec::new();
+        let mut block_input_events = Vec::new();
+        let mut block_output_events = Vec::new();
+        let mut block_output_param_changes = Vec::new();
+        let mut status = ProcessStatus::Normal;
+        let mut block_start = 0usize;
+        let mut event_idx = 0usize;
+        // The transport information for the current block, and the sample it applies to
+        let mut current_transport = self.transport;
+        let mut transport_start = 0usize;
+        loop {
+            // This works the same way as the block splitting in the VST3 wrapper, with transport
+            // changes being handled like in the CLAP wrapper
+            let mut block_end = num_samples;
+            block_input_events.clear();
+            while event_idx < process_events.len() {
+                match &process_events[event_idx] {
+                    TestProcessEvent::ParameterChange {
+                        timing,
+                        param_ptr,
+                        normalized_value,
+                    } => {
+                        if P::SAMPLE_ACCURATE_AUTOMATION && *timing as usize != block_start {
+                            block_end = *timing as usize;
+                            break;
+                        }
+
+                        self.set_normalized_value(*param_ptr, *normalized_value);
+                    }
+                    TestProcessEvent::TransportChange { timing, transport } => {
+                        if *timing as usize != block_start {
+                            block_end = *timing as usize;
+                            break;
+                        }
+
+                        current_transport = *transport;
+                        transport_start = block_start;
+                    }
+                    TestProcessEvent::NoteEvent(event) => {
+                        // MIDI learn mapped controllers are handled like parameter changes
+                        if P::SAMPLE_ACCURATE_AUTOMATION
+                            && event.timing() as usize != block_start
+                            && self.midi_learn.maps_event(event)
+                        {
+                            block_end = event.timing() as usize;
+                            break;
+                        }
+
+                        let mut event = event.clone();
+                        event.subtract_timing(block_start as u32);
+                        block_input_events.push(event);
+                    }
+                }
+
+                event_idx += 1;
+            }
+
+            if self.midi_learn.is_active() {
+                let midi_learn = &self.midi_learn;
+                let sample_rate = self.buffer_config.sample_rate;
+                block_input_events.retain(|event| {
+                    !midi_learn.handle_event(event, |param_ptr, normalized_value| unsafe {
+                        if param_ptr.set_normalized_value(normalized_value) {
+                            param_ptr.update_smoother(sample_rate, false);
+                        }
+                    })
+                });
+            }
+
+            let block_len = block_end - block_start;
+            let mut buffer = Buffer::default();
+            unsafe {
+                buffer.set_slices(block_len, |output_slices| {
+                    *output_slices = main_output
+                        .iter_mut()
+                        .map(|channel| &mut channel[block_start..block_end])
+                        .collect();
+                })
+            };
+
+            let mut aux_input_buffers: Vec<Buffer<T>> = aux_input_storage
+                .iter_mut()
+                .map(|storage| make_block_buffer(storage, block_start, block_end))
+                .collect();
+            let mut aux_output_buffers: Vec<Buffer<T>> = aux_output_storage
+                .iter_mut()
+                .map(|storage| make_block_buffer(storage, block_start, block_end))
+                .collect();
+            // SAFETY: Shortening these borrows is safe as the buffers are recreated for every
+            //         block and they are dropped before the storage is accessed again
+            let mut aux = unsafe {
+                AuxiliaryBuffers {
+                    inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                    outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                    inputs_active: &self.aux_inputs_active,
+                    outputs_active: &self.aux_outputs_active,
+                }
+            };
+
+            let transport = self.make_transport(&current_transport, block_start - transport_start);
+            let mut context = TestProcessContext {
+                task_executor: &self.task_executor,
+                current_latency: &self.current_latency,
+                input_events: &block_input_events,
+                input_events_idx: 0,
+                output_events: &mut block_output_events,
+                output_param_changes: &mut block_output_param_changes,
+                transport,
+                host_info: &self.host_info,
+            };
+            let plugin = &mut self.plugin;
+            status = process_wrapper(|| T::process(plugin, &mut buffer, &mut aux, &mut context));
+
+            for mut event in block_output_events.drain(..) {
+                event.add_timing(block_start as u32);
+                output_events.push(event);
+            }
+
+            // Parameter changes made by the plugin are only applied after the block has been
+            // processed, just like in the plugin wrappers
+            for (param_ptr, normalized_value) in block_output_param_changes.drain(..) {
+                match self
+                    .param_id_to_ptr
+                    .iter()
+                    .find(|(_, candidate)| **candidate == param_ptr)
+                {
+                    Some((param_id, _)) => {
+                        output_param_changes.push(ParamChange {
+                            timing: block_start as u32,
+                            param_id: param_id.clone(),
+                            normalized_value,
+                        });
+                        self.set_normalized_value(param_ptr, normalized_value);
+                    }
+                    None => nih_debug_assert_failure!("Unknown parameter: {:?}", param_ptr),
+                }
+            }
+
+            if block_end == num_samples || matches!(status, ProcessStatus::Error(_)) {
+                break;
+            } else {
+                block_start = block_end;
+            }
+        }
+
+        self.transport = current_transport;
+        if self.transport.playing {
+            self.transport.pos_samples += (num_samples - transport_start) as i64;
+        }
+
+        ProcessOutput {
+            main_output,
+            aux_outputs: aux_output_storage,
+            events: output_events,
+            param_changes: output_param_changes,
+            status,
+        }
+    }
+
+    /// Initialize and reset the plugin using the current audio IO layout and buffer config.
+    fn initialize(&mut self) -> Result<(), TestHostError> {
+        let mut init_context = TestInitContext {
+            task_executor: &self.task_executor,
+            current_latency: &self.current_latency,
+            host_info: &self.host_info,
+        };
+        if !self.plugin.initialize(
+            &self.audio_io_layout,
+            &self.buffer_config,
+            &mut init_context,
+        ) {
+            return Err(TestHostError::InitializationFailed);
+        }
+
+        process_wrapper(|| self.plugin.reset());
+
+        Ok(())
+    }
+
+    /// Set a parameter's normalized value and update its smoother, the same way the plugin wrappers
+    /// handle incoming parameter changes.
+    fn set_normalized_value(&self, param_ptr: ParamPtr, normalized_value: f32) {
+        unsafe {
+            if param_ptr.set_normalized_value(normalized_value) {
+                param_ptr.update_smoother(self.buffer_config.sample_rate, false);
+            }
+        }
+    }
+
+    /// Create the transport object for a (sub)block starting `offset` samples after the sample
+    /// `test_transport` applies to.
+    fn make_transport(&self, test_transport: &TestTransport, offset: usize) -> Transport {
+        let mut transport = Transport::new(self.buffer_config.sample_rate);
+        transport.playing = test_transport.playing;
+        transport.recording = test_transport.recording;
+        transport.tempo = test_transport.tempo;
+        transport.time_sig_numerator = test_transport.time_sig_numerator;
+        transport.time_sig_denominator = test_transport.time_sig_denominator;
+        transport.pos_samples = Some(test_transport.pos_samples + offset as i64);
+
+        transport
+    }
+}
+
+/// Create a buffer pointing to the `[block_start, block_end)` range of each channel in `storage`.
+fn make_block_buffer<T>(
+    storage: &mut [Vec<T>],
+    block_start: usize,
+    block_end: usize,
+) -> Buffer<'_, T> {
+    let mut buffer = Buffer::default();
+    unsafe {
+        buffer.set_slices(block_end - block_start, |output_slices| {
+            *output_slices = storage
+                .iter_mut()
+                .map(|channel| &mut channel[block_start..block_end])
+                .collect();
+        })
+    };
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::process::ProcessContext;
+    use crate::midi::learn::MidiMappingSource;
+    use crate::midi::NoteEvent;
+    use crate::params::range::FloatRange;
+    use crate::params::{FloatParam, Param};
+    use crate::wrapper::state::migration::{StateMigration, StateMigrations};
+    use crate::wrapper::state::preset::{PresetFile, PresetIssue};
+    use crate::wrapper::state::ParamValue;
+
+    struct TestPlugin {
+        params: Arc<TestParams>,
+        /// The block size, tempo, and position of every block processed by the plugin.
+        transport_log: Vec<(usize, Option<f64>, Option<i64>)>,
+    }
+
+    struct TestParams {
+        gain: FloatParam,
+        peak: FloatParam,
+    }
+
+    unsafe impl Params for TestParams {
+        fn param_map(&self) -> Vec<(String, ParamPtr, String)> {
+            vec![
+                (String::from("gain"), self.gain.as_ptr(), String::new()),
+                (String::from("peak"), self.peak.as_ptr(), String::new()),
+            ]
+        }
+    }
+
+    impl Default for TestPlugin {
+        fn default() -> Self {
+            Self {
+                params: Arc::new(TestParams {
+                    gain: FloatParam::new("Gain", 1.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+                    peak: FloatParam::new("Peak", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+                        .make_output(),
+                }),
+                transport_log: Vec::new(),
+            }
+        }
+    }
+
+    impl Plugin for TestPlugin {
+        const NAME: &'static str = "Test Plugin";
+        const VENDOR: &'static str = "";
+        const URL: &'static str = "";
+        const EMAIL: &'static str = "";
+        const VERSION: &'static str = "0.0.1";
+        const PRESET_ID: &'static str = "nih-plug.test-plugin";
+
+        const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
+            main_input_channels: NonZeroU32::new(2),
+            main_output_channels: NonZeroU32::new(2),
+            ..AudioIOLayout::const_default()
+        }];
+
+        const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+        const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
+        const SAMPLE_ACCURATE_AUTOMATION: bool = true;
+        const F64_PROCESSING: bool = true;
+
+        type SysExMessage = ();
+        type BackgroundTask = ();
+
+        fn params(&self) -> Arc<dyn Params> {
+            self.params.clone()
+        }
+
+        fn state_migrations() -> StateMigrations {
+            StateMigrations::new()
+                .with_migration(StateMigration::new("0.0.1").rename_param("volume", "gain"))
+        }
+
+        fn process(
+            &mut self,
+            buffer: &mut Buffer,
+            _aux: &mut AuxiliaryBuffers,
+            context: &mut impl ProcessContext<Self>,
+        ) -> ProcessStatus {
+            let transport = context.transport();
+            self.transport_log
+                .push((buffer.samples(), transport.tempo, transport.pos_samples()));
+
+            // Echo all note events back to the host. Volume CCs also set the gain parameter.
+            while let Some(event) = context.next_event() {
+                if let NoteEvent::MidiCC { cc: 7, value, .. } = event {
+                    let gain = &self.params.gain;
+                    context.begin_set_parameter(gain);
+                    context.set_parameter(gain, value);
+                    context.end_set_parameter(gain);
+                }
+
+                context.send_event(event);
+            }
+
+            for channel_samples in buffer.iter_samples() {
+                let gain = self.params.gain.smoothed.next();
+                for sample in channel_samples {
+                    *sample *= gain;
+                }
+            }
+
+            ProcessStatus::Normal
+        }
+
+        fn process_f64(
+            &mut self,
+            buffer: &mut Buffer<f64>,
+            _aux: &mut AuxiliaryBuffers<f64>,
+            _context: &mut impl ProcessContext<Self>,
+        ) -> ProcessStatus {
+            for channel_samples in buffer.iter_samples() {
+                let gain = self.params.gain.smoothed.next() as f64;
+                for sample in channel_samples {
+                    *sample *= gain;
+                }
+            }
+
+            ProcessStatus::Normal
+        }
+    }
+
+    fn new_host() -> TestHost<TestPlugin> {
+        TestHost::with_default_layout(default_buffer_config()).unwrap()
+    }
+
+    #[test]
+    fn passthrough() {
+        let mut host = new_host();
+        let output = host.process(ProcessInput::new(64).with_main_input(vec![vec![0.5; 64]; 2]));
+
+        assert_eq!(output.status, ProcessStatus::Normal);
+        assert_eq!(output.main_output, vec![vec![0.5; 64]; 2]);
+    }
+
+    #[test]
+    fn f64_processing() {
+        let mut host = new_host();
+        let output = host.process_f64(
+            ProcessInput::new(64)
+                .with_main_input(vec![vec![0.1; 64]; 2])
+                .with_param_change(32, "gain", 0.0),
+        );
+
+        assert_eq!(output.status, ProcessStatus::Normal);
+        assert_eq!(output.main_output[1][31], 0.1f64);
+        assert_eq!(output.main_output[1][32], 0.0f64);
+    }
+
+    #[test]
+    fn sample_accurate_param_change() {
+        let mut host = new_host();
+        let output = host.process(
+            ProcessInput::new(64)
+                .with_main_input(vec![vec![1.0; 64]; 2])
+                .with_param_change(32, "gain", 0.0),
+        );
+
+        assert_eq!(output.main_output[0][31], 1.0);
+        assert_eq!(output.main_output[0][32], 0.0);
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.0));
+    }
+
+    #[test]
+    fn event_timings() {
+        let mut host = new_host();
+        let note_on = NoteEvent::NoteOn {
+            timing: 48,
+            voice_id: None,
+            channel: 0,
+            note: 60,
+            velocity: 1.0,
+        };
+        let output = host.process(
+            ProcessInput::new(64)
+                .with_event(note_on.clone())
+                .with_param_change(32, "gain", 0.5),
+        );
+
+        // The block was split at sample 32, but the output event should still be at sample 48
+        assert_eq!(output.events, vec![note_on]);
+    }
+
+    #[test]
+    fn param_changes_from_process() {
+        let mut host = new_host();
+        let output = host.process(
+            ProcessInput::new(64)
+                .with_main_input(vec![vec![1.0; 64]; 2])
+                .with_event(NoteEvent::MidiCC {
+                    timing: 16,
+                    channel: 0,
+                    cc: 7,
+                    value: 0.5,
+                }),
+        );
+
+        // The new value only takes effect after the block has been processed
+        assert_eq!(output.main_output[0][63], 1.0);
+        assert_eq!(
+            output.param_changes,
+            vec![ParamChange {
+                timing: 0,
+                param_id: String::from("gain"),
+                normalized_value: 0.5,
+            }]
+        );
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.5));
+
+        let output = host.process(ProcessInput::new(64).with_main_input(vec![vec![1.0; 64]; 2]));
+        assert_eq!(output.main_output[0][0], 0.5);
+        assert!(output.param_changes.is_empty());
+    }
+
+    #[test]
+    fn midi_mappings() {
+        let mut host = new_host();
+        let mapping =
+            MidiMapping::new(MidiMappingSource::Cc { channel: 0, cc: 1 }).with_range(0.0, 0.5);
+        host.set_midi_mapping("gain", Some(mapping)).unwrap();
+        let output = host.process(
+            ProcessInput::new(64)
+                .with_main_input(vec![vec![1.0; 64]; 2])
+                .with_event(NoteEvent::MidiCC {
+                    timing: 0,
+                    channel: 0,
+                    cc: 1,
+                    value: 1.0,
+                }),
+        );
+
+        // The mapped CC is applied before the block is processed, and the plugin never sees it
+        assert_eq!(output.main_output[0][0], 0.5);
+        assert!(output.events.is_empty());
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.5));
+
+        // With sample accurate automation, mapped CCs split the block like parameter changes
+        let output = host.process(
+            ProcessInput::new(64)
+                .with_main_input(vec![vec![1.0; 64]; 2])
+                .with_event(NoteEvent::MidiCC {
+                    timing: 16,
+                    channel: 0,
+                    cc: 1,
+                    value: 0.0,
+                }),
+        );
+        assert_eq!(output.main_output[0][15], 0.5);
+        assert_eq!(output.main_output[0][16], 0.0);
+
+        // Mappings are stored in the plugin's state, but not in presets
+        let state = host.get_state();
+        assert!(host
+            .get_state_for(StateContext::Preset)
+            .midi_mappings
+            .is_empty());
+        host.set_midi_mapping("gain", None).unwrap();
+        host.set_state(state).unwrap();
+        assert_eq!(host.midi_mapping("gain"), Ok(Some(mapping)));
+    }
+
+    #[test]
+    fn transport_position() {
+        let mut host = new_host();
+        host.process(ProcessInput::new(64));
+        host.process(ProcessInput::new(64));
+
+        assert_eq!(host.transport().pos_samples, 128);
+    }
+
+    #[test]
+    fn transport_changes_split_block() {
+        let mut host = new_host();
+        let new_transport = TestTransport {
+            tempo: Some(140.0),
+            pos_samples: 1000,
+            ..TestTransport::default()
+        };
+        host.process(ProcessInput::new(64).with_transport_change(16, new_transport));
+
+        assert_eq!(
+            host.plugin().transport_log,
+            [(16, Some(120.0), Some(0)), (48, Some(140.0), Some(1000))]
+        );
+        assert_eq!(host.transport().tempo, Some(140.0));
+        assert_eq!(host.transport().pos_samples, 1048);
+    }
+
+    #[test]
+    fn state_roundtrip() {
+        let mut host = new_host();
+        host.set_param_normalized_value("gain", 0.25).unwrap();
+        let state = host.get_state();
+        // Output parameters are never stored
+        assert!(!state.params.contains_key("peak"));
+
+        host.set_param_normalized_value("gain", 1.0).unwrap();
+        host.set_state(state).unwrap();
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.25));
+        assert_eq!(
+            host.set_param_normalized_value("foo", 0.0),
+            Err(TestHostError::UnknownParameter(String::from("foo")))
+        );
+    }
+
+    #[test]
+    fn state_migrations() {
+        let mut host = new_host();
+        host.set_state_json(br#"{"version":"0.0.0","params":{"volume":{"f32":0.25}},"fields":{}}"#)
+            .unwrap();
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.25));
+
+        // The migration doesn't apply to state saved by the current version
+        assert_eq!(
+            host.migrate_state_json(
+                br#"{"version":"0.0.1","params":{"volume":{"f32":0.25}},"fields":{}}"#
+            )
+            .unwrap_err(),
+            TestHostError::UnknownParameter(String::from("volume"))
+        );
+        assert_eq!(
+            host.set_state_json(b"not a state"),
+            Err(TestHostError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn preset_validation() {
+        let mut host = new_host();
+        let preset = PresetFile::from_json(
+            r#"{
+                "plugin_id": "Other Plugin",
+                "version": "0.0.0",
+                "name": "Broken",
+                "params": {
+                    "volume": { "f32": 2.0 },
+                    "peak": { "f32": 0.5 },
+                    "foo": { "bool": true }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (state, issues) = preset.validate::<TestPlugin>(host.params().as_ref());
+        assert!(state.params.is_empty());
+        assert_eq!(
+            issues,
+            [
+                PresetIssue::WrongPlugin {
+                    plugin_id: String::from("Other Plugin")
+                },
+                PresetIssue::UnknownParameter {
+                    param_id: String::from("foo")
+                },
+                PresetIssue::OutOfRange {
+                    param_id: String::from("gain"),
+                    value: ParamValue::F32(2.0)
+                },
+                PresetIssue::OutputParameter {
+                    param_id: String::from("peak")
+                },
+            ]
+        );
+
+        host.set_param_normalized_value("gain", 0.25).unwrap();
+        let preset = PresetFile::new::<TestPlugin>("Quiet", host.get_state())
+            .with_tags(["utility"])
+            .to_json()
+            .unwrap();
+        host.set_param_normalized_value("gain", 1.0).unwrap();
+
+        let preset = PresetFile::from_json(&preset).unwrap();
+        assert_eq!(preset.plugin_id, TestPlugin::PRESET_ID);
+        let (state, issues) = preset.validate::<TestPlugin>(host.params().as_ref());
+        assert!(issues.is_empty());
+        host.set_state(state).unwrap();
+        assert_eq!(host.param_normalized_value("gain"), Ok(0.25));
+    }
+}
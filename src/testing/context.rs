@@ -0,0 +1,124 @@
+use std::cell::Cell;
+
+use crate::context::init::InitContext;
+use crate::context::process::{ProcessContext, Transport};
+use crate::context::{HostInfo, PluginApi, TrackInfo};
+use crate::midi::PluginNoteEvent;
+use crate::params::internals::ParamPtr;
+use crate::plugin::{Plugin, TaskExecutor};
+
+/// An [`InitContext`] implementation for the offline test host.
+pub(crate) struct TestInitContext<'a, P: Plugin> {
+    pub(super) task_executor: &'a TaskExecutor<P>,
+    pub(super) current_latency: &'a Cell<u32>,
+    pub(super) host_info: &'a HostInfo,
+}
+
+/// A [`ProcessContext`] implementation for the offline test host. Like in the standalone wrapper,
+/// the input events are read from a slice instead of from a queue.
+pub(crate) struct TestProcessContext<'a, P: Plugin> {
+    pub(super) task_executor: &'a TaskExecutor<P>,
+    pub(super) current_latency: &'a Cell<u32>,
+    pub(super) input_events: &'a [PluginNoteEvent<P>],
+    // The current index in `input_events`
+    pub(super) input_events_idx: usize,
+    pub(super) output_events: &'a mut Vec<PluginNoteEvent<P>>,
+    /// Parameter changes made by the plugin during this block. These are applied after the block
+    /// has been processed.
+    pub(super) output_param_changes: &'a mut Vec<(ParamPtr, f32)>,
+    pub(super) transport: Transport,
+    pub(super) host_info: &'a HostInfo,
+}
+
+impl<P: Plugin> InitContext<P> for TestInitContext<'_, P> {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    fn execute(&self, task: P::BackgroundTask) {
+        (self.task_executor)(task);
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.current_latency.set(samples);
+    }
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {
+        // This is only supported by CLAP
+    }
+
+    fn note_names_changed(&self) {
+        // There's no host to notify
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // The test host isn't a DAW, so there are no tracks
+        None
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        self.host_info
+    }
+}
+
+impl<P: Plugin> ProcessContext<P> for TestProcessContext<'_, P> {
+    fn plugin_api(&self) -> PluginApi {
+        PluginApi::Standalone
+    }
+
+    // There are no other threads in the test host, so tasks are run immediately to keep the
+    // results deterministic
+    fn execute_background(&self, task: P::BackgroundTask) {
+        (self.task_executor)(task);
+    }
+
+    fn execute_gui(&self, task: P::BackgroundTask) {
+        (self.task_executor)(task);
+    }
+
+    #[inline]
+    fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    fn next_event(&mut self) -> Option<PluginNoteEvent<P>> {
+        if self.input_events_idx < self.input_events.len() {
+            let event = self.input_events[self.input_events_idx].clone();
+            self.input_events_idx += 1;
+
+            Some(event)
+        } else {
+            None
+        }
+    }
+
+    fn send_event(&mut self, event: PluginNoteEvent<P>) {
+        self.output_events.push(event);
+    }
+
+    fn set_latency_samples(&self, samples: u32) {
+        self.current_latency.set(samples);
+    }
+
+    fn set_current_voice_capacity(&self, _capacity: u32) {
+        // This is only supported by CLAP
+    }
+
+    fn note_names_changed(&self) {
+        // There's no host to notify
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        self.host_info
+    }
+
+    // Gestures are not recorded, only the resulting parameter changes are reported in the
+    // `ProcessOutput`
+    unsafe fn raw_begin_set_parameter(&mut self, _param: ParamPtr) {}
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        self.output_param_changes.push((param, normalized));
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, _param: ParamPtr) {}
+}
@@ -1,14 +1,27 @@
 #[macro_use]
 mod util;
 
+mod ambisonic;
+mod audio_ports_activation;
 mod context;
 mod descriptor;
 mod factory;
 pub mod features;
+mod param_indication;
+mod preset_discovery;
+pub mod remote_controls;
+mod state_context;
+mod surround;
+mod track_info;
+mod undo;
 mod wrapper;
 
 /// Re-export for the wrapper.
 pub use self::factory::Factory;
+pub use self::preset_discovery::sys::{
+    CLAP_PRESET_DISCOVERY_FACTORY_ID, CLAP_PRESET_DISCOVERY_FACTORY_ID_COMPAT,
+};
+pub use self::preset_discovery::PresetDiscoveryFactory;
 pub use clap_sys::entry::clap_plugin_entry;
 pub use clap_sys::plugin_factory::CLAP_PLUGIN_FACTORY_ID;
 pub use clap_sys::version::CLAP_VERSION;
@@ -28,6 +41,7 @@ macro_rules! nih_export_clap {
             // escape hatch
             ::nih_plug::wrapper::clap::lazy_static! {
                 static ref FACTORY: ::nih_plug::wrapper::clap::Factory<$plugin_ty> = ::nih_plug::wrapper::clap::Factory::default();
+                static ref PRESET_DISCOVERY_FACTORY: ::nih_plug::wrapper::clap::PresetDiscoveryFactory<$plugin_ty> = ::nih_plug::wrapper::clap::PresetDiscoveryFactory::default();
             }
 
             pub extern "C" fn init(_plugin_path: *const ::std::os::raw::c_char) -> bool {
@@ -40,11 +54,19 @@ macro_rules! nih_export_clap {
             pub extern "C" fn get_factory(
                 factory_id: *const ::std::os::raw::c_char,
             ) -> *const ::std::ffi::c_void {
-                if !factory_id.is_null()
-                    && unsafe { ::std::ffi::CStr::from_ptr(factory_id) }
-                        == ::nih_plug::wrapper::clap::CLAP_PLUGIN_FACTORY_ID
-                {
+                if factory_id.is_null() {
+                    return std::ptr::null();
+                }
+
+                let factory_id = unsafe { ::std::ffi::CStr::from_ptr(factory_id) };
+                if factory_id == ::nih_plug::wrapper::clap::CLAP_PLUGIN_FACTORY_ID {
                     &(*FACTORY).clap_plugin_factory as *const _ as *const ::std::ffi::c_void
+                } else if factory_id == ::nih_plug::wrapper::clap::CLAP_PRESET_DISCOVERY_FACTORY_ID
+                    || factory_id
+                        == ::nih_plug::wrapper::clap::CLAP_PRESET_DISCOVERY_FACTORY_ID_COMPAT
+                {
+                    // This returns a null pointer if the plugin doesn't have any factory presets
+                    (*PRESET_DISCOVERY_FACTORY).clap_preset_discovery_factory_ptr()
                 } else {
                     std::ptr::null()
                 }
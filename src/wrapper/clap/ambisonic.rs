@@ -0,0 +1,53 @@
+//! Conversions for CLAP's ambisonic extension, which hosts use to query the channel ordering and
+//! normalization of the plugin's ambisonic ports.
+
+/// Bindings for the ambisonic extension. `clap-sys` 0.3 only has an older draft revision of this
+/// extension without `is_config_supported()`. These bindings follow revision 3, which CLAP 1.2
+/// stabilized.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::plugin::clap_plugin;
+    use std::ffi::CStr;
+
+    pub const CLAP_EXT_AMBISONIC: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.ambisonic/3\0") };
+    pub const CLAP_EXT_AMBISONIC_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.ambisonic.draft/3\0") };
+
+    pub const CLAP_PORT_AMBISONIC: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"ambisonic\0") };
+
+    pub const CLAP_AMBISONIC_ORDERING_ACN: u32 = 1;
+
+    pub const CLAP_AMBISONIC_NORMALIZATION_SN3D: u32 = 1;
+
+    #[repr(C)]
+    pub struct clap_ambisonic_config {
+        pub ordering: u32,
+        pub normalization: u32,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_ambisonic {
+        pub is_config_supported: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                config: *const clap_ambisonic_config,
+            ) -> bool,
+        >,
+        pub get_config: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                is_input: bool,
+                port_index: u32,
+                config: *mut clap_ambisonic_config,
+            ) -> bool,
+        >,
+    }
+}
+
+/// NIH-plug's ambisonic layouts always use ACN channel ordering with SN3D normalization (AmbiX).
+pub(crate) const AMBISONIC_CONFIG: sys::clap_ambisonic_config = sys::clap_ambisonic_config {
+    ordering: sys::CLAP_AMBISONIC_ORDERING_ACN,
+    normalization: sys::CLAP_AMBISONIC_NORMALIZATION_SN3D,
+};
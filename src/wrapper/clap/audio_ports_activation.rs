@@ -0,0 +1,31 @@
+//! Bindings for CLAP's audio ports activation extension, which hosts use to deactivate audio ports
+//! that aren't connected to anything.
+
+/// Bindings for the audio ports activation extension. `clap-sys` 0.3 only has an older draft
+/// revision of this extension, which lacks the `sample_size` argument to `set_active()` that
+/// revision 2 added.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::plugin::clap_plugin;
+    use std::ffi::CStr;
+
+    pub const CLAP_EXT_AUDIO_PORTS_ACTIVATION: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.audio-ports-activation/2\0") };
+    pub const CLAP_EXT_AUDIO_PORTS_ACTIVATION_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.audio-ports-activation/draft-2\0") };
+
+    #[repr(C)]
+    pub struct clap_plugin_audio_ports_activation {
+        pub can_activate_while_processing:
+            Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> bool>,
+        pub set_active: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                is_input: bool,
+                port_index: u32,
+                is_active: bool,
+                sample_size: u32,
+            ) -> bool,
+        >,
+    }
+}
@@ -1,15 +1,17 @@
 use atomic_refcell::AtomicRefMut;
 use std::cell::Cell;
 use std::collections::VecDeque;
+use std::os::raw::c_int;
 use std::sync::Arc;
 
 use super::wrapper::{OutputParamEvent, Task, Wrapper};
-use crate::context::gui::GuiContext;
+use crate::context::gui::{FdFlags, GuiContext, TimerId};
 use crate::context::init::InitContext;
 use crate::context::process::{ProcessContext, Transport};
-use crate::context::PluginApi;
+use crate::context::{HostInfo, PluginApi, TrackInfo};
 use crate::event_loop::EventLoop;
-use crate::midi::PluginNoteEvent;
+use crate::midi::learn::MidiMapping;
+use crate::midi::{MidiConfig, PluginNoteEvent};
 use crate::params::internals::ParamPtr;
 use crate::plugin::ClapPlugin;
 
@@ -30,6 +32,8 @@ pub(crate) struct WrapperInitContext<'a, P: ClapPlugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// Whether `.note_names_changed()` was called.
+    note_names_changed: Cell<bool>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -57,6 +61,9 @@ impl<P: ClapPlugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.wrapper.set_latency_samples(samples)
         }
+        if self.pending_requests.note_names_changed.take() {
+            self.wrapper.note_names_changed()
+        }
     }
 }
 
@@ -74,9 +81,22 @@ impl<P: ClapPlugin> InitContext<P> for WrapperInitContext<'_, P> {
         self.pending_requests.latency_changed.set(Some(samples));
     }
 
+    fn note_names_changed(&self) {
+        // See this struct's docstring
+        self.pending_requests.note_names_changed.set(true);
+    }
+
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.wrapper.track_info()
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        self.wrapper.host_info()
+    }
 }
 
 impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -114,6 +134,76 @@ impl<P: ClapPlugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, capacity: u32) {
         self.wrapper.set_current_voice_capacity(capacity)
     }
+
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
+
+    fn execute_parallel(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) {
+        self.wrapper.execute_parallel(num_tasks, task)
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        self.wrapper.host_info()
+    }
+
+    // These use the same output event queue as the `GuiContext`. The events are written to the
+    // host's output event queue at the end of the current (sub)block in `handle_out_events()`, and
+    // that's also where the parameter's value gets updated.
+    unsafe fn raw_begin_set_parameter(&mut self, param: ParamPtr) {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => {
+                let success = self
+                    .wrapper
+                    .queue_parameter_event(OutputParamEvent::BeginGesture { param_hash: *hash });
+
+                nih_debug_assert!(
+                    success,
+                    "Parameter output event queue was full, parameter change will not be sent to \
+                     the host"
+                );
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => {
+                let clap_plain_value = normalized as f64 * param.step_count().unwrap_or(1) as f64;
+                let success = self
+                    .wrapper
+                    .queue_parameter_event(OutputParamEvent::SetValue {
+                        param_hash: *hash,
+                        clap_plain_value,
+                    });
+
+                nih_debug_assert!(
+                    success,
+                    "Parameter output event queue was full, parameter change will not be sent to \
+                     the host"
+                );
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, param: ParamPtr) {
+        match self.wrapper.param_ptr_to_hash.get(&param) {
+            Some(hash) => {
+                let success = self
+                    .wrapper
+                    .queue_parameter_event(OutputParamEvent::EndGesture { param_hash: *hash });
+
+                nih_debug_assert!(
+                    success,
+                    "Parameter output event queue was full, parameter change will not be sent to \
+                     the host"
+                );
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
 }
 
 impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
@@ -139,6 +229,9 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
                     "Parameter output event queue was full, parameter change will not be sent to \
                      the host"
                 );
+
+                let update = self.wrapper.undo_manager.begin_gesture(param);
+                self.wrapper.notify_host_undo(update);
             }
             None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
         }
@@ -176,6 +269,8 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
                     "Parameter output event queue was full, parameter change will not be sent to \
                      the host"
                 );
+
+                self.wrapper.undo_manager.record_value(param, normalized);
             }
             None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
         }
@@ -204,6 +299,9 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
                     "Parameter output event queue was full, parameter change will not be sent to \
                      the host"
                 );
+
+                let update = self.wrapper.undo_manager.end_gesture(param);
+                self.wrapper.notify_host_undo(update);
             }
             None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
         }
@@ -220,11 +318,103 @@ impl<P: ClapPlugin> GuiContext for WrapperGuiContext<P> {
         }
     }
 
+    unsafe fn raw_start_midi_learn(&self, param: ParamPtr) {
+        nih_debug_assert!(
+            P::MIDI_INPUT >= MidiConfig::MidiCCs,
+            "MIDI learn requires the plugin to receive MIDI CCs"
+        );
+
+        match self.wrapper.param_id_from_ptr(param) {
+            Some(param_id) => self
+                .wrapper
+                .midi_learn
+                .start_learning(param, param_id.to_owned()),
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.wrapper.midi_learn.cancel_learning();
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.wrapper.midi_learn.learn_target()
+    }
+
+    unsafe fn raw_midi_mapping(&self, param: ParamPtr) -> Option<MidiMapping> {
+        self.wrapper.midi_learn.mapping(param)
+    }
+
+    unsafe fn raw_set_midi_mapping(&self, param: ParamPtr, mapping: Option<MidiMapping>) {
+        match self.wrapper.param_id_from_ptr(param) {
+            Some(param_id) => {
+                self.wrapper
+                    .midi_learn
+                    .set_mapping(param, param_id.to_owned(), mapping)
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
     fn get_state(&self) -> crate::wrapper::state::PluginState {
         self.wrapper.get_state_object()
     }
 
     fn set_state(&self, state: crate::wrapper::state::PluginState) {
-        self.wrapper.set_state_object_from_gui(state)
+        let update = self
+            .wrapper
+            .undo_manager
+            .record_state_change(self.wrapper.get_state_object(), state.clone());
+        self.wrapper.set_state_object_from_gui(state);
+        self.wrapper.notify_host_undo(update);
+    }
+
+    fn undo(&self) {
+        self.wrapper.undo(self)
+    }
+
+    fn redo(&self) {
+        self.wrapper.redo(self)
+    }
+
+    fn can_undo(&self) -> bool {
+        self.wrapper.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.wrapper.can_redo()
+    }
+
+    fn note_names_changed(&self) {
+        self.wrapper.note_names_changed()
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.wrapper.track_info()
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        self.wrapper.host_info()
+    }
+
+    fn register_timer(&self, period_ms: u32, callback: Box<dyn FnMut() + Send>) -> Option<TimerId> {
+        self.wrapper.register_timer(period_ms, callback)
+    }
+
+    fn unregister_timer(&self, timer_id: TimerId) {
+        self.wrapper.unregister_timer(timer_id)
+    }
+
+    fn register_fd(
+        &self,
+        fd: c_int,
+        flags: FdFlags,
+        callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        self.wrapper.register_fd(fd, flags, callback)
+    }
+
+    fn unregister_fd(&self, fd: c_int) {
+        self.wrapper.unregister_fd(fd)
     }
 }
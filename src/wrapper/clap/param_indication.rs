@@ -0,0 +1,83 @@
+//! Conversions for CLAP's param indication extension, which hosts use to tell the plugin about a
+//! parameter's controller mapping and automation state.
+
+use crate::params::indication::{AutomationState, IndicationColor};
+
+/// Bindings for the param indication extension. `clap-sys` 0.3 only has an older draft revision of
+/// this extension with a different ID. These bindings follow revision 4, which CLAP 1.2 stabilized,
+/// and the `draft/4` ID is kept around for hosts from before the stabilization.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::id::clap_id;
+    use clap_sys::plugin::clap_plugin;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    pub const CLAP_EXT_PARAM_INDICATION: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.param-indication/4\0") };
+    pub const CLAP_EXT_PARAM_INDICATION_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.param-indication.draft/4\0") };
+
+    pub const CLAP_PARAM_INDICATION_AUTOMATION_NONE: u32 = 0;
+    pub const CLAP_PARAM_INDICATION_AUTOMATION_PRESENT: u32 = 1;
+    pub const CLAP_PARAM_INDICATION_AUTOMATION_PLAYING: u32 = 2;
+    pub const CLAP_PARAM_INDICATION_AUTOMATION_RECORDING: u32 = 3;
+    pub const CLAP_PARAM_INDICATION_AUTOMATION_OVERRIDING: u32 = 4;
+
+    #[repr(C)]
+    pub struct clap_color {
+        pub alpha: u8,
+        pub red: u8,
+        pub green: u8,
+        pub blue: u8,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_param_indication {
+        pub set_mapping: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                param_id: clap_id,
+                has_mapping: bool,
+                color: *const clap_color,
+                label: *const c_char,
+                description: *const c_char,
+            ),
+        >,
+        pub set_automation: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                param_id: clap_id,
+                automation_state: u32,
+                color: *const clap_color,
+            ),
+        >,
+    }
+}
+
+/// Convert a CLAP color to an [`IndicationColor`]. Hosts pass a null pointer if they don't provide
+/// a color.
+///
+/// # Safety
+///
+/// `color` must be either a null pointer or a valid pointer to a `clap_color`.
+pub(crate) unsafe fn indication_color(color: *const sys::clap_color) -> Option<IndicationColor> {
+    color.as_ref().map(|color| IndicationColor {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    })
+}
+
+/// Convert a CLAP automation state to an [`AutomationState`]. Returns `None` for unknown states.
+pub(crate) fn indication_automation_state(automation_state: u32) -> Option<AutomationState> {
+    match automation_state {
+        sys::CLAP_PARAM_INDICATION_AUTOMATION_NONE => Some(AutomationState::None),
+        sys::CLAP_PARAM_INDICATION_AUTOMATION_PRESENT => Some(AutomationState::Present),
+        sys::CLAP_PARAM_INDICATION_AUTOMATION_PLAYING => Some(AutomationState::Playing),
+        sys::CLAP_PARAM_INDICATION_AUTOMATION_RECORDING => Some(AutomationState::Recording),
+        sys::CLAP_PARAM_INDICATION_AUTOMATION_OVERRIDING => Some(AutomationState::Overriding),
+        _ => None,
+    }
+}
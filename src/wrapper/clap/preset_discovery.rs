@@ -0,0 +1,484 @@
+//! Support for CLAP's preset discovery factory and preset load extension. These were added in CLAP
+//! 1.2 and are not yet part of `clap-sys`, so the bindings for the parts we need are defined here.
+//! The plugin's factory presets are exposed through a single provider with a single plugin
+//! location, and the presets are identified by their index using the load key.
+
+use clap_sys::version::CLAP_VERSION;
+use std::ffi::{c_void, CStr, CString};
+use std::marker::PhantomData;
+use std::os::raw::c_char;
+use std::ptr;
+
+use self::sys::*;
+use super::util::ClapPtr;
+use crate::plugin::ClapPlugin;
+use crate::wrapper::state::FactoryPreset;
+
+/// Bindings for the parts of the CLAP 1.2 preset discovery and preset load APIs that we use. These
+/// mirror the definitions from `clap/factory/preset-discovery.h` and `clap/ext/preset-load.h`.
+#[allow(non_camel_case_types, dead_code)]
+pub mod sys {
+    use clap_sys::host::clap_host;
+    use clap_sys::plugin::clap_plugin;
+    use clap_sys::version::clap_version;
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    pub const CLAP_PRESET_DISCOVERY_FACTORY_ID: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.preset-discovery-factory/2\0") };
+    pub const CLAP_PRESET_DISCOVERY_FACTORY_ID_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.preset-discovery-factory/draft-2\0") };
+
+    pub const CLAP_EXT_PRESET_LOAD: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.preset-load/2\0") };
+    pub const CLAP_EXT_PRESET_LOAD_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.preset-load.draft/2\0") };
+
+    /// The preset is stored inside of the plugin itself. The location is always a null pointer.
+    pub const CLAP_PRESET_DISCOVERY_LOCATION_PLUGIN: u32 = 1;
+
+    /// The preset is part of the plugin's factory content.
+    pub const CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT: u32 = 1 << 0;
+
+    #[repr(C)]
+    pub struct clap_universal_plugin_id {
+        pub abi: *const c_char,
+        pub id: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_metadata_receiver {
+        pub receiver_data: *mut c_void,
+        pub on_error: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                os_error: i32,
+                error_message: *const c_char,
+            ),
+        >,
+        pub begin_preset: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                name: *const c_char,
+                load_key: *const c_char,
+            ) -> bool,
+        >,
+        pub add_plugin_id: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                plugin_id: *const clap_universal_plugin_id,
+            ),
+        >,
+        pub set_soundpack_id: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                soundpack_id: *const c_char,
+            ),
+        >,
+        pub set_flags: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                flags: u32,
+            ),
+        >,
+        pub add_creator: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                creator: *const c_char,
+            ),
+        >,
+        pub set_description: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                description: *const c_char,
+            ),
+        >,
+        pub set_timestamps: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                creation_time: u64,
+                modification_time: u64,
+            ),
+        >,
+        pub add_feature: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                feature: *const c_char,
+            ),
+        >,
+        pub add_extra_info: Option<
+            unsafe extern "C" fn(
+                receiver: *const clap_preset_discovery_metadata_receiver,
+                key: *const c_char,
+                value: *const c_char,
+            ),
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_filetype {
+        pub name: *const c_char,
+        pub description: *const c_char,
+        pub file_extension: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_location {
+        pub flags: u32,
+        pub name: *const c_char,
+        pub kind: u32,
+        pub location: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_soundpack {
+        pub flags: u32,
+        pub id: *const c_char,
+        pub name: *const c_char,
+        pub description: *const c_char,
+        pub homepage_url: *const c_char,
+        pub vendor: *const c_char,
+        pub image_path: *const c_char,
+        pub release_timestamp: u64,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_provider_descriptor {
+        pub clap_version: clap_version,
+        pub id: *const c_char,
+        pub name: *const c_char,
+        pub vendor: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_provider {
+        pub desc: *const clap_preset_discovery_provider_descriptor,
+        pub provider_data: *mut c_void,
+        pub init:
+            Option<unsafe extern "C" fn(provider: *const clap_preset_discovery_provider) -> bool>,
+        pub destroy: Option<unsafe extern "C" fn(provider: *const clap_preset_discovery_provider)>,
+        pub get_metadata: Option<
+            unsafe extern "C" fn(
+                provider: *const clap_preset_discovery_provider,
+                location_kind: u32,
+                location: *const c_char,
+                metadata_receiver: *const clap_preset_discovery_metadata_receiver,
+            ) -> bool,
+        >,
+        pub get_extension: Option<
+            unsafe extern "C" fn(
+                provider: *const clap_preset_discovery_provider,
+                extension_id: *const c_char,
+            ) -> *const c_void,
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_indexer {
+        pub clap_version: clap_version,
+        pub name: *const c_char,
+        pub vendor: *const c_char,
+        pub url: *const c_char,
+        pub version: *const c_char,
+        pub indexer_data: *mut c_void,
+        pub declare_filetype: Option<
+            unsafe extern "C" fn(
+                indexer: *const clap_preset_discovery_indexer,
+                filetype: *const clap_preset_discovery_filetype,
+            ) -> bool,
+        >,
+        pub declare_location: Option<
+            unsafe extern "C" fn(
+                indexer: *const clap_preset_discovery_indexer,
+                location: *const clap_preset_discovery_location,
+            ) -> bool,
+        >,
+        pub declare_soundpack: Option<
+            unsafe extern "C" fn(
+                indexer: *const clap_preset_discovery_indexer,
+                soundpack: *const clap_preset_discovery_soundpack,
+            ) -> bool,
+        >,
+        pub get_extension: Option<
+            unsafe extern "C" fn(
+                indexer: *const clap_preset_discovery_indexer,
+                extension_id: *const c_char,
+            ) -> *const c_void,
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_preset_discovery_factory {
+        pub count:
+            Option<unsafe extern "C" fn(factory: *const clap_preset_discovery_factory) -> u32>,
+        pub get_descriptor: Option<
+            unsafe extern "C" fn(
+                factory: *const clap_preset_discovery_factory,
+                index: u32,
+            ) -> *const clap_preset_discovery_provider_descriptor,
+        >,
+        pub create: Option<
+            unsafe extern "C" fn(
+                factory: *const clap_preset_discovery_factory,
+                indexer: *const clap_preset_discovery_indexer,
+                provider_id: *const c_char,
+            ) -> *const clap_preset_discovery_provider,
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_preset_load {
+        pub from_location: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                location_kind: u32,
+                location: *const c_char,
+                load_key: *const c_char,
+            ) -> bool,
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_host_preset_load {
+        pub on_error: Option<
+            unsafe extern "C" fn(
+                host: *const clap_host,
+                location_kind: u32,
+                location: *const c_char,
+                load_key: *const c_char,
+                os_error: i32,
+                msg: *const c_char,
+            ),
+        >,
+        pub loaded: Option<
+            unsafe extern "C" fn(
+                host: *const clap_host,
+                location_kind: u32,
+                location: *const c_char,
+                load_key: *const c_char,
+            ),
+        >,
+    }
+}
+
+/// Parse a load key created by [`PresetDiscoveryProvider::get_metadata()`] back into an index in
+/// the plugin's factory presets.
+pub fn factory_preset_index_from_load_key(load_key: &CStr) -> Option<usize> {
+    load_key.to_str().ok()?.parse().ok()
+}
+
+/// The plugin's preset discovery factory. Like [`Factory`][super::Factory], this is initialized
+/// using a lazy_static from the entry point's `get_factory()` function. The host only gets to see
+/// this factory if the plugin has any factory presets.
+#[doc(hidden)]
+#[repr(C)]
+pub struct PresetDiscoveryFactory<P: ClapPlugin> {
+    // Keep the vtable as the first field so we can do a simple pointer cast
+    pub clap_preset_discovery_factory: clap_preset_discovery_factory,
+
+    /// The plugin's factory presets, queried once when the factory is created.
+    presets: Vec<FactoryPreset>,
+
+    provider_id: CString,
+    provider_name: CString,
+    provider_vendor: CString,
+    /// Contains pointers to the strings above. This is safe without pinning since the strings'
+    /// data is stored on the heap.
+    provider_descriptor: clap_preset_discovery_provider_descriptor,
+
+    /// The plugin's type.
+    _phantom: PhantomData<P>,
+}
+
+/// The single preset provider created by [`PresetDiscoveryFactory`]. This is allocated on the heap
+/// and freed again when the host calls `destroy()`.
+#[repr(C)]
+struct PresetDiscoveryProvider<P: ClapPlugin> {
+    // Keep the vtable as the first field so we can do a simple pointer cast
+    clap_preset_discovery_provider: clap_preset_discovery_provider,
+
+    /// The factory this provider was created from. This lives for the rest of the program's
+    /// lifetime.
+    factory: &'static PresetDiscoveryFactory<P>,
+    indexer: ClapPtr<clap_preset_discovery_indexer>,
+}
+
+unsafe impl<P: ClapPlugin> Send for PresetDiscoveryFactory<P> {}
+unsafe impl<P: ClapPlugin> Sync for PresetDiscoveryFactory<P> {}
+
+impl<P: ClapPlugin> Default for PresetDiscoveryFactory<P> {
+    fn default() -> Self {
+        let provider_id = CString::new(format!("{}.factory-presets", P::CLAP_ID))
+            .expect("`CLAP_ID` contained null bytes");
+        let provider_name = CString::new(format!("{} Factory Presets", P::NAME))
+            .expect("`NAME` contained null bytes");
+        let provider_vendor = CString::new(P::VENDOR).expect("`VENDOR` contained null bytes");
+
+        Self {
+            clap_preset_discovery_factory: clap_preset_discovery_factory {
+                count: Some(Self::count),
+                get_descriptor: Some(Self::get_descriptor),
+                create: Some(Self::create),
+            },
+
+            presets: P::factory_presets(),
+
+            provider_descriptor: clap_preset_discovery_provider_descriptor {
+                clap_version: CLAP_VERSION,
+                id: provider_id.as_ptr(),
+                name: provider_name.as_ptr(),
+                vendor: provider_vendor.as_ptr(),
+            },
+            provider_id,
+            provider_name,
+            provider_vendor,
+
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: ClapPlugin> PresetDiscoveryFactory<P> {
+    /// A pointer to this factory's vtable, or a null pointer if the plugin does not have any
+    /// factory presets.
+    pub fn clap_preset_discovery_factory_ptr(&self) -> *const c_void {
+        if self.presets.is_empty() {
+            ptr::null()
+        } else {
+            &self.clap_preset_discovery_factory as *const _ as *const c_void
+        }
+    }
+
+    unsafe extern "C" fn count(_factory: *const clap_preset_discovery_factory) -> u32 {
+        1
+    }
+
+    unsafe extern "C" fn get_descriptor(
+        factory: *const clap_preset_discovery_factory,
+        index: u32,
+    ) -> *const clap_preset_discovery_provider_descriptor {
+        check_null_ptr!(ptr::null(), factory);
+        let factory = &*(factory as *const Self);
+
+        if index == 0 {
+            &factory.provider_descriptor
+        } else {
+            ptr::null()
+        }
+    }
+
+    unsafe extern "C" fn create(
+        factory: *const clap_preset_discovery_factory,
+        indexer: *const clap_preset_discovery_indexer,
+        provider_id: *const c_char,
+    ) -> *const clap_preset_discovery_provider {
+        check_null_ptr!(ptr::null(), factory, indexer, provider_id);
+        // The factory is always stored in a static
+        let factory = &*(factory as *const Self);
+
+        if CStr::from_ptr(provider_id) == factory.provider_id.as_c_str() {
+            // This is turned back into a box and dropped in `destroy()`
+            let provider = Box::new(PresetDiscoveryProvider {
+                clap_preset_discovery_provider: clap_preset_discovery_provider {
+                    desc: &factory.provider_descriptor,
+                    provider_data: ptr::null_mut(),
+                    init: Some(PresetDiscoveryProvider::<P>::init),
+                    destroy: Some(PresetDiscoveryProvider::<P>::destroy),
+                    get_metadata: Some(PresetDiscoveryProvider::<P>::get_metadata),
+                    get_extension: Some(PresetDiscoveryProvider::<P>::get_extension),
+                },
+                factory,
+                indexer: ClapPtr::new(indexer),
+            });
+
+            Box::into_raw(provider) as *const clap_preset_discovery_provider
+        } else {
+            ptr::null()
+        }
+    }
+}
+
+impl<P: ClapPlugin> PresetDiscoveryProvider<P> {
+    unsafe extern "C" fn init(provider: *const clap_preset_discovery_provider) -> bool {
+        check_null_ptr!(false, provider);
+        let this = &*(provider as *const Self);
+
+        // All factory presets are stored inside of the plugin, so there's only a single location
+        let location_name = CString::new("Factory Presets").unwrap();
+        let location = clap_preset_discovery_location {
+            flags: CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT,
+            name: location_name.as_ptr(),
+            kind: CLAP_PRESET_DISCOVERY_LOCATION_PLUGIN,
+            location: ptr::null(),
+        };
+
+        clap_call! { this.indexer=>declare_location(&*this.indexer, &location) }
+    }
+
+    unsafe extern "C" fn destroy(provider: *const clap_preset_discovery_provider) {
+        check_null_ptr!((), provider);
+
+        drop(Box::from_raw(provider as *mut Self));
+    }
+
+    unsafe extern "C" fn get_metadata(
+        provider: *const clap_preset_discovery_provider,
+        location_kind: u32,
+        _location: *const c_char,
+        metadata_receiver: *const clap_preset_discovery_metadata_receiver,
+    ) -> bool {
+        check_null_ptr!(false, provider, metadata_receiver);
+        let this = &*(provider as *const Self);
+
+        if location_kind != CLAP_PRESET_DISCOVERY_LOCATION_PLUGIN {
+            nih_debug_assert_failure!("Unknown preset location kind {}", location_kind);
+            return false;
+        }
+
+        let clap_abi = CString::new("clap").unwrap();
+        let clap_id = CString::new(P::CLAP_ID).expect("`CLAP_ID` contained null bytes");
+        let plugin_id = clap_universal_plugin_id {
+            abi: clap_abi.as_ptr(),
+            id: clap_id.as_ptr(),
+        };
+
+        for (preset_idx, preset) in this.factory.presets.iter().enumerate() {
+            let name = match CString::new(preset.name.as_str()) {
+                Ok(name) => name,
+                Err(_) => {
+                    nih_debug_assert_failure!(
+                        "Factory preset name '{}' contains null bytes, skipping",
+                        preset.name
+                    );
+                    continue;
+                }
+            };
+            let load_key = CString::new(preset_idx.to_string()).unwrap();
+
+            // If the host returns false here then we must stop sending it presets
+            if !clap_call! { metadata_receiver=>begin_preset(metadata_receiver, name.as_ptr(), load_key.as_ptr()) }
+            {
+                break;
+            }
+
+            clap_call! { metadata_receiver=>add_plugin_id(metadata_receiver, &plugin_id) };
+            clap_call! { metadata_receiver=>set_flags(metadata_receiver, CLAP_PRESET_DISCOVERY_IS_FACTORY_CONTENT) };
+            if let Some(category) = preset
+                .category
+                .as_deref()
+                .and_then(|category| CString::new(category).ok())
+            {
+                clap_call! { metadata_receiver=>add_feature(metadata_receiver, category.as_ptr()) };
+            }
+        }
+
+        true
+    }
+
+    unsafe extern "C" fn get_extension(
+        _provider: *const clap_preset_discovery_provider,
+        _extension_id: *const c_char,
+    ) -> *const c_void {
+        ptr::null()
+    }
+}
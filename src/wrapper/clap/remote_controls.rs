@@ -0,0 +1,283 @@
+//! Remote control pages for hardware controllers. Hosts that support CLAP's remote controls
+//! extension map a controller's knobs to one of these pages at a time.
+
+use crate::params::ParamFlags;
+
+/// Bindings for the remote controls extension. `clap-sys` 0.3 only has the draft version of this
+/// extension in `ext::draft`, and CLAP 1.2 hosts only query the stable `clap.remote-controls/2` ID.
+/// The layout did not change, so the draft ID is still exposed for older hosts.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::id::clap_id;
+    use clap_sys::plugin::clap_plugin;
+    use clap_sys::string_sizes::CLAP_NAME_SIZE;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    pub const CLAP_EXT_REMOTE_CONTROLS: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.remote-controls/2\0") };
+    pub const CLAP_EXT_REMOTE_CONTROLS_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.remote-controls.draft/2\0") };
+
+    pub const CLAP_REMOTE_CONTROLS_COUNT: usize = 8;
+
+    #[repr(C)]
+    pub struct clap_remote_controls_page {
+        pub section_name: [c_char; CLAP_NAME_SIZE],
+        pub page_id: clap_id,
+        pub page_name: [c_char; CLAP_NAME_SIZE],
+        pub param_ids: [clap_id; CLAP_REMOTE_CONTROLS_COUNT],
+        pub is_for_preset: bool,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_remote_controls {
+        pub count: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> u32>,
+        pub get: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                page_index: u32,
+                page: *mut clap_remote_controls_page,
+            ) -> bool,
+        >,
+    }
+}
+
+/// The number of parameters on a single remote control page.
+pub const REMOTE_CONTROLS_PAGE_SIZE: usize = sys::CLAP_REMOTE_CONTROLS_COUNT;
+
+/// The name used for the page containing the parameters that don't belong to any group.
+const ROOT_GROUP_PAGE_NAME: &str = "Main";
+
+/// The remote control pages exposed to the host. See
+/// [`ClapPlugin::CLAP_REMOTE_CONTROLS`][crate::prelude::ClapPlugin::CLAP_REMOTE_CONTROLS].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClapRemoteControls {
+    /// Don't expose any remote control pages.
+    None,
+    /// Generate pages from the parameters' groups, as set using `#[nested(group = "...")]` on the
+    /// `Params` struct. Every group gets its own pages containing the group's parameters in their
+    /// normal order, and groups with more than eight parameters are split up into multiple pages.
+    /// Nested groups are listed in the section of their top level group. Hidden, output, and
+    /// non-automatable parameters are skipped.
+    FromParamGroups,
+    /// Use these pages instead.
+    Pages(&'static [ClapRemoteControlsPage]),
+}
+
+/// A single named page containing up to eight parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClapRemoteControlsPage {
+    /// The name of the section this page belongs to. Hosts may use this to group related pages.
+    pub section: &'static str,
+    /// The page's name.
+    pub name: &'static str,
+    /// The IDs of the parameters on this page, as used in the `Params` struct. Empty strings leave
+    /// the corresponding controls unassigned. Pages can contain at most eight parameters, and any
+    /// additional parameters are ignored.
+    pub params: &'static [&'static str],
+}
+
+impl ClapRemoteControlsPage {
+    /// A page named `name` in the `section` section containing the parameters with the IDs from
+    /// `params`.
+    pub const fn new(
+        section: &'static str,
+        name: &'static str,
+        params: &'static [&'static str],
+    ) -> Self {
+        Self {
+            section,
+            name,
+            params,
+        }
+    }
+}
+
+/// A remote control page with the parameters resolved to their hashes, ready to be passed to the
+/// host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteControlsPage {
+    pub section: String,
+    pub name: String,
+    /// The parameter hashes for each of the page's controls, or `None` for unassigned controls.
+    pub param_hashes: [Option<u32>; REMOTE_CONTROLS_PAGE_SIZE],
+}
+
+/// A parameter's information needed to build the remote control pages.
+pub(crate) struct RemoteControlsParam<'a> {
+    pub id: &'a str,
+    pub hash: u32,
+    /// The parameter's group, with nested groups delimited by slashes. This is an empty string
+    /// for parameters that don't belong to a group.
+    pub group: &'a str,
+    pub flags: ParamFlags,
+}
+
+impl ClapRemoteControls {
+    /// Resolve the remote control pages for the plugin's parameters. `params` should be in the
+    /// same order as the plugin's parameters.
+    pub(crate) fn pages(&self, params: &[RemoteControlsParam]) -> Vec<RemoteControlsPage> {
+        match self {
+            ClapRemoteControls::None => Vec::new(),
+            ClapRemoteControls::FromParamGroups => pages_from_param_groups(params),
+            ClapRemoteControls::Pages(pages) => pages
+                .iter()
+                .map(|page| {
+                    nih_debug_assert!(
+                        page.params.len() <= REMOTE_CONTROLS_PAGE_SIZE,
+                        "Remote control page '{}' contains more than {} parameters",
+                        page.name,
+                        REMOTE_CONTROLS_PAGE_SIZE
+                    );
+
+                    let mut param_hashes = [None; REMOTE_CONTROLS_PAGE_SIZE];
+                    for (param_hash, param_id) in param_hashes.iter_mut().zip(page.params) {
+                        if param_id.is_empty() {
+                            continue;
+                        }
+
+                        *param_hash = params
+                            .iter()
+                            .find(|param| param.id == *param_id)
+                            .map(|param| param.hash);
+                        nih_debug_assert!(
+                            param_hash.is_some(),
+                            "Unknown parameter ID '{}' on remote control page '{}'",
+                            param_id,
+                            page.name
+                        );
+                    }
+
+                    RemoteControlsPage {
+                        section: page.section.to_owned(),
+                        name: page.name.to_owned(),
+                        param_hashes,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+fn pages_from_param_groups(params: &[RemoteControlsParam]) -> Vec<RemoteControlsPage> {
+    // The groups are listed in the order they first appear in
+    let mut groups: Vec<(&str, Vec<u32>)> = Vec::new();
+    for param in params {
+        if param
+            .flags
+            .intersects(ParamFlags::HIDDEN | ParamFlags::NON_AUTOMATABLE | ParamFlags::OUTPUT)
+        {
+            continue;
+        }
+
+        match groups.iter_mut().find(|(group, _)| *group == param.group) {
+            Some((_, param_hashes)) => param_hashes.push(param.hash),
+            None => groups.push((param.group, vec![param.hash])),
+        }
+    }
+
+    let mut pages = Vec::new();
+    for (group, group_param_hashes) in groups {
+        let section = match group.split('/').next() {
+            Some(top_level_group) if !top_level_group.is_empty() => top_level_group,
+            _ => ROOT_GROUP_PAGE_NAME,
+        };
+        let group_name = match group.rsplit('/').next() {
+            Some(group_name) if !group_name.is_empty() => group_name,
+            _ => ROOT_GROUP_PAGE_NAME,
+        };
+
+        let num_pages =
+            (group_param_hashes.len() + REMOTE_CONTROLS_PAGE_SIZE - 1) / REMOTE_CONTROLS_PAGE_SIZE;
+        for (page_idx, page_param_hashes) in group_param_hashes
+            .chunks(REMOTE_CONTROLS_PAGE_SIZE)
+            .enumerate()
+        {
+            let mut param_hashes = [None; REMOTE_CONTROLS_PAGE_SIZE];
+            for (param_hash, page_param_hash) in param_hashes.iter_mut().zip(page_param_hashes) {
+                *param_hash = Some(*page_param_hash);
+            }
+
+            pages.push(RemoteControlsPage {
+                section: section.to_owned(),
+                name: if num_pages > 1 {
+                    format!("{} {}", group_name, page_idx + 1)
+                } else {
+                    group_name.to_owned()
+                },
+                param_hashes,
+            });
+        }
+    }
+
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(id: &'static str, hash: u32, group: &'static str) -> RemoteControlsParam<'static> {
+        RemoteControlsParam {
+            id,
+            hash,
+            group,
+            flags: ParamFlags::empty(),
+        }
+    }
+
+    #[test]
+    fn from_param_groups() {
+        let mut params = vec![param("gain", 0, "")];
+        params.extend((1..=10).map(|hash| param("osc", hash, "Oscillators/Osc 1")));
+        params.push(RemoteControlsParam {
+            flags: ParamFlags::HIDDEN,
+            ..param("hidden", 11, "")
+        });
+        params.push(param("cutoff", 12, "Filter"));
+
+        let pages = ClapRemoteControls::FromParamGroups.pages(&params);
+        let names: Vec<_> = pages
+            .iter()
+            .map(|page| (page.section.as_str(), page.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Main", "Main"),
+                ("Oscillators", "Osc 1 1"),
+                ("Oscillators", "Osc 1 2"),
+                ("Filter", "Filter"),
+            ]
+        );
+        assert_eq!(
+            pages[0].param_hashes,
+            [Some(0), None, None, None, None, None, None, None]
+        );
+        assert_eq!(
+            pages[2].param_hashes,
+            [Some(9), Some(10), None, None, None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn explicit_pages() {
+        const PAGES: &[ClapRemoteControlsPage] = &[ClapRemoteControlsPage::new(
+            "Mixer",
+            "Levels",
+            &["gain", "", "cutoff"],
+        )];
+        let params = [param("gain", 5, ""), param("cutoff", 6, "Filter")];
+
+        let pages = ClapRemoteControls::Pages(PAGES).pages(&params);
+        assert_eq!(
+            pages,
+            [RemoteControlsPage {
+                section: String::from("Mixer"),
+                name: String::from("Levels"),
+                param_hashes: [Some(5), None, Some(6), None, None, None, None, None],
+            }]
+        );
+    }
+}
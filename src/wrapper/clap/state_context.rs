@@ -0,0 +1,50 @@
+//! Conversions for CLAP's state context extension, which hosts use to tell the plugin whether its
+//! state is saved for a preset, for duplicating the plugin, or as part of a project.
+
+use crate::wrapper::state::StateContext;
+
+/// Bindings for the state context extension. `clap-sys` 0.3 only has the draft version of this
+/// extension in `ext::draft`, which uses a different ID than the stable `clap.state-context/2`
+/// extension from CLAP 1.2.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::plugin::clap_plugin;
+    use clap_sys::stream::{clap_istream, clap_ostream};
+    use std::ffi::CStr;
+
+    pub const CLAP_EXT_STATE_CONTEXT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.state-context/2\0") };
+
+    pub const CLAP_STATE_CONTEXT_FOR_PRESET: u32 = 1;
+    pub const CLAP_STATE_CONTEXT_FOR_DUPLICATE: u32 = 2;
+    pub const CLAP_STATE_CONTEXT_FOR_PROJECT: u32 = 3;
+
+    #[repr(C)]
+    pub struct clap_plugin_state_context {
+        pub save: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                stream: *const clap_ostream,
+                context_type: u32,
+            ) -> bool,
+        >,
+        pub load: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                stream: *const clap_istream,
+                context_type: u32,
+            ) -> bool,
+        >,
+    }
+}
+
+/// Convert a CLAP state context type to a [`StateContext`]. Returns `None` for unknown context
+/// types.
+pub(crate) fn state_context(context_type: u32) -> Option<StateContext> {
+    match context_type {
+        sys::CLAP_STATE_CONTEXT_FOR_PRESET => Some(StateContext::Preset),
+        sys::CLAP_STATE_CONTEXT_FOR_DUPLICATE => Some(StateContext::Duplicate),
+        sys::CLAP_STATE_CONTEXT_FOR_PROJECT => Some(StateContext::Project),
+        _ => None,
+    }
+}
@@ -0,0 +1,92 @@
+//! Conversions for CLAP's surround extension, which hosts use to query the speaker positions of the
+//! plugin's surround ports.
+
+use crate::audio_setup::ChannelLayout;
+
+/// Bindings for the surround extension. `clap-sys` 0.3 only has an older draft revision of this
+/// extension without `is_channel_mask_supported()`. These bindings follow revision 4, which CLAP
+/// 1.2 stabilized.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::plugin::clap_plugin;
+    use std::ffi::CStr;
+
+    pub const CLAP_EXT_SURROUND: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.surround/4\0") };
+    pub const CLAP_EXT_SURROUND_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.surround.draft/4\0") };
+
+    pub const CLAP_PORT_SURROUND: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"surround\0") };
+
+    pub const CLAP_SURROUND_FL: u8 = 0;
+    pub const CLAP_SURROUND_FR: u8 = 1;
+    pub const CLAP_SURROUND_FC: u8 = 2;
+    pub const CLAP_SURROUND_LFE: u8 = 3;
+    pub const CLAP_SURROUND_BL: u8 = 4;
+    pub const CLAP_SURROUND_BR: u8 = 5;
+    pub const CLAP_SURROUND_SL: u8 = 9;
+    pub const CLAP_SURROUND_SR: u8 = 10;
+    pub const CLAP_SURROUND_TFL: u8 = 12;
+    pub const CLAP_SURROUND_TFR: u8 = 14;
+    pub const CLAP_SURROUND_TBL: u8 = 15;
+    pub const CLAP_SURROUND_TBR: u8 = 17;
+
+    #[repr(C)]
+    pub struct clap_plugin_surround {
+        pub is_channel_mask_supported:
+            Option<unsafe extern "C" fn(plugin: *const clap_plugin, channel_mask: u64) -> bool>,
+        pub get_channel_map: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                is_input: bool,
+                port_index: u32,
+                channel_map: *mut u8,
+                channel_map_capacity: u32,
+            ) -> u32,
+        >,
+    }
+}
+
+const SURROUND_51_CHANNEL_MAP: [u8; 6] = [
+    sys::CLAP_SURROUND_FL,
+    sys::CLAP_SURROUND_FR,
+    sys::CLAP_SURROUND_FC,
+    sys::CLAP_SURROUND_LFE,
+    sys::CLAP_SURROUND_BL,
+    sys::CLAP_SURROUND_BR,
+];
+const SURROUND_714_CHANNEL_MAP: [u8; 12] = [
+    sys::CLAP_SURROUND_FL,
+    sys::CLAP_SURROUND_FR,
+    sys::CLAP_SURROUND_FC,
+    sys::CLAP_SURROUND_LFE,
+    sys::CLAP_SURROUND_BL,
+    sys::CLAP_SURROUND_BR,
+    sys::CLAP_SURROUND_SL,
+    sys::CLAP_SURROUND_SR,
+    sys::CLAP_SURROUND_TFL,
+    sys::CLAP_SURROUND_TFR,
+    sys::CLAP_SURROUND_TBL,
+    sys::CLAP_SURROUND_TBR,
+];
+
+/// The speaker position for each of a surround layout's channels. Returns `None` for layouts that
+/// are not surround layouts. The channel orders match those of the VST3 speaker arrangements.
+pub(crate) fn channel_map(layout: ChannelLayout) -> Option<&'static [u8]> {
+    match layout {
+        ChannelLayout::Surround51 => Some(&SURROUND_51_CHANNEL_MAP),
+        ChannelLayout::Surround714 => Some(&SURROUND_714_CHANNEL_MAP),
+        _ => None,
+    }
+}
+
+/// The channel mask for a surround layout, with one bit set for every speaker position in the
+/// layout. Returns `None` for layouts that are not surround layouts.
+pub(crate) fn channel_mask(layout: ChannelLayout) -> Option<u64> {
+    channel_map(layout).map(|channel_map| {
+        channel_map
+            .iter()
+            .fold(0, |mask, &position| mask | (1 << position))
+    })
+}
@@ -0,0 +1,105 @@
+//! Conversions for CLAP's track info extension, which hosts use to tell the plugin about the track
+//! it's inserted on.
+
+use crate::context::{TrackColor, TrackFlags, TrackInfo};
+
+/// Bindings for the track info extension. `clap-sys` 0.3 only has the draft version of this
+/// extension in `ext::draft`, while CLAP 1.2 hosts only query the stable `clap.track-info/1` ID.
+/// Both IDs share the same layout, so the host extension is queried under either of them.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::host::clap_host;
+    use clap_sys::plugin::clap_plugin;
+    use clap_sys::string_sizes::CLAP_NAME_SIZE;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    use crate::wrapper::clap::param_indication::sys::clap_color;
+
+    pub const CLAP_EXT_TRACK_INFO: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.track-info/1\0") };
+    pub const CLAP_EXT_TRACK_INFO_COMPAT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.track-info.draft/1\0") };
+
+    pub const CLAP_TRACK_INFO_HAS_TRACK_NAME: u64 = 1 << 0;
+    pub const CLAP_TRACK_INFO_HAS_TRACK_COLOR: u64 = 1 << 1;
+    pub const CLAP_TRACK_INFO_HAS_AUDIO_CHANNEL: u64 = 1 << 2;
+    pub const CLAP_TRACK_INFO_IS_FOR_RETURN_TRACK: u64 = 1 << 3;
+    pub const CLAP_TRACK_INFO_IS_FOR_BUS: u64 = 1 << 4;
+    pub const CLAP_TRACK_INFO_IS_FOR_MASTER: u64 = 1 << 5;
+
+    #[repr(C)]
+    pub struct clap_track_info {
+        pub flags: u64,
+        pub name: [c_char; CLAP_NAME_SIZE],
+        pub color: clap_color,
+        pub audio_channel_count: i32,
+        pub audio_port_type: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_track_info {
+        pub changed: Option<unsafe extern "C" fn(plugin: *const clap_plugin)>,
+    }
+
+    #[repr(C)]
+    pub struct clap_host_track_info {
+        pub get: Option<
+            unsafe extern "C" fn(host: *const clap_host, info: *mut clap_track_info) -> bool,
+        >,
+    }
+}
+
+/// Convert CLAP's track info to a [`TrackInfo`]. Fields the host didn't set a flag for are left
+/// empty.
+pub(crate) fn track_info(info: &sys::clap_track_info) -> TrackInfo {
+    let name = if info.flags & sys::CLAP_TRACK_INFO_HAS_TRACK_NAME != 0 {
+        // The name should be null terminated, but we won't read past the end of the array if it
+        // isn't
+        let name_bytes: Vec<u8> = info
+            .name
+            .iter()
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8)
+            .collect();
+        Some(String::from_utf8_lossy(&name_bytes).into_owned())
+    } else {
+        None
+    };
+    let color = if info.flags & sys::CLAP_TRACK_INFO_HAS_TRACK_COLOR != 0 {
+        Some(TrackColor {
+            red: info.color.red,
+            green: info.color.green,
+            blue: info.color.blue,
+            alpha: info.color.alpha,
+        })
+    } else {
+        None
+    };
+    let channel_count = if info.flags & sys::CLAP_TRACK_INFO_HAS_AUDIO_CHANNEL != 0 {
+        u32::try_from(info.audio_channel_count).ok()
+    } else {
+        None
+    };
+
+    let mut flags = TrackFlags::empty();
+    flags.set(
+        TrackFlags::RETURN_TRACK,
+        info.flags & sys::CLAP_TRACK_INFO_IS_FOR_RETURN_TRACK != 0,
+    );
+    flags.set(
+        TrackFlags::BUS,
+        info.flags & sys::CLAP_TRACK_INFO_IS_FOR_BUS != 0,
+    );
+    flags.set(
+        TrackFlags::MASTER,
+        info.flags & sys::CLAP_TRACK_INFO_IS_FOR_MASTER != 0,
+    );
+
+    TrackInfo {
+        name,
+        color,
+        channel_count,
+        flags,
+    }
+}
@@ -0,0 +1,210 @@
+//! CLAP's undo extension, which lets the plugin's own undo history become part of the host's undo
+//! history. The plugin tells the host about every transaction it records, and the host then asks
+//! the plugin to undo or redo those transactions when the user uses the host's undo functionality.
+
+use clap_sys::host::clap_host;
+use clap_sys::id::clap_id;
+use std::ffi::c_void;
+
+use super::util::{clap_call, ClapPtr};
+
+/// Bindings for the undo extension. `clap-sys` 0.3 does not have any bindings for this extension,
+/// not even as a draft. The stable version from CLAP 1.2 is split into the undo, undo context, and
+/// undo delta interfaces below.
+#[allow(non_camel_case_types)]
+pub(crate) mod sys {
+    use clap_sys::host::clap_host;
+    use clap_sys::id::clap_id;
+    use clap_sys::plugin::clap_plugin;
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    pub const CLAP_EXT_UNDO: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.undo/4\0") };
+    pub const CLAP_EXT_UNDO_CONTEXT: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.undo_context/4\0") };
+    pub const CLAP_EXT_UNDO_DELTA: &CStr =
+        unsafe { CStr::from_bytes_with_nul_unchecked(b"clap.undo_delta/4\0") };
+
+    #[repr(C)]
+    pub struct clap_undo_delta_properties {
+        pub has_delta: bool,
+        pub are_deltas_persistent: bool,
+        pub format_version: clap_id,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_undo_delta {
+        pub get_delta_properties: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                properties: *mut clap_undo_delta_properties,
+            ),
+        >,
+        pub can_use_delta_format_version: Option<
+            unsafe extern "C" fn(plugin: *const clap_plugin, format_version: clap_id) -> bool,
+        >,
+        pub undo: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                format_version: clap_id,
+                delta: *const c_void,
+                delta_size: usize,
+            ) -> bool,
+        >,
+        pub redo: Option<
+            unsafe extern "C" fn(
+                plugin: *const clap_plugin,
+                format_version: clap_id,
+                delta: *const c_void,
+                delta_size: usize,
+            ) -> bool,
+        >,
+    }
+
+    #[repr(C)]
+    pub struct clap_plugin_undo_context {
+        pub set_can_undo: Option<unsafe extern "C" fn(plugin: *const clap_plugin, can_undo: bool)>,
+        pub set_can_redo: Option<unsafe extern "C" fn(plugin: *const clap_plugin, can_redo: bool)>,
+        pub set_undo_name:
+            Option<unsafe extern "C" fn(plugin: *const clap_plugin, name: *const c_char)>,
+        pub set_redo_name:
+            Option<unsafe extern "C" fn(plugin: *const clap_plugin, name: *const c_char)>,
+    }
+
+    #[repr(C)]
+    pub struct clap_host_undo {
+        pub begin_change: Option<unsafe extern "C" fn(host: *const clap_host)>,
+        pub cancel_change: Option<unsafe extern "C" fn(host: *const clap_host)>,
+        pub change_made: Option<
+            unsafe extern "C" fn(
+                host: *const clap_host,
+                name: *const c_char,
+                delta: *const c_void,
+                delta_size: usize,
+                delta_can_undo: bool,
+            ),
+        >,
+        pub request_undo: Option<unsafe extern "C" fn(host: *const clap_host)>,
+        pub request_redo: Option<unsafe extern "C" fn(host: *const clap_host)>,
+        pub set_wants_context_updates:
+            Option<unsafe extern "C" fn(host: *const clap_host, is_subscribed: bool)>,
+    }
+}
+
+/// Query the host's undo extension. If the host supports it, then this also subscribes to the
+/// host's undo context updates. Without that subscription the host never calls the undo context
+/// extension's functions, and `GuiContext::can_undo()` and `GuiContext::can_redo()` would always
+/// return `false`.
+///
+/// # Safety
+///
+/// This may only be called from `clap_plugin::init()` or later.
+pub(crate) unsafe fn query_host_undo(
+    host_callback: &ClapPtr<clap_host>,
+) -> Option<ClapPtr<sys::clap_host_undo>> {
+    let host_undo = clap_call! {
+        host_callback=>get_extension(&**host_callback, sys::CLAP_EXT_UNDO.as_ptr())
+    } as *const sys::clap_host_undo;
+    if host_undo.is_null() {
+        return None;
+    }
+
+    clap_call! { host_undo=>set_wants_context_updates(&**host_callback, true) };
+
+    Some(ClapPtr::new(host_undo))
+}
+
+/// The format version for the deltas sent to the host. The deltas only contain a transaction ID
+/// for the plugin's undo history, so they are not persistent.
+pub(crate) const DELTA_FORMAT_VERSION: clap_id = 1;
+
+/// Encode a transaction ID as a delta for the host.
+pub(crate) fn encode_delta(transaction_id: u64) -> [u8; 8] {
+    transaction_id.to_le_bytes()
+}
+
+/// Decode a delta created with [`encode_delta()`]. Returns `None` if the delta has the wrong size.
+///
+/// # Safety
+///
+/// `delta` needs to point to `delta_size` readable bytes.
+pub(crate) unsafe fn decode_delta(delta: *const c_void, delta_size: usize) -> Option<u64> {
+    if delta.is_null() || delta_size != 8 {
+        return None;
+    }
+
+    let bytes = std::slice::from_raw_parts(delta as *const u8, delta_size);
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_sys::version::CLAP_VERSION;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// A host that only supports the undo extension, and that remembers whether the plugin
+    /// subscribed to its undo context updates.
+    struct TestHost {
+        host: clap_host,
+        host_undo: sys::clap_host_undo,
+        wants_context_updates: AtomicBool,
+    }
+
+    unsafe extern "C" fn get_extension(
+        host: *const clap_host,
+        extension_id: *const c_char,
+    ) -> *const c_void {
+        let test_host = &*((*host).host_data as *const TestHost);
+        if CStr::from_ptr(extension_id) == sys::CLAP_EXT_UNDO {
+            &test_host.host_undo as *const _ as *const c_void
+        } else {
+            ptr::null()
+        }
+    }
+
+    unsafe extern "C" fn set_wants_context_updates(host: *const clap_host, is_subscribed: bool) {
+        let test_host = &*((*host).host_data as *const TestHost);
+        test_host
+            .wants_context_updates
+            .store(is_subscribed, Ordering::Relaxed);
+    }
+
+    unsafe extern "C" fn noop(_host: *const clap_host) {}
+
+    #[test]
+    fn subscribes_to_context_updates() {
+        let mut test_host = Box::new(TestHost {
+            host: clap_host {
+                clap_version: CLAP_VERSION,
+                host_data: ptr::null_mut(),
+                name: ptr::null(),
+                vendor: ptr::null(),
+                url: ptr::null(),
+                version: ptr::null(),
+                get_extension: Some(get_extension),
+                request_restart: Some(noop),
+                request_process: Some(noop),
+                request_callback: Some(noop),
+            },
+            host_undo: sys::clap_host_undo {
+                begin_change: Some(noop),
+                cancel_change: Some(noop),
+                change_made: None,
+                request_undo: Some(noop),
+                request_redo: Some(noop),
+                set_wants_context_updates: Some(set_wants_context_updates),
+            },
+            wants_context_updates: AtomicBool::new(false),
+        });
+        test_host.host.host_data = &*test_host as *const TestHost as *mut c_void;
+
+        let host_callback = unsafe { ClapPtr::new(&test_host.host as *const clap_host) };
+        assert!(unsafe { query_host_undo(&host_callback) }.is_some());
+        assert!(test_host.wants_context_updates.load(Ordering::Relaxed));
+    }
+}
@@ -1,8 +1,13 @@
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::process::clap_process;
 use clap_sys::stream::{clap_istream, clap_ostream};
+use std::cmp;
 use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::os::raw::c_void;
 
+use crate::wrapper::util::buffer_management::ProcessSample;
+
 /// Early exit out of a function with the specified return value when one of the passed pointers is
 /// null.
 macro_rules! check_null_ptr {
@@ -85,6 +90,87 @@ impl<T> ClapPtr<T> {
     }
 }
 
+/// Abstracts over the `data32` and `data64` fields in [`clap_audio_buffer`] so the buffer handling
+/// code can be shared between single and double precision processing.
+pub(crate) trait ClapSample: ProcessSample {
+    /// Get the channel pointers for this sample type. This will be a null pointer if the host uses
+    /// the other precision for this buffer.
+    fn channel_ptrs(buffer: &clap_audio_buffer) -> *mut *mut Self;
+}
+
+impl ClapSample for f32 {
+    #[inline]
+    fn channel_ptrs(buffer: &clap_audio_buffer) -> *mut *mut Self {
+        buffer.data32 as *mut *mut f32
+    }
+}
+
+impl ClapSample for f64 {
+    #[inline]
+    fn channel_ptrs(buffer: &clap_audio_buffer) -> *mut *mut Self {
+        buffer.data64 as *mut *mut f64
+    }
+}
+
+/// Compute a silence mask for a CLAP audio buffer. CLAP only has a constant mask, so a constant
+/// channel is considered to be silent if its first sample is zero. Channels past the 64th channel
+/// are never marked as silent.
+///
+/// # Safety
+///
+/// `buffer`'s channel pointers for sample type `T` must be valid, and `sample_idx` must be within
+/// the buffer's bounds.
+pub unsafe fn clap_silence_mask<T: ClapSample>(
+    buffer: &clap_audio_buffer,
+    sample_idx: usize,
+) -> u64 {
+    let channel_ptrs = T::channel_ptrs(buffer);
+    if channel_ptrs.is_null() {
+        return 0;
+    }
+
+    let mut silence_mask = 0;
+    for channel_idx in 0..cmp::min(buffer.channel_count as usize, 64) {
+        if buffer.constant_mask & (1 << channel_idx) != 0
+            && *(*channel_ptrs.add(channel_idx)).add(sample_idx) == T::default()
+        {
+            silence_mask |= 1 << channel_idx;
+        }
+    }
+
+    silence_mask
+}
+
+/// Check whether the host passed double precision buffers for this process call. Hosts set either
+/// the `data32` or the `data64` field on each audio buffer, so this checks whether any of the
+/// buffers only has its `data64` field set.
+///
+/// # Safety
+///
+/// The audio buffer pointers in `process` must be valid for the current process call.
+pub unsafe fn uses_f64_buffers(process: &clap_process) -> bool {
+    let is_f64 = |buffers: *const clap_audio_buffer, count: u32| {
+        !buffers.is_null()
+            && (0..count as usize).any(|idx| {
+                let buffer = &*buffers.add(idx);
+                buffer.data32.is_null() && !buffer.data64.is_null()
+            })
+    };
+
+    is_f64(process.audio_outputs, process.audio_outputs_count)
+        || is_f64(process.audio_inputs, process.audio_inputs_count)
+}
+
+/// Convert a CLAP channel or key number to an optional value. CLAP uses -1 as a wildcard for these
+/// fields, which is represented as `None`.
+pub fn clap_wildcard(value: i16) -> Option<u8> {
+    if value >= 0 {
+        Some(value as u8)
+    } else {
+        None
+    }
+}
+
 /// A buffer a stream can be read into. This is needed to allow reading into uninitialized vectors
 /// using slices without invoking UB.
 ///
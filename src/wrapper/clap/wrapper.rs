@@ -1,23 +1,24 @@
 use atomic_float::AtomicF32;
 use atomic_refcell::{AtomicRefCell, AtomicRefMut};
 use clap_sys::events::{
-    clap_event_header, clap_event_midi, clap_event_midi_sysex, clap_event_note,
+    clap_event_header, clap_event_midi, clap_event_midi2, clap_event_midi_sysex, clap_event_note,
     clap_event_note_expression, clap_event_param_gesture, clap_event_param_mod,
     clap_event_param_value, clap_event_transport, clap_input_events, clap_output_events,
-    CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE, CLAP_EVENT_MIDI, CLAP_EVENT_MIDI_SYSEX,
-    CLAP_EVENT_NOTE_CHOKE, CLAP_EVENT_NOTE_END, CLAP_EVENT_NOTE_EXPRESSION, CLAP_EVENT_NOTE_OFF,
-    CLAP_EVENT_NOTE_ON, CLAP_EVENT_PARAM_GESTURE_BEGIN, CLAP_EVENT_PARAM_GESTURE_END,
-    CLAP_EVENT_PARAM_MOD, CLAP_EVENT_PARAM_VALUE, CLAP_EVENT_TRANSPORT,
-    CLAP_NOTE_EXPRESSION_BRIGHTNESS, CLAP_NOTE_EXPRESSION_EXPRESSION, CLAP_NOTE_EXPRESSION_PAN,
-    CLAP_NOTE_EXPRESSION_PRESSURE, CLAP_NOTE_EXPRESSION_TUNING, CLAP_NOTE_EXPRESSION_VIBRATO,
-    CLAP_NOTE_EXPRESSION_VOLUME, CLAP_TRANSPORT_HAS_BEATS_TIMELINE,
+    CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_IS_LIVE, CLAP_EVENT_MIDI, CLAP_EVENT_MIDI2,
+    CLAP_EVENT_MIDI_SYSEX, CLAP_EVENT_NOTE_CHOKE, CLAP_EVENT_NOTE_END, CLAP_EVENT_NOTE_EXPRESSION,
+    CLAP_EVENT_NOTE_OFF, CLAP_EVENT_NOTE_ON, CLAP_EVENT_PARAM_GESTURE_BEGIN,
+    CLAP_EVENT_PARAM_GESTURE_END, CLAP_EVENT_PARAM_MOD, CLAP_EVENT_PARAM_VALUE,
+    CLAP_EVENT_TRANSPORT, CLAP_NOTE_EXPRESSION_BRIGHTNESS, CLAP_NOTE_EXPRESSION_EXPRESSION,
+    CLAP_NOTE_EXPRESSION_PAN, CLAP_NOTE_EXPRESSION_PRESSURE, CLAP_NOTE_EXPRESSION_TUNING,
+    CLAP_NOTE_EXPRESSION_VIBRATO, CLAP_NOTE_EXPRESSION_VOLUME, CLAP_TRANSPORT_HAS_BEATS_TIMELINE,
     CLAP_TRANSPORT_HAS_SECONDS_TIMELINE, CLAP_TRANSPORT_HAS_TEMPO,
     CLAP_TRANSPORT_HAS_TIME_SIGNATURE, CLAP_TRANSPORT_IS_LOOP_ACTIVE, CLAP_TRANSPORT_IS_PLAYING,
     CLAP_TRANSPORT_IS_RECORDING, CLAP_TRANSPORT_IS_WITHIN_PRE_ROLL,
 };
 use clap_sys::ext::audio_ports::{
-    clap_audio_port_info, clap_plugin_audio_ports, CLAP_AUDIO_PORT_IS_MAIN, CLAP_EXT_AUDIO_PORTS,
-    CLAP_PORT_MONO, CLAP_PORT_STEREO,
+    clap_audio_port_info, clap_plugin_audio_ports, CLAP_AUDIO_PORT_IS_MAIN,
+    CLAP_AUDIO_PORT_REQUIRES_COMMON_SAMPLE_SIZE, CLAP_AUDIO_PORT_SUPPORTS_64BITS,
+    CLAP_EXT_AUDIO_PORTS, CLAP_PORT_MONO, CLAP_PORT_STEREO,
 };
 use clap_sys::ext::audio_ports_config::{
     clap_audio_ports_config, clap_plugin_audio_ports_config, CLAP_EXT_AUDIO_PORTS_CONFIG,
@@ -31,15 +32,23 @@ use clap_sys::ext::gui::{
     CLAP_WINDOW_API_COCOA, CLAP_WINDOW_API_WIN32, CLAP_WINDOW_API_X11,
 };
 use clap_sys::ext::latency::{clap_host_latency, clap_plugin_latency, CLAP_EXT_LATENCY};
+use clap_sys::ext::note_name::{
+    clap_host_note_name, clap_note_name, clap_plugin_note_name, CLAP_EXT_NOTE_NAME,
+};
 use clap_sys::ext::note_ports::{
     clap_note_port_info, clap_plugin_note_ports, CLAP_EXT_NOTE_PORTS, CLAP_NOTE_DIALECT_CLAP,
-    CLAP_NOTE_DIALECT_MIDI,
+    CLAP_NOTE_DIALECT_MIDI, CLAP_NOTE_DIALECT_MIDI2, CLAP_NOTE_DIALECT_MIDI_MPE,
 };
 use clap_sys::ext::params::{
     clap_host_params, clap_param_info, clap_plugin_params, CLAP_EXT_PARAMS,
     CLAP_PARAM_IS_AUTOMATABLE, CLAP_PARAM_IS_BYPASS, CLAP_PARAM_IS_HIDDEN,
-    CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID, CLAP_PARAM_IS_READONLY,
-    CLAP_PARAM_IS_STEPPED, CLAP_PARAM_RESCAN_VALUES,
+    CLAP_PARAM_IS_MODULATABLE, CLAP_PARAM_IS_MODULATABLE_PER_CHANNEL,
+    CLAP_PARAM_IS_MODULATABLE_PER_KEY, CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID,
+    CLAP_PARAM_IS_READONLY, CLAP_PARAM_IS_STEPPED, CLAP_PARAM_RESCAN_VALUES,
+};
+use clap_sys::ext::posix_fd_support::{
+    clap_host_posix_fd_support, clap_plugin_posix_fd_support, clap_posix_fd_flags,
+    CLAP_EXT_POSIX_FD_SUPPORT,
 };
 use clap_sys::ext::render::{
     clap_plugin_render, clap_plugin_render_mode, CLAP_EXT_RENDER, CLAP_RENDER_OFFLINE,
@@ -48,6 +57,12 @@ use clap_sys::ext::render::{
 use clap_sys::ext::state::{clap_plugin_state, CLAP_EXT_STATE};
 use clap_sys::ext::tail::{clap_plugin_tail, CLAP_EXT_TAIL};
 use clap_sys::ext::thread_check::{clap_host_thread_check, CLAP_EXT_THREAD_CHECK};
+use clap_sys::ext::thread_pool::{
+    clap_host_thread_pool, clap_plugin_thread_pool, CLAP_EXT_THREAD_POOL,
+};
+use clap_sys::ext::timer_support::{
+    clap_host_timer_support, clap_plugin_timer_support, CLAP_EXT_TIMER_SUPPORT,
+};
 use clap_sys::fixedpoint::{CLAP_BEATTIME_FACTOR, CLAP_SECTIME_FACTOR};
 use clap_sys::host::clap_host;
 use clap_sys::id::{clap_id, CLAP_INVALID_ID};
@@ -66,33 +81,85 @@ use std::any::Any;
 use std::borrow::Borrow;
 use std::cmp;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::ffi::{c_void, CStr};
+use std::ffi::{c_void, CStr, CString};
 use std::mem;
 use std::num::NonZeroU32;
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 use std::ptr;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Weak};
 use std::thread::{self, ThreadId};
 use std::time::Duration;
 
+use super::ambisonic::sys::{
+    clap_ambisonic_config, clap_plugin_ambisonic, CLAP_EXT_AMBISONIC, CLAP_EXT_AMBISONIC_COMPAT,
+    CLAP_PORT_AMBISONIC,
+};
+use super::ambisonic::AMBISONIC_CONFIG;
+use super::audio_ports_activation::sys::{
+    clap_plugin_audio_ports_activation, CLAP_EXT_AUDIO_PORTS_ACTIVATION,
+    CLAP_EXT_AUDIO_PORTS_ACTIVATION_COMPAT,
+};
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
 use super::descriptor::PluginDescriptor;
-use super::util::ClapPtr;
-use crate::audio_setup::{AudioIOLayout, AuxiliaryBuffers, BufferConfig, ProcessMode};
+use super::preset_discovery::factory_preset_index_from_load_key;
+use super::preset_discovery::sys::{
+    clap_host_preset_load, clap_plugin_preset_load, CLAP_EXT_PRESET_LOAD,
+    CLAP_EXT_PRESET_LOAD_COMPAT, CLAP_PRESET_DISCOVERY_LOCATION_PLUGIN,
+};
+use super::util::{clap_wildcard, uses_f64_buffers, ClapPtr, ClapSample};
+use crate::audio_setup::{
+    AudioIOLayout, AuxiliaryBuffers, BufferConfig, ChannelLayout, ProcessMode,
+};
 use crate::buffer::Buffer;
-use crate::context::gui::AsyncExecutor;
+use crate::context::gui::{AsyncExecutor, FdFlags, GuiContext, TimerId};
 use crate::context::process::Transport;
+use crate::context::{HostInfo, TrackInfo};
 use crate::editor::{Editor, ParentWindowHandle};
 use crate::event_loop::{BackgroundThread, EventLoop, MainThreadExecutor, TASK_QUEUE_CAPACITY};
+use crate::midi::note_names::NoteName;
 use crate::midi::sysex::SysExMessage;
 use crate::midi::{MidiConfig, MidiResult, NoteEvent, PluginNoteEvent};
+use crate::params::indication::ParamIndication;
 use crate::params::internals::ParamPtr;
 use crate::params::{ParamFlags, Params};
-use crate::plugin::{ClapPlugin, Plugin, ProcessStatus, TaskExecutor};
+use crate::plugin::{ClapPlugin, Plugin, PolyModulationConfig, ProcessStatus, TaskExecutor};
 use crate::util::permit_alloc;
-use crate::wrapper::clap::util::{read_stream, write_stream};
-use crate::wrapper::state::{self, PluginState};
+use crate::wrapper::clap::param_indication::sys::{
+    clap_color, clap_plugin_param_indication, CLAP_EXT_PARAM_INDICATION,
+    CLAP_EXT_PARAM_INDICATION_COMPAT,
+};
+use crate::wrapper::clap::param_indication::{indication_automation_state, indication_color};
+use crate::wrapper::clap::remote_controls::sys::{
+    clap_plugin_remote_controls, clap_remote_controls_page, CLAP_EXT_REMOTE_CONTROLS,
+    CLAP_EXT_REMOTE_CONTROLS_COMPAT,
+};
+use crate::wrapper::clap::remote_controls::{RemoteControlsPage, RemoteControlsParam};
+use crate::wrapper::clap::state_context::state_context;
+use crate::wrapper::clap::state_context::sys::{clap_plugin_state_context, CLAP_EXT_STATE_CONTEXT};
+use crate::wrapper::clap::surround;
+use crate::wrapper::clap::surround::sys::{
+    clap_plugin_surround, CLAP_EXT_SURROUND, CLAP_EXT_SURROUND_COMPAT, CLAP_PORT_SURROUND,
+};
+use crate::wrapper::clap::track_info::sys::{
+    clap_host_track_info, clap_plugin_track_info, clap_track_info, CLAP_EXT_TRACK_INFO,
+    CLAP_EXT_TRACK_INFO_COMPAT,
+};
+use crate::wrapper::clap::track_info::track_info;
+use crate::wrapper::clap::undo;
+use crate::wrapper::clap::undo::sys::{
+    clap_host_undo, clap_plugin_undo_context, clap_plugin_undo_delta, clap_undo_delta_properties,
+    CLAP_EXT_UNDO_CONTEXT, CLAP_EXT_UNDO_DELTA,
+};
+use crate::wrapper::clap::util::{clap_silence_mask, read_stream, write_stream};
+use crate::wrapper::state::{self, FactoryPreset, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::{
+    set_silent_slices, AuxPortActivation, ProcessBuffers, ProcessSample,
+};
+use crate::wrapper::util::gui_callbacks::GuiCallbacks;
+use crate::wrapper::util::midi_learn::MidiLearn;
+use crate::wrapper::util::mpe::MpeTranslator;
+use crate::wrapper::util::undo::{TransactionUpdate, UndoManager};
 use crate::wrapper::util::{
     clamp_input_event_timing, clamp_output_event_timing, hash_param_id, process_wrapper, strlcpy,
 };
@@ -141,6 +208,13 @@ pub struct Wrapper<P: ClapPlugin> {
     /// TODO: Maybe load these lazily at some point instead of needing to spool them all to this
     ///       queue first
     input_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Translates per-channel MPE expressions to per-note expression events if the plugin's
+    /// `P::MIDI_INPUT` is set to `MidiConfig::Mpe`. Note events and MIDI messages are passed
+    /// through this before being added to `input_events`.
+    mpe_translator: AtomicRefCell<MpeTranslator>,
+    /// The plugin's MIDI mappings. Mapped MIDI messages are turned into parameter changes before
+    /// the block's events are passed to the plugin.
+    pub midi_learn: MidiLearn,
     /// Stores any events the plugin has output during the current processing cycle, analogous to
     /// `input_events`.
     output_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
@@ -149,23 +223,15 @@ pub struct Wrapper<P: ClapPlugin> {
     /// The current latency in samples, as set by the plugin through the [`ProcessContext`]. Uses
     /// the latency extension.
     pub current_latency: AtomicU32,
-    /// Contains slices for the plugin's outputs. You can't directly create a nested slice from
-    /// a pointer to pointers, so this needs to be preallocated in the setup call and kept around
-    /// between process calls. This buffer owns the vector, because otherwise it would need to store
-    /// a mutable reference to the data contained in this mutex.
-    output_buffer: AtomicRefCell<Buffer<'static>>,
-    /// Stores sample data for every sidechain input the plugin has. Indexed by
-    /// `[sidechain_input][channel][sample]` We'll copy the data to these buffers since modifying
-    /// the host's sidechain input buffers may not be safe, and the plugin may want to be able to
-    /// modify the buffers.
-    aux_input_storage: AtomicRefCell<Vec<Vec<Vec<f32>>>>,
-    /// Accompanying buffers for `aux_input_storage`. There is no way to do this in safe Rust, so
-    /// the process function needs to make sure all channel pointers stored in these buffers are
-    /// still correct before passing it to the plugin, hence the static lifetime.
-    aux_input_buffers: AtomicRefCell<Vec<Buffer<'static>>>,
-    /// Buffers for auxiliary plugin outputs, if the plugin has any. These reference the host's
-    /// memory directly.
-    aux_output_buffers: AtomicRefCell<Vec<Buffer<'static>>>,
+    /// The buffers and sidechain input storage used to pass the host's audio buffers to the plugin
+    /// during single precision processing. These are preallocated when the plugin gets activated.
+    buffers: AtomicRefCell<ProcessBuffers<f32>>,
+    /// The same as `buffers`, but for double precision processing. These are only allocated when
+    /// the plugin supports 64-bit processing through [`Plugin::F64_PROCESSING`].
+    buffers_f64: AtomicRefCell<ProcessBuffers<f64>>,
+    /// Which of the auxiliary ports the host has activated through the audio ports activation
+    /// extension. This is sized for the current audio IO layout when the plugin gets activated.
+    aux_port_activation: AtomicRefCell<AuxPortActivation>,
     /// The plugin is able to restore state through a method on the `GuiContext`. To avoid changing
     /// parameters mid-processing and running into garbled data if the host also tries to load state
     /// at the same time the restoring happens at the end of each processing call. If this zero
@@ -173,13 +239,17 @@ pub struct Wrapper<P: ClapPlugin> {
     /// state out of the channel, restore the state, and then send it back through the same channel.
     /// In other words, the GUI thread acts as a sender and then as a receiver, while the audio
     /// thread acts as a receiver and then as a sender. That way deallocation can happen on the GUI
-    /// thread. All of this happens without any blocking on the audio thread.
-    updated_state_sender: channel::Sender<PluginState>,
+    /// thread. All of this happens without any blocking on the audio thread. The boolean indicates
+    /// whether the state was restored successfully, and it is only meaningful when the state gets
+    /// sent back to the GUI thread.
+    updated_state_sender: channel::Sender<(PluginState, bool)>,
     /// The receiver belonging to [`new_state_sender`][Self::new_state_sender].
-    updated_state_receiver: channel::Receiver<PluginState>,
+    updated_state_receiver: channel::Receiver<(PluginState, bool)>,
 
     // We'll query all of the host's extensions upfront
     host_callback: ClapPtr<clap_host>,
+    /// The host's name, vendor, version, and URL as read from `host_callback`.
+    host_info: HostInfo,
 
     clap_plugin_audio_ports_config: clap_plugin_audio_ports_config,
 
@@ -192,12 +262,23 @@ pub struct Wrapper<P: ClapPlugin> {
 
     clap_plugin_audio_ports: clap_plugin_audio_ports,
 
+    clap_plugin_audio_ports_activation: clap_plugin_audio_ports_activation,
+
+    clap_plugin_ambisonic: clap_plugin_ambisonic,
+
     clap_plugin_gui: clap_plugin_gui,
     host_gui: AtomicRefCell<Option<ClapPtr<clap_host_gui>>>,
 
     clap_plugin_latency: clap_plugin_latency,
     host_latency: AtomicRefCell<Option<ClapPtr<clap_host_latency>>>,
 
+    clap_plugin_note_name: clap_plugin_note_name,
+    host_note_name: AtomicRefCell<Option<ClapPtr<clap_host_note_name>>>,
+    /// The plugin's note names as returned by [`Plugin::note_names()`]. These are fetched again
+    /// when the host calls `clap_plugin_note_name::count()` so `get()` can index into a stable
+    /// list.
+    note_names: AtomicRefCell<Vec<NoteName>>,
+
     clap_plugin_note_ports: clap_plugin_note_ports,
 
     clap_plugin_params: clap_plugin_params,
@@ -238,14 +319,65 @@ pub struct Wrapper<P: ClapPlugin> {
     ///      in the same order, right?
     output_parameter_events: ArrayQueue<OutputParamEvent>,
 
+    clap_plugin_posix_fd_support: clap_plugin_posix_fd_support,
+    host_posix_fd_support: AtomicRefCell<Option<ClapPtr<clap_host_posix_fd_support>>>,
+
     host_thread_check: AtomicRefCell<Option<ClapPtr<clap_host_thread_check>>>,
 
+    clap_plugin_preset_load: clap_plugin_preset_load,
+    host_preset_load: AtomicRefCell<Option<ClapPtr<clap_host_preset_load>>>,
+    /// The plugin's factory presets as returned by [`Plugin::factory_presets()`]. The host can
+    /// discover these through the preset discovery factory, and it then loads them through the
+    /// preset load extension using the preset's index as the load key.
+    factory_presets: Vec<FactoryPreset>,
+
+    clap_plugin_param_indication: clap_plugin_param_indication,
+
+    clap_plugin_remote_controls: clap_plugin_remote_controls,
+    /// The remote control pages for the plugin's parameters, resolved from
+    /// [`ClapPlugin::CLAP_REMOTE_CONTROLS`]. The pages' indices are also used as their IDs.
+    remote_controls_pages: Vec<RemoteControlsPage>,
+
     clap_plugin_render: clap_plugin_render,
 
     clap_plugin_state: clap_plugin_state,
+    clap_plugin_state_context: clap_plugin_state_context,
+
+    clap_plugin_surround: clap_plugin_surround,
 
     clap_plugin_tail: clap_plugin_tail,
 
+    clap_plugin_thread_pool: clap_plugin_thread_pool,
+    host_thread_pool: AtomicRefCell<Option<ClapPtr<clap_host_thread_pool>>>,
+    /// The task passed to [`execute_parallel()`][Self::execute_parallel()] while the host's thread
+    /// pool is running it. The lifetime is a lie, this is only set for the duration of that
+    /// function call.
+    thread_pool_task: AtomicRefCell<Option<&'static (dyn Fn(usize) + Sync)>>,
+
+    clap_plugin_timer_support: clap_plugin_timer_support,
+    host_timer_support: AtomicRefCell<Option<ClapPtr<clap_host_timer_support>>>,
+    /// The callbacks for the timers and file descriptor watches the editor registered through the
+    /// `GuiContext`. These are unregistered when the editor gets destroyed.
+    gui_callbacks: GuiCallbacks,
+
+    clap_plugin_track_info: clap_plugin_track_info,
+    host_track_info: AtomicRefCell<Option<ClapPtr<clap_host_track_info>>>,
+    /// Information about the track the plugin is inserted on. This is fetched from the host when
+    /// the plugin is initialized and whenever the host says that it has changed.
+    track_info: Mutex<Option<TrackInfo>>,
+
+    clap_plugin_undo_context: clap_plugin_undo_context,
+    clap_plugin_undo_delta: clap_plugin_undo_delta,
+    host_undo: AtomicRefCell<Option<ClapPtr<clap_host_undo>>>,
+    /// The undo history for changes made through the `GuiContext`. If the host supports the undo
+    /// extension, then the host decides when these transactions are undone or redone. The deltas
+    /// sent to the host contain the transactions' IDs.
+    pub undo_manager: UndoManager,
+    /// Whether the host's undo history can be undone or redone, as reported through the undo
+    /// context extension. Only used if the host supports the undo extension.
+    host_can_undo: AtomicBool,
+    host_can_redo: AtomicBool,
+
     clap_plugin_voice_info: clap_plugin_voice_info,
     host_voice_info: AtomicRefCell<Option<ClapPtr<clap_host_voice_info>>>,
     /// If `P::CLAP_POLY_MODULATION_CONFIG` is set, then the plugin can configure the current number
@@ -288,8 +420,22 @@ pub enum Task<P: Plugin> {
     LatencyChanged,
     /// Inform the host that the voice info has changed.
     VoiceInfoChanged,
+    /// Inform the host that the plugin's note names have changed.
+    NoteNamesChanged,
     /// Tell the host that it should rescan the current parameter values.
     RescanParamValues,
+    /// Forward a change in the plugin's undo history to the host's undo extension.
+    UndoHistoryChanged(TransactionUpdate),
+    /// Ask the host to undo its last change through its undo extension.
+    RequestUndo,
+    /// Ask the host to redo its last undone change through its undo extension.
+    RequestRedo,
+    /// Run the callback for a timer registered through the `GuiContext`, using the timer ID
+    /// assigned by the host.
+    GuiTimer(clap_id),
+    /// Run the callback for a file descriptor registered through the `GuiContext` with the events
+    /// that occurred.
+    GuiFdReady(c_int, FdFlags),
 }
 
 /// The types of CLAP parameter updates for events.
@@ -423,6 +569,13 @@ impl<P: ClapPlugin> MainThreadExecutor<Task<P>> for Wrapper<P> {
                 }
                 None => nih_debug_assert_failure!("Host does not support the voice-info extension"),
             },
+            Task::NoteNamesChanged => match &*self.host_note_name.borrow() {
+                Some(host_note_name) => {
+                    nih_debug_assert!(is_gui_thread);
+                    unsafe_clap_call! { host_note_name=>changed(&*self.host_callback) };
+                }
+                None => nih_trace!("Host does not support the note-name extension"),
+            },
             Task::RescanParamValues => match &*self.host_params.borrow() {
                 Some(host_params) => {
                     nih_debug_assert!(is_gui_thread);
@@ -430,6 +583,56 @@ impl<P: ClapPlugin> MainThreadExecutor<Task<P>> for Wrapper<P> {
                 }
                 None => nih_debug_assert_failure!("The host does not support parameters? What?"),
             },
+            Task::UndoHistoryChanged(update) => match &*self.host_undo.borrow() {
+                Some(host_undo) => {
+                    nih_debug_assert!(is_gui_thread);
+                    match update {
+                        TransactionUpdate::Started => {
+                            unsafe_clap_call! { host_undo=>begin_change(&*self.host_callback) }
+                        }
+                        TransactionUpdate::Unchanged => (),
+                        TransactionUpdate::Cancelled => {
+                            unsafe_clap_call! { host_undo=>cancel_change(&*self.host_callback) }
+                        }
+                        TransactionUpdate::Committed { id, name } => {
+                            let name = CString::new(name).unwrap_or_default();
+                            let delta = undo::encode_delta(id);
+                            unsafe_clap_call! {
+                                host_undo=>change_made(
+                                    &*self.host_callback,
+                                    name.as_ptr(),
+                                    delta.as_ptr() as *const c_void,
+                                    delta.len(),
+                                    true,
+                                )
+                            };
+                        }
+                    }
+                }
+                None => nih_debug_assert_failure!("The host does not support the undo extension"),
+            },
+            Task::RequestUndo => match &*self.host_undo.borrow() {
+                Some(host_undo) => {
+                    nih_debug_assert!(is_gui_thread);
+                    unsafe_clap_call! { host_undo=>request_undo(&*self.host_callback) };
+                }
+                None => nih_debug_assert_failure!("The host does not support the undo extension"),
+            },
+            Task::RequestRedo => match &*self.host_undo.borrow() {
+                Some(host_undo) => {
+                    nih_debug_assert!(is_gui_thread);
+                    unsafe_clap_call! { host_undo=>request_redo(&*self.host_callback) };
+                }
+                None => nih_debug_assert_failure!("The host does not support the undo extension"),
+            },
+            Task::GuiTimer(timer_id) => {
+                nih_debug_assert!(is_gui_thread);
+                self.gui_callbacks.call_timer(timer_id);
+            }
+            Task::GuiFdReady(fd, flags) => {
+                nih_debug_assert!(is_gui_thread);
+                self.gui_callbacks.call_fd(fd, flags);
+            }
         };
     }
 }
@@ -449,6 +652,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         // need a bunch of AtomicRefCells instead
         assert!(!host_callback.is_null());
         let host_callback = unsafe { ClapPtr::new(host_callback) };
+        let host_info = unsafe { host_info(&host_callback) };
 
         // This is a mapping from the parameter IDs specified by the plugin to pointers to those
         // parameters. These pointers are assumed to be safe to dereference as long as
@@ -495,6 +699,17 @@ impl<P: ClapPlugin> Wrapper<P> {
             })
             .collect();
 
+        let remote_controls_params: Vec<_> = param_id_hashes_ptrs_groups
+            .iter()
+            .map(|(id, hash, ptr, group)| RemoteControlsParam {
+                id,
+                hash: *hash,
+                group,
+                flags: unsafe { ptr.flags() },
+            })
+            .collect();
+        let remote_controls_pages = P::CLAP_REMOTE_CONTROLS.pages(&remote_controls_params);
+
         if cfg!(debug_assertions) {
             let param_map = params.param_map();
             let param_ids: HashSet<_> = param_id_hashes_ptrs_groups
@@ -549,17 +764,19 @@ impl<P: ClapPlugin> Wrapper<P> {
             current_buffer_config: AtomicCell::new(None),
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
             input_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
+            mpe_translator: AtomicRefCell::new(MpeTranslator::new(P::MIDI_INPUT)),
+            midi_learn: MidiLearn::default(),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(512)),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
             current_latency: AtomicU32::new(0),
-            output_buffer: AtomicRefCell::new(Buffer::default()),
-            aux_input_storage: AtomicRefCell::new(Vec::new()),
-            aux_input_buffers: AtomicRefCell::new(Vec::new()),
-            aux_output_buffers: AtomicRefCell::new(Vec::new()),
+            buffers: AtomicRefCell::new(ProcessBuffers::default()),
+            buffers_f64: AtomicRefCell::new(ProcessBuffers::default()),
+            aux_port_activation: AtomicRefCell::new(AuxPortActivation::default()),
             updated_state_sender,
             updated_state_receiver,
 
             host_callback,
+            host_info,
 
             clap_plugin: AtomicRefCell::new(clap_plugin {
                 // This needs to live on the heap because the plugin object contains a direct
@@ -593,6 +810,18 @@ impl<P: ClapPlugin> Wrapper<P> {
                 get: Some(Self::ext_audio_ports_get),
             },
 
+            clap_plugin_audio_ports_activation: clap_plugin_audio_ports_activation {
+                can_activate_while_processing: Some(
+                    Self::ext_audio_ports_activation_can_activate_while_processing,
+                ),
+                set_active: Some(Self::ext_audio_ports_activation_set_active),
+            },
+
+            clap_plugin_ambisonic: clap_plugin_ambisonic {
+                is_config_supported: Some(Self::ext_ambisonic_is_config_supported),
+                get_config: Some(Self::ext_ambisonic_get_config),
+            },
+
             clap_plugin_gui: clap_plugin_gui {
                 is_api_supported: Some(Self::ext_gui_is_api_supported),
                 get_preferred_api: Some(Self::ext_gui_get_preferred_api),
@@ -617,6 +846,13 @@ impl<P: ClapPlugin> Wrapper<P> {
             },
             host_latency: AtomicRefCell::new(None),
 
+            clap_plugin_note_name: clap_plugin_note_name {
+                count: Some(Self::ext_note_name_count),
+                get: Some(Self::ext_note_name_get),
+            },
+            host_note_name: AtomicRefCell::new(None),
+            note_names: AtomicRefCell::new(Vec::new()),
+
             clap_plugin_note_ports: clap_plugin_note_ports {
                 count: Some(Self::ext_note_ports_count),
                 get: Some(Self::ext_note_ports_get),
@@ -640,8 +876,30 @@ impl<P: ClapPlugin> Wrapper<P> {
             poly_mod_ids_by_hash,
             output_parameter_events: ArrayQueue::new(OUTPUT_EVENT_QUEUE_CAPACITY),
 
+            clap_plugin_posix_fd_support: clap_plugin_posix_fd_support {
+                on_fd: Some(Self::ext_posix_fd_support_on_fd),
+            },
+            host_posix_fd_support: AtomicRefCell::new(None),
+
             host_thread_check: AtomicRefCell::new(None),
 
+            clap_plugin_preset_load: clap_plugin_preset_load {
+                from_location: Some(Self::ext_preset_load_from_location),
+            },
+            host_preset_load: AtomicRefCell::new(None),
+            factory_presets: P::factory_presets(),
+
+            clap_plugin_param_indication: clap_plugin_param_indication {
+                set_mapping: Some(Self::ext_param_indication_set_mapping),
+                set_automation: Some(Self::ext_param_indication_set_automation),
+            },
+
+            clap_plugin_remote_controls: clap_plugin_remote_controls {
+                count: Some(Self::ext_remote_controls_count),
+                get: Some(Self::ext_remote_controls_get),
+            },
+            remote_controls_pages,
+
             clap_plugin_render: clap_plugin_render {
                 has_hard_realtime_requirement: Some(Self::ext_render_has_hard_realtime_requirement),
                 set: Some(Self::ext_render_set),
@@ -651,11 +909,57 @@ impl<P: ClapPlugin> Wrapper<P> {
                 save: Some(Self::ext_state_save),
                 load: Some(Self::ext_state_load),
             },
+            clap_plugin_state_context: clap_plugin_state_context {
+                save: Some(Self::ext_state_context_save),
+                load: Some(Self::ext_state_context_load),
+            },
+
+            clap_plugin_surround: clap_plugin_surround {
+                is_channel_mask_supported: Some(Self::ext_surround_is_channel_mask_supported),
+                get_channel_map: Some(Self::ext_surround_get_channel_map),
+            },
 
             clap_plugin_tail: clap_plugin_tail {
                 get: Some(Self::ext_tail_get),
             },
 
+            clap_plugin_thread_pool: clap_plugin_thread_pool {
+                exec: Some(Self::ext_thread_pool_exec),
+            },
+            host_thread_pool: AtomicRefCell::new(None),
+            thread_pool_task: AtomicRefCell::new(None),
+
+            clap_plugin_timer_support: clap_plugin_timer_support {
+                on_timer: Some(Self::ext_timer_support_on_timer),
+            },
+            host_timer_support: AtomicRefCell::new(None),
+            gui_callbacks: GuiCallbacks::default(),
+
+            clap_plugin_track_info: clap_plugin_track_info {
+                changed: Some(Self::ext_track_info_changed),
+            },
+            host_track_info: AtomicRefCell::new(None),
+            track_info: Mutex::new(None),
+
+            clap_plugin_undo_context: clap_plugin_undo_context {
+                set_can_undo: Some(Self::ext_undo_context_set_can_undo),
+                set_can_redo: Some(Self::ext_undo_context_set_can_redo),
+                set_undo_name: Some(Self::ext_undo_context_set_name),
+                set_redo_name: Some(Self::ext_undo_context_set_name),
+            },
+            clap_plugin_undo_delta: clap_plugin_undo_delta {
+                get_delta_properties: Some(Self::ext_undo_delta_get_delta_properties),
+                can_use_delta_format_version: Some(
+                    Self::ext_undo_delta_can_use_delta_format_version,
+                ),
+                undo: Some(Self::ext_undo_delta_undo),
+                redo: Some(Self::ext_undo_delta_redo),
+            },
+            host_undo: AtomicRefCell::new(None),
+            undo_manager: UndoManager::default(),
+            host_can_undo: AtomicBool::new(false),
+            host_can_redo: AtomicBool::new(false),
+
             clap_plugin_voice_info: clap_plugin_voice_info {
                 get: Some(Self::ext_voice_info_get),
             },
@@ -747,8 +1051,7 @@ impl<P: ClapPlugin> Wrapper<P> {
     }
 
     /// Get a parameter's ID based on a `ParamPtr`. Used in the `GuiContext` implementation for the
-    /// gesture checks.
-    #[allow(unused)]
+    /// gesture checks and for MIDI learn.
     pub fn param_id_from_ptr(&self, param: ParamPtr) -> Option<&str> {
         self.param_ptr_to_hash
             .get(&param)
@@ -953,6 +1256,52 @@ impl<P: ClapPlugin> Wrapper<P> {
         None
     }
 
+    /// Turn the MIDI messages in `input_events` that have been mapped through MIDI learn into
+    /// parameter changes. The mapped messages are removed from `input_events`. The new values are
+    /// also sent to the host as output events so it can record them as automation.
+    pub fn handle_midi_learn(&self, input_events: &mut VecDeque<PluginNoteEvent<P>>) {
+        let sample_rate = self.current_buffer_config.load().map(|c| c.sample_rate);
+        input_events.retain(|event| {
+            !self
+                .midi_learn
+                .handle_event(event, |param_ptr, normalized| {
+                    let param_hash = match self.param_ptr_to_hash.get(&param_ptr) {
+                        Some(hash) => *hash,
+                        None => return,
+                    };
+                    let clap_plain_value =
+                        normalized as f64 * unsafe { param_ptr.step_count() }.unwrap_or(1) as f64;
+
+                    self.update_plain_value_by_hash(
+                        param_hash,
+                        ClapParamUpdate::PlainValueSet(clap_plain_value),
+                        sample_rate,
+                    );
+
+                    let success = self
+                        .output_parameter_events
+                        .push(OutputParamEvent::BeginGesture { param_hash })
+                        .is_ok()
+                        && self
+                            .output_parameter_events
+                            .push(OutputParamEvent::SetValue {
+                                param_hash,
+                                clap_plain_value,
+                            })
+                            .is_ok()
+                        && self
+                            .output_parameter_events
+                            .push(OutputParamEvent::EndGesture { param_hash })
+                            .is_ok();
+                    nih_debug_assert!(
+                        success,
+                        "Parameter output event queue was full, MIDI mapped parameter change will \
+                         not be sent to the host"
+                    );
+                })
+        });
+    }
+
     /// Write the unflushed parameter changes to the host's output event queue. The sample index is
     /// used as part of splitting up the input buffer for sample accurate automation changes. This
     /// will also modify the actual parameter values, since we should only do that while the wrapped
@@ -1358,6 +1707,21 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    /// Whether a `CLAP_EVENT_PARAM_MOD` event should be sent to the plugin as a polyphonic
+    /// modulation note event. This is the case for modulation targeting a note ID, and for
+    /// modulation targeting a key or channel if the plugin enabled
+    /// [`PolyModulationConfig::supports_key_modulation`]. All other modulation is applied as
+    /// monophonic modulation.
+    fn is_note_modulation(event: &clap_event_param_mod) -> bool {
+        let supports_key_modulation = P::CLAP_POLY_MODULATION_CONFIG
+            .map(|config| config.supports_key_modulation)
+            .unwrap_or(false);
+
+        P::MIDI_INPUT >= MidiConfig::Basic
+            && (event.note_id != -1
+                || (supports_key_modulation && (event.key != -1 || event.channel != -1)))
+    }
+
     /// Handle an incoming CLAP event. The sample index is provided to support block splitting for
     /// sample accurate automation. [`input_events`][Self::input_events] must be cleared at the
     /// start of each process block.
@@ -1413,7 +1777,10 @@ impl<P: ClapPlugin> Wrapper<P> {
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_PARAM_MOD) => {
                 let event = &*(event as *const clap_event_param_mod);
 
-                if event.note_id != -1 && P::MIDI_INPUT >= MidiConfig::Basic {
+                // Modulation targeting a note ID, or a key or channel if the plugin supports that,
+                // is sent to the plugin as polyphonic modulation. Everything else is regular
+                // monophonic modulation.
+                if Self::is_note_modulation(event) {
                     match self.poly_mod_ids_by_hash.get(&event.param_id) {
                         Some(poly_modulation_id) => {
                             // The modulation offset needs to be normalized to account for modulated
@@ -1422,15 +1789,25 @@ impl<P: ClapPlugin> Wrapper<P> {
                             let normalized_offset =
                                 event.amount as f32 / param_ptr.step_count().unwrap_or(1) as f32;
 
-                            // The host may also add key and channel information here, but it may
-                            // also pass -1. So not having that information here at all seems like
-                            // the safest choice.
-                            input_events.push_back(NoteEvent::PolyModulation {
-                                timing,
-                                voice_id: event.note_id,
-                                poly_modulation_id: *poly_modulation_id,
-                                normalized_offset,
-                            });
+                            // The host may also add key and channel information to note ID
+                            // modulation, but it may also pass -1. The note ID is the most specific
+                            // target, so the key and channel are only used when it's missing.
+                            if event.note_id != -1 {
+                                input_events.push_back(NoteEvent::PolyModulation {
+                                    timing,
+                                    voice_id: event.note_id,
+                                    poly_modulation_id: *poly_modulation_id,
+                                    normalized_offset,
+                                });
+                            } else {
+                                input_events.push_back(NoteEvent::KeyModulation {
+                                    timing,
+                                    channel: clap_wildcard(event.channel),
+                                    note: clap_wildcard(event.key),
+                                    poly_modulation_id: *poly_modulation_id,
+                                    normalized_offset,
+                                });
+                            }
 
                             return;
                         }
@@ -1456,51 +1833,60 @@ impl<P: ClapPlugin> Wrapper<P> {
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_NOTE_ON) => {
                 if P::MIDI_INPUT >= MidiConfig::Basic {
                     let event = &*(event as *const clap_event_note);
-                    input_events.push_back(NoteEvent::NoteOn {
-                        // When splitting up the buffer for sample accurate automation all events
-                        // should be relative to the block
-                        timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
+                    self.mpe_translator.borrow_mut().translate(
+                        NoteEvent::NoteOn {
+                            // When splitting up the buffer for sample accurate automation all
+                            // events should be relative to the block
+                            timing,
+                            voice_id: if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            channel: event.channel as u8,
+                            note: event.key as u8,
+                            velocity: event.velocity as f32,
                         },
-                        channel: event.channel as u8,
-                        note: event.key as u8,
-                        velocity: event.velocity as f32,
-                    });
+                        |event| input_events.push_back(event),
+                    );
                 }
             }
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_NOTE_OFF) => {
                 if P::MIDI_INPUT >= MidiConfig::Basic {
                     let event = &*(event as *const clap_event_note);
-                    input_events.push_back(NoteEvent::NoteOff {
-                        timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
+                    self.mpe_translator.borrow_mut().translate(
+                        NoteEvent::NoteOff {
+                            timing,
+                            voice_id: if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            channel: event.channel as u8,
+                            note: event.key as u8,
+                            velocity: event.velocity as f32,
                         },
-                        channel: event.channel as u8,
-                        note: event.key as u8,
-                        velocity: event.velocity as f32,
-                    });
+                        |event| input_events.push_back(event),
+                    );
                 }
             }
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_NOTE_CHOKE) => {
                 if P::MIDI_INPUT >= MidiConfig::Basic {
                     let event = &*(event as *const clap_event_note);
-                    input_events.push_back(NoteEvent::Choke {
-                        timing,
-                        voice_id: if event.note_id != -1 {
-                            Some(event.note_id)
-                        } else {
-                            None
+                    self.mpe_translator.borrow_mut().translate(
+                        NoteEvent::Choke {
+                            timing,
+                            voice_id: if event.note_id != -1 {
+                                Some(event.note_id)
+                            } else {
+                                None
+                            },
+                            // The channel and key may be -1 to match all channels or keys
+                            channel: clap_wildcard(event.channel),
+                            note: clap_wildcard(event.key),
                         },
-                        // FIXME: These values are also allowed to be -1, we need to support that
-                        channel: event.channel as u8,
-                        note: event.key as u8,
-                    });
+                        |event| input_events.push_back(event),
+                    );
                 }
             }
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_NOTE_EXPRESSION) => {
@@ -1616,15 +2002,45 @@ impl<P: ClapPlugin> Wrapper<P> {
                         | NoteEvent::NoteOff { .. }
                         | NoteEvent::PolyPressure { .. }),
                     ) if P::MIDI_INPUT >= MidiConfig::Basic => {
-                        input_events.push_back(note_event);
+                        self.mpe_translator
+                            .borrow_mut()
+                            .translate(note_event, |event| input_events.push_back(event));
                     }
                     Ok(note_event) if P::MIDI_INPUT >= MidiConfig::MidiCCs => {
-                        input_events.push_back(note_event);
+                        self.mpe_translator
+                            .borrow_mut()
+                            .translate(note_event, |event| input_events.push_back(event));
                     }
                     Ok(_) => (),
                     Err(n) => nih_debug_assert_failure!("Unhandled MIDI message type {}", n),
                 };
             }
+            (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_MIDI2) => {
+                // The same filtering as for MIDI 1.0 messages applies here. Per-note pitch bend is
+                // translated to a tuning expression, so it's also available at the basic level.
+                let event = &*(event as *const clap_event_midi2);
+
+                match NoteEvent::from_midi2(timing, &event.data) {
+                    Ok(
+                        note_event @ (NoteEvent::NoteOn { .. }
+                        | NoteEvent::NoteOff { .. }
+                        | NoteEvent::PolyPressure { .. }
+                        | NoteEvent::PolyTuning { .. }),
+                    ) if P::MIDI_INPUT >= MidiConfig::Basic => {
+                        self.mpe_translator
+                            .borrow_mut()
+                            .translate(note_event, |event| input_events.push_back(event));
+                    }
+                    Ok(note_event) if P::MIDI_INPUT >= MidiConfig::MidiCCs => {
+                        self.mpe_translator
+                            .borrow_mut()
+                            .translate(note_event, |event| input_events.push_back(event));
+                    }
+                    // Hosts may send valid messages we don't support, `NoteEvent::from_midi2()`
+                    // already traces those
+                    Ok(_) | Err(_) => (),
+                };
+            }
             (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_MIDI_SYSEX)
                 if P::MIDI_INPUT >= MidiConfig::Basic =>
             {
@@ -1650,23 +2066,28 @@ impl<P: ClapPlugin> Wrapper<P> {
 
     /// Get the plugin's state object, may be called by the plugin's GUI as part of its own preset
     /// management. The wrapper doesn't use these functions and serializes and deserializes directly
-    /// the JSON in the relevant plugin API methods instead.
+    /// the JSON in the relevant plugin API methods instead. Since this is used for presets, this
+    /// uses [`StateContext::Preset`].
     pub fn get_state_object(&self) -> PluginState {
         unsafe {
             state::serialize_object::<P>(
                 self.params.clone(),
                 state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
 
     /// Update the plugin's internal state, called by the plugin itself from the GUI thread. To
     /// prevent corrupting data and changing parameters during processing the actual state is only
-    /// updated at the end of the audio processing cycle.
-    pub fn set_state_object_from_gui(&self, mut state: PluginState) {
+    /// updated at the end of the audio processing cycle. This is also used to load factory presets,
+    /// so the state is loaded using [`StateContext::Preset`]. Returns `false` if the state could
+    /// not be restored.
+    pub fn set_state_object_from_gui(&self, mut state: PluginState) -> bool {
         // Use a loop and timeouts to handle the super rare edge case when this function gets called
         // between a process call and the host disabling the plugin
-        loop {
+        let success = loop {
             if self.is_processing.load(Ordering::SeqCst) {
                 // If the plugin is currently processing audio, then we'll perform the restore
                 // operation at the end of the audio call. This involves sending the state to the
@@ -1675,35 +2096,47 @@ impl<P: ClapPlugin> Wrapper<P> {
                 // deallocated without blocking the audio thread.
                 match self
                     .updated_state_sender
-                    .send_timeout(state, Duration::from_secs(1))
+                    .send_timeout((state, false), Duration::from_secs(1))
                 {
                     Ok(_) => {
                         // As mentioned above, the state object will be passed back to this thread
-                        // so we can deallocate it without blocking.
-                        let state = self.updated_state_receiver.recv();
-                        drop(state);
-                        break;
+                        // so we can deallocate it without blocking. The audio thread also tells us
+                        // whether restoring the state succeeded.
+                        match self.updated_state_receiver.recv() {
+                            Ok((state, success)) => {
+                                drop(state);
+                                break success;
+                            }
+                            Err(err) => {
+                                nih_debug_assert_failure!(
+                                    "Failed to receive state object back from the audio thread: {}",
+                                    err
+                                );
+                                break false;
+                            }
+                        }
                     }
-                    Err(SendTimeoutError::Timeout(value)) => {
+                    Err(SendTimeoutError::Timeout((value, _))) => {
                         state = value;
                         continue;
                     }
                     Err(SendTimeoutError::Disconnected(_)) => {
                         nih_debug_assert_failure!("State update channel got disconnected");
-                        return;
+                        return false;
                     }
                 }
             } else {
                 // Otherwise we'll set the state right here and now, since this function should be
                 // called from a GUI thread
-                self.set_state_inner(&mut state);
-                break;
+                break self.set_state_inner(&mut state, StateContext::Preset);
             }
-        }
+        };
 
         // After the state has been updated, notify the host about the new parameter values
         let task_posted = self.schedule_gui(Task::RescanParamValues);
         nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+
+        success
     }
 
     pub fn set_latency_samples(&self, samples: u32) {
@@ -1741,124 +2174,492 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
-    /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
-    /// state is set from a couple places, so this function aims to deduplicate that. Includes
-    /// `permit_alloc()`s around the deserialization and initialization for the use case where
-    /// `set_state_object_from_gui()` was called while the plugin is process audio.
-    ///
-    /// Implicitly emits `Task::ParameterValuesChanged`.
-    ///
-    /// # Notes
-    ///
-    /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
-    pub fn set_state_inner(&self, state: &mut PluginState) -> bool {
-        let audio_io_layout = self.current_audio_io_layout.load();
-        let buffer_config = self.current_buffer_config.load();
+    pub fn note_names_changed(&self) {
+        let task_posted = self.schedule_gui(Task::NoteNamesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
 
-        // FIXME: This is obviously not realtime-safe, but loading presets without doing this could
-        //        lead to inconsistencies. It's the plugin's responsibility to not perform any
-        //        realtime-unsafe work when the initialize function is called a second time if it
-        //        supports runtime preset loading.  `state::deserialize_object()` normally never
-        //        allocates, but if the plugin has persistent non-parameter data then its
-        //        `deserialize_fields()` implementation may still allocate.
-        let mut success = permit_alloc(|| unsafe {
-            state::deserialize_object::<P>(
-                state,
-                self.params.clone(),
-                state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
-                self.current_buffer_config.load().as_ref(),
-            )
-        });
-        if !success {
-            nih_debug_assert_failure!("Deserializing plugin state from a state object failed");
-            return false;
+    /// Run `task` for every index in `0..num_tasks` using the host's thread pool if it has one, or
+    /// serially on the calling thread otherwise. Must be called from the audio thread.
+    pub fn execute_parallel(&self, num_tasks: usize, task: &(dyn Fn(usize) + Sync)) {
+        if num_tasks == 0 {
+            return;
         }
 
-        // If the plugin was already initialized then it needs to be reinitialized
-        if let Some(buffer_config) = buffer_config {
-            // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
-            let mut init_context = self.make_init_context();
-            let mut plugin = self.plugin.lock();
+        if let Some(thread_pool) = &*self.host_thread_pool.borrow() {
+            // SAFETY: The task is removed again before this function returns, and the host won't
+            //         call `exec()` after `request_exec()` has returned
+            let static_task: &'static (dyn Fn(usize) + Sync) = unsafe { mem::transmute(task) };
+            *self.thread_pool_task.borrow_mut() = Some(static_task);
+            let executed = unsafe_clap_call! {
+                thread_pool=>request_exec(&*self.host_callback, num_tasks as u32)
+            };
+            *self.thread_pool_task.borrow_mut() = None;
 
-            // See above
-            success = permit_alloc(|| {
-                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
-            });
-            if success {
-                process_wrapper(|| plugin.reset());
+            if executed {
+                return;
             }
         }
 
-        nih_debug_assert!(
-            success,
-            "Plugin returned false when reinitializing after loading state"
-        );
+        // If the host doesn't have a thread pool or if it didn't want to run the tasks, we'll do
+        // it ourselves
+        for task_idx in 0..num_tasks {
+            task(task_idx);
+        }
+    }
 
-        // Reinitialize the plugin after loading state so it can respond to the new parameter values
-        let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
-        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    /// Register a timer with the host's `timer-support` extension. Must be called from the main
+    /// thread.
+    pub fn register_timer(
+        &self,
+        period_ms: u32,
+        callback: Box<dyn FnMut() + Send>,
+    ) -> Option<TimerId> {
+        match &*self.host_timer_support.borrow() {
+            Some(host_timer_support) => {
+                let mut timer_id = CLAP_INVALID_ID;
+                let success = unsafe_clap_call! {
+                    host_timer_support=>register_timer(
+                        &*self.host_callback,
+                        period_ms,
+                        &mut timer_id
+                    )
+                };
+                if success && timer_id != CLAP_INVALID_ID {
+                    self.gui_callbacks.insert_timer(timer_id, callback);
+                    Some(TimerId(timer_id))
+                } else {
+                    nih_debug_assert_failure!("The host rejected the timer registration");
+                    None
+                }
+            }
+            None => None,
+        }
+    }
 
-        // TODO: Right now there's no way to know if loading the state changed the GUI's size. We
-        //       could keep track of the last known size and compare the GUI's current size against
-        //       that but that also seems brittle.
-        if self.editor_handle.lock().is_some() {
-            self.request_resize();
+    /// Unregister a timer that was registered with [`register_timer()`][Self::register_timer()].
+    /// Must be called from the main thread.
+    pub fn unregister_timer(&self, timer_id: TimerId) {
+        if !self.gui_callbacks.remove_timer(timer_id.0) {
+            nih_debug_assert_failure!("Tried to unregister an unknown timer: {:?}", timer_id);
+            return;
         }
 
-        success
+        if let Some(host_timer_support) = &*self.host_timer_support.borrow() {
+            let success = unsafe_clap_call! {
+                host_timer_support=>unregister_timer(&*self.host_callback, timer_id.0)
+            };
+            nih_debug_assert!(
+                success,
+                "The host failed to unregister timer {:?}",
+                timer_id
+            );
+        }
     }
 
-    unsafe extern "C" fn init(plugin: *const clap_plugin) -> bool {
-        check_null_ptr!(false, plugin, (*plugin).plugin_data);
-        let wrapper = &*((*plugin).plugin_data as *const Self);
+    /// Watch a file descriptor using the host's `posix-fd-support` extension. Must be called from
+    /// the main thread.
+    pub fn register_fd(
+        &self,
+        fd: c_int,
+        flags: FdFlags,
+        callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        match &*self.host_posix_fd_support.borrow() {
+            Some(host_posix_fd_support) => {
+                if self.gui_callbacks.contains_fd(fd) {
+                    nih_debug_assert_failure!("File descriptor {} is already being watched", fd);
+                    return false;
+                }
 
-        // We weren't allowed to query these in the constructor, so we need to do it now instead.
-        *wrapper.host_gui.borrow_mut() =
-            query_host_extension::<clap_host_gui>(&wrapper.host_callback, CLAP_EXT_GUI);
-        *wrapper.host_latency.borrow_mut() =
-            query_host_extension::<clap_host_latency>(&wrapper.host_callback, CLAP_EXT_LATENCY);
-        *wrapper.host_params.borrow_mut() =
-            query_host_extension::<clap_host_params>(&wrapper.host_callback, CLAP_EXT_PARAMS);
-        *wrapper.host_voice_info.borrow_mut() = query_host_extension::<clap_host_voice_info>(
-            &wrapper.host_callback,
-            CLAP_EXT_VOICE_INFO,
-        );
-        *wrapper.host_thread_check.borrow_mut() = query_host_extension::<clap_host_thread_check>(
-            &wrapper.host_callback,
-            CLAP_EXT_THREAD_CHECK,
-        );
+                let success = unsafe_clap_call! {
+                    host_posix_fd_support=>register_fd(&*self.host_callback, fd, flags.bits())
+                };
+                if success {
+                    self.gui_callbacks.insert_fd(fd, callback);
+                } else {
+                    nih_debug_assert_failure!("The host rejected file descriptor {}", fd);
+                }
 
-        true
+                success
+            }
+            None => false,
+        }
     }
 
-    unsafe extern "C" fn destroy(plugin: *const clap_plugin) {
-        assert!(!plugin.is_null() && !(*plugin).plugin_data.is_null());
-        let this = Arc::from_raw((*plugin).plugin_data as *mut Self);
-        nih_debug_assert_eq!(Arc::strong_count(&this), 1);
+    /// Stop watching a file descriptor that was registered with
+    /// [`register_fd()`][Self::register_fd()]. Must be called from the main thread.
+    pub fn unregister_fd(&self, fd: c_int) {
+        if !self.gui_callbacks.remove_fd(fd) {
+            nih_debug_assert_failure!("Tried to unregister unknown file descriptor {}", fd);
+            return;
+        }
 
-        drop(this);
+        if let Some(host_posix_fd_support) = &*self.host_posix_fd_support.borrow() {
+            let success = unsafe_clap_call! {
+                host_posix_fd_support=>unregister_fd(&*self.host_callback, fd)
+            };
+            nih_debug_assert!(
+                success,
+                "The host failed to unregister file descriptor {}",
+                fd
+            );
+        }
     }
 
-    unsafe extern "C" fn activate(
-        plugin: *const clap_plugin,
-        sample_rate: f64,
-        min_frames_count: u32,
-        max_frames_count: u32,
-    ) -> bool {
-        check_null_ptr!(false, plugin, (*plugin).plugin_data);
-        let wrapper = &*((*plugin).plugin_data as *const Self);
-
-        let audio_io_layout = wrapper.current_audio_io_layout.load();
-        let buffer_config = BufferConfig {
-            sample_rate: sample_rate as f32,
-            min_buffer_size: Some(min_frames_count),
-            max_buffer_size: max_frames_count,
-            process_mode: wrapper.current_process_mode.load(),
-        };
-
-        // Before initializing the plugin, make sure all smoothers are set the the default values
-        for param in wrapper.param_by_hash.values() {
-            param.update_smoother(buffer_config.sample_rate, true);
+    /// Unregister all of the editor's timers and file descriptor watches. Called when the editor
+    /// gets destroyed.
+    fn unregister_gui_callbacks(&self) {
+        if let Some(host_timer_support) = &*self.host_timer_support.borrow() {
+            for timer_id in self.gui_callbacks.take_timer_ids() {
+                unsafe_clap_call! {
+                    host_timer_support=>unregister_timer(&*self.host_callback, timer_id)
+                };
+            }
+        }
+
+        if let Some(host_posix_fd_support) = &*self.host_posix_fd_support.borrow() {
+            for fd in self.gui_callbacks.take_fds() {
+                unsafe_clap_call! {
+                    host_posix_fd_support=>unregister_fd(&*self.host_callback, fd)
+                };
+            }
+        }
+    }
+
+    /// Get the information about the track the plugin is inserted on, if the host provided any.
+    pub fn track_info(&self) -> Option<TrackInfo> {
+        self.track_info.lock().clone()
+    }
+
+    /// Get the information about the host that was provided in the `clap_host` struct.
+    pub fn host_info(&self) -> &HostInfo {
+        &self.host_info
+    }
+
+    /// Fetch the current track information from the host. Must be called from the main thread.
+    fn update_track_info(&self) {
+        let track_info = match &*self.host_track_info.borrow() {
+            Some(host_track_info) => {
+                // SAFETY: This is a plain old data struct, and the host will fill it in
+                let mut info: clap_track_info = unsafe { mem::zeroed() };
+                let success =
+                    unsafe_clap_call! { host_track_info=>get(&*self.host_callback, &mut info) };
+                if success {
+                    Some(track_info(&info))
+                } else {
+                    nih_trace!("The host could not provide any track info");
+                    None
+                }
+            }
+            None => None,
+        };
+
+        *self.track_info.lock() = track_info;
+    }
+
+    /// Undo the last change made through the `GuiContext`. If the host supports the undo extension,
+    /// then the host is asked to undo its last change instead. That request is made from the main
+    /// thread.
+    pub fn undo(&self, context: &dyn GuiContext) {
+        match &*self.host_undo.borrow() {
+            Some(_) => {
+                let task_posted = self.schedule_gui(Task::RequestUndo);
+                nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+            }
+            None => {
+                if let Some(action) = self.undo_manager.undo() {
+                    self.undo_manager.apply(context, action);
+                }
+            }
+        }
+    }
+
+    /// The counterpart to [`undo()`][Self::undo()].
+    pub fn redo(&self, context: &dyn GuiContext) {
+        match &*self.host_undo.borrow() {
+            Some(_) => {
+                let task_posted = self.schedule_gui(Task::RequestRedo);
+                nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+            }
+            None => {
+                if let Some(action) = self.undo_manager.redo() {
+                    self.undo_manager.apply(context, action);
+                }
+            }
+        }
+    }
+
+    /// Whether [`undo()`][Self::undo()] would undo anything.
+    pub fn can_undo(&self) -> bool {
+        if self.host_undo.borrow().is_some() {
+            self.host_can_undo.load(Ordering::Relaxed)
+        } else {
+            self.undo_manager.can_undo()
+        }
+    }
+
+    /// Whether [`redo()`][Self::redo()] would redo anything.
+    pub fn can_redo(&self) -> bool {
+        if self.host_undo.borrow().is_some() {
+            self.host_can_redo.load(Ordering::Relaxed)
+        } else {
+            self.undo_manager.can_redo()
+        }
+    }
+
+    /// Forward a change in the undo history to the host if it supports the undo extension. The
+    /// host is notified from the main thread, in the same order the changes were made in.
+    pub fn notify_host_undo(&self, update: TransactionUpdate) {
+        if update == TransactionUpdate::Unchanged || self.host_undo.borrow().is_none() {
+            return;
+        }
+
+        let task_posted = self.schedule_gui(Task::UndoHistoryChanged(update));
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    /// Undo or redo a transaction from the plugin's undo history when the host asks for it through
+    /// the undo delta extension.
+    ///
+    /// # Safety
+    ///
+    /// `delta` needs to point to `delta_size` readable bytes.
+    unsafe fn apply_undo_delta(
+        &self,
+        format_version: clap_id,
+        delta: *const c_void,
+        delta_size: usize,
+        redo: bool,
+    ) -> bool {
+        if format_version != undo::DELTA_FORMAT_VERSION {
+            nih_debug_assert_failure!("Unknown undo delta format version {}", format_version);
+            return false;
+        }
+        let transaction_id = match undo::decode_delta(delta, delta_size) {
+            Some(transaction_id) => transaction_id,
+            None => {
+                nih_debug_assert_failure!("The host passed an invalid undo delta");
+                return false;
+            }
+        };
+
+        let action = if redo {
+            self.undo_manager.redo_transaction(transaction_id)
+        } else {
+            self.undo_manager.undo_transaction(transaction_id)
+        };
+        let action = match action {
+            Some(action) => action,
+            None => {
+                nih_debug_assert_failure!(
+                    "The host tried to undo or redo unknown transaction {}",
+                    transaction_id
+                );
+                return false;
+            }
+        };
+
+        // The changes are applied through a `GuiContext` so they are sent to the host as regular
+        // parameter changes
+        match self.this.borrow().upgrade() {
+            Some(this) => {
+                let context = this.make_gui_context();
+                self.undo_manager.apply(&*context, action);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Update the indication for the parameter with hash `param_hash` and let the editor know about
+    /// it. The param indication extension's functions are always called from the main thread, so
+    /// the editor can be notified immediately.
+    fn update_param_indication(&self, param_hash: u32, update: impl FnOnce(&mut ParamIndication)) {
+        let param_ptr = match self.param_by_hash.get(&param_hash) {
+            Some(param_ptr) => param_ptr,
+            None => {
+                nih_debug_assert_failure!("Unknown parameter hash '{}'", param_hash);
+                return;
+            }
+        };
+
+        let mut indication = unsafe { param_ptr.indication() };
+        update(&mut indication);
+        unsafe { param_ptr.set_indication(indication) };
+
+        if self.editor_handle.lock().is_some() {
+            if let Some(editor) = self.editor.borrow().as_ref() {
+                let param_id = &self.param_id_by_hash[&param_hash];
+                editor.lock().param_indication_changed(param_id);
+            }
+        }
+    }
+
+    /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
+    /// state is set from a couple places, so this function aims to deduplicate that. Includes
+    /// `permit_alloc()`s around the deserialization and initialization for the use case where
+    /// `set_state_object_from_gui()` was called while the plugin is process audio.
+    ///
+    /// Implicitly emits `Task::ParameterValuesChanged`.
+    ///
+    /// # Notes
+    ///
+    /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
+    pub fn set_state_inner(&self, state: &mut PluginState, context: StateContext) -> bool {
+        let audio_io_layout = self.current_audio_io_layout.load();
+        let buffer_config = self.current_buffer_config.load();
+
+        // FIXME: This is obviously not realtime-safe, but loading presets without doing this could
+        //        lead to inconsistencies. It's the plugin's responsibility to not perform any
+        //        realtime-unsafe work when the initialize function is called a second time if it
+        //        supports runtime preset loading.  `state::deserialize_object()` normally never
+        //        allocates, but if the plugin has persistent non-parameter data then its
+        //        `deserialize_fields()` implementation may still allocate.
+        let mut success = permit_alloc(|| unsafe {
+            state::deserialize_object::<P>(
+                state,
+                self.params.clone(),
+                state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                self.current_buffer_config.load().as_ref(),
+                context,
+            )
+        });
+        if !success {
+            nih_debug_assert_failure!("Deserializing plugin state from a state object failed");
+            return false;
+        }
+
+        // If the plugin was already initialized then it needs to be reinitialized
+        if let Some(buffer_config) = buffer_config {
+            // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
+            let mut init_context = self.make_init_context();
+            let mut plugin = self.plugin.lock();
+
+            // See above
+            success = permit_alloc(|| {
+                plugin.initialize(&audio_io_layout, &buffer_config, &mut init_context)
+            });
+            if success {
+                process_wrapper(|| plugin.reset());
+            }
+        }
+
+        nih_debug_assert!(
+            success,
+            "Plugin returned false when reinitializing after loading state"
+        );
+
+        // Reinitialize the plugin after loading state so it can respond to the new parameter values
+        let task_posted = self.schedule_gui(Task::ParameterValuesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+
+        // TODO: Right now there's no way to know if loading the state changed the GUI's size. We
+        //       could keep track of the last known size and compare the GUI's current size against
+        //       that but that also seems brittle.
+        if self.editor_handle.lock().is_some() {
+            self.request_resize();
+        }
+
+        success
+    }
+
+    unsafe extern "C" fn init(plugin: *const clap_plugin) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // We weren't allowed to query these in the constructor, so we need to do it now instead.
+        *wrapper.host_gui.borrow_mut() =
+            query_host_extension::<clap_host_gui>(&wrapper.host_callback, CLAP_EXT_GUI);
+        *wrapper.host_latency.borrow_mut() =
+            query_host_extension::<clap_host_latency>(&wrapper.host_callback, CLAP_EXT_LATENCY);
+        *wrapper.host_note_name.borrow_mut() =
+            query_host_extension::<clap_host_note_name>(&wrapper.host_callback, CLAP_EXT_NOTE_NAME);
+        *wrapper.host_params.borrow_mut() =
+            query_host_extension::<clap_host_params>(&wrapper.host_callback, CLAP_EXT_PARAMS);
+        *wrapper.host_voice_info.borrow_mut() = query_host_extension::<clap_host_voice_info>(
+            &wrapper.host_callback,
+            CLAP_EXT_VOICE_INFO,
+        );
+        *wrapper.host_thread_check.borrow_mut() = query_host_extension::<clap_host_thread_check>(
+            &wrapper.host_callback,
+            CLAP_EXT_THREAD_CHECK,
+        );
+        *wrapper.host_preset_load.borrow_mut() = query_host_extension::<clap_host_preset_load>(
+            &wrapper.host_callback,
+            CLAP_EXT_PRESET_LOAD,
+        )
+        .or_else(|| {
+            query_host_extension::<clap_host_preset_load>(
+                &wrapper.host_callback,
+                CLAP_EXT_PRESET_LOAD_COMPAT,
+            )
+        });
+        *wrapper.host_thread_pool.borrow_mut() = query_host_extension::<clap_host_thread_pool>(
+            &wrapper.host_callback,
+            CLAP_EXT_THREAD_POOL,
+        );
+        *wrapper.host_timer_support.borrow_mut() = query_host_extension::<clap_host_timer_support>(
+            &wrapper.host_callback,
+            CLAP_EXT_TIMER_SUPPORT,
+        );
+        *wrapper.host_posix_fd_support.borrow_mut() =
+            query_host_extension::<clap_host_posix_fd_support>(
+                &wrapper.host_callback,
+                CLAP_EXT_POSIX_FD_SUPPORT,
+            );
+        *wrapper.host_track_info.borrow_mut() = query_host_extension::<clap_host_track_info>(
+            &wrapper.host_callback,
+            CLAP_EXT_TRACK_INFO,
+        )
+        .or_else(|| {
+            query_host_extension::<clap_host_track_info>(
+                &wrapper.host_callback,
+                CLAP_EXT_TRACK_INFO_COMPAT,
+            )
+        });
+
+        // The plugin may already want to know about its track during `Plugin::initialize()`
+        wrapper.update_track_info();
+
+        *wrapper.host_undo.borrow_mut() = undo::query_host_undo(&wrapper.host_callback);
+        // The host refers to transactions in its own history, so none of them may be evicted
+        wrapper
+            .undo_manager
+            .set_host_managed(wrapper.host_undo.borrow().is_some());
+
+        true
+    }
+
+    unsafe extern "C" fn destroy(plugin: *const clap_plugin) {
+        assert!(!plugin.is_null() && !(*plugin).plugin_data.is_null());
+        let this = Arc::from_raw((*plugin).plugin_data as *mut Self);
+        nih_debug_assert_eq!(Arc::strong_count(&this), 1);
+
+        drop(this);
+    }
+
+    unsafe extern "C" fn activate(
+        plugin: *const clap_plugin,
+        sample_rate: f64,
+        min_frames_count: u32,
+        max_frames_count: u32,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let audio_io_layout = wrapper.current_audio_io_layout.load();
+        let buffer_config = BufferConfig {
+            sample_rate: sample_rate as f32,
+            min_buffer_size: Some(min_frames_count),
+            max_buffer_size: max_frames_count,
+            process_mode: wrapper.current_process_mode.load(),
+        };
+
+        // Before initializing the plugin, make sure all smoothers are set the the default values
+        for param in wrapper.param_by_hash.values() {
+            param.update_smoother(buffer_config.sample_rate, true);
         }
 
         // NOTE: This needs to be dropped after the `plugin` lock to avoid deadlocks
@@ -1869,59 +2670,23 @@ impl<P: ClapPlugin> Wrapper<P> {
             //       this function
 
             // Preallocate enough room in the output slices vector so we can convert a `*mut *mut
-            // f32` to a `&mut [&mut f32]` in the process call
+            // f32` to a `&mut [&mut f32]` in the process call. The same is done for the sidechain
+            // buffers. If the plugin supports 64-bit processing, then the host may use either
+            // precision so we'll need to preallocate both sets of buffers.
             wrapper
-                .output_buffer
+                .buffers
                 .borrow_mut()
-                .set_slices(0, |output_slices| {
-                    output_slices.resize_with(
-                        audio_io_layout
-                            .main_output_channels
-                            .map(NonZeroU32::get)
-                            .unwrap_or_default() as usize,
-                        || &mut [],
-                    );
-                    // All slices must have the same length, so if the number of output channels has
-                    // changed since the last call then we should make sure to clear any old
-                    // (dangling) slices to be consistent
-                    output_slices.fill_with(|| &mut []);
-                });
-
-            // Also allocate both the buffers and the slices pointing to those buffers for sidechain
-            // inputs. The slices will be assigned in the process function as this object may have
-            // been moved before then.
-            let mut aux_input_storage = wrapper.aux_input_storage.borrow_mut();
-            let mut aux_input_buffers = wrapper.aux_input_buffers.borrow_mut();
-            aux_input_storage.resize_with(audio_io_layout.aux_input_ports.len(), Vec::new);
-            aux_input_buffers.resize_with(audio_io_layout.aux_input_ports.len(), Buffer::default);
-            for ((buffer_storage, buffer), num_channels) in aux_input_storage
-                .iter_mut()
-                .zip(aux_input_buffers.iter_mut())
-                .zip(audio_io_layout.aux_input_ports.iter())
-            {
-                buffer_storage.resize_with(num_channels.get() as usize, Vec::new);
-                for channel_storage in buffer_storage {
-                    channel_storage.resize(max_frames_count as usize, 0.0);
-                }
-
-                buffer.set_slices(0, |channel_slices| {
-                    channel_slices.resize_with(num_channels.get() as usize, || &mut []);
-                    channel_slices.fill_with(|| &mut []);
-                });
-            }
-
-            // And the same thing for the output buffers
-            let mut aux_output_buffers = wrapper.aux_output_buffers.borrow_mut();
-            aux_output_buffers.resize_with(audio_io_layout.aux_output_ports.len(), Buffer::default);
-            for (buffer, num_channels) in aux_output_buffers
-                .iter_mut()
-                .zip(audio_io_layout.aux_output_ports.iter())
-            {
-                buffer.set_slices(0, |channel_slices| {
-                    channel_slices.resize_with(num_channels.get() as usize, || &mut []);
-                    channel_slices.fill_with(|| &mut []);
-                });
+                .preallocate(&audio_io_layout, max_frames_count as usize);
+            if P::F64_PROCESSING {
+                wrapper
+                    .buffers_f64
+                    .borrow_mut()
+                    .preallocate(&audio_io_layout, max_frames_count as usize);
             }
+            wrapper
+                .aux_port_activation
+                .borrow_mut()
+                .resize(&audio_io_layout);
 
             // Also store this for later, so we can reinitialize the plugin after restoring state
             wrapper.current_buffer_config.store(Some(buffer_config));
@@ -1951,6 +2716,7 @@ impl<P: ClapPlugin> Wrapper<P> {
 
         // To be consistent with the VST3 wrapper, we'll also reset the buffers here in addition to
         // the dedicated `reset()` function.
+        wrapper.mpe_translator.borrow_mut().reset();
         process_wrapper(|| wrapper.plugin.lock().reset());
 
         true
@@ -1967,6 +2733,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         check_null_ptr!((), plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
+        wrapper.mpe_translator.borrow_mut().reset();
         process_wrapper(|| wrapper.plugin.lock().reset());
     }
 
@@ -1989,9 +2756,7 @@ impl<P: ClapPlugin> Wrapper<P> {
             // Before doing anything, clear out any auxiliary outputs since they may contain
             // uninitialized data when the host assumes that we'll always write something there
             let current_audio_io_layout = wrapper.current_audio_io_layout.load();
-            let has_main_input = current_audio_io_layout.main_input_channels.is_some();
             let has_main_output = current_audio_io_layout.main_output_channels.is_some();
-            let aux_input_start_idx = if has_main_input { 1 } else { 0 };
             let aux_output_start_idx = if has_main_output { 1 } else { 0 };
             if process.audio_outputs_count > 0 && !process.audio_outputs.is_null() {
                 for output_idx in aux_output_start_idx..process.audio_outputs_count as usize {
@@ -2004,10 +2769,23 @@ impl<P: ClapPlugin> Wrapper<P> {
                                 total_buffer_len,
                             );
                         }
+                    } else if !(*host_output).data64.is_null() {
+                        for channel_idx in 0..(*host_output).channel_count as isize {
+                            ptr::write_bytes(
+                                *((*host_output).data64.offset(channel_idx)) as *mut f64,
+                                0,
+                                total_buffer_len,
+                            );
+                        }
                     }
                 }
             }
 
+            // The host indicates that it wants to use double precision buffers by setting the
+            // `data64` fields instead of the `data32` fields. Since we set
+            // `CLAP_AUDIO_PORT_REQUIRES_COMMON_SAMPLE_SIZE`, all ports will use the same precision.
+            let use_f64 = P::F64_PROCESSING && uses_f64_buffers(process);
+
             // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we'll split up the audio buffer into
             // chunks whenever a parameter change occurs
             let mut block_start = 0;
@@ -2041,11 +2819,31 @@ impl<P: ClapPlugin> Wrapper<P> {
 
                                         // The buffer should not be split on polyphonic modulation
                                         // as those events will be converted to note events
-                                        !(next_event.note_id != -1
+                                        !(Self::is_note_modulation(next_event)
                                             && wrapper
                                                 .poly_mod_ids_by_hash
                                                 .contains_key(&next_event.param_id))
                                     }
+                                    // MIDI messages mapped through MIDI learn are parameter
+                                    // changes as far as the plugin is concerned
+                                    (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_MIDI)
+                                        if wrapper.midi_learn.is_active() =>
+                                    {
+                                        let next_event = &*(next_event as *const clap_event_midi);
+                                        NoteEvent::<()>::from_midi(0, &next_event.data)
+                                            .map_or(false, |event| {
+                                                wrapper.midi_learn.maps_event(&event)
+                                            })
+                                    }
+                                    (CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_MIDI2)
+                                        if wrapper.midi_learn.is_active() =>
+                                    {
+                                        let next_event = &*(next_event as *const clap_event_midi2);
+                                        NoteEvent::<()>::from_midi2(0, &next_event.data)
+                                            .map_or(false, |event| {
+                                                wrapper.midi_learn.maps_event(&event)
+                                            })
+                                    }
                                     _ => false,
                                 }
                             } else {
@@ -2070,214 +2868,13 @@ impl<P: ClapPlugin> Wrapper<P> {
                     }
                 }
 
-                // This vector has been preallocated to contain enough slices as there are output
-                // channels. If the host does not provide outputs or if it does not provide the
-                // required number of channels (should not happen, but Ableton Live does this for
-                // bypassed VST3 plugins) then we'll skip audio processing .
-                // TODO: The audio buffers have a latency field, should we use those?
-                // TODO: Like with VST3, should we expose some way to access or set the silence/constant
-                //       flags?
-                let block_len = block_end - block_start;
-                let mut output_buffer = wrapper.output_buffer.borrow_mut();
-                let mut buffer_is_valid = false;
-                output_buffer.set_slices(block_len, |output_slices| {
-                    // Buffers for zero-channel plugins like note effects should always be allowed
-                    buffer_is_valid = output_slices.is_empty();
-
-                    // Explicitly take plugins with no main output that does have auxiliary outputs
-                    // into account. Shouldn't happen, but if we just start copying audio here then
-                    // that would result in unsoundness.
-                    if process.audio_outputs_count > 0
-                        && !process.audio_outputs.is_null()
-                        && !(*process.audio_outputs).data32.is_null()
-                        && !output_slices.is_empty()
-                        && has_main_output
-                    {
-                        let audio_outputs = &*process.audio_outputs;
-                        let num_output_channels = audio_outputs.channel_count as usize;
-                        // This ensures that we never feed dangling slices to the wrapped plugin
-                        buffer_is_valid = num_output_channels == output_slices.len();
-                        nih_debug_assert_eq!(num_output_channels, output_slices.len());
-
-                        // NOTE: This `.take()` should not be necessary, but we'll do it as a safe
-                        //       guard. Apparently Ableton Live implements parameter flushes wrong
-                        //       for VST3 plugins, so if they ever add CLAP support they'll probably
-                        //       do it wrong here as well.
-                        for (output_channel_idx, output_channel_slice) in output_slices
-                            .iter_mut()
-                            .take(num_output_channels)
-                            .enumerate()
-                        {
-                            // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we may be iterating over
-                            // the buffer in smaller sections.
-                            // SAFETY: These pointers may not be valid outside of this function even though
-                            // their lifetime is equal to this structs. This is still safe because they are
-                            // only dereferenced here later as part of this process function.
-                            let channel_ptr =
-                                *(audio_outputs.data32 as *mut *mut f32).add(output_channel_idx);
-                            *output_channel_slice = std::slice::from_raw_parts_mut(
-                                channel_ptr.add(block_start),
-                                block_len,
-                            );
-                        }
-                    }
-                });
-                // Some hosts process data in place, in which case we don't need to do any copying
-                // ourselves. If the pointers do not alias, then we'll do the copy here and then the
-                // plugin can just do normal in place processing.
-                if process.audio_outputs_count > 0
-                    && !process.audio_outputs.is_null()
-                    && !(*process.audio_outputs).data32.is_null()
-                    && process.audio_inputs_count > 0
-                    && !process.audio_inputs.is_null()
-                    && !(*process.audio_inputs).data32.is_null()
-                    && has_main_input
-                    && has_main_output
-                {
-                    // We currently don't support sidechain inputs
-                    let audio_outputs = &*process.audio_outputs;
-                    let audio_inputs = &*process.audio_inputs;
-                    let num_output_channels = audio_outputs.channel_count as usize;
-                    let num_input_channels = audio_inputs.channel_count as usize;
-                    nih_debug_assert!(
-                        num_input_channels <= num_output_channels,
-                        "Stereo to mono and similar configurations are not supported"
-                    );
-                    for input_channel_idx in 0..cmp::min(num_input_channels, num_output_channels) {
-                        let output_channel_ptr =
-                            *(audio_outputs.data32 as *mut *mut f32).add(input_channel_idx);
-                        let input_channel_ptr = *(audio_inputs.data32).add(input_channel_idx);
-                        if input_channel_ptr != output_channel_ptr {
-                            ptr::copy_nonoverlapping(
-                                input_channel_ptr.add(block_start),
-                                output_channel_ptr.add(block_start),
-                                block_len,
-                            );
-                        }
-                    }
+                // MIDI messages that have been mapped to parameters are turned into parameter
+                // changes before the plugin gets to see them
+                if wrapper.midi_learn.is_active() {
+                    wrapper.handle_midi_learn(&mut wrapper.input_events.borrow_mut());
                 }
 
-                // We'll need to do the same thing for auxiliary input sidechain buffers. Since we
-                // don't know whether overwriting the host's buffers is safe here or not, we'll copy
-                // the data to our own buffers instead. These buffers are only accessible through
-                // the `aux` parameter on the `process()` function.
-                let mut aux_input_storage = wrapper.aux_input_storage.borrow_mut();
-                let mut aux_input_buffers = wrapper.aux_input_buffers.borrow_mut();
-                for (auxiliary_input_idx, (storage, buffer)) in aux_input_storage
-                    .iter_mut()
-                    .zip(aux_input_buffers.iter_mut())
-                    .enumerate()
-                {
-                    let host_input_idx = auxiliary_input_idx + aux_input_start_idx;
-                    let host_input = process.audio_inputs.add(host_input_idx);
-                    if host_input_idx >= process.audio_inputs_count as usize
-                            || process.audio_inputs.is_null()
-                            || (*host_input).data32.is_null()
-                            // Would only happen if the user configured zero channels for the
-                            // auxiliary buffers
-                            || storage.is_empty()
-                            || (*host_input).channel_count != buffer.channels() as u32
-                    {
-                        nih_debug_assert!(host_input_idx < process.audio_inputs_count as usize);
-                        nih_debug_assert!(!process.audio_inputs.is_null());
-                        nih_debug_assert!(!storage.is_empty());
-                        if !process.audio_inputs.is_null()
-                            && host_input_idx < process.audio_inputs_count as usize
-                        {
-                            nih_debug_assert!(!(*host_input).data32.is_null());
-                            nih_debug_assert_eq!(
-                                (*host_input).channel_count,
-                                buffer.channels() as u32
-                            );
-
-                            // This could indicate a parameter flush for a plugin with no main input
-                            // but with auxiliary sidechain inputs, since NIH-plug forbids inputs
-                            // and outputs from having 0 channels
-                            if !(*host_input).channel_count == 0 {
-                                buffer_is_valid = false;
-                            }
-                        }
-
-                        // If the host passes weird data then we need to be very sure that there are
-                        // no dangling references to previous data
-                        buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
-                        continue;
-                    }
-
-                    // We'll always reuse the start of the buffer even of the current block is
-                    // shorter for cache locality reasons
-                    for (channel_idx, channel_storage) in storage.iter_mut().enumerate() {
-                        // The `set_len()` avoids having to unnecessarily fill the buffer with
-                        // zeroes when sizing up
-                        assert!(block_len <= channel_storage.capacity());
-                        channel_storage.set_len(block_len);
-                        channel_storage.copy_from_slice(std::slice::from_raw_parts(
-                            (*(*host_input).data32.add(channel_idx)).add(block_start),
-                            block_len,
-                        ));
-                    }
-
-                    buffer.set_slices(block_len, |slices| {
-                        for (channel_slice, channel_storage) in
-                            slices.iter_mut().zip(storage.iter_mut())
-                        {
-                            // SAFETY: The 'static cast is required because Rust does not allow you
-                            //         to store references to a field in another field.  Because
-                            //         these slices are set here before the process function is
-                            //         called, we ensure that there are no dangling slices. These
-                            //         buffers/slices are only ever read from in the second part of
-                            //         this block process loop.
-                            *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [f32]);
-                        }
-                    });
-                }
-
-                // And the same thing for auxiliary output buffers
-                let mut aux_output_buffers = wrapper.aux_output_buffers.borrow_mut();
-                for (auxiliary_output_idx, buffer) in aux_output_buffers.iter_mut().enumerate() {
-                    let host_output_idx = auxiliary_output_idx + aux_output_start_idx;
-                    let host_output = process.audio_outputs.add(host_output_idx);
-                    if host_output_idx >= process.audio_outputs_count as usize
-                        || process.audio_outputs.is_null()
-                        || (*host_output).data32.is_null()
-                        || buffer.channels() == 0
-                        || (*host_output).channel_count != buffer.channels() as u32
-                    {
-                        nih_debug_assert!(host_output_idx < process.audio_outputs_count as usize);
-                        nih_debug_assert!(!process.audio_outputs.is_null());
-                        if !process.audio_outputs.is_null()
-                            && host_output_idx < process.audio_outputs_count as usize
-                        {
-                            nih_debug_assert!(!(*host_output).data32.is_null());
-                            nih_debug_assert_eq!(
-                                !(*host_output).channel_count,
-                                buffer.channels() as u32
-                            );
-
-                            // This could indicate a parameter flush for a plugin with no main
-                            // output but with auxiliary outputs, since NIH-plug forbids inputs and
-                            // outputs from having 0 channels
-                            if !(*host_output).channel_count == 0 {
-                                buffer_is_valid = false;
-                            }
-                        }
-
-                        // If the host passes weird data then we need to be very sure that there are
-                        // no dangling references to previous data
-                        buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
-                        continue;
-                    }
-
-                    buffer.set_slices(block_len, |slices| {
-                        for (channel_idx, channel_slice) in slices.iter_mut().enumerate() {
-                            *channel_slice = std::slice::from_raw_parts_mut(
-                                (*(*host_output).data32.add(channel_idx)).add(block_start)
-                                    as *mut f32,
-                                block_len,
-                            );
-                        }
-                    });
-                }
+                let block_len = block_end - block_start;
 
                 // Some of the fields are left empty because CLAP does not provide this information,
                 // but the methods on [`Transport`] can reconstruct these values from the other
@@ -2290,6 +2887,14 @@ impl<P: ClapPlugin> Wrapper<P> {
                 let mut transport = Transport::new(sample_rate);
                 if !transport_info.is_null() {
                     let context = &*transport_info;
+                    // The transport information applies to the sample the event was sent at, or to
+                    // the start of the buffer if it came from the process data. If the block
+                    // doesn't start at that sample, then the position needs to be compensated.
+                    let transport_offset = if transport_info == process.transport {
+                        block_start
+                    } else {
+                        block_start.saturating_sub(context.header.time as usize)
+                    };
 
                     transport.playing = context.flags & CLAP_TRANSPORT_IS_PLAYING != 0;
                     transport.recording = context.flags & CLAP_TRANSPORT_IS_RECORDING != 0;
@@ -2308,13 +2913,10 @@ impl<P: ClapPlugin> Wrapper<P> {
                         // This is a bit messy, but we'll try to compensate for the block splitting.
                         // We can't use the functions on the transport information object for this
                         // because we don't have any sample information.
-                        if P::SAMPLE_ACCURATE_AUTOMATION
-                            && block_start > 0
-                            && (context.flags & CLAP_TRANSPORT_HAS_TEMPO != 0)
-                        {
+                        if transport_offset > 0 && (context.flags & CLAP_TRANSPORT_HAS_TEMPO != 0) {
                             transport.pos_beats = Some(
                                 beats
-                                    + (block_start as f64 / sample_rate as f64 / 60.0
+                                    + (transport_offset as f64 / sample_rate as f64 / 60.0
                                         * context.tempo),
                             );
                         } else {
@@ -2325,18 +2927,15 @@ impl<P: ClapPlugin> Wrapper<P> {
                         let seconds = context.song_pos_seconds as f64 / CLAP_SECTIME_FACTOR as f64;
 
                         // Same here
-                        if P::SAMPLE_ACCURATE_AUTOMATION
-                            && block_start > 0
-                            && (context.flags & CLAP_TRANSPORT_HAS_TEMPO != 0)
-                        {
+                        if transport_offset > 0 && (context.flags & CLAP_TRANSPORT_HAS_TEMPO != 0) {
                             transport.pos_seconds =
-                                Some(seconds + (block_start as f64 / sample_rate as f64));
+                                Some(seconds + (transport_offset as f64 / sample_rate as f64));
                         } else {
                             transport.pos_seconds = Some(seconds);
                         }
                     }
                     // TODO: CLAP does not mention whether this is behind a flag or not
-                    if P::SAMPLE_ACCURATE_AUTOMATION && block_start > 0 {
+                    if transport_offset > 0 {
                         transport.bar_start_pos_beats = match transport.bar_start_pos_beats() {
                             Some(updated) => Some(updated),
                             None => Some(context.bar_start as f64 / CLAP_BEATTIME_FACTOR as f64),
@@ -2371,21 +2970,22 @@ impl<P: ClapPlugin> Wrapper<P> {
                     }
                 }
 
-                let result = if buffer_is_valid {
-                    let mut plugin = wrapper.plugin.lock();
-                    // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
-                    //         slices (which it cannot do without using unsafe code), then they
-                    //         would still be reset on the next iteration
-                    let mut aux = AuxiliaryBuffers {
-                        inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer]),
-                        outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer]),
-                    };
-                    let mut context = wrapper.make_process_context(transport);
-                    let result = plugin.process(&mut output_buffer, &mut aux, &mut context);
-                    wrapper.last_process_status.store(result);
-                    result
+                let result = if use_f64 {
+                    wrapper.process_block(
+                        &wrapper.buffers_f64,
+                        process,
+                        block_start,
+                        block_len,
+                        transport,
+                    )
                 } else {
-                    ProcessStatus::Normal
+                    wrapper.process_block(
+                        &wrapper.buffers,
+                        process,
+                        block_start,
+                        block_len,
+                        transport,
+                    )
                 };
 
                 let clap_result = match result {
@@ -2422,12 +3022,13 @@ impl<P: ClapPlugin> Wrapper<P> {
             // FIXME: Zero capacity channels allocate on receiving, find a better alternative that
             //        doesn't do that
             let updated_state = permit_alloc(|| wrapper.updated_state_receiver.try_recv());
-            if let Ok(mut state) = updated_state {
-                wrapper.set_state_inner(&mut state);
+            if let Ok((mut state, _)) = updated_state {
+                // These states always come from `set_state_object_from_gui()`
+                let success = wrapper.set_state_inner(&mut state, StateContext::Preset);
 
                 // We'll pass the state object back to the GUI thread so deallocation can happen
                 // there without potentially blocking the audio thread
-                if let Err(err) = wrapper.updated_state_sender.send(state) {
+                if let Err(err) = wrapper.updated_state_sender.send((state, success)) {
                     nih_debug_assert_failure!(
                         "Failed to send state object back to GUI thread: {}",
                         err
@@ -2439,6 +3040,303 @@ impl<P: ClapPlugin> Wrapper<P> {
         })
     }
 
+    /// Point the plugin's buffers at the host's audio buffers for the current block and then call
+    /// the plugin's process function. This is generic over the sample type so the same code can be
+    /// used for both the host's `data32` and `data64` buffers. If the host's buffers don't match
+    /// the current audio IO layout, then the plugin won't be called and this returns
+    /// [`ProcessStatus::Normal`].
+    ///
+    /// # Safety
+    ///
+    /// `process` must point to the host's data for the current process call, and the block defined
+    /// by `block_start` and `block_len` must fit within the host's audio buffers.
+    unsafe fn process_block<T: ClapSample>(
+        &self,
+        buffers: &AtomicRefCell<ProcessBuffers<T>>,
+        process: &clap_process,
+        block_start: usize,
+        block_len: usize,
+        transport: Transport,
+    ) -> ProcessStatus {
+        let current_audio_io_layout = self.current_audio_io_layout.load();
+        let has_main_input = current_audio_io_layout.main_input_channels.is_some();
+        let has_main_output = current_audio_io_layout.main_output_channels.is_some();
+        let aux_input_start_idx = if has_main_input { 1 } else { 0 };
+        let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+
+        let aux_port_activation = self.aux_port_activation.borrow();
+        let mut buffers = buffers.borrow_mut();
+        let ProcessBuffers {
+            output_buffer,
+            aux_input_storage,
+            aux_input_buffers,
+            aux_output_buffers,
+            aux_output_storage,
+        } = &mut *buffers;
+
+        // This vector has been preallocated to contain enough slices as there are output
+        // channels. If the host does not provide outputs or if it does not provide the
+        // required number of channels (should not happen, but Ableton Live does this for
+        // bypassed VST3 plugins) then we'll skip audio processing .
+        // TODO: The audio buffers have a latency field, should we use those?
+        let mut buffer_is_valid = false;
+        output_buffer.set_slices(block_len, |output_slices| {
+            // Buffers for zero-channel plugins like note effects should always be allowed
+            buffer_is_valid = output_slices.is_empty();
+
+            // Explicitly take plugins with no main output that does have auxiliary outputs
+            // into account. Shouldn't happen, but if we just start copying audio here then
+            // that would result in unsoundness.
+            if process.audio_outputs_count > 0
+                && !process.audio_outputs.is_null()
+                && !T::channel_ptrs(&*process.audio_outputs).is_null()
+                && !output_slices.is_empty()
+                && has_main_output
+            {
+                let audio_outputs = &*process.audio_outputs;
+                let num_output_channels = audio_outputs.channel_count as usize;
+                // This ensures that we never feed dangling slices to the wrapped plugin
+                buffer_is_valid = num_output_channels == output_slices.len();
+                nih_debug_assert_eq!(num_output_channels, output_slices.len());
+
+                // NOTE: This `.take()` should not be necessary, but we'll do it as a safe
+                //       guard. Apparently Ableton Live implements parameter flushes wrong
+                //       for VST3 plugins, so if they ever add CLAP support they'll probably
+                //       do it wrong here as well.
+                for (output_channel_idx, output_channel_slice) in output_slices
+                    .iter_mut()
+                    .take(num_output_channels)
+                    .enumerate()
+                {
+                    // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we may be iterating over
+                    // the buffer in smaller sections.
+                    // SAFETY: These pointers may not be valid outside of this function even though
+                    // their lifetime is equal to this structs. This is still safe because they are
+                    // only dereferenced here later as part of this process function.
+                    let channel_ptr = *T::channel_ptrs(audio_outputs).add(output_channel_idx);
+                    *output_channel_slice =
+                        std::slice::from_raw_parts_mut(channel_ptr.add(block_start), block_len);
+                }
+            }
+        });
+        // Some hosts process data in place, in which case we don't need to do any copying
+        // ourselves. If the pointers do not alias, then we'll do the copy here and then the
+        // plugin can just do normal in place processing.
+        if process.audio_outputs_count > 0
+            && !process.audio_outputs.is_null()
+            && !T::channel_ptrs(&*process.audio_outputs).is_null()
+            && process.audio_inputs_count > 0
+            && !process.audio_inputs.is_null()
+            && !T::channel_ptrs(&*process.audio_inputs).is_null()
+            && has_main_input
+            && has_main_output
+        {
+            // We currently don't support sidechain inputs
+            let audio_outputs = &*process.audio_outputs;
+            let audio_inputs = &*process.audio_inputs;
+            let num_output_channels = audio_outputs.channel_count as usize;
+            let num_input_channels = audio_inputs.channel_count as usize;
+            nih_debug_assert!(
+                num_input_channels <= num_output_channels,
+                "Stereo to mono and similar configurations are not supported"
+            );
+            for input_channel_idx in 0..cmp::min(num_input_channels, num_output_channels) {
+                let output_channel_ptr = *T::channel_ptrs(audio_outputs).add(input_channel_idx);
+                let input_channel_ptr = *T::channel_ptrs(audio_inputs).add(input_channel_idx);
+                if input_channel_ptr != output_channel_ptr {
+                    ptr::copy_nonoverlapping(
+                        input_channel_ptr.add(block_start),
+                        output_channel_ptr.add(block_start),
+                        block_len,
+                    );
+                }
+            }
+
+            // The main input's constant flags also apply to the main output buffer since the input
+            // has been copied there
+            output_buffer.set_channel_flags(
+                clap_silence_mask::<T>(audio_inputs, block_start),
+                audio_inputs.constant_mask,
+            );
+        }
+
+        // We'll need to do the same thing for auxiliary input sidechain buffers. Since we
+        // don't know whether overwriting the host's buffers is safe here or not, we'll copy
+        // the data to our own buffers instead. These buffers are only accessible through
+        // the `aux` parameter on the `process()` function.
+        for (auxiliary_input_idx, (storage, buffer)) in aux_input_storage
+            .iter_mut()
+            .zip(aux_input_buffers.iter_mut())
+            .enumerate()
+        {
+            let host_input_idx = auxiliary_input_idx + aux_input_start_idx;
+            let host_input = process.audio_inputs.add(host_input_idx);
+            if host_input_idx >= process.audio_inputs_count as usize
+                    || process.audio_inputs.is_null()
+                    || T::channel_ptrs(&*host_input).is_null()
+                    // Would only happen if the user configured zero channels for the
+                    // auxiliary buffers
+                    || storage.is_empty()
+                    || (*host_input).channel_count != buffer.channels() as u32
+            {
+                // Hosts don't need to provide buffers for inactive ports, but the plugin should
+                // still receive silent buffers for them
+                if !aux_port_activation.inputs[auxiliary_input_idx] {
+                    set_silent_slices(buffer, storage, block_len);
+                    continue;
+                }
+
+                nih_debug_assert!(host_input_idx < process.audio_inputs_count as usize);
+                nih_debug_assert!(!process.audio_inputs.is_null());
+                nih_debug_assert!(!storage.is_empty());
+                if !process.audio_inputs.is_null()
+                    && host_input_idx < process.audio_inputs_count as usize
+                {
+                    nih_debug_assert!(!T::channel_ptrs(&*host_input).is_null());
+                    nih_debug_assert_eq!((*host_input).channel_count, buffer.channels() as u32);
+
+                    // This could indicate a parameter flush for a plugin with no main input
+                    // but with auxiliary sidechain inputs, since NIH-plug forbids inputs
+                    // and outputs from having 0 channels
+                    if !(*host_input).channel_count == 0 {
+                        buffer_is_valid = false;
+                    }
+                }
+
+                // If the host passes weird data then we need to be very sure that there are
+                // no dangling references to previous data
+                buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
+                continue;
+            }
+
+            // We'll always reuse the start of the buffer even of the current block is
+            // shorter for cache locality reasons
+            for (channel_idx, channel_storage) in storage.iter_mut().enumerate() {
+                // The `set_len()` avoids having to unnecessarily fill the buffer with
+                // zeroes when sizing up
+                assert!(block_len <= channel_storage.capacity());
+                channel_storage.set_len(block_len);
+                channel_storage.copy_from_slice(std::slice::from_raw_parts(
+                    (*T::channel_ptrs(&*host_input).add(channel_idx)).add(block_start),
+                    block_len,
+                ));
+            }
+
+            buffer.set_slices(block_len, |slices| {
+                for (channel_slice, channel_storage) in slices.iter_mut().zip(storage.iter_mut()) {
+                    // SAFETY: The 'static cast is required because Rust does not allow you
+                    //         to store references to a field in another field.  Because
+                    //         these slices are set here before the process function is
+                    //         called, we ensure that there are no dangling slices. These
+                    //         buffers/slices are only ever read from in the second part of
+                    //         this block process loop.
+                    *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [T]);
+                }
+            });
+            buffer.set_channel_flags(
+                clap_silence_mask::<T>(&*host_input, block_start),
+                (*host_input).constant_mask,
+            );
+        }
+
+        // And the same thing for auxiliary output buffers
+        for (auxiliary_output_idx, (buffer, storage)) in aux_output_buffers
+            .iter_mut()
+            .zip(aux_output_storage.iter_mut())
+            .enumerate()
+        {
+            let host_output_idx = auxiliary_output_idx + aux_output_start_idx;
+            let host_output = process.audio_outputs.add(host_output_idx);
+            if host_output_idx >= process.audio_outputs_count as usize
+                || process.audio_outputs.is_null()
+                || T::channel_ptrs(&*host_output).is_null()
+                || buffer.channels() == 0
+                || (*host_output).channel_count != buffer.channels() as u32
+            {
+                // The host discards the output of inactive ports, so if it doesn't provide any
+                // buffers for those then the plugin will write to our scratch buffers instead
+                if !aux_port_activation.outputs[auxiliary_output_idx] {
+                    set_silent_slices(buffer, storage, block_len);
+                    continue;
+                }
+
+                nih_debug_assert!(host_output_idx < process.audio_outputs_count as usize);
+                nih_debug_assert!(!process.audio_outputs.is_null());
+                if !process.audio_outputs.is_null()
+                    && host_output_idx < process.audio_outputs_count as usize
+                {
+                    nih_debug_assert!(!T::channel_ptrs(&*host_output).is_null());
+                    nih_debug_assert_eq!(!(*host_output).channel_count, buffer.channels() as u32);
+
+                    // This could indicate a parameter flush for a plugin with no main
+                    // output but with auxiliary outputs, since NIH-plug forbids inputs and
+                    // outputs from having 0 channels
+                    if !(*host_output).channel_count == 0 {
+                        buffer_is_valid = false;
+                    }
+                }
+
+                // If the host passes weird data then we need to be very sure that there are
+                // no dangling references to previous data
+                buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
+                continue;
+            }
+
+            buffer.set_slices(block_len, |slices| {
+                for (channel_idx, channel_slice) in slices.iter_mut().enumerate() {
+                    *channel_slice = std::slice::from_raw_parts_mut(
+                        (*T::channel_ptrs(&*host_output).add(channel_idx)).add(block_start),
+                        block_len,
+                    );
+                }
+            });
+        }
+
+        if buffer_is_valid {
+            let mut plugin = self.plugin.lock();
+            // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
+            //         slices (which it cannot do without using unsafe code), then they
+            //         would still be reset on the next iteration
+            let mut aux = AuxiliaryBuffers {
+                inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                inputs_active: &aux_port_activation.inputs,
+                outputs_active: &aux_port_activation.outputs,
+            };
+            let mut context = self.make_process_context(transport);
+            let result = T::process(&mut *plugin, output_buffer, &mut aux, &mut context);
+            self.last_process_status.store(result);
+
+            // Report the output channels the plugin marked as silent back to the host. CLAP only
+            // has a constant mask for this, and with block splitting a channel is only constant if
+            // the plugin marked it as silent in every block.
+            if !process.audio_outputs.is_null() {
+                let main_output_buffer = if has_main_output {
+                    Some(&*output_buffer)
+                } else {
+                    None
+                };
+                for (host_output_idx, buffer) in main_output_buffer
+                    .into_iter()
+                    .chain(aux_output_buffers.iter())
+                    .enumerate()
+                    .take(process.audio_outputs_count as usize)
+                {
+                    let host_output = &mut *process.audio_outputs.add(host_output_idx);
+                    if block_start == 0 {
+                        host_output.constant_mask = buffer.output_silence_mask();
+                    } else {
+                        host_output.constant_mask &= buffer.output_silence_mask();
+                    }
+                }
+            }
+
+            result
+        } else {
+            ProcessStatus::Normal
+        }
+    }
+
     unsafe extern "C" fn get_extension(
         plugin: *const clap_plugin,
         id: *const c_char,
@@ -2452,23 +3350,59 @@ impl<P: ClapPlugin> Wrapper<P> {
             &wrapper.clap_plugin_audio_ports_config as *const _ as *const c_void
         } else if id == CLAP_EXT_AUDIO_PORTS {
             &wrapper.clap_plugin_audio_ports as *const _ as *const c_void
+        } else if id == CLAP_EXT_AUDIO_PORTS_ACTIVATION
+            || id == CLAP_EXT_AUDIO_PORTS_ACTIVATION_COMPAT
+        {
+            &wrapper.clap_plugin_audio_ports_activation as *const _ as *const c_void
+        } else if id == CLAP_EXT_AMBISONIC || id == CLAP_EXT_AMBISONIC_COMPAT {
+            &wrapper.clap_plugin_ambisonic as *const _ as *const c_void
         } else if id == CLAP_EXT_GUI && wrapper.editor.borrow().is_some() {
             // Only report that we support this extension if the plugin has an editor
             &wrapper.clap_plugin_gui as *const _ as *const c_void
         } else if id == CLAP_EXT_LATENCY {
             &wrapper.clap_plugin_latency as *const _ as *const c_void
+        } else if id == CLAP_EXT_NOTE_NAME && P::MIDI_INPUT >= MidiConfig::Basic {
+            &wrapper.clap_plugin_note_name as *const _ as *const c_void
         } else if id == CLAP_EXT_NOTE_PORTS
             && (P::MIDI_INPUT >= MidiConfig::Basic || P::MIDI_OUTPUT >= MidiConfig::Basic)
         {
             &wrapper.clap_plugin_note_ports as *const _ as *const c_void
         } else if id == CLAP_EXT_PARAMS {
             &wrapper.clap_plugin_params as *const _ as *const c_void
+        } else if id == CLAP_EXT_POSIX_FD_SUPPORT && wrapper.editor.borrow().is_some() {
+            // File descriptors can only be watched from the editor
+            &wrapper.clap_plugin_posix_fd_support as *const _ as *const c_void
+        } else if id == CLAP_EXT_PARAM_INDICATION || id == CLAP_EXT_PARAM_INDICATION_COMPAT {
+            &wrapper.clap_plugin_param_indication as *const _ as *const c_void
+        } else if (id == CLAP_EXT_PRESET_LOAD || id == CLAP_EXT_PRESET_LOAD_COMPAT)
+            && !wrapper.factory_presets.is_empty()
+        {
+            &wrapper.clap_plugin_preset_load as *const _ as *const c_void
+        } else if (id == CLAP_EXT_REMOTE_CONTROLS || id == CLAP_EXT_REMOTE_CONTROLS_COMPAT)
+            && !wrapper.remote_controls_pages.is_empty()
+        {
+            &wrapper.clap_plugin_remote_controls as *const _ as *const c_void
         } else if id == CLAP_EXT_RENDER {
             &wrapper.clap_plugin_render as *const _ as *const c_void
         } else if id == CLAP_EXT_STATE {
             &wrapper.clap_plugin_state as *const _ as *const c_void
+        } else if id == CLAP_EXT_STATE_CONTEXT {
+            &wrapper.clap_plugin_state_context as *const _ as *const c_void
+        } else if id == CLAP_EXT_SURROUND || id == CLAP_EXT_SURROUND_COMPAT {
+            &wrapper.clap_plugin_surround as *const _ as *const c_void
         } else if id == CLAP_EXT_TAIL {
             &wrapper.clap_plugin_tail as *const _ as *const c_void
+        } else if id == CLAP_EXT_THREAD_POOL {
+            &wrapper.clap_plugin_thread_pool as *const _ as *const c_void
+        } else if id == CLAP_EXT_TIMER_SUPPORT && wrapper.editor.borrow().is_some() {
+            // The same goes for timers
+            &wrapper.clap_plugin_timer_support as *const _ as *const c_void
+        } else if id == CLAP_EXT_TRACK_INFO || id == CLAP_EXT_TRACK_INFO_COMPAT {
+            &wrapper.clap_plugin_track_info as *const _ as *const c_void
+        } else if id == CLAP_EXT_UNDO_CONTEXT {
+            &wrapper.clap_plugin_undo_context as *const _ as *const c_void
+        } else if id == CLAP_EXT_UNDO_DELTA {
+            &wrapper.clap_plugin_undo_delta as *const _ as *const c_void
         } else if id == CLAP_EXT_VOICE_INFO && P::CLAP_POLY_MODULATION_CONFIG.is_some() {
             &wrapper.clap_plugin_voice_info as *const _ as *const c_void
         } else {
@@ -2510,16 +3444,8 @@ impl<P: ClapPlugin> Wrapper<P> {
                 let main_input_channels = audio_io_layout.main_input_channels.map(NonZeroU32::get);
                 let main_output_channels =
                     audio_io_layout.main_output_channels.map(NonZeroU32::get);
-                let input_port_type = match main_input_channels {
-                    Some(1) => CLAP_PORT_MONO.as_ptr(),
-                    Some(2) => CLAP_PORT_STEREO.as_ptr(),
-                    _ => ptr::null(),
-                };
-                let output_port_type = match main_output_channels {
-                    Some(1) => CLAP_PORT_MONO.as_ptr(),
-                    Some(2) => CLAP_PORT_STEREO.as_ptr(),
-                    _ => ptr::null(),
-                };
+                let input_port_type = port_type(audio_io_layout.main_input_layout());
+                let output_port_type = port_type(audio_io_layout.main_output_layout());
 
                 *config = std::mem::zeroed();
 
@@ -2665,11 +3591,11 @@ impl<P: ClapPlugin> Wrapper<P> {
             (n, false) => current_audio_io_layout.aux_output_ports[n as usize].get(),
         };
 
-        let port_type = match channel_count {
-            1 => CLAP_PORT_MONO.as_ptr(),
-            2 => CLAP_PORT_STEREO.as_ptr(),
-            _ => ptr::null(),
-        };
+        let port_type = port_type(port_channel_layout(
+            &current_audio_io_layout,
+            is_input,
+            index,
+        ));
 
         *info = std::mem::zeroed();
 
@@ -2702,6 +3628,12 @@ impl<P: ClapPlugin> Wrapper<P> {
         } else {
             0
         };
+        if P::F64_PROCESSING {
+            // The plugin's process function receives a single buffer type for all ports, so the
+            // host cannot mix precisions between ports
+            info.flags |=
+                CLAP_AUDIO_PORT_SUPPORTS_64BITS | CLAP_AUDIO_PORT_REQUIRES_COMMON_SAMPLE_SIZE;
+        }
         info.channel_count = channel_count;
         info.port_type = port_type;
         info.in_place_pair = pair_stable_id;
@@ -2709,6 +3641,102 @@ impl<P: ClapPlugin> Wrapper<P> {
         true
     }
 
+    unsafe extern "C" fn ext_audio_ports_activation_can_activate_while_processing(
+        _plugin: *const clap_plugin,
+    ) -> bool {
+        // The activation states are read from the audio thread without any synchronization
+        false
+    }
+
+    unsafe extern "C" fn ext_audio_ports_activation_set_active(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        is_active: bool,
+        _sample_size: u32,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let audio_io_layout = wrapper.current_audio_io_layout.load();
+        let has_main_port = if is_input {
+            audio_io_layout.main_input_channels.is_some()
+        } else {
+            audio_io_layout.main_output_channels.is_some()
+        };
+
+        // The main ports are always processed, so those are only tracked for the auxiliary ports.
+        // `port_index` is off by one for the auxiliary ports if the plugin has a main port.
+        let aux_port_idx = match (port_index, has_main_port) {
+            (0, true) => return true,
+            (n, true) => n as usize - 1,
+            (n, false) => n as usize,
+        };
+
+        let mut aux_port_activation = wrapper.aux_port_activation.borrow_mut();
+        aux_port_activation.resize(&audio_io_layout);
+        let aux_ports_active = if is_input {
+            &mut aux_port_activation.inputs
+        } else {
+            &mut aux_port_activation.outputs
+        };
+        match aux_ports_active.get_mut(aux_port_idx) {
+            Some(active) => {
+                *active = is_active;
+                true
+            }
+            None => {
+                nih_debug_assert_failure!(
+                    "Host tried to activate out of bounds audio port {} (input: {})",
+                    port_index,
+                    is_input
+                );
+                false
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_ambisonic_is_config_supported(
+        plugin: *const clap_plugin,
+        config: *const clap_ambisonic_config,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, config);
+
+        // All of NIH-plug's ambisonic layouts use the same ordering and normalization
+        let config = &*config;
+        config.ordering == AMBISONIC_CONFIG.ordering
+            && config.normalization == AMBISONIC_CONFIG.normalization
+    }
+
+    unsafe extern "C" fn ext_ambisonic_get_config(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        config: *mut clap_ambisonic_config,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, config);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let current_audio_io_layout = wrapper.current_audio_io_layout.load();
+        match port_channel_layout(&current_audio_io_layout, is_input, port_index) {
+            Some(layout) if layout.ambisonic_order().is_some() => {
+                *config = AMBISONIC_CONFIG;
+
+                true
+            }
+            _ => {
+                nih_debug_assert_failure!(
+                    "Host queried the ambisonic config for non-ambisonic audio port {} (input: \
+                     {})",
+                    port_index,
+                    is_input
+                );
+
+                false
+            }
+        }
+    }
+
     unsafe extern "C" fn ext_gui_is_api_supported(
         _plugin: *const clap_plugin,
         api: *const c_char,
@@ -2793,6 +3821,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         let mut editor_handle = wrapper.editor_handle.lock();
         if editor_handle.is_some() {
             *editor_handle = None;
+            wrapper.unregister_gui_callbacks();
         } else {
             nih_debug_assert_failure!("Tried destroying editor while the editor was not active");
         }
@@ -2845,26 +3874,91 @@ impl<P: ClapPlugin> Wrapper<P> {
         true
     }
 
-    unsafe extern "C" fn ext_gui_can_resize(_plugin: *const clap_plugin) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+    unsafe extern "C" fn ext_gui_can_resize(plugin: *const clap_plugin) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper
+            .editor
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .lock()
+            .resize_constraints()
+            .is_some()
     }
 
     unsafe extern "C" fn ext_gui_get_resize_hints(
-        _plugin: *const clap_plugin,
-        _hints: *mut clap_gui_resize_hints,
+        plugin: *const clap_plugin,
+        hints: *mut clap_gui_resize_hints,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, hints);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let constraints = wrapper
+            .editor
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .lock()
+            .resize_constraints();
+        match constraints {
+            Some(constraints) => {
+                let (min_width, min_height) = constraints.min_size;
+                let (aspect_ratio_width, aspect_ratio_height) =
+                    constraints.aspect_ratio.unwrap_or((0, 0));
+                *hints = clap_gui_resize_hints {
+                    can_resize_horizontally: constraints
+                        .max_size
+                        .map(|(max_width, _)| max_width > min_width)
+                        .unwrap_or(true),
+                    can_resize_vertically: constraints
+                        .max_size
+                        .map(|(_, max_height)| max_height > min_height)
+                        .unwrap_or(true),
+                    preserve_aspect_ratio: constraints.aspect_ratio.is_some(),
+                    aspect_ratio_width,
+                    aspect_ratio_height,
+                };
+
+                true
+            }
+            None => false,
+        }
     }
 
     unsafe extern "C" fn ext_gui_adjust_size(
-        _plugin: *const clap_plugin,
-        _width: *mut u32,
-        _height: *mut u32,
+        plugin: *const clap_plugin,
+        width: *mut u32,
+        height: *mut u32,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
-        false
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, width, height);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let constraints = wrapper
+            .editor
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .lock()
+            .resize_constraints();
+        match constraints {
+            Some(constraints) => {
+                // The constraints are in logical pixels, while the host uses physical pixels
+                let scaling_factor = wrapper.editor_scaling_factor.load(Ordering::Relaxed);
+                let (unscaled_width, unscaled_height) = constraints.constrain((
+                    (*width as f32 / scaling_factor).round() as u32,
+                    (*height as f32 / scaling_factor).round() as u32,
+                ));
+                (*width, *height) = (
+                    (unscaled_width as f32 * scaling_factor).round() as u32,
+                    (unscaled_height as f32 * scaling_factor).round() as u32,
+                );
+
+                true
+            }
+            None => false,
+        }
     }
 
     unsafe extern "C" fn ext_gui_set_size(
@@ -2872,14 +3966,27 @@ impl<P: ClapPlugin> Wrapper<P> {
         width: u32,
         height: u32,
     ) -> bool {
-        // TODO: Implement Host->Plugin GUI resizing
         // TODO: The host will also call this if an asynchronous (on Linux) resize request fails
         check_null_ptr!(false, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
-        let (unscaled_width, unscaled_height) =
-            wrapper.editor.borrow().as_ref().unwrap().lock().size();
+        let editor = wrapper.editor.borrow();
+        let editor = editor.as_ref().unwrap().lock();
         let scaling_factor = wrapper.editor_scaling_factor.load(Ordering::Relaxed);
+
+        // If the editor can be resized then the new size is passed through to the editor. The host
+        // should have called `adjust_size()` first, but we'll constrain the size here anyways.
+        if let Some(constraints) = editor.resize_constraints() {
+            let (unscaled_width, unscaled_height) = constraints.constrain((
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            ));
+
+            return editor.set_size(unscaled_width, unscaled_height);
+        }
+
+        // Otherwise the host is allowed to 'resize' the editor to its current size
+        let (unscaled_width, unscaled_height) = editor.size();
         let (editor_width, editor_height) = (
             (unscaled_width as f32 * scaling_factor).round() as u32,
             (unscaled_height as f32 * scaling_factor).round() as u32,
@@ -2971,6 +4078,49 @@ impl<P: ClapPlugin> Wrapper<P> {
         wrapper.current_latency.load(Ordering::SeqCst)
     }
 
+    unsafe extern "C" fn ext_note_name_count(plugin: *const clap_plugin) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // The host always calls this function before querying the individual names, so this is
+        // where we'll fetch the current names from the plugin
+        let note_names = wrapper.plugin.lock().note_names();
+        nih_debug_assert!(
+            note_names
+                .iter()
+                .all(|name| name.note < 128 && name.channel.map_or(true, |c| c < 16)),
+            "Note names must use MIDI key numbers below 128 and channels below 16"
+        );
+
+        let mut cached_names = wrapper.note_names.borrow_mut();
+        *cached_names = note_names;
+        cached_names.len() as u32
+    }
+
+    unsafe extern "C" fn ext_note_name_get(
+        plugin: *const clap_plugin,
+        index: u32,
+        note_name: *mut clap_note_name,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, note_name);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        match wrapper.note_names.borrow().get(index as usize) {
+            Some(name) => {
+                *note_name = std::mem::zeroed();
+
+                let note_name = &mut *note_name;
+                strlcpy(&mut note_name.name, &name.name);
+                note_name.port = 0;
+                note_name.key = name.note as i16;
+                note_name.channel = name.channel.map(|c| c as i16).unwrap_or(-1);
+
+                true
+            }
+            None => false,
+        }
+    }
+
     unsafe extern "C" fn ext_note_ports_count(_plugin: *const clap_plugin, is_input: bool) -> u32 {
         match is_input {
             true if P::MIDI_INPUT >= MidiConfig::Basic => 1,
@@ -2992,9 +4142,15 @@ impl<P: ClapPlugin> Wrapper<P> {
                 let info = &mut *info;
                 info.id = 0;
                 // NOTE: REAPER won't send us SysEx if we don't support the MIDI dialect
-                // TODO: Implement MPE (would just be a toggle for the plugin to expose it) and MIDI2
-                info.supported_dialects = CLAP_NOTE_DIALECT_CLAP | CLAP_NOTE_DIALECT_MIDI;
+                info.supported_dialects =
+                    CLAP_NOTE_DIALECT_CLAP | CLAP_NOTE_DIALECT_MIDI | CLAP_NOTE_DIALECT_MIDI2;
                 info.preferred_dialect = CLAP_NOTE_DIALECT_CLAP;
+                // MPE plugins want the host to send the controller's per-channel MIDI messages as
+                // is, these are then translated to polyphonic expressions by the wrapper
+                if P::MIDI_INPUT.mpe_zones().is_some() {
+                    info.supported_dialects |= CLAP_NOTE_DIALECT_MIDI_MPE;
+                    info.preferred_dialect = CLAP_NOTE_DIALECT_MIDI_MPE;
+                }
                 strlcpy(&mut info.name, "Note Input");
 
                 true
@@ -3045,6 +4201,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         let automatable = !flags.contains(ParamFlags::NON_AUTOMATABLE);
         let hidden = flags.contains(ParamFlags::HIDDEN);
         let is_bypass = flags.contains(ParamFlags::BYPASS);
+        let is_output = flags.contains(ParamFlags::OUTPUT);
 
         *param_info = std::mem::zeroed();
 
@@ -3052,17 +4209,28 @@ impl<P: ClapPlugin> Wrapper<P> {
         //       hashmap lookup, but for now we'll stay consistent with the VST3 implementation.
         let param_info = &mut *param_info;
         param_info.id = *param_hash;
-        // TODO: Somehow expose per note/channel/port modulation
+        // NOTE: Per-port modulation is not exposed since the plugin only ever has a single note
+        //       input port
         param_info.flags = 0;
-        if automatable && !hidden {
+        if automatable && !hidden && !is_output {
             param_info.flags |= CLAP_PARAM_IS_AUTOMATABLE | CLAP_PARAM_IS_MODULATABLE;
             if wrapper.poly_mod_ids_by_hash.contains_key(param_hash) {
                 param_info.flags |= CLAP_PARAM_IS_MODULATABLE_PER_NOTE_ID;
+                if P::CLAP_POLY_MODULATION_CONFIG
+                    .map(|config| config.supports_key_modulation)
+                    .unwrap_or(false)
+                {
+                    param_info.flags |=
+                        CLAP_PARAM_IS_MODULATABLE_PER_KEY | CLAP_PARAM_IS_MODULATABLE_PER_CHANNEL;
+                }
             }
         }
         if hidden {
             param_info.flags |= CLAP_PARAM_IS_HIDDEN | CLAP_PARAM_IS_READONLY;
         }
+        if is_output {
+            param_info.flags |= CLAP_PARAM_IS_READONLY;
+        }
         if is_bypass {
             param_info.flags |= CLAP_PARAM_IS_BYPASS
         }
@@ -3178,6 +4346,158 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_posix_fd_support_on_fd(
+        plugin: *const clap_plugin,
+        fd: c_int,
+        flags: clap_posix_fd_flags,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // This is always called from the main thread, so the callback runs immediately
+        let task_posted =
+            wrapper.schedule_gui(Task::GuiFdReady(fd, FdFlags::from_bits_truncate(flags)));
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    unsafe extern "C" fn ext_preset_load_from_location(
+        plugin: *const clap_plugin,
+        location_kind: u32,
+        location: *const c_char,
+        load_key: *const c_char,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, load_key);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // We only expose the factory presets, and those are all stored inside of the plugin
+        let preset = if location_kind == CLAP_PRESET_DISCOVERY_LOCATION_PLUGIN {
+            factory_preset_index_from_load_key(CStr::from_ptr(load_key))
+                .and_then(|preset_idx| wrapper.factory_presets.get(preset_idx))
+        } else {
+            None
+        };
+
+        match preset {
+            Some(preset) => {
+                // The host should only be told that the preset has been loaded once the state has
+                // actually been restored
+                let success = wrapper.set_state_object_from_gui(preset.state.clone());
+                if let Some(host_preset_load) = &*wrapper.host_preset_load.borrow() {
+                    if success {
+                        clap_call! { host_preset_load=>loaded(&*wrapper.host_callback, location_kind, location, load_key) };
+                    } else {
+                        let message =
+                            CStr::from_bytes_with_nul_unchecked(b"Could not restore the preset\0");
+                        clap_call! { host_preset_load=>on_error(&*wrapper.host_callback, location_kind, location, load_key, 0, message.as_ptr()) };
+                    }
+                }
+
+                success
+            }
+            None => {
+                nih_debug_assert_failure!(
+                    "Unknown preset with location kind {} and load key {:?}",
+                    location_kind,
+                    CStr::from_ptr(load_key)
+                );
+
+                if let Some(host_preset_load) = &*wrapper.host_preset_load.borrow() {
+                    let message = CStr::from_bytes_with_nul_unchecked(b"Unknown preset\0");
+                    clap_call! { host_preset_load=>on_error(&*wrapper.host_callback, location_kind, location, load_key, 0, message.as_ptr()) };
+                }
+
+                false
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_param_indication_set_mapping(
+        plugin: *const clap_plugin,
+        param_id: clap_id,
+        has_mapping: bool,
+        color: *const clap_color,
+        _label: *const c_char,
+        _description: *const c_char,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let mapping_color = if has_mapping {
+            indication_color(color)
+        } else {
+            None
+        };
+        wrapper.update_param_indication(param_id, |indication| {
+            indication.mapped = has_mapping;
+            indication.mapping_color = mapping_color;
+        });
+    }
+
+    unsafe extern "C" fn ext_param_indication_set_automation(
+        plugin: *const clap_plugin,
+        param_id: clap_id,
+        automation_state: u32,
+        color: *const clap_color,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let automation = match indication_automation_state(automation_state) {
+            Some(automation) => automation,
+            None => {
+                nih_debug_assert_failure!("Unknown automation state '{}'", automation_state);
+                return;
+            }
+        };
+        let automation_color = if automation.is_automated() {
+            indication_color(color)
+        } else {
+            None
+        };
+        wrapper.update_param_indication(param_id, |indication| {
+            indication.automation = automation;
+            indication.automation_color = automation_color;
+        });
+    }
+
+    unsafe extern "C" fn ext_remote_controls_count(plugin: *const clap_plugin) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.remote_controls_pages.len() as u32
+    }
+
+    unsafe extern "C" fn ext_remote_controls_get(
+        plugin: *const clap_plugin,
+        page_index: u32,
+        page: *mut clap_remote_controls_page,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, page);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        match wrapper.remote_controls_pages.get(page_index as usize) {
+            Some(remote_controls_page) => {
+                *page = std::mem::zeroed();
+
+                let page = &mut *page;
+                strlcpy(&mut page.section_name, &remote_controls_page.section);
+                page.page_id = page_index;
+                strlcpy(&mut page.page_name, &remote_controls_page.name);
+                for (param_id, param_hash) in page
+                    .param_ids
+                    .iter_mut()
+                    .zip(remote_controls_page.param_hashes)
+                {
+                    *param_id = param_hash.unwrap_or(CLAP_INVALID_ID);
+                }
+                page.is_for_preset = false;
+
+                true
+            }
+            None => false,
+        }
+    }
+
     unsafe extern "C" fn ext_render_has_hard_realtime_requirement(
         _plugin: *const clap_plugin,
     ) -> bool {
@@ -3212,22 +4532,74 @@ impl<P: ClapPlugin> Wrapper<P> {
         check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
         let wrapper = &*((*plugin).plugin_data as *const Self);
 
+        wrapper.save_state(&*stream, StateContext::Project)
+    }
+
+    unsafe extern "C" fn ext_state_load(
+        plugin: *const clap_plugin,
+        stream: *const clap_istream,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.load_state(&*stream, StateContext::Project)
+    }
+
+    unsafe extern "C" fn ext_state_context_save(
+        plugin: *const clap_plugin,
+        stream: *const clap_ostream,
+        context_type: u32,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        match state_context(context_type) {
+            Some(context) => wrapper.save_state(&*stream, context),
+            None => {
+                nih_debug_assert_failure!("Unknown state context type '{}'", context_type);
+                false
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_state_context_load(
+        plugin: *const clap_plugin,
+        stream: *const clap_istream,
+        context_type: u32,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        match state_context(context_type) {
+            Some(context) => wrapper.load_state(&*stream, context),
+            None => {
+                nih_debug_assert_failure!("Unknown state context type '{}'", context_type);
+                false
+            }
+        }
+    }
+
+    /// Serialize the plugin's state for `context` and write it to `stream`. Used for both the state
+    /// and the state context extensions.
+    unsafe fn save_state(&self, stream: &clap_ostream, context: StateContext) -> bool {
         let serialized = state::serialize_json::<P>(
-            wrapper.params.clone(),
-            state::make_params_iter(&wrapper.param_by_hash, &wrapper.param_id_to_hash),
+            self.params.clone(),
+            state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+            &self.midi_learn,
+            context,
         );
         match serialized {
             Ok(serialized) => {
                 // CLAP does not provide a way to tell how much data there is left in a stream, so
                 // we need to prepend it to our actual state data.
                 let length_bytes = (serialized.len() as u64).to_le_bytes();
-                if !write_stream(&*stream, &length_bytes) {
+                if !write_stream(stream, &length_bytes) {
                     nih_debug_assert_failure!(
                         "Error or end of stream while writing the state length to the stream."
                     );
                     return false;
                 }
-                if !write_stream(&*stream, &serialized) {
+                if !write_stream(stream, &serialized) {
                     nih_debug_assert_failure!(
                         "Error or end of stream while writing the state buffer to the stream."
                     );
@@ -3245,17 +4617,13 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
-    unsafe extern "C" fn ext_state_load(
-        plugin: *const clap_plugin,
-        stream: *const clap_istream,
-    ) -> bool {
-        check_null_ptr!(false, plugin, (*plugin).plugin_data, stream);
-        let wrapper = &*((*plugin).plugin_data as *const Self);
-
+    /// Read the plugin's state from `stream` and load it for `context`. Used for both the state and
+    /// the state context extensions.
+    unsafe fn load_state(&self, stream: &clap_istream, context: StateContext) -> bool {
         // CLAP does not have a way to tell how much data there is left in a stream, so we've
         // prepended the size in front of our JSON state
         let mut length_bytes = [0u8; 8];
-        if !read_stream(&*stream, length_bytes.as_mut_slice()) {
+        if !read_stream(stream, length_bytes.as_mut_slice()) {
             nih_debug_assert_failure!(
                 "Error or end of stream while reading the state length from the stream."
             );
@@ -3264,7 +4632,7 @@ impl<P: ClapPlugin> Wrapper<P> {
         let length = u64::from_le_bytes(length_bytes);
 
         let mut read_buffer: Vec<u8> = Vec::with_capacity(length as usize);
-        if !read_stream(&*stream, read_buffer.spare_capacity_mut()) {
+        if !read_stream(stream, read_buffer.spare_capacity_mut()) {
             nih_debug_assert_failure!(
                 "Error or end of stream while reading the state buffer from the stream."
             );
@@ -3274,7 +4642,7 @@ impl<P: ClapPlugin> Wrapper<P> {
 
         match state::deserialize_json(&read_buffer) {
             Some(mut state) => {
-                let success = wrapper.set_state_inner(&mut state);
+                let success = self.set_state_inner(&mut state, context);
                 if success {
                     nih_trace!("Loaded state ({} bytes)", read_buffer.len());
                 }
@@ -3285,6 +4653,65 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_surround_is_channel_mask_supported(
+        plugin: *const clap_plugin,
+        channel_mask: u64,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+
+        // The host can use this to pick one of the plugin's audio port configurations, so this
+        // needs to consider the ports from all layouts and not just the current one
+        P::AUDIO_IO_LAYOUTS.iter().any(|audio_io_layout| {
+            let aux_input_layouts = (0..audio_io_layout.aux_input_ports.len())
+                .map(|idx| audio_io_layout.aux_input_layout(idx));
+            let aux_output_layouts = (0..audio_io_layout.aux_output_ports.len())
+                .map(|idx| audio_io_layout.aux_output_layout(idx));
+
+            [
+                audio_io_layout.main_input_layout(),
+                audio_io_layout.main_output_layout(),
+            ]
+            .into_iter()
+            .chain(aux_input_layouts)
+            .chain(aux_output_layouts)
+            .flatten()
+            .any(|layout| surround::channel_mask(layout) == Some(channel_mask))
+        })
+    }
+
+    unsafe extern "C" fn ext_surround_get_channel_map(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        port_index: u32,
+        channel_map: *mut u8,
+        channel_map_capacity: u32,
+    ) -> u32 {
+        check_null_ptr!(0, plugin, (*plugin).plugin_data, channel_map);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        let current_audio_io_layout = wrapper.current_audio_io_layout.load();
+        match port_channel_layout(&current_audio_io_layout, is_input, port_index)
+            .and_then(surround::channel_map)
+        {
+            Some(port_channel_map) => {
+                let num_channels = port_channel_map.len().min(channel_map_capacity as usize);
+                std::slice::from_raw_parts_mut(channel_map, num_channels)
+                    .copy_from_slice(&port_channel_map[..num_channels]);
+
+                num_channels as u32
+            }
+            None => {
+                nih_debug_assert_failure!(
+                    "Host queried the channel map for non-surround audio port {} (input: {})",
+                    port_index,
+                    is_input
+                );
+
+                0
+            }
+        }
+    }
+
     unsafe extern "C" fn ext_tail_get(plugin: *const clap_plugin) -> u32 {
         check_null_ptr!(0, plugin, (*plugin).plugin_data);
         let wrapper = &*((*plugin).plugin_data as *const Self);
@@ -3296,6 +4723,110 @@ impl<P: ClapPlugin> Wrapper<P> {
         }
     }
 
+    unsafe extern "C" fn ext_thread_pool_exec(plugin: *const clap_plugin, task_index: u32) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        match *wrapper.thread_pool_task.borrow() {
+            Some(task) => task(task_index as usize),
+            None => nih_debug_assert_failure!(
+                "The host called 'clap_plugin_thread_pool::exec()' outside of \
+                 'clap_host_thread_pool::request_exec()'"
+            ),
+        }
+    }
+
+    unsafe extern "C" fn ext_timer_support_on_timer(plugin: *const clap_plugin, timer_id: clap_id) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        // This is always called from the main thread, so the callback runs immediately
+        let task_posted = wrapper.schedule_gui(Task::GuiTimer(timer_id));
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    unsafe extern "C" fn ext_track_info_changed(plugin: *const clap_plugin) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.update_track_info();
+
+        // This is always called from the main thread, so the editor can be notified immediately
+        if wrapper.editor_handle.lock().is_some() {
+            if let Some(editor) = wrapper.editor.borrow().as_ref() {
+                editor.lock().track_info_changed();
+            }
+        }
+    }
+
+    unsafe extern "C" fn ext_undo_context_set_can_undo(plugin: *const clap_plugin, can_undo: bool) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.host_can_undo.store(can_undo, Ordering::Relaxed);
+    }
+
+    unsafe extern "C" fn ext_undo_context_set_can_redo(plugin: *const clap_plugin, can_redo: bool) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.host_can_redo.store(can_redo, Ordering::Relaxed);
+    }
+
+    unsafe extern "C" fn ext_undo_context_set_name(
+        _plugin: *const clap_plugin,
+        _name: *const c_char,
+    ) {
+        // The names of the host's undo and redo steps are not exposed to the plugin
+    }
+
+    unsafe extern "C" fn ext_undo_delta_get_delta_properties(
+        plugin: *const clap_plugin,
+        properties: *mut clap_undo_delta_properties,
+    ) {
+        check_null_ptr!((), plugin, (*plugin).plugin_data, properties);
+
+        // The deltas are transaction IDs, so they are only valid for this plugin instance
+        *properties = clap_undo_delta_properties {
+            has_delta: true,
+            are_deltas_persistent: false,
+            format_version: undo::DELTA_FORMAT_VERSION,
+        };
+    }
+
+    unsafe extern "C" fn ext_undo_delta_can_use_delta_format_version(
+        plugin: *const clap_plugin,
+        format_version: clap_id,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data);
+
+        format_version == undo::DELTA_FORMAT_VERSION
+    }
+
+    unsafe extern "C" fn ext_undo_delta_undo(
+        plugin: *const clap_plugin,
+        format_version: clap_id,
+        delta: *const c_void,
+        delta_size: usize,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, delta);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.apply_undo_delta(format_version, delta, delta_size, false)
+    }
+
+    unsafe extern "C" fn ext_undo_delta_redo(
+        plugin: *const clap_plugin,
+        format_version: clap_id,
+        delta: *const c_void,
+        delta_size: usize,
+    ) -> bool {
+        check_null_ptr!(false, plugin, (*plugin).plugin_data, delta);
+        let wrapper = &*((*plugin).plugin_data as *const Self);
+
+        wrapper.apply_undo_delta(format_version, delta, delta_size, true)
+    }
+
     unsafe extern "C" fn ext_voice_info_get(
         plugin: *const clap_plugin,
         info: *mut clap_voice_info,
@@ -3339,3 +4870,63 @@ unsafe fn query_host_extension<T>(
         None
     }
 }
+
+/// The channel layout for an audio port. `port_index` is the port's CLAP port index, so the
+/// auxiliary ports are offset by one if the plugin has a main port. Returns `None` if the port
+/// doesn't exist or if it doesn't have a known channel layout.
+fn port_channel_layout(
+    audio_io_layout: &AudioIOLayout,
+    is_input: bool,
+    port_index: u32,
+) -> Option<ChannelLayout> {
+    let has_main_input = audio_io_layout.main_input_channels.is_some();
+    let has_main_output = audio_io_layout.main_output_channels.is_some();
+    match (port_index as usize, is_input) {
+        (0, true) if has_main_input => audio_io_layout.main_input_layout(),
+        (0, false) if has_main_output => audio_io_layout.main_output_layout(),
+        (n, true) if has_main_input => audio_io_layout.aux_input_layout(n - 1),
+        (n, false) if has_main_output => audio_io_layout.aux_output_layout(n - 1),
+        (n, true) => audio_io_layout.aux_input_layout(n),
+        (n, false) => audio_io_layout.aux_output_layout(n),
+    }
+}
+
+/// The CLAP port type for a port with the given channel layout. Ports without a known channel
+/// layout don't have a port type.
+fn port_type(layout: Option<ChannelLayout>) -> *const c_char {
+    match layout {
+        Some(ChannelLayout::Mono) => CLAP_PORT_MONO.as_ptr(),
+        Some(ChannelLayout::Stereo) => CLAP_PORT_STEREO.as_ptr(),
+        Some(ChannelLayout::Surround51 | ChannelLayout::Surround714) => CLAP_PORT_SURROUND.as_ptr(),
+        Some(
+            ChannelLayout::AmbisonicFirstOrder
+            | ChannelLayout::AmbisonicSecondOrder
+            | ChannelLayout::AmbisonicThirdOrder,
+        ) => CLAP_PORT_AMBISONIC.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Read the host's information from the `clap_host` struct. Null pointers and empty strings are
+/// treated as missing values.
+///
+/// # Safety
+///
+/// All of the non-null string pointers in `host` must point to null terminated strings.
+unsafe fn host_info(host: &clap_host) -> HostInfo {
+    let read_string = |string: *const c_char| {
+        if string.is_null() {
+            return None;
+        }
+
+        Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+            .filter(|string| !string.is_empty())
+    };
+
+    HostInfo {
+        name: read_string(host.name),
+        vendor: read_string(host.vendor),
+        version: read_string(host.version),
+        url: read_string(host.url),
+    }
+}
@@ -732,6 +732,10 @@ impl CpalMidir {
             aux_output_buffers.push(aux_buffer);
         }
 
+        // The standalone backends don't have a way to deactivate ports, so they're always active
+        let aux_inputs_active = vec![true; self.audio_io_layout.aux_input_ports.len()];
+        let aux_outputs_active = vec![true; self.audio_io_layout.aux_output_ports.len()];
+
         let mut midi_input_events = Vec::with_capacity(MIDI_EVENT_QUEUE_CAPACITY);
         let mut midi_output_events = Vec::with_capacity(MIDI_EVENT_QUEUE_CAPACITY);
 
@@ -844,6 +848,8 @@ impl CpalMidir {
                 AuxiliaryBuffers {
                     inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer]),
                     outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer]),
+                    inputs_active: &aux_inputs_active,
+                    outputs_active: &aux_outputs_active,
                 }
             };
 
@@ -99,6 +99,10 @@ impl<P: Plugin> Backend<P> for Dummy {
             aux_output_buffers.push(aux_buffer);
         }
 
+        // The standalone backends don't have a way to deactivate ports, so they're always active
+        let aux_inputs_active = vec![true; self.audio_io_layout.aux_input_ports.len()];
+        let aux_outputs_active = vec![true; self.audio_io_layout.aux_output_ports.len()];
+
         // This queue will never actually be used
         let mut midi_output_events = Vec::with_capacity(1024);
         let mut num_processed_samples = 0;
@@ -133,6 +137,8 @@ impl<P: Plugin> Backend<P> for Dummy {
                 AuxiliaryBuffers {
                     inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer]),
                     outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer]),
+                    inputs_active: &aux_inputs_active,
+                    outputs_active: &aux_outputs_active,
                 }
             };
 
@@ -91,6 +91,10 @@ impl<P: Plugin> Backend<P> for Jack {
             aux_output_buffers.push(aux_buffer);
         }
 
+        // The standalone backends don't have a way to deactivate ports, so they're always active
+        let aux_inputs_active = vec![true; self.audio_io_layout.aux_input_ports.len()];
+        let aux_outputs_active = vec![true; self.audio_io_layout.aux_output_ports.len()];
+
         let mut input_events: Vec<PluginNoteEvent<P>> = Vec::with_capacity(2048);
         let mut output_events: Vec<PluginNoteEvent<P>> = Vec::with_capacity(2048);
 
@@ -223,6 +227,8 @@ impl<P: Plugin> Backend<P> for Jack {
                 AuxiliaryBuffers {
                     inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer]),
                     outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer]),
+                    inputs_active: &aux_inputs_active,
+                    outputs_active: &aux_outputs_active,
                 }
             };
 
@@ -1,5 +1,6 @@
 use clap::{Parser, ValueEnum};
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 
 use crate::audio_setup::AudioIOLayout;
 use crate::plugin::Plugin;
@@ -85,6 +86,12 @@ pub struct WrapperConfig {
     #[clap(value_parser, long, default_value = "1.0")]
     pub dpi_scale: f32,
 
+    /// A preset file to load when the plugin starts.
+    ///
+    /// Parameter values from the preset that could not be loaded are listed as warnings.
+    #[clap(value_parser, long)]
+    pub preset: Option<PathBuf>,
+
     /// The transport's tempo.
     #[clap(value_parser, long, default_value = "120")]
     pub tempo: f32,
@@ -1,15 +1,25 @@
+use std::os::raw::c_int;
 use std::sync::Arc;
 
 use super::backend::Backend;
 use super::wrapper::{Task, Wrapper};
-use crate::context::gui::GuiContext;
+use crate::context::gui::{FdFlags, GuiContext, TimerId};
 use crate::context::init::InitContext;
 use crate::context::process::{ProcessContext, Transport};
-use crate::context::PluginApi;
-use crate::midi::PluginNoteEvent;
+use crate::context::{HostInfo, PluginApi, TrackInfo};
+use crate::midi::learn::MidiMapping;
+use crate::midi::{MidiConfig, PluginNoteEvent};
 use crate::params::internals::ParamPtr;
 use crate::plugin::Plugin;
 
+/// The standalone doesn't run in a host, so there's no information to provide.
+static HOST_INFO: HostInfo = HostInfo {
+    name: None,
+    vendor: None,
+    version: None,
+    url: None,
+};
+
 /// An [`InitContext`] implementation for the standalone wrapper.
 pub(crate) struct WrapperInitContext<'a, P: Plugin, B: Backend<P>> {
     pub(super) wrapper: &'a Wrapper<P, B>,
@@ -19,7 +29,6 @@ pub(crate) struct WrapperInitContext<'a, P: Plugin, B: Backend<P>> {
 /// can hold on to lock guards for event queues. Otherwise reading these events would require
 /// constant unnecessary atomic operations to lock the uncontested RwLocks.
 pub(crate) struct WrapperProcessContext<'a, P: Plugin, B: Backend<P>> {
-    #[allow(dead_code)]
     pub(super) wrapper: &'a Wrapper<P, B>,
     pub(super) input_events: &'a [PluginNoteEvent<P>],
     // The current index in `input_events`, since we're not actually popping anything from a queue
@@ -55,6 +64,19 @@ impl<P: Plugin, B: Backend<P>> InitContext<P> for WrapperInitContext<'_, P, B> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn note_names_changed(&self) {
+        // There's no host to notify
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // The standalone isn't inserted on a track
+        None
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &HOST_INFO
+    }
 }
 
 impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P, B> {
@@ -100,6 +122,24 @@ impl<P: Plugin, B: Backend<P>> ProcessContext<P> for WrapperProcessContext<'_, P
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn note_names_changed(&self) {
+        // There's no host to notify
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &HOST_INFO
+    }
+
+    // There's no host to record automation for, so only the value changes are relevant. These are
+    // applied at the end of the process call, just like the changes made from the GUI.
+    unsafe fn raw_begin_set_parameter(&mut self, _param: ParamPtr) {}
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        self.wrapper.set_parameter(param, normalized);
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, _param: ParamPtr) {}
 }
 
 impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
@@ -112,11 +152,13 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         true
     }
 
-    unsafe fn raw_begin_set_parameter(&self, _param: ParamPtr) {
-        // Since there's no automation being recorded here, gestures don't mean anything
+    unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
+        // Since there's no automation being recorded here, gestures are only used for the undo
+        // history
+        self.wrapper.undo_manager.begin_gesture(param);
 
         #[cfg(debug_assertions)]
-        match self.wrapper.param_id_from_ptr(_param) {
+        match self.wrapper.param_id_from_ptr(param) {
             Some(param_id) => self
                 .param_gesture_checker
                 .borrow_mut()
@@ -129,6 +171,7 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
 
     unsafe fn raw_set_parameter_normalized(&self, param: ParamPtr, normalized: f32) {
         self.wrapper.set_parameter(param, normalized);
+        self.wrapper.undo_manager.record_value(param, normalized);
 
         #[cfg(debug_assertions)]
         match self.wrapper.param_id_from_ptr(param) {
@@ -142,9 +185,11 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         }
     }
 
-    unsafe fn raw_end_set_parameter(&self, _param: ParamPtr) {
+    unsafe fn raw_end_set_parameter(&self, param: ParamPtr) {
+        self.wrapper.undo_manager.end_gesture(param);
+
         #[cfg(debug_assertions)]
-        match self.wrapper.param_id_from_ptr(_param) {
+        match self.wrapper.param_id_from_ptr(param) {
             Some(param_id) => self
                 .param_gesture_checker
                 .borrow_mut()
@@ -155,11 +200,112 @@ impl<P: Plugin, B: Backend<P>> GuiContext for WrapperGuiContext<P, B> {
         }
     }
 
+    unsafe fn raw_start_midi_learn(&self, param: ParamPtr) {
+        nih_debug_assert!(
+            P::MIDI_INPUT >= MidiConfig::MidiCCs,
+            "MIDI learn requires the plugin to receive MIDI CCs"
+        );
+
+        match self.wrapper.param_id_from_ptr(param) {
+            Some(param_id) => self
+                .wrapper
+                .midi_learn
+                .start_learning(param, param_id.to_owned()),
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.wrapper.midi_learn.cancel_learning();
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.wrapper.midi_learn.learn_target()
+    }
+
+    unsafe fn raw_midi_mapping(&self, param: ParamPtr) -> Option<MidiMapping> {
+        self.wrapper.midi_learn.mapping(param)
+    }
+
+    unsafe fn raw_set_midi_mapping(&self, param: ParamPtr, mapping: Option<MidiMapping>) {
+        match self.wrapper.param_id_from_ptr(param) {
+            Some(param_id) => {
+                self.wrapper
+                    .midi_learn
+                    .set_mapping(param, param_id.to_owned(), mapping)
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
     fn get_state(&self) -> crate::wrapper::state::PluginState {
         self.wrapper.get_state_object()
     }
 
     fn set_state(&self, state: crate::wrapper::state::PluginState) {
+        self.wrapper
+            .undo_manager
+            .record_state_change(self.wrapper.get_state_object(), state.clone());
         self.wrapper.set_state_object_from_gui(state)
     }
+
+    fn undo(&self) {
+        if let Some(action) = self.wrapper.undo_manager.undo() {
+            self.wrapper.undo_manager.apply(self, action);
+        }
+    }
+
+    fn redo(&self) {
+        if let Some(action) = self.wrapper.undo_manager.redo() {
+            self.wrapper.undo_manager.apply(self, action);
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        self.wrapper.undo_manager.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.wrapper.undo_manager.can_redo()
+    }
+
+    fn note_names_changed(&self) {
+        // There's no host to notify
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        // The standalone isn't inserted on a track
+        None
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &HOST_INFO
+    }
+
+    fn register_timer(
+        &self,
+        _period_ms: u32,
+        _callback: Box<dyn FnMut() + Send>,
+    ) -> Option<TimerId> {
+        // There's no host event loop to register timers with
+        None
+    }
+
+    fn unregister_timer(&self, _timer_id: TimerId) {
+        nih_debug_assert_failure!("The standalone wrapper doesn't support timers");
+    }
+
+    fn register_fd(
+        &self,
+        _fd: c_int,
+        _flags: FdFlags,
+        _callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        // Or to watch file descriptors with
+        false
+    }
+
+    fn unregister_fd(&self, _fd: c_int) {
+        nih_debug_assert_failure!("The standalone wrapper doesn't support file descriptor watches");
+    }
 }
@@ -23,8 +23,12 @@ use crate::params::internals::ParamPtr;
 use crate::params::{ParamFlags, Params};
 use crate::plugin::{Plugin, ProcessStatus, TaskExecutor};
 use crate::util::permit_alloc;
-use crate::wrapper::state::{self, PluginState};
+use crate::wrapper::state::preset::PresetFile;
+use crate::wrapper::state::{self, PluginState, StateContext};
+use crate::wrapper::util::midi_learn::MidiLearn;
+use crate::wrapper::util::mpe::MpeTranslator;
 use crate::wrapper::util::process_wrapper;
+use crate::wrapper::util::undo::UndoManager;
 
 /// How many parameter changes we can store in our unprocessed parameter change queue. Storing more
 /// than this many parameters at a time will cause changes to get lost.
@@ -75,6 +79,11 @@ pub struct Wrapper<P: Plugin, B: Backend<P>> {
     /// This queue will be flushed at the end of every processing cycle, just like in the plugin
     /// versions.
     unprocessed_param_changes: ArrayQueue<(ParamPtr, f32)>,
+    /// The plugin's MIDI mappings. Mapped MIDI messages are turned into parameter changes before
+    /// the events are passed to the plugin.
+    pub midi_learn: MidiLearn,
+    /// The undo history for parameter gestures and state changes made through the `GuiContext`.
+    pub undo_manager: UndoManager,
     /// The plugin is able to restore state through a method on the `GuiContext`. To avoid changing
     /// parameters mid-processing and running into garbled data if the host also tries to load state
     /// at the same time the restoring happens at the end of each processing call. If this zero
@@ -253,6 +262,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
             config,
 
             unprocessed_param_changes: ArrayQueue::new(EVENT_QUEUE_CAPACITY),
+            midi_learn: MidiLearn::default(),
+            undo_manager: UndoManager::default(),
             updated_state_sender,
             updated_state_receiver,
             current_latency: AtomicU32::new(0),
@@ -285,6 +296,32 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
             })
             .map(|editor| Arc::new(Mutex::new(editor)));
 
+        // A preset passed on the command line is loaded before the plugin is initialized, so the
+        // smoothers below also pick up the preset's values
+        if let Some(preset_path) = &wrapper.config.preset {
+            match PresetFile::load(preset_path) {
+                Ok(preset) => {
+                    let (mut state, issues) = preset.validate::<P>(wrapper.params.as_ref());
+                    for issue in issues {
+                        nih_warn!("{}: {}", preset_path.display(), issue);
+                    }
+
+                    let success = unsafe {
+                        state::deserialize_object::<P>(
+                            &mut state,
+                            wrapper.params.clone(),
+                            |param_id| wrapper.param_id_to_ptr.get(param_id).copied(),
+                            &wrapper.midi_learn,
+                            None,
+                            StateContext::Preset,
+                        )
+                    };
+                    nih_debug_assert!(success, "Could not load the preset's state");
+                }
+                Err(err) => nih_error!("Could not load '{}': {}", preset_path.display(), err),
+            }
+        }
+
         // Before initializing the plugin, make sure all smoothers are set the the default values
         for param in wrapper.param_id_to_ptr.values() {
             unsafe { param.update_smoother(wrapper.buffer_config.sample_rate, true) };
@@ -385,8 +422,7 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
     }
 
     /// Get a parameter's ID based on a `ParamPtr`. Used in the `GuiContext` implementation for the
-    /// gesture checks.
-    #[allow(unused)]
+    /// gesture checks and for MIDI learn.
     pub fn param_id_from_ptr(&self, param: ParamPtr) -> Option<&str> {
         self.param_ptr_to_id.get(&param).map(|s| s.as_str())
     }
@@ -421,6 +457,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                 self.param_id_to_ptr
                     .iter()
                     .map(|(param_id, param_ptr)| (param_id, *param_ptr)),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
@@ -497,6 +535,12 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
         should_terminate: Arc<AtomicBool>,
         gui_task_sender: channel::Sender<GuiTask>,
     ) {
+        // The backends only parse the raw MIDI messages. If the plugin uses MPE, then the
+        // per-channel expressions are translated to per-note expressions here so all backends
+        // behave the same.
+        let mut mpe_translator = MpeTranslator::new(P::MIDI_INPUT);
+        let mut translated_input_events = Vec::with_capacity(1024);
+
         self.clone().backend.borrow_mut().run(
             move |buffer, aux, transport, input_events, output_events| {
                 // TODO: This process wrapper should actually be in the backends (since the backends
@@ -508,6 +552,50 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                     }
 
                     let sample_rate = self.buffer_config.sample_rate;
+                    let input_events = if mpe_translator.is_enabled() || self.midi_learn.is_active()
+                    {
+                        translated_input_events.clear();
+                        for event in input_events {
+                            mpe_translator.translate(event.clone(), |event| {
+                                // The backends don't limit the number of events per buffer, so
+                                // in the rare case that this exceeds the preallocated capacity
+                                // we'll need to allocate
+                                if translated_input_events.len()
+                                    == translated_input_events.capacity()
+                                {
+                                    permit_alloc(|| translated_input_events.push(event))
+                                } else {
+                                    translated_input_events.push(event)
+                                }
+                            });
+                        }
+
+                        // MIDI messages that have been mapped to parameters are turned into
+                        // parameter changes before the plugin gets to see them
+                        translated_input_events.retain(|event| {
+                            !self
+                                .midi_learn
+                                .handle_event(event, |param_ptr, normalized_value| {
+                                    if unsafe { param_ptr.set_normalized_value(normalized_value) } {
+                                        unsafe { param_ptr.update_smoother(sample_rate, false) };
+                                        let task_posted =
+                                            self.schedule_gui(Task::ParameterValueChanged(
+                                                param_ptr,
+                                                normalized_value,
+                                            ));
+                                        nih_debug_assert!(
+                                            task_posted,
+                                            "The task queue is full, dropping task..."
+                                        );
+                                    }
+                                })
+                        });
+
+                        translated_input_events.as_slice()
+                    } else {
+                        input_events
+                    };
+
                     {
                         let mut plugin = self.plugin.lock();
                         if let ProcessStatus::Error(err) = plugin.process(
@@ -606,7 +694,8 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
     /// wrappers state is set from a couple places, so this function is here to be consistent and to
     /// centralize all of this behavior. Includes `permit_alloc()`s around the deserialization and
     /// initialization for the use case where `set_state_object_from_gui()` was called while the
-    /// plugin is process audio.
+    /// plugin is process audio. The standalone only loads states sent by the plugin's GUI, so these
+    /// are always loaded using [`StateContext::Preset`].
     ///
     /// Implicitly emits `Task::ParameterValuesChanged`.
     ///
@@ -625,7 +714,9 @@ impl<P: Plugin, B: Backend<P>> Wrapper<P, B> {
                 state,
                 self.params.clone(),
                 |param_id| self.param_id_to_ptr.get(param_id).copied(),
+                &self.midi_learn,
                 Some(&self.buffer_config),
+                StateContext::Preset,
             )
         });
         if !success {
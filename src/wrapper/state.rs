@@ -6,16 +6,21 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+use super::util::midi_learn::MidiLearn;
 use crate::audio_setup::BufferConfig;
+use crate::midi::learn::MidiMapping;
 use crate::params::internals::ParamPtr;
-use crate::params::{Param, ParamMut, Params};
+use crate::params::{Param, ParamFlags, ParamMut, Params};
 use crate::plugin::Plugin;
 
+pub mod migration;
+pub mod preset;
+
 // These state objects are also exposed directly to the plugin so it can do its own internal preset
 // management
 
 /// A plain, unnormalized value for a parameter.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ParamValue {
     F32(f32),
@@ -31,9 +36,9 @@ pub enum ParamValue {
 /// The fields are stored as `BTreeMap`s so the order in the serialized file is consistent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginState {
-    /// The plugin version this state was saved with. Right now this is not used, but later versions
-    /// of NIH-plug may allow you to modify the plugin state object directly before it is loaded to
-    /// allow migrating plugin states between breaking parameter changes.
+    /// The plugin version this state was saved with. This is used to decide which of the plugin's
+    /// [`state_migrations()`][crate::prelude::Plugin::state_migrations()] need to be applied
+    /// before the state is loaded.
     ///
     /// # Notes
     ///
@@ -52,6 +57,60 @@ pub struct PluginState {
     /// The individual fields are also serialized as JSON so they can safely be restored
     /// independently of the other fields.
     pub fields: BTreeMap<String, String>,
+    /// The MIDI mappings created through MIDI learn, indexed by parameter ID. These are not stored
+    /// in or restored from presets.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub midi_mappings: BTreeMap<String, MidiMapping>,
+}
+
+/// What a plugin's state is being saved or loaded for. This is passed to
+/// [`Plugin::filter_state()`][crate::prelude::Plugin::filter_state()]. Only CLAP hosts that
+/// support the state context extension distinguish between these. Everything else is treated as
+/// [`StateContext::Project`], except for states saved and loaded through the
+/// [`GuiContext`][crate::prelude::GuiContext] and for factory presets, which use
+/// [`StateContext::Preset`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StateContext {
+    /// The state is saved as part of the host's project, or it's loaded from one.
+    #[default]
+    Project,
+    /// The state is saved as a preset, or a preset is being loaded. Persistent fields marked with
+    /// `#[persist(key = "...", skip_presets)]` are not stored in or restored from presets.
+    Preset,
+    /// The state is used to duplicate the plugin instance, for instance when the user copies a
+    /// track in the host.
+    Duplicate,
+}
+
+/// A preset bundled with the plugin, returned from
+/// [`Plugin::factory_presets()`][crate::prelude::Plugin::factory_presets()]. The plugin wrappers
+/// expose these to the host so they can be browsed and loaded from the host's preset browser.
+#[derive(Debug, Clone)]
+pub struct FactoryPreset {
+    /// The preset's name as shown by the host.
+    pub name: String,
+    /// An optional category for the preset, like `Bass` or `Pads`. Hosts can use this to group or
+    /// filter presets.
+    pub category: Option<String>,
+    /// The state that gets loaded when the host selects this preset.
+    pub state: PluginState,
+}
+
+impl FactoryPreset {
+    /// Create a new factory preset without a category.
+    pub fn new(name: impl Into<String>, state: PluginState) -> Self {
+        Self {
+            name: name.into(),
+            category: None,
+            state,
+        }
+    }
+
+    /// Set the preset's category.
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
 }
 
 /// Create a parameters iterator from the hashtables stored in the plugin wrappers. This avoids
@@ -87,12 +146,16 @@ pub(crate) fn make_params_getter<'a>(
 pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
     plugin_params: Arc<dyn Params>,
     params_iter: impl IntoIterator<Item = (&'a String, ParamPtr)>,
+    midi_learn: &MidiLearn,
+    context: StateContext,
 ) -> PluginState {
-    // We'll serialize parameter values as a simple `string_param_id: display_value` map.
+    // We'll serialize parameter values as a simple `string_param_id: display_value` map. Output
+    // parameters are set by the plugin itself, so those are not part of the state.
     // NOTE: If the plugin is being modulated (and the plugin is a CLAP plugin in Bitwig Studio),
     //       then this should save the values without any modulation applied to it
     let params: BTreeMap<_, _> = params_iter
         .into_iter()
+        .filter(|(_, param_ptr)| !param_ptr.flags().contains(ParamFlags::OUTPUT))
         .map(|(param_id_str, param_ptr)| match param_ptr {
             ParamPtr::FloatParam(p) => (
                 param_id_str.clone(),
@@ -120,13 +183,28 @@ pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
         .collect();
 
     // The plugin can also persist arbitrary fields alongside its parameters. This is useful for
-    // storing things like sample data.
-    let fields = plugin_params.serialize_fields();
+    // storing things like sample data. Fields that should not be baked into presets are left out
+    // when saving a preset.
+    let mut fields = plugin_params.serialize_fields();
+    if context == StateContext::Preset {
+        for key in plugin_params.preset_excluded_fields() {
+            fields.remove(&key);
+        }
+    }
+
+    // MIDI mappings belong to the user's setup rather than to the sound, so presets don't include
+    // them
+    let midi_mappings = if context == StateContext::Preset {
+        BTreeMap::new()
+    } else {
+        midi_learn.mappings()
+    };
 
     PluginState {
         version: String::from(P::VERSION),
         params,
         fields,
+        midi_mappings,
     }
 }
 
@@ -136,8 +214,10 @@ pub(crate) unsafe fn serialize_object<'a, P: Plugin>(
 pub(crate) unsafe fn serialize_json<'a, P: Plugin>(
     plugin_params: Arc<dyn Params>,
     params_iter: impl IntoIterator<Item = (&'a String, ParamPtr)>,
+    midi_learn: &MidiLearn,
+    context: StateContext,
 ) -> Result<Vec<u8>> {
-    let plugin_state = serialize_object::<P>(plugin_params, params_iter);
+    let plugin_state = serialize_object::<P>(plugin_params, params_iter, midi_learn, context);
     let json = serde_json::to_vec(&plugin_state).context("Could not format as JSON")?;
 
     #[cfg(feature = "zstd")]
@@ -171,16 +251,19 @@ pub(crate) unsafe fn serialize_json<'a, P: Plugin>(
 /// Make sure to reinitialize plugin after deserializing the state so it can react to the new
 /// parameter values. The smoothers have already been reset by this function.
 ///
-/// The [`Plugin`] argument is used to call [`Plugin::filter_state()`] just before loading the
-/// state.
+/// The [`Plugin`] argument is used to apply [`Plugin::state_migrations()`] and to call
+/// [`Plugin::filter_state()`] just before loading the state.
 pub(crate) unsafe fn deserialize_object<P: Plugin>(
     state: &mut PluginState,
     plugin_params: Arc<dyn Params>,
     params_getter: impl Fn(&str) -> Option<ParamPtr>,
+    midi_learn: &MidiLearn,
     current_buffer_config: Option<&BufferConfig>,
+    context: StateContext,
 ) -> bool {
     // This lets the plugin perform migrations on old state if needed
-    P::filter_state(state);
+    P::state_migrations().apply(state);
+    P::filter_state(state, context);
 
     let sample_rate = current_buffer_config.map(|c| c.sample_rate);
     for (param_id_str, param_value) in &state.params {
@@ -191,6 +274,11 @@ pub(crate) unsafe fn deserialize_object<P: Plugin>(
                 continue;
             }
         };
+        // Output parameters are not restored, as they may still be present in state saved by older
+        // versions of the plugin
+        if param_ptr.flags().contains(ParamFlags::OUTPUT) {
+            continue;
+        }
 
         match (param_ptr, param_value) {
             (ParamPtr::FloatParam(p), ParamValue::F32(v)) => {
@@ -234,8 +322,29 @@ pub(crate) unsafe fn deserialize_object<P: Plugin>(
     }
 
     // The plugin can also persist arbitrary fields alongside its parameters. This is useful for
-    // storing things like sample data.
-    plugin_params.deserialize_fields(&state.fields);
+    // storing things like sample data. Presets saved before a field was excluded from presets may
+    // still contain that field, so those fields are also filtered out here.
+    if context == StateContext::Preset {
+        let preset_excluded_fields = plugin_params.preset_excluded_fields();
+        if preset_excluded_fields.is_empty() {
+            plugin_params.deserialize_fields(&state.fields);
+        } else {
+            let fields = state
+                .fields
+                .iter()
+                .filter(|(key, _)| !preset_excluded_fields.contains(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            plugin_params.deserialize_fields(&fields);
+        }
+    } else {
+        plugin_params.deserialize_fields(&state.fields);
+    }
+
+    // Loading a preset keeps the current MIDI mappings, see `serialize_object()`
+    if context != StateContext::Preset {
+        midi_learn.set_mappings(&state.midi_mappings, &params_getter);
+    }
 
     true
 }
@@ -0,0 +1,323 @@
+//! Declarative migrations for old plugin state. These take care of the common changes to a plugin's
+//! parameters and persistent fields, like renaming a parameter ID or turning a `BoolParam` into an
+//! `EnumParam`, so they don't need to be handled by hand in
+//! [`Plugin::filter_state()`][crate::prelude::Plugin::filter_state()]. Return them from
+//! [`Plugin::state_migrations()`][crate::prelude::Plugin::state_migrations()].
+//!
+//! ```ignore
+//! fn state_migrations() -> StateMigrations {
+//!     StateMigrations::new()
+//!         .with_migration(StateMigration::new("0.2.0").rename_param("gain", "output_gain"))
+//!         .with_migration(
+//!             StateMigration::new("0.3.0")
+//!                 .convert_param("mode", |value| match value {
+//!                     ParamValue::Bool(true) => ParamValue::String(String::from("fancy")),
+//!                     _ => ParamValue::String(String::from("plain")),
+//!                 })
+//!                 .rename_enum_ids("filter_type", &[("lp", "lowpass"), ("hp", "highpass")]),
+//!         )
+//! }
+//! ```
+
+use std::cmp::Ordering;
+
+use super::{ParamValue, PluginState};
+
+/// A set of [`StateMigration`]s. When a state is loaded, every migration for a version newer than
+/// the one the state was saved with is applied to the state in order of their versions.
+#[derive(Default)]
+pub struct StateMigrations {
+    migrations: Vec<StateMigration>,
+}
+
+/// The changes needed to load a state saved with a plugin version older than
+/// [`version`][Self::new()]. The steps are applied in the order they were added.
+pub struct StateMigration {
+    version: String,
+    steps: Vec<MigrationStep>,
+}
+
+enum MigrationStep {
+    RenameParam {
+        from: String,
+        to: String,
+    },
+    RemoveParam(String),
+    ConvertParam {
+        param_id: String,
+        convert: Box<dyn Fn(ParamValue) -> ParamValue + Send + Sync>,
+    },
+    RenameEnumIds {
+        param_id: String,
+        renames: Vec<(String, String)>,
+    },
+    RenameField {
+        from: String,
+        to: String,
+    },
+    TransformField {
+        key: String,
+        transform: Box<dyn Fn(serde_json::Value) -> serde_json::Value + Send + Sync>,
+    },
+}
+
+impl StateMigrations {
+    /// Create an empty set of migrations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a migration. The migrations don't need to be added in order.
+    pub fn with_migration(mut self, migration: StateMigration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// Whether there are any migrations.
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+
+    /// Apply all migrations for versions newer than `state.version` to the state. States without
+    /// a version are assumed to be older than every migration. The state's version is not changed.
+    pub fn apply(&self, state: &mut PluginState) {
+        let mut migrations: Vec<&StateMigration> = self
+            .migrations
+            .iter()
+            .filter(|migration| compare_versions(&state.version, &migration.version).is_lt())
+            .collect();
+        migrations.sort_by(|a, b| compare_versions(&a.version, &b.version));
+
+        for migration in migrations {
+            nih_trace!(
+                "Migrating state from version {:?} using the migration for version {}",
+                state.version,
+                migration.version
+            );
+            migration.apply(state);
+        }
+    }
+}
+
+impl StateMigration {
+    /// Create a migration for states saved with a plugin version older than `version`. This
+    /// should be the first version that contains the changes the migration corrects for. Versions
+    /// are compared as dot separated numbers, and pre-release versions like `1.0.0-beta` are
+    /// considered to be older than the release version.
+    pub fn new(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Rename a parameter. This is needed after changing a parameter's `#[id = "..."]` attribute.
+    /// Any MIDI mappings for the parameter are moved to the new ID as well.
+    pub fn rename_param(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.steps.push(MigrationStep::RenameParam {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// Remove a parameter's value from the state. Use this for parameters that no longer exist.
+    pub fn remove_param(mut self, param_id: impl Into<String>) -> Self {
+        self.steps.push(MigrationStep::RemoveParam(param_id.into()));
+        self
+    }
+
+    /// Convert a parameter's stored value, for instance when a `BoolParam` has been replaced by an
+    /// `EnumParam`, or when a parameter's unit has changed. The values are plain, unnormalized
+    /// values. Enum parameters are stored as [`ParamValue::String`] if their variants have an
+    /// `#[id = "..."]` attribute, and as [`ParamValue::I32`] variant indices otherwise.
+    pub fn convert_param(
+        mut self,
+        param_id: impl Into<String>,
+        convert: impl Fn(ParamValue) -> ParamValue + Send + Sync + 'static,
+    ) -> Self {
+        self.steps.push(MigrationStep::ConvertParam {
+            param_id: param_id.into(),
+            convert: Box::new(convert),
+        });
+        self
+    }
+
+    /// Rename the stable `#[id = "..."]` IDs of an enum parameter's variants. Each tuple contains
+    /// an old ID followed by its new ID. Values that don't match any of the old IDs are kept as
+    /// is.
+    pub fn rename_enum_ids(
+        mut self,
+        param_id: impl Into<String>,
+        renames: &[(&str, &str)],
+    ) -> Self {
+        self.steps.push(MigrationStep::RenameEnumIds {
+            param_id: param_id.into(),
+            renames: renames
+                .iter()
+                .map(|(from, to)| (String::from(*from), String::from(*to)))
+                .collect(),
+        });
+        self
+    }
+
+    /// Rename a persistent field. This is needed after changing a field's `#[persist = "..."]`
+    /// key.
+    pub fn rename_field(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.steps.push(MigrationStep::RenameField {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// Transform a persistent field's JSON value, for instance after changing the field's type.
+    /// Fields that are not present in the state or that don't contain valid JSON are left alone.
+    pub fn transform_field(
+        mut self,
+        key: impl Into<String>,
+        transform: impl Fn(serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    ) -> Self {
+        self.steps.push(MigrationStep::TransformField {
+            key: key.into(),
+            transform: Box::new(transform),
+        });
+        self
+    }
+
+    fn apply(&self, state: &mut PluginState) {
+        for step in &self.steps {
+            match step {
+                MigrationStep::RenameParam { from, to } => {
+                    if let Some(value) = state.params.remove(from) {
+                        state.params.insert(to.clone(), value);
+                    }
+                    if let Some(mapping) = state.midi_mappings.remove(from) {
+                        state.midi_mappings.insert(to.clone(), mapping);
+                    }
+                }
+                MigrationStep::RemoveParam(param_id) => {
+                    state.params.remove(param_id);
+                    state.midi_mappings.remove(param_id);
+                }
+                MigrationStep::ConvertParam { param_id, convert } => {
+                    if let Some(value) = state.params.remove(param_id) {
+                        state.params.insert(param_id.clone(), convert(value));
+                    }
+                }
+                MigrationStep::RenameEnumIds { param_id, renames } => {
+                    if let Some(ParamValue::String(id)) = state.params.get_mut(param_id) {
+                        if let Some((_, new_id)) = renames.iter().find(|(old_id, _)| old_id == id) {
+                            *id = new_id.clone();
+                        }
+                    }
+                }
+                MigrationStep::RenameField { from, to } => {
+                    if let Some(value) = state.fields.remove(from) {
+                        state.fields.insert(to.clone(), value);
+                    }
+                }
+                MigrationStep::TransformField { key, transform } => {
+                    let field = match state.fields.get_mut(key) {
+                        Some(field) => field,
+                        None => continue,
+                    };
+
+                    match serde_json::from_str(field) {
+                        Ok(value) => match serde_json::to_string(&transform(value)) {
+                            Ok(transformed) => *field = transformed,
+                            Err(err) => nih_debug_assert_failure!(
+                                "Could not serialize the migrated field '{}': {}",
+                                key,
+                                err
+                            ),
+                        },
+                        Err(err) => nih_debug_assert_failure!(
+                            "Could not parse the field '{}' for migration: {}",
+                            key,
+                            err
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compare two dot separated version numbers. Missing components are treated as zeroes, and a
+/// version with a pre-release or build suffix (`1.0.0-beta`) is older than the same version
+/// without one. Empty versions are older than everything else.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parse(version: &str) -> (Vec<u64>, bool) {
+        let (numbers, suffix) = match version.find(|c| c == '-' || c == '+') {
+            Some(idx) => (&version[..idx], true),
+            None => (version, false),
+        };
+        let numbers = numbers
+            .trim_start_matches('v')
+            .split('.')
+            .map(|number| number.trim().parse().unwrap_or(0))
+            .collect();
+
+        (numbers, !suffix)
+    }
+
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => (),
+    }
+
+    let (a_numbers, a_is_release) = parse(a);
+    let (b_numbers, b_is_release) = parse(b);
+    for idx in 0..a_numbers.len().max(b_numbers.len()) {
+        let a_number = a_numbers.get(idx).copied().unwrap_or(0);
+        let b_number = b_numbers.get(idx).copied().unwrap_or(0);
+        match a_number.cmp(&b_number) {
+            Ordering::Equal => (),
+            ordering => return ordering,
+        }
+    }
+
+    a_is_release.cmp(&b_is_release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_ordering() {
+        assert_eq!(compare_versions("0.2.0", "0.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("", "0.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("2.1.0", "2.0.5"), Ordering::Greater);
+    }
+
+    #[test]
+    fn field_transforms() {
+        let mut state = PluginState {
+            version: String::from("0.1.0"),
+            params: Default::default(),
+            fields: [(String::from("size"), String::from("[400,300]"))]
+                .into_iter()
+                .collect(),
+            midi_mappings: Default::default(),
+        };
+
+        StateMigrations::new()
+            .with_migration(StateMigration::new("0.2.0").transform_field(
+                "size",
+                |value| serde_json::json!({ "width": value[0], "height": value[1] }),
+            ))
+            .with_migration(StateMigration::new("0.3.0").rename_field("size", "editor-size"))
+            .apply(&mut state);
+
+        assert_eq!(
+            state.fields.get("editor-size").map(String::as_str),
+            Some(r#"{"height":300,"width":400}"#)
+        );
+    }
+}
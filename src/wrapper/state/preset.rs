@@ -0,0 +1,314 @@
+//! A human readable preset file format shared between all NIH-plug plugins. Presets are stored as
+//! pretty printed JSON files containing the preset's metadata and the plugin's parameter values:
+//!
+//! ```json
+//! {
+//!   "plugin_id": "com.moist-plugins-gmbh.gain",
+//!   "version": "0.1.0",
+//!   "name": "Quiet",
+//!   "author": "Jane Doe",
+//!   "tags": ["utility"],
+//!   "params": {
+//!     "gain": { "f32": -10.0 }
+//!   }
+//! }
+//! ```
+//!
+//! Editors can use [`save_preset()`] and [`load_preset()`] to save and load presets through their
+//! [`GuiContext`]. Loaded presets are validated against the plugin's parameters, and any values
+//! that could not be loaded are returned as [`PresetIssue`]s so they can be shown to the user.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use super::{ParamValue, PluginState};
+use crate::context::gui::GuiContext;
+use crate::params::internals::ParamPtr;
+use crate::params::{Param, ParamFlags, Params};
+use crate::plugin::Plugin;
+
+/// A preset file. See the module's documentation for an example.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetFile {
+    /// The plugin the preset was saved for. This is the plugin's [`Plugin::PRESET_ID`].
+    pub plugin_id: String,
+    /// The plugin version the preset was saved with. This is used to apply the plugin's
+    /// [`state_migrations()`][Plugin::state_migrations()] when the preset is loaded.
+    pub version: String,
+    /// The preset's name.
+    pub name: String,
+    /// The preset's author, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Free-form tags for categorizing the preset, like `bass` or `pad`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The plugin's parameter values, stored the same way as in [`PluginState`].
+    pub params: BTreeMap<String, ParamValue>,
+    /// The plugin's persistent fields, stored the same way as in [`PluginState`]. Fields that are
+    /// excluded from presets are not included.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Errors that may arise while reading or writing a preset file.
+#[derive(Debug)]
+pub enum PresetError {
+    /// The preset file could not be read or written.
+    Io(std::io::Error),
+    /// The preset file does not contain a valid preset.
+    InvalidPreset(serde_json::Error),
+}
+
+/// A problem found while validating a preset against the plugin's parameters. The affected values
+/// are not loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetIssue {
+    /// The preset was saved for a different plugin. This is only reported, the preset's values are
+    /// still validated and loaded.
+    WrongPlugin { plugin_id: String },
+    /// The preset contains a parameter the plugin doesn't have.
+    UnknownParameter { param_id: String },
+    /// The preset contains a value for an output parameter. These are set by the plugin itself.
+    OutputParameter { param_id: String },
+    /// The value's type does not match the parameter's type.
+    InvalidValue { param_id: String, value: ParamValue },
+    /// The value is outside of the parameter's range.
+    OutOfRange { param_id: String, value: ParamValue },
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetError::Io(err) => write!(f, "Could not access the preset file: {err}"),
+            PresetError::InvalidPreset(err) => write!(f, "Invalid preset file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<std::io::Error> for PresetError {
+    fn from(err: std::io::Error) -> Self {
+        PresetError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(err: serde_json::Error) -> Self {
+        PresetError::InvalidPreset(err)
+    }
+}
+
+impl Display for PresetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetIssue::WrongPlugin { plugin_id } => {
+                write!(
+                    f,
+                    "The preset was saved for a different plugin ({plugin_id})"
+                )
+            }
+            PresetIssue::UnknownParameter { param_id } => {
+                write!(f, "Unknown parameter '{param_id}'")
+            }
+            PresetIssue::OutputParameter { param_id } => {
+                write!(
+                    f,
+                    "'{param_id}' is an output parameter and cannot be loaded"
+                )
+            }
+            PresetIssue::InvalidValue { param_id, value } => {
+                write!(f, "Invalid value {value:?} for parameter '{param_id}'")
+            }
+            PresetIssue::OutOfRange { param_id, value } => {
+                write!(
+                    f,
+                    "Value {value:?} for parameter '{param_id}' is out of range"
+                )
+            }
+        }
+    }
+}
+
+impl PresetFile {
+    /// Create a preset for plugin `P` from a state object, usually obtained through
+    /// [`GuiContext::get_state()`].
+    pub fn new<P: Plugin>(name: impl Into<String>, state: PluginState) -> Self {
+        Self {
+            plugin_id: String::from(P::PRESET_ID),
+            version: state.version,
+            name: name.into(),
+            author: None,
+            tags: Vec::new(),
+            params: state.params,
+            fields: state.fields,
+        }
+    }
+
+    /// Set the preset's author.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Set the preset's tags.
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Parse a preset from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Format the preset as pretty printed JSON.
+    pub fn to_json(&self) -> Result<String, PresetError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Read a preset file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Write the preset to a file, overwriting the file if it already exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+
+    /// Check the preset against plugin `P`'s parameters. The plugin's
+    /// [`state_migrations()`][Plugin::state_migrations()] are applied first, so presets saved with
+    /// older versions of the plugin are checked using the current parameter IDs. Returns the
+    /// migrated state with all problematic values removed, along with a list of the problems that
+    /// were found. The state can then be loaded with [`GuiContext::set_state()`]. Its version is
+    /// set to the plugin's current version since the migrations have already been applied.
+    pub fn validate<P: Plugin>(&self, params: &dyn Params) -> (PluginState, Vec<PresetIssue>) {
+        let mut state = PluginState {
+            version: self.version.clone(),
+            params: self.params.clone(),
+            fields: self.fields.clone(),
+            midi_mappings: BTreeMap::new(),
+        };
+        P::state_migrations().apply(&mut state);
+        // The state is now up to date, so the migrations won't be applied a second time when the
+        // state is loaded
+        state.version = String::from(P::VERSION);
+
+        let mut issues = Vec::new();
+        if self.plugin_id != P::PRESET_ID {
+            issues.push(PresetIssue::WrongPlugin {
+                plugin_id: self.plugin_id.clone(),
+            });
+        }
+
+        let param_map: BTreeMap<String, ParamPtr> = params
+            .param_map()
+            .into_iter()
+            .map(|(param_id, param_ptr, _)| (param_id, param_ptr))
+            .collect();
+        state.params.retain(|param_id, value| {
+            let issue = match param_map.get(param_id) {
+                // SAFETY: The parameters are alive for as long as `params` is
+                Some(param_ptr) => unsafe { check_param_value(param_id, *param_ptr, value) },
+                None => Some(PresetIssue::UnknownParameter {
+                    param_id: param_id.clone(),
+                }),
+            };
+
+            match issue {
+                Some(issue) => {
+                    issues.push(issue);
+                    false
+                }
+                None => true,
+            }
+        });
+
+        (state, issues)
+    }
+}
+
+/// Check whether a value can be loaded into a parameter.
+///
+/// # Safety
+///
+/// `param_ptr` needs to point to a parameter that's still alive.
+unsafe fn check_param_value(
+    param_id: &str,
+    param_ptr: ParamPtr,
+    value: &ParamValue,
+) -> Option<PresetIssue> {
+    if param_ptr.flags().contains(ParamFlags::OUTPUT) {
+        return Some(PresetIssue::OutputParameter {
+            param_id: param_id.to_owned(),
+        });
+    }
+
+    let in_range = match (param_ptr, value) {
+        (ParamPtr::FloatParam(p), ParamValue::F32(v)) => {
+            let (min, max) = ((*p).preview_plain(0.0), (*p).preview_plain(1.0));
+            *v >= min.min(max) && *v <= min.max(max)
+        }
+        (ParamPtr::IntParam(p), ParamValue::I32(v)) => {
+            let (min, max) = ((*p).preview_plain(0.0), (*p).preview_plain(1.0));
+            *v >= min.min(max) && *v <= min.max(max)
+        }
+        (ParamPtr::BoolParam(_), ParamValue::Bool(_)) => true,
+        (ParamPtr::EnumParam(p), ParamValue::I32(v)) => *v >= 0 && (*v as usize) < (*p).len(),
+        (ParamPtr::EnumParam(p), ParamValue::String(id)) => (*p).has_id(id),
+        _ => {
+            return Some(PresetIssue::InvalidValue {
+                param_id: param_id.to_owned(),
+                value: value.clone(),
+            })
+        }
+    };
+
+    if in_range {
+        None
+    } else {
+        Some(PresetIssue::OutOfRange {
+            param_id: param_id.to_owned(),
+            value: value.clone(),
+        })
+    }
+}
+
+/// Save the plugin's current state as a preset file. The name, author, and tags are taken from
+/// `preset`. Its parameter values and fields are replaced by the plugin's current state.
+pub fn save_preset<P: Plugin>(
+    context: &dyn GuiContext,
+    preset: PresetFile,
+    path: impl AsRef<Path>,
+) -> Result<(), PresetError> {
+    let state = context.get_state();
+    let preset = PresetFile {
+        plugin_id: String::from(P::PRESET_ID),
+        version: state.version,
+        params: state.params,
+        fields: state.fields,
+        ..preset
+    };
+
+    preset.save(path)
+}
+
+/// Load a preset file, validate it using [`PresetFile::validate()`], and load the values that
+/// passed validation through the [`GuiContext`]. Returns the problems found during validation so
+/// they can be shown to the user.
+pub fn load_preset<P: Plugin>(
+    context: &dyn GuiContext,
+    params: &dyn Params,
+    path: impl AsRef<Path>,
+) -> Result<Vec<PresetIssue>, PresetError> {
+    let preset = PresetFile::load(path)?;
+    let (state, issues) = preset.validate::<P>(params);
+    context.set_state(state);
+
+    Ok(issues)
+}
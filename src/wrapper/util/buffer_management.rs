@@ -0,0 +1,239 @@
+//! Buffers shared between the plugin API wrappers for passing audio to the plugin. These are
+//! generic over the sample type so the same code can be used for both single and double precision
+//! processing.
+
+use std::num::NonZeroU32;
+
+use crate::audio_setup::{AudioIOLayout, AuxiliaryBuffers};
+use crate::buffer::Buffer;
+use crate::context::process::ProcessContext;
+use crate::plugin::{Plugin, ProcessStatus};
+
+/// A sample type the wrappers can process audio with. This is implemented for `f32` and `f64`, and
+/// it dispatches to the matching [`Plugin`] process function.
+pub(crate) trait ProcessSample: Copy + Default + PartialEq + Send + 'static {
+    /// Call either [`Plugin::process()`] or [`Plugin::process_f64()`] depending on the sample type.
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus;
+}
+
+impl ProcessSample for f32 {
+    #[inline]
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus {
+        plugin.process(buffer, aux, context)
+    }
+}
+
+impl ProcessSample for f64 {
+    #[inline]
+    fn process<P: Plugin>(
+        plugin: &mut P,
+        buffer: &mut Buffer<Self>,
+        aux: &mut AuxiliaryBuffers<Self>,
+        context: &mut impl ProcessContext<P>,
+    ) -> ProcessStatus {
+        plugin.process_f64(buffer, aux, context)
+    }
+}
+
+/// The buffers a wrapper needs to pass audio to the plugin for a single sample type. The wrappers
+/// store one of these for `f32` and, if the plugin supports it, one for `f64`.
+pub(crate) struct ProcessBuffers<T: 'static> {
+    /// Contains slices for the plugin's outputs. You can't directly create a nested slice from
+    /// a pointer to pointers, so this needs to be preallocated in the setup call and kept around
+    /// between process calls. This buffer owns the vector, because otherwise it would need to store
+    /// a mutable reference to the data contained in this mutex.
+    pub output_buffer: Buffer<'static, T>,
+    /// Stores sample data for every sidechain input the plugin has. Indexed by
+    /// `[sidechain_input][channel][sample]` We'll copy the data to these buffers since modifying
+    /// the host's sidechain input buffers may not be safe, and the plugin may want to be able to
+    /// modify the buffers.
+    pub aux_input_storage: Vec<Vec<Vec<T>>>,
+    /// Accompanying buffers for `aux_input_storage`. There is no way to do this in safe Rust, so
+    /// the process function needs to make sure all channel pointers stored in these buffers are
+    /// still correct before passing it to the plugin, hence the static lifetime.
+    pub aux_input_buffers: Vec<Buffer<'static, T>>,
+    /// Buffers for auxiliary plugin outputs, if the plugin has any. These reference the host's
+    /// memory directly.
+    pub aux_output_buffers: Vec<Buffer<'static, T>>,
+    /// Scratch storage for the auxiliary outputs, indexed in the same way as `aux_input_storage`.
+    /// This is only used for inactive outputs when the host doesn't provide any buffers for them.
+    pub aux_output_storage: Vec<Vec<Vec<T>>>,
+}
+
+/// Which of the plugin's auxiliary ports the host has activated. Hosts can deactivate ports that
+/// aren't connected to anything so the plugin can skip processing them. All ports are active by
+/// default.
+#[derive(Debug, Default)]
+pub(crate) struct AuxPortActivation {
+    /// Whether each auxiliary input port is active.
+    pub inputs: Vec<bool>,
+    /// Whether each auxiliary output port is active.
+    pub outputs: Vec<bool>,
+}
+
+impl<T: ProcessSample> Default for ProcessBuffers<T> {
+    fn default() -> Self {
+        Self {
+            output_buffer: Buffer::default(),
+            aux_input_storage: Vec::new(),
+            aux_input_buffers: Vec::new(),
+            aux_output_buffers: Vec::new(),
+            aux_output_storage: Vec::new(),
+        }
+    }
+}
+
+impl<T: ProcessSample> ProcessBuffers<T> {
+    /// Preallocate the slice vectors and the sidechain input storage for an audio IO layout. This
+    /// needs to be called when the plugin gets initialized so the process function can convert the
+    /// host's `*mut *mut T` pointers to a `&mut [&mut T]` without allocating. The slices will be
+    /// assigned in the process function as this object may have been moved before then.
+    pub fn preallocate(&mut self, audio_io_layout: &AudioIOLayout, max_buffer_size: usize) {
+        // SAFETY: All slices are set to empty slices here, so there won't be any dangling slices
+        unsafe {
+            self.output_buffer.set_slices(0, |output_slices| {
+                output_slices.resize_with(
+                    audio_io_layout
+                        .main_output_channels
+                        .map(NonZeroU32::get)
+                        .unwrap_or_default() as usize,
+                    || &mut [],
+                );
+                // All slices must have the same length, so if the number of output channels has
+                // changed since the last call then we should make sure to clear any old
+                // (dangling) slices to be consistent
+                output_slices.fill_with(|| &mut []);
+            });
+        }
+
+        self.aux_input_storage
+            .resize_with(audio_io_layout.aux_input_ports.len(), Vec::new);
+        self.aux_input_buffers
+            .resize_with(audio_io_layout.aux_input_ports.len(), Buffer::default);
+        for ((buffer_storage, buffer), num_channels) in self
+            .aux_input_storage
+            .iter_mut()
+            .zip(self.aux_input_buffers.iter_mut())
+            .zip(audio_io_layout.aux_input_ports.iter())
+        {
+            buffer_storage.resize_with(num_channels.get() as usize, Vec::new);
+            for channel_storage in buffer_storage {
+                channel_storage.resize(max_buffer_size, T::default());
+            }
+
+            unsafe {
+                buffer.set_slices(0, |channel_slices| {
+                    channel_slices.resize_with(num_channels.get() as usize, || &mut []);
+                    channel_slices.fill_with(|| &mut []);
+                });
+            }
+        }
+
+        // And the same thing for the output buffers
+        self.aux_output_storage
+            .resize_with(audio_io_layout.aux_output_ports.len(), Vec::new);
+        self.aux_output_buffers
+            .resize_with(audio_io_layout.aux_output_ports.len(), Buffer::default);
+        for ((buffer_storage, buffer), num_channels) in self
+            .aux_output_storage
+            .iter_mut()
+            .zip(self.aux_output_buffers.iter_mut())
+            .zip(audio_io_layout.aux_output_ports.iter())
+        {
+            buffer_storage.resize_with(num_channels.get() as usize, Vec::new);
+            for channel_storage in buffer_storage {
+                channel_storage.resize(max_buffer_size, T::default());
+            }
+
+            unsafe {
+                buffer.set_slices(0, |channel_slices| {
+                    channel_slices.resize_with(num_channels.get() as usize, || &mut []);
+                    channel_slices.fill_with(|| &mut []);
+                });
+            }
+        }
+    }
+}
+
+impl AuxPortActivation {
+    /// Make sure there's an activation state for every auxiliary port in the audio IO layout.
+    /// Ports that didn't have a state yet are active. This needs to be called before the plugin
+    /// starts processing audio since the process function cannot allocate.
+    pub fn resize(&mut self, audio_io_layout: &AudioIOLayout) {
+        self.inputs
+            .resize(audio_io_layout.aux_input_ports.len(), true);
+        self.outputs
+            .resize(audio_io_layout.aux_output_ports.len(), true);
+    }
+}
+
+/// Point `buffer` at `storage` and fill the first `block_len` samples with silence. This is used
+/// for inactive auxiliary ports when the host doesn't provide any buffers for them, so the plugin
+/// still receives correctly sized buffers. `storage` must have been preallocated using
+/// [`ProcessBuffers::preallocate()`].
+///
+/// # Safety
+///
+/// `buffer` may only be used while `storage` is alive and hasn't been reallocated.
+pub(crate) unsafe fn set_silent_slices<T: ProcessSample>(
+    buffer: &mut Buffer<'static, T>,
+    storage: &mut [Vec<T>],
+    block_len: usize,
+) {
+    for channel_storage in storage.iter_mut() {
+        // This never allocates as the storage already has enough capacity
+        assert!(block_len <= channel_storage.capacity());
+        channel_storage.clear();
+        channel_storage.resize(block_len, T::default());
+    }
+
+    buffer.set_slices(block_len, |slices| {
+        for (channel_slice, channel_storage) in slices.iter_mut().zip(storage.iter_mut()) {
+            *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [T]);
+        }
+    });
+    buffer.set_channel_flags(u64::MAX, u64::MAX);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_setup::new_nonzero_u32;
+
+    const LAYOUT: AudioIOLayout = AudioIOLayout {
+        main_input_channels: NonZeroU32::new(2),
+        main_output_channels: NonZeroU32::new(2),
+        aux_input_ports: &[new_nonzero_u32(2)],
+        aux_output_ports: &[new_nonzero_u32(2), new_nonzero_u32(2)],
+        ..AudioIOLayout::const_default()
+    };
+
+    #[test]
+    fn aux_port_activation_defaults_to_active() {
+        let mut activation = AuxPortActivation::default();
+        activation.resize(&LAYOUT);
+
+        assert_eq!(activation.inputs, [true]);
+        assert_eq!(activation.outputs, [true, true]);
+    }
+
+    #[test]
+    fn aux_port_activation_resize_keeps_state() {
+        let mut activation = AuxPortActivation::default();
+        activation.resize(&LAYOUT);
+        activation.outputs[1] = false;
+        activation.resize(&LAYOUT);
+
+        assert_eq!(activation.outputs, [true, false]);
+    }
+}
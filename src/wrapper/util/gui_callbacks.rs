@@ -0,0 +1,171 @@
+//! Storage for the timer and file descriptor callbacks registered through
+//! [`GuiContext`][crate::prelude::GuiContext]. The wrappers register the timers and file
+//! descriptors with the host, and the host's callbacks are then forwarded to the matching callback
+//! stored here on the GUI thread.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::context::gui::FdFlags;
+
+type TimerCallback = Box<dyn FnMut() + Send>;
+type FdCallback = Box<dyn FnMut(FdFlags) + Send>;
+
+/// See the module's documentation. Every callback is stored behind its own mutex so the map's lock
+/// doesn't need to be held while the callback runs. That way a callback can unregister itself or
+/// register new timers without deadlocking.
+#[derive(Default)]
+pub(crate) struct GuiCallbacks {
+    /// The timer callbacks, indexed by timer ID. With CLAP these IDs are assigned by the host.
+    timers: Mutex<HashMap<u32, Arc<Mutex<TimerCallback>>>>,
+    /// The next timer ID to hand out for wrappers where the host doesn't assign the IDs.
+    next_timer_id: AtomicU32,
+    /// The file descriptor watch callbacks, indexed by file descriptor.
+    fds: Mutex<HashMap<c_int, Arc<Mutex<FdCallback>>>>,
+}
+
+impl GuiCallbacks {
+    /// Get a new unique timer ID. This is only needed when the host doesn't assign timer IDs
+    /// itself.
+    pub fn next_timer_id(&self) -> u32 {
+        self.next_timer_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Store the callback for a timer that has been registered with the host.
+    pub fn insert_timer(&self, timer_id: u32, callback: TimerCallback) {
+        let old_callback = self
+            .timers
+            .lock()
+            .insert(timer_id, Arc::new(Mutex::new(callback)));
+        nih_debug_assert!(
+            old_callback.is_none(),
+            "Timer {} was registered twice, the old callback has been replaced",
+            timer_id
+        );
+    }
+
+    /// Remove a timer's callback. Returns `false` if the timer was not registered.
+    pub fn remove_timer(&self, timer_id: u32) -> bool {
+        self.timers.lock().remove(&timer_id).is_some()
+    }
+
+    /// Remove all timer callbacks, returning the IDs of the removed timers so they can be
+    /// unregistered from the host.
+    pub fn take_timer_ids(&self) -> Vec<u32> {
+        self.timers
+            .lock()
+            .drain()
+            .map(|(timer_id, _)| timer_id)
+            .collect()
+    }
+
+    /// Run a timer's callback. Must be called from the GUI thread.
+    pub fn call_timer(&self, timer_id: u32) {
+        // The lock is released before calling the callback, see the struct's docstring
+        let callback = self.timers.lock().get(&timer_id).cloned();
+        match callback {
+            Some(callback) => (callback.lock())(),
+            None => nih_trace!("Received a timer event for unknown timer {}", timer_id),
+        }
+    }
+
+    /// Store the callback for a file descriptor that has been registered with the host. Returns
+    /// `false` if the file descriptor is already being watched.
+    pub fn insert_fd(&self, fd: c_int, callback: FdCallback) -> bool {
+        let mut fds = self.fds.lock();
+        if fds.contains_key(&fd) {
+            return false;
+        }
+
+        fds.insert(fd, Arc::new(Mutex::new(callback)));
+        true
+    }
+
+    /// Whether a callback has been registered for the file descriptor.
+    pub fn contains_fd(&self, fd: c_int) -> bool {
+        self.fds.lock().contains_key(&fd)
+    }
+
+    /// Remove a file descriptor's callback. Returns `false` if the file descriptor was not being
+    /// watched.
+    pub fn remove_fd(&self, fd: c_int) -> bool {
+        self.fds.lock().remove(&fd).is_some()
+    }
+
+    /// Remove all file descriptor callbacks, returning the removed file descriptors so they can be
+    /// unregistered from the host.
+    pub fn take_fds(&self) -> Vec<c_int> {
+        self.fds.lock().drain().map(|(fd, _)| fd).collect()
+    }
+
+    /// Run a file descriptor's callback with the events that occurred. Must be called from the GUI
+    /// thread.
+    pub fn call_fd(&self, fd: c_int, flags: FdFlags) {
+        let callback = self.fds.lock().get(&fd).cloned();
+        match callback {
+            Some(callback) => (callback.lock())(flags),
+            None => nih_trace!("Received an event for unknown file descriptor {}", fd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_callbacks() {
+        let callbacks = Arc::new(GuiCallbacks::default());
+        let num_calls = Arc::new(AtomicU32::new(0));
+        callbacks.insert_timer(1, {
+            let num_calls = num_calls.clone();
+            Box::new(move || {
+                num_calls.fetch_add(1, Ordering::Relaxed);
+            })
+        });
+
+        callbacks.call_timer(1);
+        callbacks.call_timer(1);
+        callbacks.call_timer(2);
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+
+        assert!(callbacks.remove_timer(1));
+        assert!(!callbacks.remove_timer(1));
+        callbacks.call_timer(1);
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn timer_can_unregister_itself() {
+        let callbacks = Arc::new(GuiCallbacks::default());
+        callbacks.insert_timer(1, {
+            let callbacks = callbacks.clone();
+            Box::new(move || {
+                callbacks.remove_timer(1);
+            })
+        });
+
+        callbacks.call_timer(1);
+        assert!(callbacks.take_timer_ids().is_empty());
+    }
+
+    #[test]
+    fn fd_callbacks() {
+        let callbacks = GuiCallbacks::default();
+        let received_flags = Arc::new(Mutex::new(FdFlags::empty()));
+        assert!(callbacks.insert_fd(3, {
+            let received_flags = received_flags.clone();
+            Box::new(move |flags| *received_flags.lock() = flags)
+        }));
+        assert!(!callbacks.insert_fd(3, Box::new(|_| ())));
+
+        callbacks.call_fd(3, FdFlags::READ | FdFlags::ERROR);
+        assert_eq!(*received_flags.lock(), FdFlags::READ | FdFlags::ERROR);
+
+        assert_eq!(callbacks.take_fds(), [3]);
+        assert!(!callbacks.contains_fd(3));
+    }
+}
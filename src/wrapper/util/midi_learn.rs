@@ -0,0 +1,313 @@
+//! The wrapper side of MIDI learn. This stores the plugin's active MIDI mappings and the parameter
+//! that's currently waiting for a controller to be learned. The wrappers run the plugin's incoming
+//! events through [`MidiLearn::handle_event()`] before the plugin gets to see them.
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::midi::learn::{MidiMapping, MidiMappingSource};
+use crate::midi::NoteEvent;
+use crate::params::internals::ParamPtr;
+use crate::util::permit_alloc;
+
+/// See the module's documentation. The audio thread never blocks on the state's lock. If another
+/// thread is currently holding it, then the event is treated as if it wasn't mapped.
+#[derive(Default)]
+pub(crate) struct MidiLearn {
+    state: Mutex<MidiLearnState>,
+    /// Whether there are any mappings or a pending learn. This lets the audio thread skip MIDI
+    /// learn entirely when it's not used.
+    active: AtomicBool,
+}
+
+#[derive(Default)]
+struct MidiLearnState {
+    /// The parameter that will be mapped to the next incoming controller, along with its ID.
+    learn_target: Option<(ParamPtr, String)>,
+    /// The active mappings. There is at most one mapping per parameter.
+    mappings: Vec<ActiveMapping>,
+}
+
+struct ActiveMapping {
+    param_ptr: ParamPtr,
+    /// The parameter's ID, needed to store the mapping in the plugin's state.
+    param_id: String,
+    mapping: MidiMapping,
+}
+
+impl MidiLearnState {
+    fn is_active(&self) -> bool {
+        self.learn_target.is_some() || !self.mappings.is_empty()
+    }
+}
+
+impl MidiLearn {
+    /// Whether there are any mappings or a pending learn. If this returns `false`, then
+    /// [`handle_event()`][Self::handle_event()] will never consume any events.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Map the next incoming controller to this parameter. This replaces any previous learn
+    /// target.
+    pub fn start_learning(&self, param_ptr: ParamPtr, param_id: String) {
+        let mut state = self.state.lock();
+        state.learn_target = Some((param_ptr, param_id));
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop waiting for a controller to learn.
+    pub fn cancel_learning(&self) {
+        let mut state = self.state.lock();
+        state.learn_target = None;
+        self.active.store(state.is_active(), Ordering::Relaxed);
+    }
+
+    /// The parameter that's currently waiting for a controller to be learned, if any.
+    pub fn learn_target(&self) -> Option<ParamPtr> {
+        self.state
+            .lock()
+            .learn_target
+            .as_ref()
+            .map(|(param_ptr, _)| *param_ptr)
+    }
+
+    /// Get a parameter's mapping, if it has one.
+    pub fn mapping(&self, param_ptr: ParamPtr) -> Option<MidiMapping> {
+        self.state
+            .lock()
+            .mappings
+            .iter()
+            .find(|active_mapping| active_mapping.param_ptr == param_ptr)
+            .map(|active_mapping| active_mapping.mapping)
+    }
+
+    /// Set or remove a parameter's mapping.
+    pub fn set_mapping(&self, param_ptr: ParamPtr, param_id: String, mapping: Option<MidiMapping>) {
+        let mut state = self.state.lock();
+        state
+            .mappings
+            .retain(|active_mapping| active_mapping.param_ptr != param_ptr);
+        if let Some(mapping) = mapping {
+            state.mappings.push(ActiveMapping {
+                param_ptr,
+                param_id,
+                mapping,
+            });
+        }
+
+        self.active.store(state.is_active(), Ordering::Relaxed);
+    }
+
+    /// All active mappings, indexed by parameter ID. Used when saving the plugin's state.
+    pub fn mappings(&self) -> BTreeMap<String, MidiMapping> {
+        self.state
+            .lock()
+            .mappings
+            .iter()
+            .map(|active_mapping| (active_mapping.param_id.clone(), active_mapping.mapping))
+            .collect()
+    }
+
+    /// Replace all mappings with mappings restored from the plugin's state. This also cancels any
+    /// pending learn.
+    pub fn set_mappings(
+        &self,
+        mappings: &BTreeMap<String, MidiMapping>,
+        params_getter: impl Fn(&str) -> Option<ParamPtr>,
+    ) {
+        let mappings: Vec<_> = mappings
+            .iter()
+            .filter_map(|(param_id, mapping)| match params_getter(param_id) {
+                Some(param_ptr) => Some(ActiveMapping {
+                    param_ptr,
+                    param_id: param_id.clone(),
+                    mapping: *mapping,
+                }),
+                None => {
+                    nih_debug_assert_failure!("Unknown parameter in MIDI mapping: {}", param_id);
+                    None
+                }
+            })
+            .collect();
+
+        let mut state = self.state.lock();
+        state.learn_target = None;
+        state.mappings = mappings;
+        self.active.store(state.is_active(), Ordering::Relaxed);
+    }
+
+    /// Whether [`handle_event()`][Self::handle_event()] would consume this event. When the plugin
+    /// uses sample accurate automation, the wrappers split the buffer on these events so the mapped
+    /// values are applied at the event's sample instead of at the start of the buffer.
+    ///
+    /// This is called from the audio thread.
+    pub fn maps_event<S>(&self, event: &NoteEvent<S>) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+
+        let source = match MidiMappingSource::from_event(event) {
+            Some((source, _)) => source,
+            None => return false,
+        };
+
+        let state = match self.state.try_lock() {
+            Some(state) => state,
+            None => return false,
+        };
+        state.learn_target.is_some()
+            || state
+                .mappings
+                .iter()
+                .any(|active_mapping| active_mapping.mapping.source == source)
+    }
+
+    /// Apply the mappings to an incoming event. If there is a pending learn, then the first
+    /// mappable controller is mapped to the learn target. `set_parameter` is called with the new
+    /// normalized value for every parameter mapped to the event's controller. Returns `true` if
+    /// the event was consumed by a mapping, in which case it should not be passed to the plugin.
+    /// The new values apply to the whole block the event is part of, so the wrappers should start
+    /// a new block at the event's sample if they support sample accurate automation. See
+    /// [`maps_event()`][Self::maps_event()].
+    ///
+    /// This is called from the audio thread. If the GUI thread is modifying the mappings at the same
+    /// time, then the event is passed through to the plugin instead of waiting for the lock.
+    pub fn handle_event<S>(
+        &self,
+        event: &NoteEvent<S>,
+        mut set_parameter: impl FnMut(ParamPtr, f32),
+    ) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+
+        let (source, midi_value) = match MidiMappingSource::from_event(event) {
+            Some(source) => source,
+            None => return false,
+        };
+
+        let mut state = match self.state.try_lock() {
+            Some(state) => state,
+            None => return false,
+        };
+        if let Some((param_ptr, param_id)) = state.learn_target.take() {
+            // This is a one-off (de)allocation in response to user input. The replaced mappings
+            // own their parameter IDs, so dropping them deallocates.
+            permit_alloc(|| {
+                // A controller can only be learned for a single parameter, and a parameter can
+                // only have a single mapping
+                state.mappings.retain(|active_mapping| {
+                    active_mapping.param_ptr != param_ptr && active_mapping.mapping.source != source
+                });
+                state.mappings.push(ActiveMapping {
+                    param_ptr,
+                    param_id,
+                    mapping: MidiMapping::new(source),
+                })
+            });
+        }
+
+        let mut consumed = false;
+        for active_mapping in &state.mappings {
+            if active_mapping.mapping.source == source {
+                set_parameter(
+                    active_mapping.param_ptr,
+                    active_mapping.mapping.normalized_value(midi_value),
+                );
+                consumed = true;
+            }
+        }
+
+        consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{BoolParam, FloatParam, Param};
+    use crate::prelude::FloatRange;
+
+    fn cc_event(cc: u8, value: f32) -> NoteEvent<()> {
+        NoteEvent::MidiCC {
+            timing: 0,
+            channel: 0,
+            cc,
+            value,
+        }
+    }
+
+    #[test]
+    fn learn_maps_next_controller() {
+        let param = FloatParam::new("Foo", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 });
+        let param_ptr = param.as_ptr();
+        let midi_learn = MidiLearn::default();
+        assert!(!midi_learn.handle_event(&cc_event(1, 0.5), |_, _| panic!()));
+
+        midi_learn.start_learning(param_ptr, String::from("foo"));
+        assert_eq!(midi_learn.learn_target(), Some(param_ptr));
+
+        let mut updates = Vec::new();
+        assert!(
+            midi_learn.handle_event(&cc_event(1, 0.5), |param_ptr, value| {
+                updates.push((param_ptr, value))
+            })
+        );
+        assert!(!midi_learn.handle_event(&cc_event(2, 0.5), |_, _| panic!()));
+        assert_eq!(updates, [(param_ptr, 0.5)]);
+        assert!(midi_learn.maps_event(&cc_event(1, 0.25)));
+        assert!(!midi_learn.maps_event(&cc_event(2, 0.25)));
+        assert_eq!(midi_learn.learn_target(), None);
+        assert_eq!(
+            midi_learn.mapping(param_ptr),
+            Some(MidiMapping::new(MidiMappingSource::Cc {
+                channel: 0,
+                cc: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn learning_steals_controller() {
+        let foo = FloatParam::new("Foo", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 });
+        let bar = BoolParam::new("Bar", false);
+        let midi_learn = MidiLearn::default();
+        midi_learn.set_mapping(
+            foo.as_ptr(),
+            String::from("foo"),
+            Some(MidiMapping::new(MidiMappingSource::Cc {
+                channel: 0,
+                cc: 1,
+            })),
+        );
+
+        midi_learn.start_learning(bar.as_ptr(), String::from("bar"));
+        midi_learn.handle_event(&cc_event(1, 1.0), |_, _| ());
+        assert_eq!(midi_learn.mapping(foo.as_ptr()), None);
+        assert_eq!(
+            midi_learn.mappings().keys().collect::<Vec<_>>(),
+            [&String::from("bar")]
+        );
+
+        midi_learn.set_mapping(bar.as_ptr(), String::from("bar"), None);
+        assert!(!midi_learn.is_active());
+    }
+
+    #[test]
+    fn contention_does_not_block() {
+        let param = FloatParam::new("Foo", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 });
+        let midi_learn = MidiLearn::default();
+        midi_learn.start_learning(param.as_ptr(), String::from("foo"));
+
+        // While another thread holds the lock the event is passed through to the plugin
+        {
+            let _state = midi_learn.state.lock();
+            assert!(!midi_learn.maps_event(&cc_event(1, 0.5)));
+            assert!(!midi_learn.handle_event(&cc_event(1, 0.5), |_, _| panic!()));
+        }
+
+        assert!(midi_learn.handle_event(&cc_event(1, 0.5), |_, _| ()));
+    }
+}
@@ -0,0 +1,327 @@
+//! Translation from MPE's per-channel expressions to per-note expression events. This is shared
+//! between all wrappers so MPE controllers behave the same everywhere.
+
+use crate::midi::mpe::MpeZones;
+use crate::midi::{MidiConfig, NoteEvent};
+
+/// MPE uses CC74 for the brightness or timbre dimension.
+const BRIGHTNESS_CC: u8 = 74;
+
+/// Keeps track of the notes playing on each MPE member channel, and translates pitch bend, CC74,
+/// and channel pressure messages on those channels to
+/// [`NoteEvent::PolyTuning`]/[`NoteEvent::PolyBrightness`]/[`NoteEvent::PolyPressure`] events for
+/// those notes. All events pass through unmodified when the plugin doesn't use MPE.
+pub(crate) struct MpeTranslator {
+    /// The plugin's zone layout, or `None` if the plugin doesn't use MPE.
+    zones: Option<MpeZones>,
+    /// The voice IDs for all active notes, indexed by `[channel][note]`. `Some(None)` indicates an
+    /// active note without a voice ID.
+    active_notes: Box<[[Option<Option<i32>>; 128]; 16]>,
+    /// The most recent expression values sent on each channel. MPE controllers send these right
+    /// before a note on to set the new note's initial expression, so they need to be applied to
+    /// notes that start after they were received.
+    channel_expressions: [ChannelExpressions; 16],
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ChannelExpressions {
+    /// The tuning offset in semitones resulting from the channel's last pitch bend message.
+    tuning: Option<f32>,
+    brightness: Option<f32>,
+    pressure: Option<f32>,
+}
+
+impl MpeTranslator {
+    pub fn new(midi_config: MidiConfig) -> Self {
+        Self {
+            zones: midi_config.mpe_zones(),
+            active_notes: Box::new([[None; 128]; 16]),
+            channel_expressions: Default::default(),
+        }
+    }
+
+    /// Whether the plugin uses MPE. If this returns `false` then [`translate()`][Self::translate()]
+    /// passes all events through as is.
+    pub fn is_enabled(&self) -> bool {
+        self.zones.is_some()
+    }
+
+    /// Forget about all active notes and channel expressions. Should be called when the plugin is
+    /// reset.
+    pub fn reset(&mut self) {
+        for channel_notes in self.active_notes.iter_mut() {
+            channel_notes.fill(None);
+        }
+        self.channel_expressions = Default::default();
+    }
+
+    /// Translate `event` and pass the results to `emit`. Events that don't need to be translated
+    /// are passed through as is. Pitch bend, CC74, and channel pressure messages on member channels
+    /// are replaced by per-note expression events, and note on events on member channels are
+    /// followed by expression events for the channel's current expression values.
+    pub fn translate<S>(&mut self, event: NoteEvent<S>, mut emit: impl FnMut(NoteEvent<S>)) {
+        let zones = match self.zones {
+            Some(zones) => zones,
+            None => return emit(event),
+        };
+
+        match event {
+            NoteEvent::NoteOn {
+                timing,
+                voice_id,
+                channel,
+                note,
+                ..
+            } => {
+                emit(event);
+
+                if zones.member_zone(channel).is_none() || note >= 128 {
+                    return;
+                }
+
+                self.active_notes[channel as usize][note as usize] = Some(voice_id);
+
+                let expressions = self.channel_expressions[channel as usize];
+                if let Some(tuning) = expressions.tuning {
+                    emit(NoteEvent::PolyTuning {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        tuning,
+                    });
+                }
+                if let Some(brightness) = expressions.brightness {
+                    emit(NoteEvent::PolyBrightness {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        brightness,
+                    });
+                }
+                if let Some(pressure) = expressions.pressure {
+                    emit(NoteEvent::PolyPressure {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        pressure,
+                    });
+                }
+            }
+            NoteEvent::NoteOff { channel, note, .. } => {
+                if channel < 16 && note < 128 {
+                    self.active_notes[channel as usize][note as usize] = None;
+                }
+
+                emit(event);
+            }
+            NoteEvent::Choke { channel, note, .. } => {
+                // Chokes may use wildcards for the channel and the note
+                for (channel_idx, channel_notes) in self.active_notes.iter_mut().enumerate() {
+                    if channel.map_or(false, |channel| channel as usize != channel_idx) {
+                        continue;
+                    }
+
+                    for (note_idx, active_note) in channel_notes.iter_mut().enumerate() {
+                        if note.map_or(true, |note| note as usize == note_idx) {
+                            *active_note = None;
+                        }
+                    }
+                }
+
+                emit(event);
+            }
+            NoteEvent::MidiPitchBend {
+                timing,
+                channel,
+                value,
+            } => match zones.member_zone(channel) {
+                Some(zone) => {
+                    let tuning = (value * 2.0 - 1.0) * zone.pitch_bend_range as f32;
+                    self.channel_expressions[channel as usize].tuning = Some(tuning);
+
+                    self.for_each_active_note(channel, |voice_id, note| {
+                        emit(NoteEvent::PolyTuning {
+                            timing,
+                            voice_id,
+                            channel,
+                            note,
+                            tuning,
+                        })
+                    });
+                }
+                None => emit(event),
+            },
+            NoteEvent::MidiCC {
+                timing,
+                channel,
+                cc: BRIGHTNESS_CC,
+                value,
+            } if zones.member_zone(channel).is_some() => {
+                self.channel_expressions[channel as usize].brightness = Some(value);
+
+                self.for_each_active_note(channel, |voice_id, note| {
+                    emit(NoteEvent::PolyBrightness {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        brightness: value,
+                    })
+                });
+            }
+            NoteEvent::MidiChannelPressure {
+                timing,
+                channel,
+                pressure,
+            } if zones.member_zone(channel).is_some() => {
+                self.channel_expressions[channel as usize].pressure = Some(pressure);
+
+                self.for_each_active_note(channel, |voice_id, note| {
+                    emit(NoteEvent::PolyPressure {
+                        timing,
+                        voice_id,
+                        channel,
+                        note,
+                        pressure,
+                    })
+                });
+            }
+            event => emit(event),
+        }
+    }
+
+    fn for_each_active_note(&self, channel: u8, mut f: impl FnMut(Option<i32>, u8)) {
+        for (note, voice_id) in self.active_notes[channel as usize].iter().enumerate() {
+            if let Some(voice_id) = voice_id {
+                f(*voice_id, note as u8);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate_all(
+        translator: &mut MpeTranslator,
+        events: impl IntoIterator<Item = NoteEvent<()>>,
+    ) -> Vec<NoteEvent<()>> {
+        let mut output = Vec::new();
+        for event in events {
+            translator.translate(event, |event| output.push(event));
+        }
+
+        output
+    }
+
+    #[test]
+    fn passthrough_without_mpe() {
+        let mut translator = MpeTranslator::new(MidiConfig::MidiCCs);
+        let events = [
+            NoteEvent::MidiPitchBend {
+                timing: 0,
+                channel: 1,
+                value: 1.0,
+            },
+            NoteEvent::MidiCC {
+                timing: 0,
+                channel: 1,
+                cc: BRIGHTNESS_CC,
+                value: 0.25,
+            },
+        ];
+
+        assert_eq!(translate_all(&mut translator, events), events);
+    }
+
+    #[test]
+    fn member_channel_expressions() {
+        let mut translator = MpeTranslator::new(MidiConfig::Mpe(MpeZones::LOWER));
+        let output = translate_all(
+            &mut translator,
+            [
+                // The initial pressure is sent before the note on
+                NoteEvent::MidiChannelPressure {
+                    timing: 0,
+                    channel: 2,
+                    pressure: 0.5,
+                },
+                NoteEvent::NoteOn {
+                    timing: 1,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    velocity: 1.0,
+                },
+                NoteEvent::MidiPitchBend {
+                    timing: 2,
+                    channel: 2,
+                    value: 1.0,
+                },
+                // Manager channel messages are passed through as is
+                NoteEvent::MidiPitchBend {
+                    timing: 3,
+                    channel: 0,
+                    value: 1.0,
+                },
+                NoteEvent::NoteOff {
+                    timing: 4,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    velocity: 0.0,
+                },
+                // There are no notes left on this channel
+                NoteEvent::MidiCC {
+                    timing: 5,
+                    channel: 2,
+                    cc: BRIGHTNESS_CC,
+                    value: 0.75,
+                },
+            ],
+        );
+
+        assert_eq!(
+            output,
+            [
+                NoteEvent::NoteOn {
+                    timing: 1,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    velocity: 1.0,
+                },
+                NoteEvent::PolyPressure {
+                    timing: 1,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    pressure: 0.5,
+                },
+                NoteEvent::PolyTuning {
+                    timing: 2,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    tuning: 48.0,
+                },
+                NoteEvent::MidiPitchBend {
+                    timing: 3,
+                    channel: 0,
+                    value: 1.0,
+                },
+                NoteEvent::NoteOff {
+                    timing: 4,
+                    voice_id: Some(10),
+                    channel: 2,
+                    note: 60,
+                    velocity: 0.0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! The plugin's undo history. Parameter gestures made through a
+//! [`GuiContext`][crate::prelude::GuiContext] are recorded as transactions, as are states loaded
+//! through [`GuiContext::set_state()`][crate::prelude::GuiContext::set_state()]. Undoing or redoing
+//! a transaction replays it through the `GuiContext` so the host sees the changes as regular
+//! parameter gestures.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::context::gui::GuiContext;
+use crate::params::internals::ParamPtr;
+use crate::wrapper::state::PluginState;
+
+/// The maximum number of transactions kept in the undo history. Older transactions are dropped,
+/// unless the history is [managed by the host][UndoManager::set_host_managed()].
+const MAX_UNDO_HISTORY: usize = 128;
+
+/// See the module's documentation. This is only used from the GUI thread.
+#[derive(Default)]
+pub(crate) struct UndoManager {
+    history: Mutex<UndoHistory>,
+    /// Set while an undo or redo action is being applied, so the changes made while applying it
+    /// are not recorded as new transactions.
+    replaying: AtomicBool,
+}
+
+#[derive(Default)]
+struct UndoHistory {
+    /// Whether the host keeps track of the transactions. See
+    /// [`UndoManager::set_host_managed()`].
+    host_managed: bool,
+    /// The parameters changed during the current transaction. A transaction stays open as long as
+    /// any of its parameters still has an active gesture, so gestures that overlap, like from an
+    /// X-Y pad, are undone together.
+    pending: Vec<PendingChange>,
+    undo_stack: VecDeque<Transaction>,
+    redo_stack: Vec<Transaction>,
+    next_id: u64,
+}
+
+struct PendingChange {
+    param_ptr: ParamPtr,
+    /// The parameter's normalized value when the gesture started.
+    before: f32,
+    /// The last normalized value set during the gesture, if any.
+    after: Option<f32>,
+    gesture_active: bool,
+}
+
+struct Transaction {
+    id: u64,
+    change: Change,
+}
+
+enum Change {
+    Params(Vec<ParamChange>),
+    State {
+        before: Box<PluginState>,
+        after: Box<PluginState>,
+    },
+}
+
+struct ParamChange {
+    param_ptr: ParamPtr,
+    before: f32,
+    after: f32,
+}
+
+/// What happened to the current transaction after recording a change. The CLAP wrapper uses this
+/// to keep the host's undo history in sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TransactionUpdate {
+    /// A new transaction was started.
+    Started,
+    /// The current transaction is still open, or nothing was recorded.
+    Unchanged,
+    /// The transaction was closed without changing anything.
+    Cancelled,
+    /// The transaction was added to the undo history.
+    Committed { id: u64, name: String },
+}
+
+/// The changes needed to undo or redo a transaction.
+pub(crate) enum UndoAction {
+    /// Set these parameters to these normalized values.
+    SetParams(Vec<(ParamPtr, f32)>),
+    /// Load this state.
+    SetState(PluginState),
+}
+
+impl UndoManager {
+    /// Mark the history as being managed by the host. The host may still ask the plugin to undo
+    /// or redo any transaction it has been told about, so the history is no longer capped at
+    /// [`MAX_UNDO_HISTORY`] transactions. The host decides how much of its history to keep.
+    pub fn set_host_managed(&self, host_managed: bool) {
+        self.history.lock().host_managed = host_managed;
+    }
+
+    /// Record the start of a parameter gesture. `param_ptr` must point to a parameter that's
+    /// still alive.
+    pub fn begin_gesture(&self, param_ptr: ParamPtr) -> TransactionUpdate {
+        if self.replaying.load(Ordering::Relaxed) {
+            return TransactionUpdate::Unchanged;
+        }
+
+        let mut history = self.history.lock();
+        let is_new_transaction = history.pending.is_empty();
+        match history
+            .pending
+            .iter_mut()
+            .find(|change| change.param_ptr == param_ptr)
+        {
+            // If the parameter was already changed during this transaction, then the transaction
+            // should restore the value from before its first gesture
+            Some(change) => change.gesture_active = true,
+            None => history.pending.push(PendingChange {
+                param_ptr,
+                before: unsafe { param_ptr.unmodulated_normalized_value() },
+                after: None,
+                gesture_active: true,
+            }),
+        }
+
+        if is_new_transaction {
+            TransactionUpdate::Started
+        } else {
+            TransactionUpdate::Unchanged
+        }
+    }
+
+    /// Record a parameter change. Changes made outside of a gesture are not recorded.
+    pub fn record_value(&self, param_ptr: ParamPtr, normalized: f32) {
+        if self.replaying.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut history = self.history.lock();
+        match history
+            .pending
+            .iter_mut()
+            .find(|change| change.param_ptr == param_ptr && change.gesture_active)
+        {
+            Some(change) => change.after = Some(normalized),
+            None => nih_trace!(
+                "Parameter {:?} was changed outside of a gesture, the change cannot be undone",
+                param_ptr
+            ),
+        }
+    }
+
+    /// Record the end of a parameter gesture. The transaction is committed once all of its
+    /// gestures have ended.
+    pub fn end_gesture(&self, param_ptr: ParamPtr) -> TransactionUpdate {
+        if self.replaying.load(Ordering::Relaxed) {
+            return TransactionUpdate::Unchanged;
+        }
+
+        let mut history = self.history.lock();
+        match history
+            .pending
+            .iter_mut()
+            .find(|change| change.param_ptr == param_ptr)
+        {
+            Some(change) => change.gesture_active = false,
+            None => return TransactionUpdate::Unchanged,
+        }
+        if history.pending.iter().any(|change| change.gesture_active) {
+            return TransactionUpdate::Unchanged;
+        }
+
+        let param_changes: Vec<ParamChange> = history
+            .pending
+            .drain(..)
+            .filter_map(|change| match change.after {
+                Some(after) if after != change.before => Some(ParamChange {
+                    param_ptr: change.param_ptr,
+                    before: change.before,
+                    after,
+                }),
+                _ => None,
+            })
+            .collect();
+        if param_changes.is_empty() {
+            return TransactionUpdate::Cancelled;
+        }
+
+        history.commit(Change::Params(param_changes))
+    }
+
+    /// Record a state being loaded from the GUI. `before` should be the plugin's state from right
+    /// before `after` was loaded.
+    pub fn record_state_change(
+        &self,
+        before: PluginState,
+        after: PluginState,
+    ) -> TransactionUpdate {
+        if self.replaying.load(Ordering::Relaxed) {
+            return TransactionUpdate::Unchanged;
+        }
+
+        self.history.lock().commit(Change::State {
+            before: Box::new(before),
+            after: Box::new(after),
+        })
+    }
+
+    /// Whether there is a transaction that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.history.lock().undo_stack.is_empty()
+    }
+
+    /// Whether there is a transaction that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.history.lock().redo_stack.is_empty()
+    }
+
+    /// Move the last transaction to the redo stack, and return the changes needed to undo it.
+    pub fn undo(&self) -> Option<UndoAction> {
+        let mut history = self.history.lock();
+        let transaction = history.undo_stack.pop_back()?;
+        let action = transaction.change.undo_action();
+        history.redo_stack.push(transaction);
+
+        Some(action)
+    }
+
+    /// Move the last undone transaction back to the undo stack, and return the changes needed to
+    /// redo it.
+    pub fn redo(&self) -> Option<UndoAction> {
+        let mut history = self.history.lock();
+        let transaction = history.redo_stack.pop()?;
+        let action = transaction.change.redo_action();
+        history.undo_stack.push_back(transaction);
+
+        Some(action)
+    }
+
+    /// [`undo()`][Self::undo()], but for a specific transaction. Used when the host manages the
+    /// undo history. Returns `None` if the transaction is not in the undo stack.
+    pub fn undo_transaction(&self, id: u64) -> Option<UndoAction> {
+        let mut history = self.history.lock();
+        let idx = history
+            .undo_stack
+            .iter()
+            .position(|transaction| transaction.id == id)?;
+        let transaction = history.undo_stack.remove(idx)?;
+        let action = transaction.change.undo_action();
+        history.redo_stack.push(transaction);
+
+        Some(action)
+    }
+
+    /// [`redo()`][Self::redo()], but for a specific transaction. Used when the host manages the
+    /// undo history. Returns `None` if the transaction is not in the redo stack.
+    pub fn redo_transaction(&self, id: u64) -> Option<UndoAction> {
+        let mut history = self.history.lock();
+        let idx = history
+            .redo_stack
+            .iter()
+            .position(|transaction| transaction.id == id)?;
+        let transaction = history.redo_stack.remove(idx);
+        let action = transaction.change.redo_action();
+        history.undo_stack.push_back(transaction);
+
+        Some(action)
+    }
+
+    /// Apply an undo or redo action through a GUI context, without recording the changes as a new
+    /// transaction. Parameters are changed using regular gestures so the host also sees the
+    /// changes.
+    pub fn apply(&self, context: &dyn GuiContext, action: UndoAction) {
+        self.replaying.store(true, Ordering::Relaxed);
+        match action {
+            UndoAction::SetParams(values) => {
+                for (param_ptr, normalized) in values {
+                    unsafe {
+                        context.raw_begin_set_parameter(param_ptr);
+                        context.raw_set_parameter_normalized(param_ptr, normalized);
+                        context.raw_end_set_parameter(param_ptr);
+                    }
+                }
+            }
+            UndoAction::SetState(state) => context.set_state(state),
+        }
+        self.replaying.store(false, Ordering::Relaxed);
+    }
+}
+
+impl UndoHistory {
+    fn commit(&mut self, change: Change) -> TransactionUpdate {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let name = change.name();
+        self.redo_stack.clear();
+        self.undo_stack.push_back(Transaction { id, change });
+        if !self.host_managed && self.undo_stack.len() > MAX_UNDO_HISTORY {
+            self.undo_stack.pop_front();
+        }
+
+        TransactionUpdate::Committed { id, name }
+    }
+}
+
+impl Change {
+    /// A human readable name for the change, used in the host's undo history.
+    fn name(&self) -> String {
+        match self {
+            Change::Params(param_changes) if param_changes.len() == 1 => {
+                format!("Change {}", unsafe { param_changes[0].param_ptr.name() })
+            }
+            Change::Params(param_changes) => format!("Change {} parameters", param_changes.len()),
+            Change::State { .. } => String::from("Load preset"),
+        }
+    }
+
+    fn undo_action(&self) -> UndoAction {
+        match self {
+            Change::Params(param_changes) => UndoAction::SetParams(
+                param_changes
+                    .iter()
+                    .map(|change| (change.param_ptr, change.before))
+                    .collect(),
+            ),
+            Change::State { before, .. } => UndoAction::SetState((**before).clone()),
+        }
+    }
+
+    fn redo_action(&self) -> UndoAction {
+        match self {
+            Change::Params(param_changes) => UndoAction::SetParams(
+                param_changes
+                    .iter()
+                    .map(|change| (change.param_ptr, change.after))
+                    .collect(),
+            ),
+            Change::State { after, .. } => UndoAction::SetState((**after).clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{FloatParam, Param};
+    use crate::prelude::FloatRange;
+
+    fn new_param(name: &str) -> FloatParam {
+        FloatParam::new(name, 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+    }
+
+    fn param_values(action: Option<UndoAction>) -> Vec<(ParamPtr, f32)> {
+        match action {
+            Some(UndoAction::SetParams(values)) => values,
+            _ => panic!("Expected a parameter change"),
+        }
+    }
+
+    #[test]
+    fn gesture_transactions() {
+        let foo = new_param("Foo");
+        let undo = UndoManager::default();
+
+        assert_eq!(undo.begin_gesture(foo.as_ptr()), TransactionUpdate::Started);
+        undo.record_value(foo.as_ptr(), 0.25);
+        undo.record_value(foo.as_ptr(), 0.5);
+        assert_eq!(
+            undo.end_gesture(foo.as_ptr()),
+            TransactionUpdate::Committed {
+                id: 0,
+                name: String::from("Change Foo")
+            }
+        );
+
+        // A gesture that doesn't change anything isn't recorded
+        undo.begin_gesture(foo.as_ptr());
+        assert_eq!(undo.end_gesture(foo.as_ptr()), TransactionUpdate::Cancelled);
+
+        assert_eq!(param_values(undo.undo()), [(foo.as_ptr(), 0.0)]);
+        assert!(!undo.can_undo());
+        assert_eq!(param_values(undo.redo()), [(foo.as_ptr(), 0.5)]);
+        assert!(!undo.can_redo());
+    }
+
+    #[test]
+    fn overlapping_gestures() {
+        let foo = new_param("Foo");
+        let bar = new_param("Bar");
+        let undo = UndoManager::default();
+
+        undo.begin_gesture(foo.as_ptr());
+        assert_eq!(
+            undo.begin_gesture(bar.as_ptr()),
+            TransactionUpdate::Unchanged
+        );
+        undo.record_value(foo.as_ptr(), 0.5);
+        undo.record_value(bar.as_ptr(), 0.75);
+        assert_eq!(undo.end_gesture(foo.as_ptr()), TransactionUpdate::Unchanged);
+        assert_eq!(
+            undo.end_gesture(bar.as_ptr()),
+            TransactionUpdate::Committed {
+                id: 0,
+                name: String::from("Change 2 parameters")
+            }
+        );
+
+        assert!(undo.undo_transaction(1).is_none());
+        assert_eq!(
+            param_values(undo.undo_transaction(0)),
+            [(foo.as_ptr(), 0.0), (bar.as_ptr(), 0.0)]
+        );
+    }
+
+    #[test]
+    fn host_managed_history_is_not_capped() {
+        let foo = new_param("Foo");
+        let undo = UndoManager::default();
+        undo.set_host_managed(true);
+
+        for idx in 0..=MAX_UNDO_HISTORY {
+            undo.begin_gesture(foo.as_ptr());
+            undo.record_value(foo.as_ptr(), (idx + 1) as f32 / 1000.0);
+            undo.end_gesture(foo.as_ptr());
+        }
+
+        // The host may still refer to the very first transaction
+        assert_eq!(
+            param_values(undo.undo_transaction(0)),
+            [(foo.as_ptr(), 0.0)]
+        );
+    }
+}
@@ -1,11 +1,15 @@
 #[macro_use]
 mod util;
 
+mod channel_context;
 mod context;
 mod factory;
 mod inner;
 mod note_expressions;
 mod param_units;
+#[cfg(target_os = "linux")]
+mod run_loop;
+mod speaker_arrangements;
 pub mod subcategories;
 mod view;
 mod wrapper;
@@ -0,0 +1,69 @@
+//! Channel context information, which VST3 hosts use to tell the plugin about the mixer channel it's
+//! inserted on. The host passes this information as an attribute list to
+//! `IInfoListener::setChannelContextInfos()`.
+//!
+//! <https://steinbergmedia.github.io/vst3_doc/vstinterfaces/classSteinberg_1_1Vst_1_1ChannelContext_1_1IInfoListener.html>
+
+use std::ffi::CStr;
+use vst3_sys::base::kResultOk;
+use vst3_sys::vst::{IAttributeList, TChar};
+use widestring::U16CStr;
+
+use crate::context::{TrackColor, TrackInfo};
+
+// These keys are not part of `vst3-sys`
+const CHANNEL_NAME_KEY: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"channel name\0") };
+const CHANNEL_NAME_LENGTH_KEY: &CStr =
+    unsafe { CStr::from_bytes_with_nul_unchecked(b"channel name length\0") };
+const CHANNEL_COLOR_KEY: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"channel color\0") };
+
+/// The buffer size used to read the channel's name if the host doesn't tell us how long the name
+/// is.
+const DEFAULT_CHANNEL_NAME_LENGTH: usize = 128;
+
+/// Read the channel context information from the attribute list passed to
+/// `IInfoListener::setChannelContextInfos()`. The attribute list only contains the information that
+/// has changed, so `track_info` should contain the previously received information. VST3 doesn't
+/// have a portable way to tell the kind of channel the plugin is inserted on or the channel's
+/// number of audio channels, so those fields are left untouched.
+///
+/// # Safety
+///
+/// `list` must be a valid attribute list provided by the host.
+pub unsafe fn update_track_info(track_info: &mut TrackInfo, list: &dyn IAttributeList) {
+    let mut name_length = 0i64;
+    let name_length = if list.get_int(CHANNEL_NAME_LENGTH_KEY.as_ptr(), &mut name_length)
+        == kResultOk
+        && name_length > 0
+    {
+        name_length as usize
+    } else {
+        DEFAULT_CHANNEL_NAME_LENGTH
+    };
+
+    // The size passed to `get_string()` is in bytes, and it includes the null terminator
+    let mut name: Vec<TChar> = vec![0; name_length + 1];
+    let result = list.get_string(
+        CHANNEL_NAME_KEY.as_ptr(),
+        name.as_mut_ptr(),
+        (name.len() * std::mem::size_of::<TChar>()) as u32,
+    );
+    if result == kResultOk {
+        // Just in case the host didn't terminate the string
+        *name.last_mut().unwrap() = 0;
+        track_info.name =
+            Some(U16CStr::from_ptr_str(name.as_ptr() as *const u16).to_string_lossy());
+    }
+
+    // This is stored as a 32-bit ARGB color
+    let mut color = 0i64;
+    if list.get_int(CHANNEL_COLOR_KEY.as_ptr(), &mut color) == kResultOk {
+        let color = color as u32;
+        track_info.color = Some(TrackColor {
+            red: (color >> 16) as u8,
+            green: (color >> 8) as u8,
+            blue: color as u8,
+            alpha: (color >> 24) as u8,
+        });
+    }
+}
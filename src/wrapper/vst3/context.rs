@@ -1,16 +1,18 @@
-use atomic_refcell::AtomicRefMut;
+use atomic_refcell::{AtomicRef, AtomicRefMut};
 use std::cell::Cell;
 use std::collections::VecDeque;
+use std::os::raw::c_int;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use vst3_sys::vst::IComponentHandler;
 
 use super::inner::{Task, WrapperInner};
-use crate::context::gui::GuiContext;
+use crate::context::gui::{FdFlags, GuiContext, TimerId};
 use crate::context::init::InitContext;
 use crate::context::process::{ProcessContext, Transport};
-use crate::context::PluginApi;
-use crate::midi::PluginNoteEvent;
+use crate::context::{HostInfo, PluginApi, TrackInfo};
+use crate::midi::learn::MidiMapping;
+use crate::midi::{MidiConfig, PluginNoteEvent};
 use crate::params::internals::ParamPtr;
 use crate::plugin::Vst3Plugin;
 use crate::wrapper::state::PluginState;
@@ -27,6 +29,7 @@ use crate::wrapper::state::PluginState;
 pub(crate) struct WrapperInitContext<'a, P: Vst3Plugin> {
     pub(super) inner: &'a WrapperInner<P>,
     pub(super) pending_requests: PendingInitContextRequests,
+    pub(super) host_info_guard: AtomicRef<'a, HostInfo>,
 }
 
 /// Any requests that should be sent out when the [`WrapperInitContext`] is dropped. See that
@@ -35,6 +38,8 @@ pub(crate) struct WrapperInitContext<'a, P: Vst3Plugin> {
 pub(crate) struct PendingInitContextRequests {
     /// The value of the last `.set_latency_samples()` call.
     latency_changed: Cell<Option<u32>>,
+    /// Whether `.note_names_changed()` was called.
+    note_names_changed: Cell<bool>,
 }
 
 /// A [`ProcessContext`] implementation for the wrapper. This is a separate object so it can hold on
@@ -44,6 +49,8 @@ pub(crate) struct WrapperProcessContext<'a, P: Vst3Plugin> {
     pub(super) inner: &'a WrapperInner<P>,
     pub(super) input_events_guard: AtomicRefMut<'a, VecDeque<PluginNoteEvent<P>>>,
     pub(super) output_events_guard: AtomicRefMut<'a, VecDeque<PluginNoteEvent<P>>>,
+    pub(super) output_param_changes_guard: AtomicRefMut<'a, VecDeque<(u32, f32)>>,
+    pub(super) host_info_guard: AtomicRef<'a, HostInfo>,
     pub(super) transport: Transport,
 }
 
@@ -52,6 +59,9 @@ pub(crate) struct WrapperProcessContext<'a, P: Vst3Plugin> {
 /// with the host for things like setting parameters.
 pub(crate) struct WrapperGuiContext<P: Vst3Plugin> {
     pub(super) inner: Arc<WrapperInner<P>>,
+    /// A copy of the host's information. This doesn't change after the plugin has been
+    /// initialized, so it's copied when the editor gets opened instead of holding on to a guard.
+    pub(super) host_info: HostInfo,
     #[cfg(debug_assertions)]
     pub(super) param_gesture_checker:
         atomic_refcell::AtomicRefCell<crate::wrapper::util::context_checks::ParamGestureChecker>,
@@ -62,6 +72,9 @@ impl<P: Vst3Plugin> Drop for WrapperInitContext<'_, P> {
         if let Some(samples) = self.pending_requests.latency_changed.take() {
             self.inner.set_latency_samples(samples)
         }
+        if self.pending_requests.note_names_changed.take() {
+            self.inner.note_names_changed()
+        }
     }
 }
 
@@ -79,9 +92,22 @@ impl<P: Vst3Plugin> InitContext<P> for WrapperInitContext<'_, P> {
         self.pending_requests.latency_changed.set(Some(samples));
     }
 
+    fn note_names_changed(&self) {
+        // See this struct's docstring
+        self.pending_requests.note_names_changed.set(true);
+    }
+
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.inner.track_info()
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &self.host_info_guard
+    }
 }
 
 impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
@@ -119,6 +145,41 @@ impl<P: Vst3Plugin> ProcessContext<P> for WrapperProcessContext<'_, P> {
     fn set_current_voice_capacity(&self, _capacity: u32) {
         // This is only supported by CLAP
     }
+
+    fn note_names_changed(&self) {
+        self.inner.note_names_changed()
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &self.host_info_guard
+    }
+
+    // VST3 doesn't have gestures for parameter changes coming from the audio processor, the host
+    // only receives the values through the process data's output parameter changes
+    unsafe fn raw_begin_set_parameter(&mut self, param: ParamPtr) {
+        nih_debug_assert!(
+            self.inner.param_ptr_to_hash.contains_key(&param),
+            "Unknown parameter: {:?}",
+            param
+        );
+    }
+
+    unsafe fn raw_set_parameter_normalized(&mut self, param: ParamPtr, normalized: f32) {
+        match self.inner.param_ptr_to_hash.get(&param) {
+            Some(hash) => self
+                .output_param_changes_guard
+                .push_back((*hash, normalized)),
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    unsafe fn raw_end_set_parameter(&mut self, param: ParamPtr) {
+        nih_debug_assert!(
+            self.inner.param_ptr_to_hash.contains_key(&param),
+            "Unknown parameter: {:?}",
+            param
+        );
+    }
 }
 
 impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
@@ -142,6 +203,7 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
             Some(handler) => match self.inner.param_ptr_to_hash.get(&param) {
                 Some(hash) => {
                     handler.begin_edit(*hash);
+                    self.inner.undo_manager.begin_gesture(param);
                 }
                 None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
             },
@@ -183,6 +245,7 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
                     }
 
                     handler.perform_edit(*hash, normalized as f64);
+                    self.inner.undo_manager.record_value(param, normalized);
                 }
                 None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
             },
@@ -206,6 +269,7 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
             Some(handler) => match self.inner.param_ptr_to_hash.get(&param) {
                 Some(hash) => {
                     handler.end_edit(*hash);
+                    self.inner.undo_manager.end_gesture(param);
                 }
                 None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
             },
@@ -224,11 +288,106 @@ impl<P: Vst3Plugin> GuiContext for WrapperGuiContext<P> {
         }
     }
 
+    unsafe fn raw_start_midi_learn(&self, param: ParamPtr) {
+        nih_debug_assert!(
+            P::MIDI_INPUT >= MidiConfig::MidiCCs,
+            "MIDI learn requires the plugin to receive MIDI CCs"
+        );
+
+        match self.inner.param_id_from_ptr(param) {
+            Some(param_id) => self
+                .inner
+                .midi_learn
+                .start_learning(param, param_id.to_owned()),
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
+    fn cancel_midi_learn(&self) {
+        self.inner.midi_learn.cancel_learning();
+    }
+
+    fn midi_learn_target(&self) -> Option<ParamPtr> {
+        self.inner.midi_learn.learn_target()
+    }
+
+    unsafe fn raw_midi_mapping(&self, param: ParamPtr) -> Option<MidiMapping> {
+        self.inner.midi_learn.mapping(param)
+    }
+
+    unsafe fn raw_set_midi_mapping(&self, param: ParamPtr, mapping: Option<MidiMapping>) {
+        match self.inner.param_id_from_ptr(param) {
+            Some(param_id) => {
+                self.inner
+                    .midi_learn
+                    .set_mapping(param, param_id.to_owned(), mapping)
+            }
+            None => nih_debug_assert_failure!("Unknown parameter: {:?}", param),
+        }
+    }
+
     fn get_state(&self) -> PluginState {
         self.inner.get_state_object()
     }
 
     fn set_state(&self, state: PluginState) {
+        self.inner
+            .undo_manager
+            .record_state_change(self.inner.get_state_object(), state.clone());
         self.inner.set_state_object_from_gui(state)
     }
+
+    // VST3 hosts don't provide a way to add the plugin's changes to their undo history
+    fn undo(&self) {
+        if let Some(action) = self.inner.undo_manager.undo() {
+            self.inner.undo_manager.apply(self, action);
+        }
+    }
+
+    fn redo(&self) {
+        if let Some(action) = self.inner.undo_manager.redo() {
+            self.inner.undo_manager.apply(self, action);
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        self.inner.undo_manager.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.inner.undo_manager.can_redo()
+    }
+
+    fn note_names_changed(&self) {
+        self.inner.note_names_changed()
+    }
+
+    fn track_info(&self) -> Option<TrackInfo> {
+        self.inner.track_info()
+    }
+
+    fn host_info(&self) -> &HostInfo {
+        &self.host_info
+    }
+
+    fn register_timer(&self, period_ms: u32, callback: Box<dyn FnMut() + Send>) -> Option<TimerId> {
+        self.inner.register_timer(period_ms, callback)
+    }
+
+    fn unregister_timer(&self, timer_id: TimerId) {
+        self.inner.unregister_timer(timer_id)
+    }
+
+    fn register_fd(
+        &self,
+        fd: c_int,
+        flags: FdFlags,
+        callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        self.inner.register_fd(fd, flags, callback)
+    }
+
+    fn unregister_fd(&self, fd: c_int) {
+        self.inner.unregister_fd(fd)
+    }
 }
@@ -3,30 +3,42 @@ use crossbeam::atomic::AtomicCell;
 use crossbeam::channel::{self, SendTimeoutError};
 use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::os::raw::c_int;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use vst3_sys::base::{kInvalidArgument, kResultOk, tresult};
-use vst3_sys::vst::{IComponentHandler, RestartFlags};
+use vst3_sys::vst::{IComponentHandler, IUnitHandler, RestartFlags};
 
 use super::context::{WrapperGuiContext, WrapperInitContext, WrapperProcessContext};
 use super::note_expressions::NoteExpressionController;
 use super::param_units::ParamUnits;
-use super::util::{ObjectPtr, VstPtr, VST3_MIDI_PARAMS_END, VST3_MIDI_PARAMS_START};
+use super::util::{
+    ObjectPtr, VstPtr, VST3_FACTORY_PRESETS_PROGRAM_LIST_ID, VST3_MIDI_PARAMS_END,
+    VST3_MIDI_PARAMS_START, VST3_NOTE_NAMES_PROGRAM_LIST_ID, VST3_PROGRAM_CHANGE_PARAM_ID,
+};
 use super::view::WrapperView;
 use crate::audio_setup::{AudioIOLayout, BufferConfig, ProcessMode};
-use crate::buffer::Buffer;
-use crate::context::gui::AsyncExecutor;
+use crate::context::gui::{AsyncExecutor, FdFlags, TimerId};
 use crate::context::process::Transport;
+use crate::context::{HostInfo, TrackInfo};
 use crate::editor::Editor;
 use crate::event_loop::{EventLoop, MainThreadExecutor, OsEventLoop};
+use crate::midi::note_names::NoteName;
 use crate::midi::{MidiConfig, PluginNoteEvent};
 use crate::params::internals::ParamPtr;
 use crate::params::{ParamFlags, Params};
 use crate::plugin::{Plugin, ProcessStatus, TaskExecutor, Vst3Plugin};
 use crate::util::permit_alloc;
-use crate::wrapper::state::{self, PluginState};
+use crate::wrapper::state::{self, FactoryPreset, PluginState, StateContext};
+use crate::wrapper::util::buffer_management::{AuxPortActivation, ProcessBuffers};
+use crate::wrapper::util::gui_callbacks::GuiCallbacks;
+use crate::wrapper::util::midi_learn::MidiLearn;
+use crate::wrapper::util::mpe::MpeTranslator;
+use crate::wrapper::util::undo::UndoManager;
 use crate::wrapper::util::{hash_param_id, process_wrapper};
+#[cfg(target_os = "linux")]
+use {super::run_loop::RunLoopRegistrations, vst3_sys::gui::linux::IRunLoop};
 
 /// The actual wrapper bits. We need this as an `Arc<T>` so we can safely use our event loop API.
 /// Since we can't combine that with VST3's interior reference counting this just has to be moved to
@@ -82,23 +94,16 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// The current latency in samples, as set by the plugin through the [`InitContext`] and the
     /// [`ProcessContext`].
     pub current_latency: AtomicU32,
-    /// Contains slices for the plugin's outputs. You can't directly create a nested slice from
-    /// a pointer to pointers, so this needs to be preallocated in the setup call and kept around
-    /// between process calls. This buffer owns the vector, because otherwise it would need to store
-    /// a mutable reference to the data contained in this mutex.
-    pub output_buffer: AtomicRefCell<Buffer<'static>>,
-    /// Stores sample data for every sidechain input the plugin has. Indexed by
-    /// `[sidechain_input][channel][sample]` We'll copy the data to these buffers since modifying
-    /// the host's sidechain input buffers may not be safe, and the plugin may want to be able to
-    /// modify the buffers.
-    pub aux_input_storage: AtomicRefCell<Vec<Vec<Vec<f32>>>>,
-    /// Accompanying buffers for `aux_input_storage`. There is no way to do this in safe Rust, so
-    /// the process function needs to make sure all channel pointers stored in these buffers are
-    /// still correct before passing it to the plugin, hence the static lifetime.
-    pub aux_input_buffers: AtomicRefCell<Vec<Buffer<'static>>>,
-    /// Buffers for auxiliary plugin outputs, if the plugin has any. These reference the host's
-    /// memory directly.
-    pub aux_output_buffers: AtomicRefCell<Vec<Buffer<'static>>>,
+    /// The buffers and sidechain input storage used to pass the host's audio buffers to the plugin
+    /// during single precision processing. These are preallocated when the plugin gets activated.
+    pub buffers: AtomicRefCell<ProcessBuffers<f32>>,
+    /// The same as `buffers`, but for double precision processing. These are only allocated when
+    /// the plugin supports 64-bit processing through [`Plugin::F64_PROCESSING`].
+    pub buffers_f64: AtomicRefCell<ProcessBuffers<f64>>,
+    /// Which of the auxiliary busses the host has activated through
+    /// `IComponent::activateBus()`. This is sized for the current audio IO layout when the plugin
+    /// gets activated.
+    pub aux_port_activation: AtomicRefCell<AuxPortActivation>,
     /// The incoming events for the plugin, if `P::ACCEPTS_MIDI` is set. If
     /// `P::SAMPLE_ACCURATE_AUTOMATION`, this is also read in lockstep with the parameter change
     /// block splitting.
@@ -107,9 +112,23 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     ///       interleave parameter changes and note events, this queue has to be sorted when
     ///       creating the process context
     pub input_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Translates per-channel MPE expressions to per-note expression events if the plugin's
+    /// `P::MIDI_INPUT` is set to `MidiConfig::Mpe`. The sorted note events are passed through this
+    /// before being added to `input_events`.
+    pub mpe_translator: AtomicRefCell<MpeTranslator>,
+    /// The plugin's MIDI mappings. Mapped MIDI messages are turned into parameter changes before
+    /// the block's events are passed to the plugin.
+    pub midi_learn: MidiLearn,
+    /// The undo history for parameter gestures and state changes made through the `GuiContext`.
+    pub undo_manager: UndoManager,
     /// Stores any events the plugin has output during the current processing cycle, analogous to
     /// `input_events`.
     pub output_events: AtomicRefCell<VecDeque<PluginNoteEvent<P>>>,
+    /// Parameter changes made by the plugin from the audio thread during the current processing
+    /// cycle, stored as parameter hashes and normalized values. These are written to the host's
+    /// output parameter changes, and applied to the plugin's parameters, after the current
+    /// (sub)block has been processed.
+    pub output_param_changes: AtomicRefCell<VecDeque<(u32, f32)>>,
     /// VST3 has several useful predefined note expressions, but for some reason they are the only
     /// note event type that don't have MIDI note ID and channel fields. So we need to keep track of
     /// the most recent VST3 note IDs we've seen, and then map those back to MIDI note IDs and
@@ -155,6 +174,32 @@ pub(crate) struct WrapperInner<P: Vst3Plugin> {
     /// having to add a setter function to the parameter (or even worse, have it be completely
     /// untyped).
     pub param_ptr_to_hash: HashMap<ParamPtr, u32>,
+
+    /// The plugin's factory presets as returned by [`Plugin::factory_presets()`]. If this is not
+    /// empty, then the presets are exposed to the host as a program list on the root unit together
+    /// with a program change parameter.
+    pub factory_presets: Vec<FactoryPreset>,
+    /// The index of the factory preset that was last loaded through the program change parameter,
+    /// if any. Used to report the program change parameter's current value back to the host.
+    pub current_factory_preset: AtomicCell<Option<usize>>,
+    /// The plugin's note names as returned by [`Plugin::note_names()`]. These are fetched when the
+    /// plugin gets created and again when the plugin informs us that the names have changed, so
+    /// the host's queries don't need to lock the plugin.
+    pub note_names: Mutex<Vec<NoteName>>,
+    /// Information about the track the plugin is inserted on. This is set when the host calls
+    /// `IInfoListener::setChannelContextInfos()`, and it stays `None` if the host never does.
+    pub track_info: Mutex<Option<TrackInfo>>,
+    /// Information about the host, queried through the `IHostApplication` object the host passes
+    /// to `IPluginBase::initialize()`. This is only written to during that call.
+    pub host_info: AtomicRefCell<HostInfo>,
+
+    /// The callbacks for the timers and file descriptor watches the editor registered through the
+    /// `GuiContext`. These are removed again when the editor is closed.
+    pub gui_callbacks: GuiCallbacks,
+    /// The host's `IRunLoop` along with the timers and file descriptor watches registered with it.
+    /// This is set while the editor's view has an `IPlugFrame` that provides a run loop.
+    #[cfg(target_os = "linux")]
+    pub run_loop_registrations: Mutex<Option<RunLoopRegistrations<P>>>,
 }
 
 /// Tasks that can be sent from the plugin to be executed on the main thread in a non-blocking
@@ -175,6 +220,19 @@ pub enum Task<P: Plugin> {
     /// Request the editor to be resized according to its current size. Right now there is no way to
     /// handle "denied resize" requests yet.
     RequestResize,
+    /// Load the factory preset with the given index from [`WrapperInner::factory_presets`]. Presets
+    /// are selected through the program change parameter, which may also be changed from the audio
+    /// thread.
+    LoadFactoryPreset(usize),
+    /// Clear the cached note names and inform the host that the plugin's note names have changed.
+    NoteNamesChanged,
+    /// Run the callback for a timer registered through the `GuiContext`.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    GuiTimer(u32),
+    /// Run the callback for a file descriptor registered through the `GuiContext` with the events
+    /// that occurred.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    GuiFdReady(c_int, FdFlags),
 }
 
 /// VST3 makes audio processing pretty complicated. In order to support both block splitting for
@@ -208,6 +266,11 @@ impl<P: Vst3Plugin> WrapperInner<P> {
     pub fn new() -> Arc<Self> {
         let plugin = P::default();
         let task_executor = Mutex::new(plugin.task_executor());
+        let note_names = if P::MIDI_INPUT >= MidiConfig::Basic {
+            plugin.note_names()
+        } else {
+            Vec::new()
+        };
 
         // This is used to allow the plugin to restore preset data from its editor, see the comment
         // on `Self::updated_state_sender`
@@ -262,6 +325,14 @@ impl<P: Vst3Plugin> WrapperInner<P> {
                         id
                     );
                 }
+
+                if *hash == VST3_PROGRAM_CHANGE_PARAM_ID {
+                    nih_debug_assert_failure!(
+                        "Parameter '{}' collides with the program change parameter, consider \
+                         giving it a different ID",
+                        id
+                    );
+                }
             }
         }
 
@@ -317,12 +388,15 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             current_process_mode: AtomicCell::new(ProcessMode::Realtime),
             last_process_status: AtomicCell::new(ProcessStatus::Normal),
             current_latency: AtomicU32::new(0),
-            output_buffer: AtomicRefCell::new(Buffer::default()),
-            aux_input_storage: AtomicRefCell::new(Vec::new()),
-            aux_input_buffers: AtomicRefCell::new(Vec::new()),
-            aux_output_buffers: AtomicRefCell::new(Vec::new()),
+            buffers: AtomicRefCell::new(ProcessBuffers::default()),
+            buffers_f64: AtomicRefCell::new(ProcessBuffers::default()),
+            aux_port_activation: AtomicRefCell::new(AuxPortActivation::default()),
             input_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
+            mpe_translator: AtomicRefCell::new(MpeTranslator::new(P::MIDI_INPUT)),
+            midi_learn: MidiLearn::default(),
+            undo_manager: UndoManager::default(),
             output_events: AtomicRefCell::new(VecDeque::with_capacity(1024)),
+            output_param_changes: AtomicRefCell::new(VecDeque::with_capacity(1024)),
             note_expression_controller: AtomicRefCell::new(NoteExpressionController::default()),
             process_events: AtomicRefCell::new(Vec::with_capacity(4096)),
             updated_state_sender,
@@ -334,6 +408,16 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             param_units,
             param_id_to_hash,
             param_ptr_to_hash,
+
+            factory_presets: P::factory_presets(),
+            current_factory_preset: AtomicCell::new(None),
+            note_names: Mutex::new(note_names),
+            track_info: Mutex::new(None),
+            host_info: AtomicRefCell::new(HostInfo::default()),
+
+            gui_callbacks: GuiCallbacks::default(),
+            #[cfg(target_os = "linux")]
+            run_loop_registrations: Mutex::new(None),
         });
 
         // FIXME: Right now this is safe, but if we are going to have a singleton main thread queue
@@ -371,6 +455,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
 
     pub fn make_gui_context(self: Arc<Self>) -> Arc<WrapperGuiContext<P>> {
         Arc::new(WrapperGuiContext {
+            host_info: self.host_info.borrow().clone(),
             inner: self,
             #[cfg(debug_assertions)]
             param_gesture_checker: Default::default(),
@@ -385,6 +470,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         WrapperInitContext {
             inner: self,
             pending_requests: Default::default(),
+            host_info_guard: self.host_info.borrow(),
         }
     }
 
@@ -393,6 +479,8 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             inner: self,
             input_events_guard: self.input_events.borrow_mut(),
             output_events_guard: self.output_events.borrow_mut(),
+            output_param_changes_guard: self.output_param_changes.borrow_mut(),
+            host_info_guard: self.host_info.borrow(),
             transport,
         }
     }
@@ -437,8 +525,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
     }
 
     /// Get a parameter's ID based on a `ParamPtr`. Used in the `GuiContext` implementation for the
-    /// gesture checks.
-    #[allow(unused)]
+    /// gesture checks and for MIDI learn.
     pub fn param_id_from_ptr(&self, param: ParamPtr) -> Option<&str> {
         self.param_ptr_to_hash
             .get(&param)
@@ -479,19 +566,23 @@ impl<P: Vst3Plugin> WrapperInner<P> {
 
     /// Get the plugin's state object, may be called by the plugin's GUI as part of its own preset
     /// management. The wrapper doesn't use these functions and serializes and deserializes directly
-    /// the JSON in the relevant plugin API methods instead.
+    /// the JSON in the relevant plugin API methods instead. Since this is used for presets, this
+    /// uses [`StateContext::Preset`].
     pub fn get_state_object(&self) -> PluginState {
         unsafe {
             state::serialize_object::<P>(
                 self.params.clone(),
                 state::make_params_iter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
+                StateContext::Preset,
             )
         }
     }
 
     /// Update the plugin's internal state, called by the plugin itself from the GUI thread. To
     /// prevent corrupting data and changing parameters during processing the actual state is only
-    /// updated at the end of the audio processing cycle.
+    /// updated at the end of the audio processing cycle. This is also used to load factory presets,
+    /// so the state is loaded using [`StateContext::Preset`].
     pub fn set_state_object_from_gui(&self, mut state: PluginState) {
         // Use a loop and timeouts to handle the super rare edge case when this function gets called
         // between a process call and the host disabling the plugin
@@ -525,7 +616,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
             } else {
                 // Otherwise we'll set the state right here and now, since this function should be
                 // called from a GUI thread
-                self.set_state_inner(&mut state);
+                self.set_state_inner(&mut state, StateContext::Preset);
                 break;
             }
         }
@@ -552,6 +643,158 @@ impl<P: Vst3Plugin> WrapperInner<P> {
         }
     }
 
+    pub fn note_names_changed(&self) {
+        let task_posted = self.schedule_gui(Task::NoteNamesChanged);
+        nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+    }
+
+    /// Get the information about the track the plugin is inserted on, if the host provided any.
+    pub fn track_info(&self) -> Option<TrackInfo> {
+        self.track_info.lock().clone()
+    }
+
+    /// Register a timer with the host's `IRunLoop`. This is only possible while the editor's view
+    /// has a run loop. Must be called from the GUI thread.
+    #[cfg(target_os = "linux")]
+    pub fn register_timer(
+        self: &Arc<Self>,
+        period_ms: u32,
+        callback: Box<dyn FnMut() + Send>,
+    ) -> Option<TimerId> {
+        match &mut *self.run_loop_registrations.lock() {
+            Some(registrations) => {
+                let timer_id = self.gui_callbacks.next_timer_id();
+                if registrations.register_timer(Arc::downgrade(self), timer_id, period_ms) {
+                    self.gui_callbacks.insert_timer(timer_id, callback);
+                    Some(TimerId(timer_id))
+                } else {
+                    nih_debug_assert_failure!("The host rejected the timer registration");
+                    None
+                }
+            }
+            None => None,
+        }
+    }
+
+    /// Register a timer with the host's `IRunLoop`. This is only supported on Linux.
+    #[cfg(not(target_os = "linux"))]
+    pub fn register_timer(
+        self: &Arc<Self>,
+        _period_ms: u32,
+        _callback: Box<dyn FnMut() + Send>,
+    ) -> Option<TimerId> {
+        None
+    }
+
+    /// Unregister a timer that was registered with [`register_timer()`][Self::register_timer()].
+    /// Must be called from the GUI thread.
+    pub fn unregister_timer(&self, timer_id: TimerId) {
+        #[cfg(target_os = "linux")]
+        if let Some(registrations) = &mut *self.run_loop_registrations.lock() {
+            registrations.unregister_timer(timer_id.0);
+        }
+
+        if !self.gui_callbacks.remove_timer(timer_id.0) {
+            nih_debug_assert_failure!("Tried to unregister an unknown timer: {:?}", timer_id);
+        }
+    }
+
+    /// Watch a file descriptor using the host's `IRunLoop`. This is only possible while the
+    /// editor's view has a run loop. Must be called from the GUI thread.
+    #[cfg(target_os = "linux")]
+    pub fn register_fd(
+        self: &Arc<Self>,
+        fd: c_int,
+        _flags: FdFlags,
+        callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        match &mut *self.run_loop_registrations.lock() {
+            Some(_) if self.gui_callbacks.contains_fd(fd) => {
+                nih_debug_assert_failure!("File descriptor {} is already being watched", fd);
+                false
+            }
+            Some(registrations) => {
+                // `IRunLoop` only watches file descriptors for reads, so the flags are ignored
+                let success = registrations.register_fd(Arc::downgrade(self), fd);
+                if success {
+                    self.gui_callbacks.insert_fd(fd, callback);
+                } else {
+                    nih_debug_assert_failure!("The host rejected file descriptor {}", fd);
+                }
+
+                success
+            }
+            None => false,
+        }
+    }
+
+    /// Watch a file descriptor using the host's `IRunLoop`. This is only supported on Linux.
+    #[cfg(not(target_os = "linux"))]
+    pub fn register_fd(
+        self: &Arc<Self>,
+        _fd: c_int,
+        _flags: FdFlags,
+        _callback: Box<dyn FnMut(FdFlags) + Send>,
+    ) -> bool {
+        false
+    }
+
+    /// Stop watching a file descriptor that was registered with
+    /// [`register_fd()`][Self::register_fd()]. Must be called from the GUI thread.
+    pub fn unregister_fd(&self, fd: c_int) {
+        #[cfg(target_os = "linux")]
+        if let Some(registrations) = &mut *self.run_loop_registrations.lock() {
+            registrations.unregister_fd(fd);
+        }
+
+        if !self.gui_callbacks.remove_fd(fd) {
+            nih_debug_assert_failure!("Tried to unregister unknown file descriptor {}", fd);
+        }
+    }
+
+    /// Unregister all of the editor's timers and file descriptor watches. Called when the editor
+    /// is closed.
+    pub fn unregister_gui_callbacks(&self) {
+        #[cfg(target_os = "linux")]
+        if let Some(registrations) = &mut *self.run_loop_registrations.lock() {
+            registrations.unregister_all();
+        }
+
+        // The callbacks won't be called anymore, so they can be dropped
+        self.gui_callbacks.take_timer_ids();
+        self.gui_callbacks.take_fds();
+    }
+
+    /// Set the host's `IRunLoop` when the host passes a new `IPlugFrame` to the editor's view, or
+    /// clear it when the frame is removed. Anything registered with the old run loop is
+    /// unregistered.
+    #[cfg(target_os = "linux")]
+    pub fn set_run_loop(&self, run_loop: Option<VstPtr<dyn IRunLoop>>) {
+        self.unregister_gui_callbacks();
+        *self.run_loop_registrations.lock() = run_loop.map(RunLoopRegistrations::new);
+    }
+
+    /// Call `f` with the plugin's cached note names.
+    pub fn with_note_names<T>(&self, f: impl FnOnce(&[NoteName]) -> T) -> T {
+        f(&self.note_names.lock())
+    }
+
+    /// The ID of the program list on the root unit, if there is one. This is the factory preset
+    /// list if the plugin has factory presets. VST3 only supports pitch names for programs, so
+    /// otherwise a list with a single program is published when the plugin has note names for the
+    /// first channel.
+    pub fn program_list_id(&self) -> Option<i32> {
+        if !self.factory_presets.is_empty() {
+            Some(VST3_FACTORY_PRESETS_PROGRAM_LIST_ID)
+        } else if self
+            .with_note_names(|note_names| note_names.iter().any(|name| name.matches_channel(0)))
+        {
+            Some(VST3_NOTE_NAMES_PROGRAM_LIST_ID)
+        } else {
+            None
+        }
+    }
+
     /// Immediately set the plugin state. Returns `false` if the deserialization failed. The plugin
     /// state is set from a couple places, so this function aims to deduplicate that. Includes
     /// `permit_alloc()`s around the deserialization and initialization for the use case where
@@ -562,7 +805,7 @@ impl<P: Vst3Plugin> WrapperInner<P> {
     /// # Notes
     ///
     /// `self.plugin` must _not_ be locked while calling this function or it will deadlock.
-    pub fn set_state_inner(&self, state: &mut PluginState) -> bool {
+    pub fn set_state_inner(&self, state: &mut PluginState, context: StateContext) -> bool {
         let audio_io_layout = self.current_audio_io_layout.load();
         let buffer_config = self.current_buffer_config.load();
 
@@ -577,7 +820,9 @@ impl<P: Vst3Plugin> WrapperInner<P> {
                 state,
                 self.params.clone(),
                 state::make_params_getter(&self.param_by_hash, &self.param_id_to_hash),
+                &self.midi_learn,
                 buffer_config.as_ref(),
+                context,
             )
         });
         if !success {
@@ -664,6 +909,50 @@ impl<P: Vst3Plugin> MainThreadExecutor<Task<P>> for WrapperInner<P> {
                 },
                 None => nih_debug_assert_failure!("Can't resize a closed editor"),
             },
+            Task::LoadFactoryPreset(preset_idx) => match self.factory_presets.get(preset_idx) {
+                Some(preset) => {
+                    self.current_factory_preset.store(Some(preset_idx));
+                    self.set_state_object_from_gui(preset.state.clone());
+                }
+                None => nih_debug_assert_failure!("Unknown factory preset index {}", preset_idx),
+            },
+            Task::NoteNamesChanged => {
+                if P::MIDI_INPUT >= MidiConfig::Basic {
+                    let note_names = self.plugin.lock().note_names();
+                    *self.note_names.lock() = note_names;
+                }
+
+                match &*self.component_handler.borrow() {
+                    Some(handler) => unsafe {
+                        nih_debug_assert!(is_gui_thread);
+
+                        // The pitch names are attached to the root unit's program list, and the
+                        // keyswitches have their own restart flag
+                        if let Some(program_list_id) = self.program_list_id() {
+                            if let Some(unit_handler) = handler.cast::<dyn IUnitHandler>() {
+                                unit_handler.notify_program_list_change(program_list_id, -1);
+                            }
+                        }
+
+                        let result =
+                            handler.restart_component(RestartFlags::kKeyswitchChanged as i32);
+                        nih_debug_assert_eq!(
+                            result,
+                            kResultOk,
+                            "Failed the restart request call for the changed keyswitches"
+                        );
+                    },
+                    None => nih_debug_assert_failure!("Component handler not yet set"),
+                }
+            }
+            Task::GuiTimer(timer_id) => {
+                nih_debug_assert!(is_gui_thread);
+                self.gui_callbacks.call_timer(timer_id);
+            }
+            Task::GuiFdReady(fd, flags) => {
+                nih_debug_assert!(is_gui_thread);
+                self.gui_callbacks.call_fd(fd, flags);
+            }
         }
     }
 }
@@ -0,0 +1,190 @@
+//! Timers and file descriptor watches registered with the host's `IRunLoop` on Linux. These are
+//! used to implement [`GuiContext::register_timer()`][crate::prelude::GuiContext::register_timer()]
+//! and [`GuiContext::register_fd()`][crate::prelude::GuiContext::register_fd()]. `IRunLoop`
+//! identifies timers and file descriptor watches by their handler objects, so every timer and file
+//! descriptor gets its own handler.
+
+use std::collections::HashMap;
+use std::mem;
+use std::os::raw::c_int;
+use std::sync::Weak;
+use vst3_sys::base::kResultOk;
+use vst3_sys::gui::linux::{FileDescriptor, IEventHandler, IRunLoop, ITimerHandler};
+use vst3_sys::utils::SharedVstPtr;
+use vst3_sys::VST3;
+
+use super::inner::{Task, WrapperInner};
+use super::util::VstPtr;
+use crate::context::gui::FdFlags;
+use crate::event_loop::MainThreadExecutor;
+use crate::plugin::Vst3Plugin;
+
+// Alias needed for the VST3 attribute macro
+use vst3_sys as vst3_com;
+
+/// The host's run loop along with the timers and file descriptor watches registered with it. This
+/// is created when the host passes an `IPlugFrame` to the editor's view, and everything is
+/// unregistered from the host again when this object gets dropped.
+pub(crate) struct RunLoopRegistrations<P: Vst3Plugin> {
+    run_loop: VstPtr<dyn IRunLoop>,
+
+    /// The handlers for the registered timers, indexed by timer ID.
+    timers: HashMap<u32, Box<RunLoopTimerHandler<P>>>,
+    /// The handlers for the watched file descriptors, indexed by file descriptor.
+    fds: HashMap<c_int, Box<RunLoopFdHandler<P>>>,
+    /// Handlers that have already been unregistered from the host. A callback may unregister its
+    /// own timer or file descriptor while the host is still calling that handler, so the handlers
+    /// are only freed when this object gets dropped.
+    unregistered_timers: Vec<Box<RunLoopTimerHandler<P>>>,
+    unregistered_fds: Vec<Box<RunLoopFdHandler<P>>>,
+}
+
+/// An `ITimerHandler` for a single timer. The timer's callback is run through the wrapper's
+/// [`MainThreadExecutor`] implementation.
+#[VST3(implements(ITimerHandler))]
+struct RunLoopTimerHandler<P: Vst3Plugin> {
+    /// This is a weak reference because the wrapper owns this handler.
+    inner: Weak<WrapperInner<P>>,
+    timer_id: u32,
+}
+
+/// An `IEventHandler` for a single file descriptor. Works the same way as [`RunLoopTimerHandler`].
+#[VST3(implements(IEventHandler))]
+struct RunLoopFdHandler<P: Vst3Plugin> {
+    inner: Weak<WrapperInner<P>>,
+}
+
+// SAFETY: The handlers are only `!Send` because of their vtable pointers. They're only created and
+//         used from the GUI thread.
+unsafe impl<P: Vst3Plugin> Send for RunLoopRegistrations<P> {}
+
+impl<P: Vst3Plugin> RunLoopRegistrations<P> {
+    pub fn new(run_loop: VstPtr<dyn IRunLoop>) -> Self {
+        Self {
+            run_loop,
+
+            timers: HashMap::new(),
+            fds: HashMap::new(),
+            unregistered_timers: Vec::new(),
+            unregistered_fds: Vec::new(),
+        }
+    }
+
+    /// Register a timer with the host's run loop. Returns `false` if the host rejected the timer.
+    pub fn register_timer(
+        &mut self,
+        inner: Weak<WrapperInner<P>>,
+        timer_id: u32,
+        period_ms: u32,
+    ) -> bool {
+        let handler = RunLoopTimerHandler::allocate(inner, timer_id);
+        let result = unsafe {
+            self.run_loop
+                .register_timer(handler.as_shared_ptr(), period_ms as u64)
+        };
+        if result == kResultOk {
+            self.timers.insert(timer_id, handler);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Unregister a timer. Returns `false` if the timer was not registered.
+    pub fn unregister_timer(&mut self, timer_id: u32) -> bool {
+        match self.timers.remove(&timer_id) {
+            Some(handler) => {
+                unsafe { self.run_loop.unregister_timer(handler.as_shared_ptr()) };
+                self.unregistered_timers.push(handler);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Watch a file descriptor using the host's run loop. Returns `false` if the host rejected the
+    /// file descriptor.
+    pub fn register_fd(&mut self, inner: Weak<WrapperInner<P>>, fd: c_int) -> bool {
+        let handler = RunLoopFdHandler::allocate(inner);
+        let result = unsafe {
+            self.run_loop
+                .register_event_handler(handler.as_shared_ptr(), fd)
+        };
+        if result == kResultOk {
+            self.fds.insert(fd, handler);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Stop watching a file descriptor. Returns `false` if the file descriptor was not being
+    /// watched.
+    pub fn unregister_fd(&mut self, fd: c_int) -> bool {
+        match self.fds.remove(&fd) {
+            Some(handler) => {
+                unsafe {
+                    self.run_loop
+                        .unregister_event_handler(handler.as_shared_ptr())
+                };
+                self.unregistered_fds.push(handler);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Unregister all timers and file descriptor watches from the host.
+    pub fn unregister_all(&mut self) {
+        for (_, handler) in self.timers.drain() {
+            unsafe { self.run_loop.unregister_timer(handler.as_shared_ptr()) };
+            self.unregistered_timers.push(handler);
+        }
+        for (_, handler) in self.fds.drain() {
+            unsafe {
+                self.run_loop
+                    .unregister_event_handler(handler.as_shared_ptr())
+            };
+            self.unregistered_fds.push(handler);
+        }
+    }
+}
+
+impl<P: Vst3Plugin> Drop for RunLoopRegistrations<P> {
+    fn drop(&mut self) {
+        self.unregister_all();
+    }
+}
+
+impl<P: Vst3Plugin> RunLoopTimerHandler<P> {
+    /// vst3-sys provides no way to convert to a `SharedVstPtr`, so this is a pointer to the vtable
+    /// pointer.
+    fn as_shared_ptr(&self) -> SharedVstPtr<dyn ITimerHandler> {
+        unsafe { mem::transmute(&self.__itimerhandlervptr as *const *const _) }
+    }
+}
+
+impl<P: Vst3Plugin> RunLoopFdHandler<P> {
+    /// See [`RunLoopTimerHandler::as_shared_ptr()`].
+    fn as_shared_ptr(&self) -> SharedVstPtr<dyn IEventHandler> {
+        unsafe { mem::transmute(&self.__ieventhandlervptr as *const *const _) }
+    }
+}
+
+impl<P: Vst3Plugin> ITimerHandler for RunLoopTimerHandler<P> {
+    unsafe fn on_timer(&self) {
+        // This is called from the host's GUI thread
+        if let Some(inner) = self.inner.upgrade() {
+            inner.execute(Task::GuiTimer(self.timer_id), true);
+        }
+    }
+}
+
+impl<P: Vst3Plugin> IEventHandler for RunLoopFdHandler<P> {
+    unsafe fn on_fd_is_set(&self, fd: FileDescriptor) {
+        // VST3 doesn't tell us which events occurred, but the host only watches for reads
+        if let Some(inner) = self.inner.upgrade() {
+            inner.execute(Task::GuiFdReady(fd, FdFlags::READ), true);
+        }
+    }
+}
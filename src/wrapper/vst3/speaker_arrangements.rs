@@ -0,0 +1,90 @@
+//! Conversions between NIH-plug's channel layouts and VST3 speaker arrangements. A speaker
+//! arrangement is a bit mask of speakers, and the channels are ordered by their bit positions.
+
+use std::num::NonZeroU32;
+use vst3_sys::vst::SpeakerArrangement;
+
+use crate::audio_setup::ChannelLayout;
+
+// These arrangements are not part of `vst3-sys`
+/// `k71_4`: L, R, C, Lfe, Ls, Rs, Sl, Sr, Tfl, Tfr, Trl, Trr.
+const K71_4: SpeakerArrangement =
+    vst3_sys::vst::k51 | (1 << 9) | (1 << 10) | (1 << 12) | (1 << 14) | (1 << 15) | (1 << 17);
+/// `kAmbi1stOrderACN`: `kSpeakerACN0` through `kSpeakerACN3`.
+const K_AMBI_1ST_ORDER_ACN: SpeakerArrangement = 0b1111 << 20;
+/// `kAmbi2ndOrderACN`: the first order channels plus `kSpeakerACN4` through `kSpeakerACN8`.
+const K_AMBI_2ND_ORDER_ACN: SpeakerArrangement = K_AMBI_1ST_ORDER_ACN | (0b11111 << 38);
+/// `kAmbi3rdOrderACN`: the second order channels plus `kSpeakerACN9` through `kSpeakerACN15`.
+const K_AMBI_3RD_ORDER_ACN: SpeakerArrangement = K_AMBI_2ND_ORDER_ACN | (0b1111111 << 43);
+
+/// The speaker arrangement for a channel layout.
+pub fn speaker_arrangement(layout: ChannelLayout) -> SpeakerArrangement {
+    match layout {
+        ChannelLayout::Mono => vst3_sys::vst::kMono,
+        ChannelLayout::Stereo => vst3_sys::vst::kStereo,
+        ChannelLayout::Surround51 => vst3_sys::vst::k51,
+        ChannelLayout::Surround714 => K71_4,
+        ChannelLayout::AmbisonicFirstOrder => K_AMBI_1ST_ORDER_ACN,
+        ChannelLayout::AmbisonicSecondOrder => K_AMBI_2ND_ORDER_ACN,
+        ChannelLayout::AmbisonicThirdOrder => K_AMBI_3RD_ORDER_ACN,
+    }
+}
+
+/// A speaker arrangement for a port without an explicit channel layout. This picks the most common
+/// arrangement for the channel count.
+pub fn speaker_arrangement_for_channel_count(num_channels: u32) -> SpeakerArrangement {
+    match num_channels {
+        0 => vst3_sys::vst::kEmpty,
+        1 => vst3_sys::vst::kMono,
+        2 => vst3_sys::vst::kStereo,
+        5 => vst3_sys::vst::k50,
+        6 => vst3_sys::vst::k51,
+        7 => vst3_sys::vst::k70Cine,
+        8 => vst3_sys::vst::k71Cine,
+        n => {
+            nih_debug_assert_failure!(
+                "No defined layout for {} channels, making something up on the spot...",
+                n
+            );
+            (1 << n) - 1
+        }
+    }
+}
+
+/// Check whether a speaker arrangement requested by the host can be used for a port. Ports with an
+/// explicit channel layout need to match that layout's speaker arrangement exactly. For other ports
+/// only the number of channels is compared.
+pub fn speaker_arrangement_matches(
+    arrangement: SpeakerArrangement,
+    num_channels: NonZeroU32,
+    layout: Option<ChannelLayout>,
+) -> bool {
+    match layout {
+        Some(layout) => arrangement == speaker_arrangement(layout),
+        None => arrangement.count_ones() == num_channels.get(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_counts_match() {
+        for layout in [
+            ChannelLayout::Mono,
+            ChannelLayout::Stereo,
+            ChannelLayout::Surround51,
+            ChannelLayout::Surround714,
+            ChannelLayout::AmbisonicFirstOrder,
+            ChannelLayout::AmbisonicSecondOrder,
+            ChannelLayout::AmbisonicThirdOrder,
+        ] {
+            assert_eq!(
+                speaker_arrangement(layout).count_ones(),
+                layout.num_channels(),
+                "{layout:?}"
+            );
+        }
+    }
+}
@@ -19,6 +19,15 @@ pub const VST3_MIDI_PARAMS_START: u32 = VST3_MIDI_PARAMS_END - VST3_MIDI_NUM_PAR
 /// The (exclusive) end of the MIDI CC parameter range. Anything above this is reserved by the host.
 pub const VST3_MIDI_PARAMS_END: u32 = 1 << 31;
 
+/// The ID of the program change parameter used to select factory presets. This parameter only
+/// exists if the plugin has factory presets, and it sits right below the MIDI CC parameter range.
+pub const VST3_PROGRAM_CHANGE_PARAM_ID: u32 = VST3_MIDI_PARAMS_START - 1;
+/// The ID of the program list containing the plugin's factory presets.
+pub const VST3_FACTORY_PRESETS_PROGRAM_LIST_ID: i32 = 0;
+/// The ID of the program list containing a single program that's used to expose the plugin's note
+/// names as pitch names when the plugin doesn't have any factory presets.
+pub const VST3_NOTE_NAMES_PROGRAM_LIST_ID: i32 = 1;
+
 /// Early exit out of a VST3 function when one of the passed pointers is null
 macro_rules! check_null_ptr {
     ($ptr:expr $(, $ptrs:expr)* $(, )?) => {
@@ -116,6 +125,23 @@ unsafe impl<T: ComInterface + ?Sized> Sync for VstPtr<T> {}
 unsafe impl<T: IUnknown> Send for ObjectPtr<T> {}
 unsafe impl<T: IUnknown> Sync for ObjectPtr<T> {}
 
+/// Convert a normalized program change parameter value to an index in a program list with
+/// `num_programs` entries.
+pub fn program_index_from_normalized(normalized: f64, num_programs: usize) -> usize {
+    let max_idx = num_programs.saturating_sub(1);
+
+    ((normalized * max_idx as f64).round() as usize).min(max_idx)
+}
+
+/// The inverse of [`program_index_from_normalized()`].
+pub fn normalized_from_program_index(program_idx: usize, num_programs: usize) -> f64 {
+    if num_programs > 1 {
+        program_idx as f64 / (num_programs - 1) as f64
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod miri {
     use widestring::U16CStr;
@@ -147,4 +173,15 @@ mod miri {
             "Hello"
         );
     }
+
+    #[test]
+    fn program_index_roundtrip() {
+        for program_idx in 0..5 {
+            let normalized = normalized_from_program_index(program_idx, 5);
+            assert_eq!(program_index_from_normalized(normalized, 5), program_idx);
+        }
+
+        assert_eq!(program_index_from_normalized(1.0, 1), 0);
+        assert_eq!(normalized_from_program_index(0, 1), 0.0);
+    }
 }
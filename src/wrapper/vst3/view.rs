@@ -6,7 +6,7 @@ use std::ffi::{c_void, CStr};
 use std::mem;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use vst3_sys::base::{kInvalidArgument, kResultFalse, kResultOk, tresult, TBool};
+use vst3_sys::base::{kInvalidArgument, kResultFalse, kResultOk, kResultTrue, tresult, TBool};
 use vst3_sys::gui::{IPlugFrame, IPlugView, IPlugViewContentScaleSupport, ViewRect};
 use vst3_sys::utils::SharedVstPtr;
 use vst3_sys::VST3;
@@ -336,6 +336,7 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
         if editor_handle.is_some() {
             *self.inner.plug_view.write() = None;
             *editor_handle = None;
+            self.inner.unregister_gui_callbacks();
 
             kResultOk
         } else {
@@ -391,16 +392,35 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
     unsafe fn on_size(&self, new_size: *mut ViewRect) -> tresult {
         check_null_ptr!(new_size);
 
-        // TODO: Implement Host->Plugin resizing
-        let (unscaled_width, unscaled_height) = self.editor.lock().size();
+        let editor = self.editor.lock();
         let scaling_factor = self.scaling_factor.load(Ordering::Relaxed);
+        let width = (*new_size).right - (*new_size).left;
+        let height = (*new_size).bottom - (*new_size).top;
+        if width <= 0 || height <= 0 {
+            return kResultFalse;
+        }
+
+        // If the editor can be resized then the new size is passed through to the editor. This
+        // should have already been checked with `check_size_constraint()`.
+        if let Some(constraints) = editor.resize_constraints() {
+            let (unscaled_width, unscaled_height) = constraints.constrain((
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            ));
+
+            return if editor.set_size(unscaled_width, unscaled_height) {
+                kResultOk
+            } else {
+                kResultFalse
+            };
+        }
+
+        // Otherwise the host is only allowed to 'resize' the editor to its current size
+        let (unscaled_width, unscaled_height) = editor.size();
         let (editor_width, editor_height) = (
             (unscaled_width as f32 * scaling_factor).round() as i32,
             (unscaled_height as f32 * scaling_factor).round() as i32,
         );
-
-        let width = (*new_size).right - (*new_size).left;
-        let height = (*new_size).bottom - (*new_size).top;
         if width == editor_width && height == editor_height {
             kResultOk
         } else {
@@ -424,6 +444,9 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
                     *self.run_loop_event_handler.0.write() = frame.cast().map(|run_loop| {
                         RunLoopEventHandler::new(self.inner.clone(), VstPtr::from(run_loop))
                     });
+                    // The editor's timers and file descriptor watches also use the run loop
+                    self.inner
+                        .set_run_loop(frame.cast::<dyn IRunLoop>().map(VstPtr::from));
                 }
                 *self.plug_frame.write() = Some(VstPtr::from(frame));
             }
@@ -431,6 +454,7 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
                 #[cfg(target_os = "linux")]
                 {
                     *self.run_loop_event_handler.0.write() = None;
+                    self.inner.set_run_loop(None);
                 }
                 *self.plug_frame.write() = None;
             }
@@ -440,19 +464,38 @@ impl<P: Vst3Plugin> IPlugView for WrapperView<P> {
     }
 
     unsafe fn can_resize(&self) -> tresult {
-        // TODO: Implement Host->Plugin resizing
-        kResultFalse
+        if self.editor.lock().resize_constraints().is_some() {
+            kResultTrue
+        } else {
+            kResultFalse
+        }
     }
 
     unsafe fn check_size_constraint(&self, rect: *mut ViewRect) -> tresult {
         check_null_ptr!(rect);
 
-        // TODO: Implement Host->Plugin resizing
-        if (*rect).right - (*rect).left > 0 && (*rect).bottom - (*rect).top > 0 {
-            kResultOk
-        } else {
-            kResultFalse
+        let width = (*rect).right - (*rect).left;
+        let height = (*rect).bottom - (*rect).top;
+        if width <= 0 || height <= 0 {
+            return kResultFalse;
         }
+
+        // The host expects us to modify the rectangle so it matches the closest size the editor
+        // can have. The constraints are in logical pixels, while the rectangle is in physical
+        // pixels.
+        if let Some(constraints) = self.editor.lock().resize_constraints() {
+            let scaling_factor = self.scaling_factor.load(Ordering::Relaxed);
+            let (unscaled_width, unscaled_height) = constraints.constrain((
+                (width as f32 / scaling_factor).round() as u32,
+                (height as f32 / scaling_factor).round() as u32,
+            ));
+
+            let rect = &mut *rect;
+            rect.right = rect.left + (unscaled_width as f32 * scaling_factor).round() as i32;
+            rect.bottom = rect.top + (unscaled_height as f32 * scaling_factor).round() as i32;
+        }
+
+        kResultOk
     }
 }
 
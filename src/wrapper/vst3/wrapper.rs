@@ -1,8 +1,10 @@
+use atomic_refcell::AtomicRefCell;
 use std::borrow::Borrow;
 use std::cmp;
-use std::ffi::c_void;
+use std::ffi::{c_void, CStr};
 use std::mem::{self, MaybeUninit};
 use std::num::NonZeroU32;
+use std::os::raw::c_char;
 use std::ptr;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -11,31 +13,40 @@ use vst3_sys::base::{kInvalidArgument, kNoInterface, kResultFalse, kResultOk, tr
 use vst3_sys::base::{IBStream, IPluginBase};
 use vst3_sys::utils::SharedVstPtr;
 use vst3_sys::vst::{
-    kNoParamId, kNoParentUnitId, kNoProgramListId, kRootUnitId, Event, EventTypes, IAudioProcessor,
-    IComponent, IEditController, IEventList, IMidiMapping, INoteExpressionController,
-    IParamValueQueue, IParameterChanges, IProcessContextRequirements, IUnitInfo,
-    LegacyMidiCCOutEvent, NoteExpressionTypeInfo, NoteExpressionValueDescription, NoteOffEvent,
-    NoteOnEvent, ParameterFlags, PolyPressureEvent, ProgramListInfo, TChar, UnitInfo,
+    kNoParamId, kNoParentUnitId, kNoProgramListId, kRootUnitId, Event, EventTypes, IAttributeList,
+    IAudioProcessor, IComponent, IEditController, IEventList, IHostApplication, IInfoListener,
+    IKeyswitchController, IMidiMapping, INoteExpressionController, IParamValueQueue,
+    IParameterChanges, IProcessContextRequirements, IUnitInfo, KeyswitchInfo, LegacyMidiCCOutEvent,
+    NoteExpressionTypeInfo, NoteExpressionValueDescription, NoteOffEvent, NoteOnEvent,
+    ParameterFlags, PolyPressureEvent, ProgramListInfo, String128, TChar, UnitInfo,
 };
 use vst3_sys::VST3;
 use widestring::U16CStr;
 
-use super::inner::{ProcessEvent, WrapperInner};
+use super::channel_context;
+use super::inner::{ProcessEvent, Task, WrapperInner};
 use super::note_expressions::{self, NoteExpressionController};
+use super::speaker_arrangements::{
+    speaker_arrangement, speaker_arrangement_for_channel_count, speaker_arrangement_matches,
+};
 use super::util::{
-    u16strlcpy, VstPtr, VST3_MIDI_CCS, VST3_MIDI_NUM_PARAMS, VST3_MIDI_PARAMS_START,
+    normalized_from_program_index, program_index_from_normalized, u16strlcpy, VstPtr,
+    VST3_FACTORY_PRESETS_PROGRAM_LIST_ID, VST3_MIDI_CCS, VST3_MIDI_NUM_PARAMS,
+    VST3_MIDI_PARAMS_START, VST3_NOTE_NAMES_PROGRAM_LIST_ID, VST3_PROGRAM_CHANGE_PARAM_ID,
 };
 use super::util::{VST3_MIDI_CHANNELS, VST3_MIDI_PARAMS_END};
 use super::view::WrapperView;
 use crate::audio_setup::{AuxiliaryBuffers, BufferConfig, ProcessMode};
 use crate::buffer::Buffer;
 use crate::context::process::Transport;
+use crate::context::HostInfo;
 use crate::midi::sysex::SysExMessage;
 use crate::midi::{MidiConfig, NoteEvent};
 use crate::params::ParamFlags;
 use crate::plugin::{ProcessStatus, Vst3Plugin};
 use crate::util::permit_alloc;
-use crate::wrapper::state;
+use crate::wrapper::state::{self, StateContext};
+use crate::wrapper::util::buffer_management::{set_silent_slices, ProcessBuffers, ProcessSample};
 use crate::wrapper::util::{clamp_input_event_timing, clamp_output_event_timing, process_wrapper};
 
 // Alias needed for the VST3 attribute macro
@@ -47,8 +58,10 @@ use vst3_sys as vst3_com;
     IAudioProcessor,
     IMidiMapping,
     INoteExpressionController,
+    IKeyswitchController,
     IProcessContextRequirements,
-    IUnitInfo
+    IUnitInfo,
+    IInfoListener
 ))]
 pub(crate) struct Wrapper<P: Vst3Plugin> {
     inner: Arc<WrapperInner<P>>,
@@ -58,6 +71,290 @@ impl<P: Vst3Plugin> Wrapper<P> {
     pub fn new() -> Box<Self> {
         Self::allocate(WrapperInner::new())
     }
+
+    /// Load the factory preset corresponding to a normalized program change parameter value. The
+    /// preset is always loaded from the GUI thread, so this can also be called from the audio
+    /// thread. Selecting the last loaded preset again is a no-op since some hosts echo the program
+    /// change parameter's value back to the plugin.
+    fn load_factory_preset_from_normalized(&self, normalized: f64) {
+        let num_presets = self.inner.factory_presets.len();
+        let preset_idx = program_index_from_normalized(normalized, num_presets);
+        if self.inner.current_factory_preset.load() != Some(preset_idx) {
+            let task_posted = self.inner.schedule_gui(Task::LoadFactoryPreset(preset_idx));
+            nih_debug_assert!(task_posted, "The task queue is full, dropping task...");
+        }
+    }
+
+    /// Point the plugin's buffers at the host's audio buffers for the current block and then call
+    /// the plugin's process function. This is generic over the sample type so the same code can be
+    /// used for both 32-bit and 64-bit processing. If the host's buffers don't match the current
+    /// audio IO layout, then the plugin won't be called and this returns
+    /// [`ProcessStatus::Normal`].
+    ///
+    /// # Safety
+    ///
+    /// `data` must be the host's data for the current process call, `T` must match its symbolic
+    /// sample size, and the block defined by `block_start` and `block_len` must fit within the
+    /// host's audio buffers.
+    unsafe fn process_block<T: ProcessSample>(
+        &self,
+        buffers: &AtomicRefCell<ProcessBuffers<T>>,
+        data: &vst3_sys::vst::ProcessData,
+        block_start: usize,
+        block_len: usize,
+        transport: Transport,
+    ) -> ProcessStatus {
+        let current_audio_io_layout = self.inner.current_audio_io_layout.load();
+        let has_main_input = current_audio_io_layout.main_input_channels.is_some();
+        let has_main_output = current_audio_io_layout.main_output_channels.is_some();
+        let aux_input_start_idx = if has_main_input { 1 } else { 0 };
+        let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+
+        let aux_port_activation = self.inner.aux_port_activation.borrow();
+        let mut buffers = buffers.borrow_mut();
+        let ProcessBuffers {
+            output_buffer,
+            aux_input_storage,
+            aux_input_buffers,
+            aux_output_buffers,
+            aux_output_storage,
+        } = &mut *buffers;
+
+        // This vector has been preallocated to contain enough slices as there are output
+        // channels. In case the does does not provide an output or if they don't provide
+        // all of the channels (this should not happen, but Ableton Live might do it) then
+        // we'll skip the process function.
+        let mut buffer_is_valid = false;
+        output_buffer.set_slices(block_len, |output_slices| {
+            // Buffers for zero-channel plugins like note effects should always be allowed
+            buffer_is_valid = output_slices.is_empty();
+
+            if !data.outputs.is_null() && has_main_output {
+                let num_output_channels = (*data.outputs).num_channels as usize;
+                // This ensures that we never feed dangling slices to the wrapped plugin
+                buffer_is_valid = num_output_channels == output_slices.len();
+                nih_debug_assert_eq!(num_output_channels, output_slices.len());
+
+                // In case the host does provide fewer output channels than we expect, we
+                // should still try to handle that gracefully. This happens when the plugin
+                // is bypassed in Ableton Live and a parameter is modified. In that case the
+                // above assertion will still trigger.
+                for (output_channel_idx, output_channel_slice) in output_slices
+                    .iter_mut()
+                    .take(num_output_channels)
+                    .enumerate()
+                {
+                    // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we may be iterating
+                    // over the buffer in smaller sections.
+                    // SAFETY: These pointers may not be valid outside of this function even
+                    // though their lifetime is equal to this structs. This is still safe
+                    // because they are only dereferenced here later as part of this process
+                    // function.
+                    let channel_ptr =
+                        *((*data.outputs).buffers as *mut *mut T).add(output_channel_idx);
+                    *output_channel_slice =
+                        std::slice::from_raw_parts_mut(channel_ptr.add(block_start), block_len);
+                }
+            }
+        });
+
+        // Some hosts process data in place, in which case we don't need to do any copying
+        // ourselves. If the pointers do not alias, then we'll do the copy here and then the
+        // plugin can just do normal in place processing.
+        if !data.outputs.is_null() && !data.inputs.is_null() && has_main_input && has_main_output {
+            let num_output_channels = (*data.outputs).num_channels as usize;
+            let num_input_channels = (*data.inputs).num_channels as usize;
+            nih_debug_assert!(
+                num_input_channels <= num_output_channels,
+                "Stereo to mono and similar configurations are not supported"
+            );
+            for input_channel_idx in 0..cmp::min(num_input_channels, num_output_channels) {
+                let output_channel_ptr =
+                    *((*data.outputs).buffers as *mut *mut T).add(input_channel_idx);
+                let input_channel_ptr =
+                    *((*data.inputs).buffers as *const *const T).add(input_channel_idx);
+                if input_channel_ptr != output_channel_ptr {
+                    ptr::copy_nonoverlapping(
+                        input_channel_ptr.add(block_start),
+                        output_channel_ptr.add(block_start),
+                        block_len,
+                    );
+                }
+            }
+
+            // The main input's silence flags also apply to the main output buffer since the input
+            // has been copied there. VST3 doesn't have a separate flag for constant channels.
+            let silence_flags = (*data.inputs).silence_flags;
+            output_buffer.set_channel_flags(silence_flags, silence_flags);
+        }
+
+        // We'll need to do the same thing for auxiliary input sidechain buffers. Since we
+        // don't know whether overwriting the host's buffers is safe here or not, we'll copy
+        // the data to our own buffers instead. These buffers are only accessible through
+        // the `aux` parameter on the `process()` function.
+        for (aux_input_idx, (storage, buffer)) in aux_input_storage
+            .iter_mut()
+            .zip(aux_input_buffers.iter_mut())
+            .enumerate()
+        {
+            let host_input_idx = aux_input_start_idx + aux_input_idx;
+            let host_input = data.inputs.add(host_input_idx);
+            if host_input_idx >= data.num_inputs as usize
+                     || data.inputs.is_null()
+                     || (*host_input).buffers.is_null()
+                     // Would only happen if the user configured zero channels for the
+                     // auxiliary buffers
+                     || storage.is_empty()
+                     || (*host_input).num_channels != buffer.channels() as i32
+            {
+                // Hosts don't need to provide buffers for inactive busses, but the plugin should
+                // still receive silent buffers for them
+                if !aux_port_activation.inputs[aux_input_idx] {
+                    set_silent_slices(buffer, storage, block_len);
+                    continue;
+                }
+
+                // During a parameter flush the number of inputs/outputs may be 0 and the
+                // number of channels may be 0, so these assertions need to be a bit more
+                // relaxed
+                nih_debug_assert!(
+                    data.num_inputs == 0 || host_input_idx < data.num_inputs as usize
+                );
+                nih_debug_assert!(!storage.is_empty());
+                if !data.inputs.is_null() && host_input_idx < data.num_inputs as usize {
+                    nih_debug_assert!(!(*host_input).buffers.is_null());
+                    nih_debug_assert!(
+                        (*host_input).num_channels == 0
+                            || (*host_input).num_channels == buffer.channels() as i32
+                    );
+                }
+
+                // If the host passes weird data then we need to be very sure that there are
+                // no dangling references to previous data
+                buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
+                continue;
+            }
+
+            // We'll always reuse the start of the buffer even of the current block is
+            // shorter for cache locality reasons
+            for (channel_idx, channel_storage) in storage.iter_mut().enumerate() {
+                // The `set_len()` avoids having to unnecessarily fill the buffer with
+                // zeroes when sizing up
+                assert!(block_len <= channel_storage.capacity());
+                channel_storage.set_len(block_len);
+                channel_storage.copy_from_slice(std::slice::from_raw_parts(
+                    (*(*host_input).buffers.add(channel_idx) as *const T).add(block_start),
+                    block_len,
+                ));
+            }
+
+            buffer.set_slices(block_len, |slices| {
+                for (channel_slice, channel_storage) in slices.iter_mut().zip(storage.iter_mut()) {
+                    // SAFETY: The 'static cast is required because Rust does not allow you
+                    //         to store references to a field in another field.  Because
+                    //         these slices are set here before the process function is
+                    //         called, we ensure that there are no dangling slices. These
+                    //         buffers/slices are only ever read from in the second part of
+                    //         this block process loop.
+                    *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [T]);
+                }
+            });
+            let silence_flags = (*host_input).silence_flags;
+            buffer.set_channel_flags(silence_flags, silence_flags);
+        }
+
+        // And the same thing for auxiliary output buffers
+        for (aux_output_idx, (buffer, storage)) in aux_output_buffers
+            .iter_mut()
+            .zip(aux_output_storage.iter_mut())
+            .enumerate()
+        {
+            let host_output_idx = aux_output_start_idx + aux_output_idx;
+            let host_output = data.outputs.add(host_output_idx);
+            if host_output_idx >= data.num_outputs as usize
+                || data.outputs.is_null()
+                || (*host_output).buffers.is_null()
+                || buffer.channels() == 0
+                || (*host_output).num_channels != buffer.channels() as i32
+            {
+                // The host discards the output of inactive busses, so if it doesn't provide any
+                // buffers for those then the plugin will write to our scratch buffers instead
+                if !aux_port_activation.outputs[aux_output_idx] {
+                    set_silent_slices(buffer, storage, block_len);
+                    continue;
+                }
+
+                nih_debug_assert!(host_output_idx < data.num_outputs as usize);
+                nih_debug_assert!(!data.outputs.is_null());
+                if !data.outputs.is_null() && host_output_idx < data.num_outputs as usize {
+                    nih_debug_assert!(!(*host_output).buffers.is_null());
+                    nih_debug_assert!(
+                        !(*host_output).num_channels == 0
+                            || !(*host_output).num_channels == buffer.channels() as i32
+                    );
+                }
+
+                // If the host passes weird data then we need to be very sure that there are
+                // no dangling references to previous data
+                buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
+                continue;
+            }
+
+            buffer.set_slices(block_len, |slices| {
+                for (channel_idx, channel_slice) in slices.iter_mut().enumerate() {
+                    *channel_slice = std::slice::from_raw_parts_mut(
+                        (*(*host_output).buffers.add(channel_idx) as *mut T).add(block_start),
+                        block_len,
+                    );
+                }
+            });
+        }
+
+        if buffer_is_valid {
+            // NOTE: `parking_lot`'s mutexes sometimes allocate because of their use of thread
+            //       locals
+            let mut plugin = permit_alloc(|| self.inner.plugin.lock());
+            // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
+            //         slices (which it cannot do without using unsafe code), then they would still
+            //         be reset on the next iteration
+            let mut aux = AuxiliaryBuffers {
+                inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer<T>]),
+                inputs_active: &aux_port_activation.inputs,
+                outputs_active: &aux_port_activation.outputs,
+            };
+            let mut context = self.inner.make_process_context(transport);
+            let result = T::process(&mut *plugin, output_buffer, &mut aux, &mut context);
+            self.inner.last_process_status.store(result);
+
+            // Report the output channels the plugin marked as silent back to the host. With block
+            // splitting a channel is only silent if the plugin marked it as silent in every block.
+            if !data.outputs.is_null() {
+                let main_output_buffer = if has_main_output {
+                    Some(&*output_buffer)
+                } else {
+                    None
+                };
+                for (host_output_idx, buffer) in main_output_buffer
+                    .into_iter()
+                    .chain(aux_output_buffers.iter())
+                    .enumerate()
+                    .take(data.num_outputs as usize)
+                {
+                    let host_output = &mut *data.outputs.add(host_output_idx);
+                    if block_start == 0 {
+                        host_output.silence_flags = buffer.output_silence_mask();
+                    } else {
+                        host_output.silence_flags &= buffer.output_silence_mask();
+                    }
+                }
+            }
+
+            result
+        } else {
+            ProcessStatus::Normal
+        }
+    }
 }
 
 impl<P: Vst3Plugin> Drop for Wrapper<P> {
@@ -67,8 +364,27 @@ impl<P: Vst3Plugin> Drop for Wrapper<P> {
 }
 
 impl<P: Vst3Plugin> IPluginBase for Wrapper<P> {
-    unsafe fn initialize(&self, _context: *mut c_void) -> tresult {
-        // We currently don't need or allow any initialization logic
+    unsafe fn initialize(&self, context: *mut c_void) -> tresult {
+        // The host passes its `IHostApplication` object here. The host's name is the only
+        // information about the host VST3 provides.
+        let context: SharedVstPtr<dyn IHostApplication> = mem::transmute(context);
+        if let Some(host_application) = context
+            .upgrade()
+            .and_then(|context| context.cast::<dyn IHostApplication>())
+        {
+            let mut name: String128 = [0; 128];
+            if host_application.get_name(&mut name) == kResultOk {
+                // Just in case the host didn't terminate the string
+                *name.last_mut().unwrap() = 0;
+                let name = U16CStr::from_ptr_str(name.as_ptr() as *const u16).to_string_lossy();
+
+                *self.inner.host_info.borrow_mut() = HostInfo {
+                    name: Some(name).filter(|name| !name.is_empty()),
+                    ..HostInfo::default()
+                };
+            }
+        }
+
         kResultOk
     }
 
@@ -311,12 +627,13 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
         type_: vst3_sys::vst::MediaType,
         dir: vst3_sys::vst::BusDirection,
         index: i32,
-        _state: vst3_sys::base::TBool,
+        state: vst3_sys::base::TBool,
     ) -> tresult {
         let current_audio_io_layout = self.inner.current_audio_io_layout.load();
 
-        // We don't support this, but the validator will get very angry with us if we let it know
-        // that
+        // Deactivated auxiliary audio busses are passed to the plugin as silent buffers, and the
+        // plugin can check `AuxiliaryBuffers::inputs_active` and `outputs_active` to skip
+        // processing them. Event busses are always active.
         match (type_, dir, index) {
             (t, d, _)
                 if t == vst3_sys::vst::MediaTypes::kAudio as i32
@@ -330,6 +647,14 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                 let aux_busses = current_audio_io_layout.aux_input_ports.len() as i32;
 
                 if (0..main_busses + aux_busses).contains(&index) {
+                    // The main bus is always processed, so only the auxiliary busses' activation
+                    // states are tracked
+                    if index >= main_busses {
+                        let mut aux_port_activation = self.inner.aux_port_activation.borrow_mut();
+                        aux_port_activation.resize(&current_audio_io_layout);
+                        aux_port_activation.inputs[(index - main_busses) as usize] = state != 0;
+                    }
+
                     kResultOk
                 } else {
                     kInvalidArgument
@@ -347,6 +672,14 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                 let aux_busses = current_audio_io_layout.aux_output_ports.len() as i32;
 
                 if (0..main_busses + aux_busses).contains(&index) {
+                    // The main bus is always processed, so only the auxiliary busses' activation
+                    // states are tracked
+                    if index >= main_busses {
+                        let mut aux_port_activation = self.inner.aux_port_activation.borrow_mut();
+                        aux_port_activation.resize(&current_audio_io_layout);
+                        aux_port_activation.outputs[(index - main_busses) as usize] = state != 0;
+                    }
+
                     kResultOk
                 } else {
                     kInvalidArgument
@@ -391,61 +724,24 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
                     //       to be called after this function before the plugin may process audio again.
 
                     // Preallocate enough room in the output slices vector so we can convert a `*mut *mut
-                    // f32` to a `&mut [&mut f32]` in the process call
+                    // f32` to a `&mut [&mut f32]` in the process call. The same is done for the
+                    // sidechain buffers. If the plugin supports 64-bit processing, then the host
+                    // may use either precision so we'll need to preallocate both sets of buffers.
+                    let max_buffer_size = buffer_config.max_buffer_size as usize;
                     self.inner
-                        .output_buffer
+                        .buffers
                         .borrow_mut()
-                        .set_slices(0, |output_slices| {
-                            output_slices.resize_with(
-                                audio_io_layout
-                                    .main_output_channels
-                                    .map(NonZeroU32::get)
-                                    .unwrap_or_default() as usize,
-                                || &mut [],
-                            );
-                            // All slices must have the same length, so if the number of output
-                            // channels has changed since the last call then we should make sure to
-                            // clear any old (dangling) slices to be consistent
-                            output_slices.fill_with(|| &mut []);
-                        });
-
-                    // Also allocate both the buffers and the slices pointing to those buffers for
-                    // sidechain inputs. The slices will be assigned in the process function as this
-                    // object may have been moved before then.
-                    let mut aux_input_storage = self.inner.aux_input_storage.borrow_mut();
-                    let mut aux_input_buffers = self.inner.aux_input_buffers.borrow_mut();
-                    aux_input_storage.resize_with(audio_io_layout.aux_input_ports.len(), Vec::new);
-                    aux_input_buffers
-                        .resize_with(audio_io_layout.aux_input_ports.len(), Buffer::default);
-                    for ((buffer_storage, buffer), num_channels) in aux_input_storage
-                        .iter_mut()
-                        .zip(aux_input_buffers.iter_mut())
-                        .zip(audio_io_layout.aux_input_ports.iter())
-                    {
-                        buffer_storage.resize_with(num_channels.get() as usize, Vec::new);
-                        for channel_storage in buffer_storage {
-                            channel_storage.resize(buffer_config.max_buffer_size as usize, 0.0);
-                        }
-
-                        buffer.set_slices(0, |channel_slices| {
-                            channel_slices.resize_with(num_channels.get() as usize, || &mut []);
-                            channel_slices.fill_with(|| &mut []);
-                        });
-                    }
-
-                    // And the same thing for the output buffers
-                    let mut aux_output_buffers = self.inner.aux_output_buffers.borrow_mut();
-                    aux_output_buffers
-                        .resize_with(audio_io_layout.aux_output_ports.len(), Buffer::default);
-                    for (buffer, num_channels) in aux_output_buffers
-                        .iter_mut()
-                        .zip(audio_io_layout.aux_output_ports.iter())
-                    {
-                        buffer.set_slices(0, |channel_slices| {
-                            channel_slices.resize_with(num_channels.get() as usize, || &mut []);
-                            channel_slices.fill_with(|| &mut []);
-                        });
+                        .preallocate(&audio_io_layout, max_buffer_size);
+                    if P::F64_PROCESSING {
+                        self.inner
+                            .buffers_f64
+                            .borrow_mut()
+                            .preallocate(&audio_io_layout, max_buffer_size);
                     }
+                    self.inner
+                        .aux_port_activation
+                        .borrow_mut()
+                        .resize(&audio_io_layout);
 
                     kResultOk
                 } else {
@@ -499,7 +795,12 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
 
         match state::deserialize_json(&read_buffer) {
             Some(mut state) => {
-                if self.inner.set_state_inner(&mut state) {
+                // VST3 doesn't tell us why the state is being loaded, so we'll always treat this as
+                // project state
+                if self
+                    .inner
+                    .set_state_inner(&mut state, StateContext::Project)
+                {
                     nih_trace!("Loaded state ({} bytes)", read_buffer.len());
                     kResultOk
                 } else {
@@ -518,6 +819,8 @@ impl<P: Vst3Plugin> IComponent for Wrapper<P> {
         let serialized = state::serialize_json::<P>(
             self.inner.params.clone(),
             state::make_params_iter(&self.inner.param_by_hash, &self.inner.param_id_to_hash),
+            &self.inner.midi_learn,
+            StateContext::Project,
         );
         match serialized {
             Ok(serialized) => {
@@ -562,12 +865,19 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn get_parameter_count(&self) -> i32 {
+        let mut num_params = self.inner.param_hashes.len() as i32;
+
+        // Factory presets are selected through a program change parameter
+        if !self.inner.factory_presets.is_empty() {
+            num_params += 1;
+        }
+
         // We need to add a whole bunch of parameters if the plugin accepts MIDI CCs
         if P::MIDI_INPUT >= MidiConfig::MidiCCs {
-            self.inner.param_hashes.len() as i32 + VST3_MIDI_NUM_PARAMS as i32
-        } else {
-            self.inner.param_hashes.len() as i32
+            num_params += VST3_MIDI_NUM_PARAMS as i32;
         }
+
+        num_params
     }
 
     unsafe fn get_parameter_info(
@@ -584,11 +894,22 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
         *info = std::mem::zeroed();
         let info = &mut *info;
 
-        // If the parameter is a generated MIDI CC/channel pressure/pitch bend then it needs to be
-        // handled separately
+        // If the parameter is the program change parameter or a generated MIDI CC/channel
+        // pressure/pitch bend then it needs to be handled separately. The program change parameter
+        // comes directly after the plugin's own parameters.
         let num_actual_params = self.inner.param_hashes.len() as i32;
-        if P::MIDI_INPUT >= MidiConfig::MidiCCs && param_index >= num_actual_params {
-            let midi_param_relative_idx = (param_index - num_actual_params) as u32;
+        let num_presets = self.inner.factory_presets.len();
+        let num_non_midi_params = num_actual_params + (num_presets > 0) as i32;
+        if num_presets > 0 && param_index == num_actual_params {
+            info.id = VST3_PROGRAM_CHANGE_PARAM_ID;
+            u16strlcpy(&mut info.title, "Program");
+            u16strlcpy(&mut info.short_title, "Program");
+            info.step_count = (num_presets - 1) as i32;
+            info.default_normalized_value = 0.0;
+            info.unit_id = kRootUnitId;
+            info.flags = ParameterFlags::kIsList as i32 | ParameterFlags::kIsProgramChange as i32;
+        } else if P::MIDI_INPUT >= MidiConfig::MidiCCs && param_index >= num_non_midi_params {
+            let midi_param_relative_idx = (param_index - num_non_midi_params) as u32;
             // This goes up to 130 for the 128 CCs followed by channel pressure and pitch bend
             let midi_cc = midi_param_relative_idx % VST3_MIDI_CCS;
             let midi_channel = midi_param_relative_idx / VST3_MIDI_CCS;
@@ -617,6 +938,7 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             let automatable = !flags.contains(ParamFlags::NON_AUTOMATABLE);
             let hidden = flags.contains(ParamFlags::HIDDEN);
             let is_bypass = flags.contains(ParamFlags::BYPASS);
+            let is_output = flags.contains(ParamFlags::OUTPUT);
 
             info.id = *param_hash;
             u16strlcpy(&mut info.title, param_ptr.name());
@@ -626,12 +948,15 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             info.default_normalized_value = default_value as f64;
             info.unit_id = *param_unit;
             info.flags = 0;
-            if automatable && !hidden {
+            if automatable && !hidden && !is_output {
                 info.flags |= ParameterFlags::kCanAutomate as i32;
             }
             if hidden {
                 info.flags |= ParameterFlags::kIsReadOnly as i32 | (1 << 4); // kIsHidden
             }
+            if is_output {
+                info.flags |= ParameterFlags::kIsReadOnly as i32;
+            }
             if is_bypass {
                 info.flags |= ParameterFlags::kIsBypass as i32;
             }
@@ -650,6 +975,14 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
 
         let dest = &mut *(string as *mut [TChar; 128]);
 
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            let num_presets = self.inner.factory_presets.len();
+            let preset_idx = program_index_from_normalized(value_normalized, num_presets);
+            u16strlcpy(dest, &self.inner.factory_presets[preset_idx].name);
+
+            return kResultOk;
+        }
+
         // TODO: We don't implement these methods at all for our generated MIDI CC parameters,
         //       should be fine right? They should be hidden anyways.
         match self.inner.param_by_hash.get(&id) {
@@ -678,6 +1011,22 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             Err(_) => return kInvalidArgument,
         };
 
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            let num_presets = self.inner.factory_presets.len();
+            return match self
+                .inner
+                .factory_presets
+                .iter()
+                .position(|preset| preset.name == string)
+            {
+                Some(preset_idx) => {
+                    *value_normalized = normalized_from_program_index(preset_idx, num_presets);
+                    kResultOk
+                }
+                None => kResultFalse,
+            };
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => {
                 let value = match param_ptr.string_to_normalized_value(&string) {
@@ -693,6 +1042,12 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn normalized_param_to_plain(&self, id: u32, value_normalized: f64) -> f64 {
+        // The program change parameter's plain value is the preset index
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            let num_presets = self.inner.factory_presets.len();
+            return program_index_from_normalized(value_normalized, num_presets) as f64;
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.preview_plain(value_normalized as f32) as f64,
             _ => value_normalized,
@@ -700,6 +1055,12 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn plain_param_to_normalized(&self, id: u32, plain_value: f64) -> f64 {
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            let num_presets = self.inner.factory_presets.len();
+            let preset_idx = (plain_value.max(0.0).round() as usize).min(num_presets - 1);
+            return normalized_from_program_index(preset_idx, num_presets);
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.preview_normalized(plain_value as f32) as f64,
             _ => plain_value,
@@ -707,6 +1068,13 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
     }
 
     unsafe fn get_param_normalized(&self, id: u32) -> f64 {
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            return normalized_from_program_index(
+                self.inner.current_factory_preset.load().unwrap_or(0),
+                self.inner.factory_presets.len(),
+            );
+        }
+
         match self.inner.param_by_hash.get(&id) {
             Some(param_ptr) => param_ptr.modulated_normalized_value() as f64,
             _ => 0.5,
@@ -720,6 +1088,11 @@ impl<P: Vst3Plugin> IEditController for Wrapper<P> {
             return kResultOk;
         }
 
+        if id == VST3_PROGRAM_CHANGE_PARAM_ID && !self.inner.factory_presets.is_empty() {
+            self.load_factory_preset_from_normalized(value);
+            return kResultOk;
+        }
+
         let sample_rate = self
             .inner
             .current_buffer_config
@@ -786,37 +1159,55 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                     return false;
                 }
 
-                // NOTE: We completely ignore the speaker arrangements and only look at the channel
-                //       counts here. This may cause issues at some point, but it works for now.
+                // Ports with an explicit channel layout need to match that layout's speaker
+                // arrangement. For all other ports we only look at the channel counts.
+                let channel_layouts = &layout.channel_layouts;
                 let has_main_input = layout.main_input_channels.is_some();
-                let aux_input_start_idx = if has_main_input { 0 } else { 1 };
-                if has_main_input
-                    && (*inputs).count_ones() != layout.main_input_channels.unwrap().get()
-                {
-                    return false;
+                let aux_input_start_idx = if has_main_input { 1 } else { 0 };
+                if let Some(num_channels) = layout.main_input_channels {
+                    if !speaker_arrangement_matches(
+                        *inputs,
+                        num_channels,
+                        channel_layouts.main_input,
+                    ) {
+                        return false;
+                    }
                 }
-                for (aux_input_idx, channel_count) in layout.aux_input_ports.iter().enumerate() {
-                    if (*inputs.add(aux_input_idx + aux_input_start_idx)).count_ones()
-                        != channel_count.get()
-                    {
+                for (aux_input_idx, num_channels) in layout.aux_input_ports.iter().enumerate() {
+                    if !speaker_arrangement_matches(
+                        *inputs.add(aux_input_idx + aux_input_start_idx),
+                        *num_channels,
+                        channel_layouts
+                            .aux_inputs
+                            .get(aux_input_idx)
+                            .copied()
+                            .flatten(),
+                    ) {
                         return false;
                     }
                 }
 
                 let has_main_output = layout.main_output_channels.is_some();
-                let aux_output_start_idx = if has_main_output { 0 } else { 1 };
-                if (*outputs).count_ones()
-                    != layout
-                        .main_output_channels
-                        .map(NonZeroU32::get)
-                        .unwrap_or_default()
-                {
-                    return false;
+                let aux_output_start_idx = if has_main_output { 1 } else { 0 };
+                if let Some(num_channels) = layout.main_output_channels {
+                    if !speaker_arrangement_matches(
+                        *outputs,
+                        num_channels,
+                        channel_layouts.main_output,
+                    ) {
+                        return false;
+                    }
                 }
-                for (aux_output_idx, channel_count) in layout.aux_output_ports.iter().enumerate() {
-                    if (*outputs.add(aux_output_idx + aux_output_start_idx)).count_ones()
-                        != channel_count.get()
-                    {
+                for (aux_output_idx, num_channels) in layout.aux_output_ports.iter().enumerate() {
+                    if !speaker_arrangement_matches(
+                        *outputs.add(aux_output_idx + aux_output_start_idx),
+                        *num_channels,
+                        channel_layouts
+                            .aux_outputs
+                            .get(aux_output_idx)
+                            .copied()
+                            .flatten(),
+                    ) {
                         return false;
                     }
                 }
@@ -845,32 +1236,21 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
     ) -> tresult {
         check_null_ptr!(arr);
 
-        let channel_count_to_map = |count| match count {
-            0 => vst3_sys::vst::kEmpty,
-            1 => vst3_sys::vst::kMono,
-            2 => vst3_sys::vst::kStereo,
-            5 => vst3_sys::vst::k50,
-            6 => vst3_sys::vst::k51,
-            7 => vst3_sys::vst::k70Cine,
-            8 => vst3_sys::vst::k71Cine,
-            n => {
-                nih_debug_assert_failure!(
-                    "No defined layout for {} channels, making something up on the spot...",
-                    n
-                );
-                (1 << n) - 1
-            }
-        };
-
         let current_audio_io_layout = self.inner.current_audio_io_layout.load();
-        let num_channels = if dir == vst3_sys::vst::BusDirections::kInput as i32 {
+        let (num_channels, channel_layout) = if dir == vst3_sys::vst::BusDirections::kInput as i32 {
             let has_main_input = current_audio_io_layout.main_input_channels.is_some();
             let aux_input_start_idx = if has_main_input { 1 } else { 0 };
             let aux_input_idx = (index - aux_input_start_idx).max(0) as usize;
             if index == 0 && has_main_input {
-                current_audio_io_layout.main_input_channels.unwrap().get()
+                (
+                    current_audio_io_layout.main_input_channels.unwrap().get(),
+                    current_audio_io_layout.main_input_layout(),
+                )
             } else if aux_input_idx < current_audio_io_layout.aux_input_ports.len() {
-                current_audio_io_layout.aux_input_ports[aux_input_idx].get()
+                (
+                    current_audio_io_layout.aux_input_ports[aux_input_idx].get(),
+                    current_audio_io_layout.aux_input_layout(aux_input_idx),
+                )
             } else {
                 return kInvalidArgument;
             }
@@ -879,16 +1259,25 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             let aux_output_start_idx = if has_main_output { 1 } else { 0 };
             let aux_output_idx = (index - aux_output_start_idx).max(0) as usize;
             if index == 0 && has_main_output {
-                current_audio_io_layout.main_output_channels.unwrap().get()
+                (
+                    current_audio_io_layout.main_output_channels.unwrap().get(),
+                    current_audio_io_layout.main_output_layout(),
+                )
             } else if aux_output_idx < current_audio_io_layout.aux_output_ports.len() {
-                current_audio_io_layout.aux_output_ports[aux_output_idx].get()
+                (
+                    current_audio_io_layout.aux_output_ports[aux_output_idx].get(),
+                    current_audio_io_layout.aux_output_layout(aux_output_idx),
+                )
             } else {
                 return kInvalidArgument;
             }
         } else {
             return kInvalidArgument;
         };
-        let channel_map = channel_count_to_map(num_channels);
+        let channel_map = match channel_layout {
+            Some(channel_layout) => speaker_arrangement(channel_layout),
+            None => speaker_arrangement_for_channel_count(num_channels),
+        };
 
         nih_debug_assert_eq!(num_channels, channel_map.count_ones());
         *arr = channel_map;
@@ -897,10 +1286,12 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
     }
 
     unsafe fn can_process_sample_size(&self, symbolic_sample_size: i32) -> tresult {
-        if symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32 {
-            kResultOk
-        } else {
-            kResultFalse
+        match symbolic_sample_size {
+            n if n == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32 => kResultOk,
+            n if n == vst3_sys::vst::SymbolicSampleSizes::kSample64 as i32 && P::F64_PROCESSING => {
+                kResultOk
+            }
+            _ => kResultFalse,
         }
     }
 
@@ -913,9 +1304,13 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
 
         // There's no special handling for offline processing at the moment
         let setup = &*setup;
-        nih_debug_assert_eq!(
-            setup.symbolic_sample_size,
-            vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+        nih_debug_assert!(
+            setup.symbolic_sample_size == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+                || (P::F64_PROCESSING
+                    && setup.symbolic_sample_size
+                        == vst3_sys::vst::SymbolicSampleSizes::kSample64 as i32),
+            "Unsupported sample size: {}",
+            setup.symbolic_sample_size
         );
 
         // This is needed when activating the plugin and when restoring state
@@ -969,6 +1364,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 }
             };
 
+            self.inner.mpe_translator.borrow_mut().reset();
             process_wrapper(|| plugin.reset());
         }
 
@@ -994,31 +1390,37 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 .sample_rate;
 
             nih_debug_assert!(data.num_inputs >= 0 && data.num_outputs >= 0);
-            nih_debug_assert_eq!(
-                data.symbolic_sample_size,
-                vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
-            );
             nih_debug_assert!(data.num_samples >= 0);
 
+            // The host will only use 64-bit buffers if we reported support for them in
+            // `can_process_sample_size()`
+            let use_f64 = P::F64_PROCESSING
+                && data.symbolic_sample_size
+                    == vst3_sys::vst::SymbolicSampleSizes::kSample64 as i32;
+            nih_debug_assert!(
+                use_f64
+                    || data.symbolic_sample_size
+                        == vst3_sys::vst::SymbolicSampleSizes::kSample32 as i32
+            );
+
             let total_buffer_len = data.num_samples as usize;
 
             // Before doing anything, clear out any auxiliary outputs since they may contain
             // uninitialized data when the host assumes that we'll always write something there
             let current_audio_io_layout = self.inner.current_audio_io_layout.load();
-            let has_main_input = current_audio_io_layout.main_input_channels.is_some();
             let has_main_output = current_audio_io_layout.main_output_channels.is_some();
-            let aux_input_start_idx = if has_main_input { 1 } else { 0 };
             let aux_output_start_idx = if has_main_output { 1 } else { 0 };
             if !data.outputs.is_null() {
                 for output_idx in aux_output_start_idx..data.num_outputs as usize {
                     let host_output = data.outputs.add(output_idx);
                     if !(*host_output).buffers.is_null() {
                         for channel_idx in 0..(*host_output).num_channels as isize {
-                            ptr::write_bytes(
-                                *((*host_output).buffers.offset(channel_idx)) as *mut f32,
-                                0,
-                                total_buffer_len,
-                            );
+                            let channel_ptr = *((*host_output).buffers.offset(channel_idx));
+                            if use_f64 {
+                                ptr::write_bytes(channel_ptr as *mut f64, 0, total_buffer_len);
+                            } else {
+                                ptr::write_bytes(channel_ptr as *mut f32, 0, total_buffer_len);
+                            }
                         }
                     }
                 }
@@ -1065,9 +1467,14 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                 );
                                 let value = value as f32;
 
-                                // MIDI CC messages, channel pressure, and pitch bend are also sent
-                                // as parameter changes
-                                if P::MIDI_INPUT >= MidiConfig::MidiCCs
+                                // Factory presets can't be loaded from the audio thread, so this
+                                // is deferred to the GUI thread. MIDI CC messages, channel
+                                // pressure, and pitch bend are also sent as parameter changes.
+                                if param_hash == VST3_PROGRAM_CHANGE_PARAM_ID
+                                    && !self.inner.factory_presets.is_empty()
+                                {
+                                    self.load_factory_preset_from_normalized(value as f64);
+                                } else if P::MIDI_INPUT >= MidiConfig::MidiCCs
                                     && (VST3_MIDI_PARAMS_START..VST3_MIDI_PARAMS_END)
                                         .contains(&param_hash)
                                 {
@@ -1235,6 +1642,7 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                 // The extra scope is here to make sure we release the borrow on input_events
                 {
                     let mut input_events = self.inner.input_events.borrow_mut();
+                    let mut mpe_translator = self.inner.mpe_translator.borrow_mut();
                     input_events.clear();
 
                     block_end = total_buffer_len;
@@ -1261,195 +1669,55 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                                 );
                             }
                             ProcessEvent::NoteEvent(event) => {
+                                // MIDI messages mapped through MIDI learn are parameter changes as
+                                // far as the plugin is concerned, so they also split the block
+                                if P::SAMPLE_ACCURATE_AUTOMATION
+                                    && event.timing() != block_start as u32
+                                    && self.inner.midi_learn.maps_event(event)
+                                {
+                                    event_start_idx = event_idx;
+                                    block_end = event.timing() as usize;
+                                    break;
+                                }
+
                                 // We need to make sure to compensate the event for any block splitting,
                                 // since we had to create the event object beforehand
                                 let mut event = event.clone();
                                 event.subtract_timing(block_start as u32);
-                                input_events.push_back(event);
+                                // MPE expressions are translated here since the MIDI CC parameter
+                                // changes and note events only end up in the right order after
+                                // sorting
+                                mpe_translator
+                                    .translate(event, |event| input_events.push_back(event));
                             }
                         }
                     }
                 }
 
-                // This vector has been preallocated to contain enough slices as there are output
-                // channels. In case the does does not provide an output or if they don't provide
-                // all of the channels (this should not happen, but Ableton Live might do it) then
-                // we'll skip the process function.
-                let block_len = block_end - block_start;
-                let mut output_buffer = self.inner.output_buffer.borrow_mut();
-                let mut buffer_is_valid = false;
-                output_buffer.set_slices(block_len, |output_slices| {
-                    // Buffers for zero-channel plugins like note effects should always be allowed
-                    buffer_is_valid = output_slices.is_empty();
-
-                    if !data.outputs.is_null() && has_main_output {
-                        let num_output_channels = (*data.outputs).num_channels as usize;
-                        // This ensures that we never feed dangling slices to the wrapped plugin
-                        buffer_is_valid = num_output_channels == output_slices.len();
-                        nih_debug_assert_eq!(num_output_channels, output_slices.len());
-
-                        // In case the host does provide fewer output channels than we expect, we
-                        // should still try to handle that gracefully. This happens when the plugin
-                        // is bypassed in Ableton Live and a parameter is modified. In that case the
-                        // above assertion will still trigger.
-                        for (output_channel_idx, output_channel_slice) in output_slices
-                            .iter_mut()
-                            .take(num_output_channels)
-                            .enumerate()
-                        {
-                            // If `P::SAMPLE_ACCURATE_AUTOMATION` is set, then we may be iterating
-                            // over the buffer in smaller sections.
-                            // SAFETY: These pointers may not be valid outside of this function even
-                            // though their lifetime is equal to this structs. This is still safe
-                            // because they are only dereferenced here later as part of this process
-                            // function.
-                            let channel_ptr =
-                                *((*data.outputs).buffers as *mut *mut f32).add(output_channel_idx);
-                            *output_channel_slice = std::slice::from_raw_parts_mut(
-                                channel_ptr.add(block_start),
-                                block_len,
-                            );
-                        }
-                    }
-                });
-
-                // Some hosts process data in place, in which case we don't need to do any copying
-                // ourselves. If the pointers do not alias, then we'll do the copy here and then the
-                // plugin can just do normal in place processing.
-                if !data.outputs.is_null()
-                    && !data.inputs.is_null()
-                    && has_main_input
-                    && has_main_output
-                {
-                    let num_output_channels = (*data.outputs).num_channels as usize;
-                    let num_input_channels = (*data.inputs).num_channels as usize;
-                    nih_debug_assert!(
-                        num_input_channels <= num_output_channels,
-                        "Stereo to mono and similar configurations are not supported"
-                    );
-                    for input_channel_idx in 0..cmp::min(num_input_channels, num_output_channels) {
-                        let output_channel_ptr =
-                            *((*data.outputs).buffers as *mut *mut f32).add(input_channel_idx);
-                        let input_channel_ptr =
-                            *((*data.inputs).buffers as *const *const f32).add(input_channel_idx);
-                        if input_channel_ptr != output_channel_ptr {
-                            ptr::copy_nonoverlapping(
-                                input_channel_ptr.add(block_start),
-                                output_channel_ptr.add(block_start),
-                                block_len,
-                            );
-                        }
-                    }
-                }
-
-                // We'll need to do the same thing for auxiliary input sidechain buffers. Since we
-                // don't know whether overwriting the host's buffers is safe here or not, we'll copy
-                // the data to our own buffers instead. These buffers are only accessible through
-                // the `aux` parameter on the `process()` function.
-                let mut aux_input_storage = self.inner.aux_input_storage.borrow_mut();
-                let mut aux_input_buffers = self.inner.aux_input_buffers.borrow_mut();
-                for (aux_input_idx, (storage, buffer)) in aux_input_storage
-                    .iter_mut()
-                    .zip(aux_input_buffers.iter_mut())
-                    .enumerate()
-                {
-                    let host_input_idx = aux_input_start_idx + aux_input_idx;
-                    let host_input = data.inputs.add(host_input_idx);
-                    if host_input_idx >= data.num_inputs as usize
-                             || data.inputs.is_null()
-                             || (*host_input).buffers.is_null()
-                             // Would only happen if the user configured zero channels for the
-                             // auxiliary buffers
-                             || storage.is_empty()
-                             || (*host_input).num_channels != buffer.channels() as i32
-                    {
-                        // During a parameter flush the number of inputs/outputs may be 0 and the
-                        // number of channels may be 0, so these assertions need to be a bit more
-                        // relaxed
-                        nih_debug_assert!(
-                            data.num_inputs == 0 || host_input_idx < data.num_inputs as usize
-                        );
-                        nih_debug_assert!(!storage.is_empty());
-                        if !data.inputs.is_null() && host_input_idx < data.num_inputs as usize {
-                            nih_debug_assert!(!(*host_input).buffers.is_null());
-                            nih_debug_assert!(
-                                (*host_input).num_channels == 0
-                                    || (*host_input).num_channels == buffer.channels() as i32
-                            );
-                        }
-
-                        // If the host passes weird data then we need to be very sure that there are
-                        // no dangling references to previous data
-                        buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
-                        continue;
-                    }
-
-                    // We'll always reuse the start of the buffer even of the current block is
-                    // shorter for cache locality reasons
-                    for (channel_idx, channel_storage) in storage.iter_mut().enumerate() {
-                        // The `set_len()` avoids having to unnecessarily fill the buffer with
-                        // zeroes when sizing up
-                        assert!(block_len <= channel_storage.capacity());
-                        channel_storage.set_len(block_len);
-                        channel_storage.copy_from_slice(std::slice::from_raw_parts(
-                            (*(*host_input).buffers.add(channel_idx)).add(block_start)
-                                as *const f32,
-                            block_len,
-                        ));
-                    }
-
-                    buffer.set_slices(block_len, |slices| {
-                        for (channel_slice, channel_storage) in
-                            slices.iter_mut().zip(storage.iter_mut())
-                        {
-                            // SAFETY: The 'static cast is required because Rust does not allow you
-                            //         to store references to a field in another field.  Because
-                            //         these slices are set here before the process function is
-                            //         called, we ensure that there are no dangling slices. These
-                            //         buffers/slices are only ever read from in the second part of
-                            //         this block process loop.
-                            *channel_slice = &mut *(channel_storage.as_mut_slice() as *mut [f32]);
-                        }
+                // MIDI messages that have been mapped to parameters are turned into parameter
+                // changes before the plugin gets to see them. The new values are also added to the
+                // output parameter changes so the host can record them as automation.
+                if self.inner.midi_learn.is_active() {
+                    let mut input_events = self.inner.input_events.borrow_mut();
+                    let mut output_param_changes = self.inner.output_param_changes.borrow_mut();
+                    input_events.retain(|event| {
+                        !self
+                            .inner
+                            .midi_learn
+                            .handle_event(event, |param_ptr, normalized| {
+                                if let Some(hash) = self.inner.param_ptr_to_hash.get(&param_ptr) {
+                                    self.inner.set_normalized_value_by_hash(
+                                        *hash,
+                                        normalized,
+                                        Some(sample_rate),
+                                    );
+                                    output_param_changes.push_back((*hash, normalized));
+                                }
+                            })
                     });
                 }
 
-                // And the same thing for auxiliary output buffers
-                let mut aux_output_buffers = self.inner.aux_output_buffers.borrow_mut();
-                for (aux_output_idx, buffer) in aux_output_buffers.iter_mut().enumerate() {
-                    let host_output_idx = aux_output_start_idx + aux_output_idx;
-                    let host_output = data.outputs.add(host_output_idx);
-                    if host_output_idx >= data.num_outputs as usize
-                        || data.outputs.is_null()
-                        || (*host_output).buffers.is_null()
-                        || buffer.channels() == 0
-                        || (*host_output).num_channels != buffer.channels() as i32
-                    {
-                        nih_debug_assert!(host_output_idx < data.num_outputs as usize);
-                        nih_debug_assert!(!data.outputs.is_null());
-                        if !data.outputs.is_null() && host_output_idx < data.num_outputs as usize {
-                            nih_debug_assert!(!(*host_output).buffers.is_null());
-                            nih_debug_assert!(
-                                !(*host_output).num_channels == 0
-                                    || !(*host_output).num_channels == buffer.channels() as i32
-                            );
-                        }
-
-                        // If the host passes weird data then we need to be very sure that there are
-                        // no dangling references to previous data
-                        buffer.set_slices(0, |slices| slices.fill_with(|| &mut []));
-                        continue;
-                    }
-
-                    buffer.set_slices(block_len, |slices| {
-                        for (channel_idx, channel_slice) in slices.iter_mut().enumerate() {
-                            *channel_slice = std::slice::from_raw_parts_mut(
-                                (*(*host_output).buffers.add(channel_idx)).add(block_start)
-                                    as *mut f32,
-                                block_len,
-                            );
-                        }
-                    });
-                }
+                let block_len = block_end - block_start;
 
                 // Some of the fields are left empty because VST3 does not provide this
                 // information, but the methods on [`Transport`] can reconstruct these values
@@ -1510,25 +1778,56 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
                     }
                 }
 
-                let result = if buffer_is_valid {
-                    // NOTE: `parking_lot`'s mutexes sometimes allocate because of their use of
-                    //       thread locals
-                    let mut plugin = permit_alloc(|| self.inner.plugin.lock());
-                    // SAFETY: Shortening these borrows is safe as even if the plugin overwrites the
-                    //         slices (which it cannot do without using unsafe code), then they
-                    //         would still be reset on the next iteration
-                    let mut aux = AuxiliaryBuffers {
-                        inputs: &mut *(aux_input_buffers.as_mut_slice() as *mut [Buffer]),
-                        outputs: &mut *(aux_output_buffers.as_mut_slice() as *mut [Buffer]),
-                    };
-                    let mut context = self.inner.make_process_context(transport);
-                    let result = plugin.process(&mut output_buffer, &mut aux, &mut context);
-                    self.inner.last_process_status.store(result);
-                    result
+                let result = if use_f64 {
+                    self.process_block(
+                        &self.inner.buffers_f64,
+                        data,
+                        block_start,
+                        block_len,
+                        transport,
+                    )
                 } else {
-                    ProcessStatus::Normal
+                    self.process_block(&self.inner.buffers, data, block_start, block_len, transport)
                 };
 
+                // Parameter changes made by the plugin are sent to the host at the start of the
+                // block, and they're only applied to the plugin's parameters now so the values
+                // don't change in the middle of processing
+                {
+                    let mut output_param_changes = self.inner.output_param_changes.borrow_mut();
+                    let host_param_changes = data.output_param_changes.upgrade();
+                    while let Some((param_hash, normalized_value)) =
+                        output_param_changes.pop_front()
+                    {
+                        self.inner.set_normalized_value_by_hash(
+                            param_hash,
+                            normalized_value,
+                            Some(sample_rate),
+                        );
+
+                        if let Some(host_param_changes) = &host_param_changes {
+                            let mut queue_idx = 0;
+                            match host_param_changes
+                                .add_parameter_data(&param_hash, &mut queue_idx)
+                                .upgrade()
+                            {
+                                Some(param_change_queue) => {
+                                    let mut point_idx = 0;
+                                    let result = param_change_queue.add_point(
+                                        block_start as i32,
+                                        normalized_value as f64,
+                                        &mut point_idx,
+                                    );
+                                    nih_debug_assert_eq!(result, kResultOk);
+                                }
+                                None => nih_debug_assert_failure!(
+                                    "The host did not provide an output parameter value queue"
+                                ),
+                            }
+                        }
+                    }
+                }
+
                 // Send any events output by the plugin during the process cycle
                 if let Some(events) = data.output_events.upgrade() {
                     let mut output_events = self.inner.output_events.borrow_mut();
@@ -1777,7 +2076,8 @@ impl<P: Vst3Plugin> IAudioProcessor for Wrapper<P> {
             //        doesn't do that
             let updated_state = permit_alloc(|| self.inner.updated_state_receiver.try_recv());
             if let Ok(mut state) = updated_state {
-                self.inner.set_state_inner(&mut state);
+                // These states always come from `set_state_object_from_gui()`
+                self.inner.set_state_inner(&mut state, StateContext::Preset);
 
                 // We'll pass the state object back to the GUI thread so deallocation can happen
                 // there without potentially blocking the audio thread
@@ -1904,6 +2204,62 @@ impl<P: Vst3Plugin> INoteExpressionController for Wrapper<P> {
     }
 }
 
+impl<P: Vst3Plugin> IKeyswitchController for Wrapper<P> {
+    unsafe fn get_keyswitch_count(&self, bus_idx: i32, channel: i16) -> i32 {
+        if P::MIDI_INPUT < MidiConfig::Basic || bus_idx != 0 {
+            return 0;
+        }
+
+        self.inner.with_note_names(|note_names| {
+            note_names
+                .iter()
+                .filter(|name| name.keyswitch && name.matches_channel(channel as u8))
+                .count() as i32
+        })
+    }
+
+    unsafe fn get_keyswitch_info(
+        &self,
+        bus_idx: i32,
+        channel: i16,
+        keyswitch_idx: i32,
+        info: *mut KeyswitchInfo,
+    ) -> tresult {
+        if P::MIDI_INPUT < MidiConfig::Basic || bus_idx != 0 || keyswitch_idx < 0 {
+            return kInvalidArgument;
+        }
+
+        check_null_ptr!(info);
+
+        self.inner.with_note_names(|note_names| {
+            let keyswitch = note_names
+                .iter()
+                .filter(|name| name.keyswitch && name.matches_channel(channel as u8))
+                .nth(keyswitch_idx as usize);
+
+            match keyswitch {
+                Some(keyswitch) => {
+                    *info = mem::zeroed();
+
+                    // Type ID 0 is `kNoteOnKeyswitchTypeID`, meaning that the keyswitch is
+                    // triggered by a note on event for the key
+                    let info = &mut *info;
+                    info.type_id = 0;
+                    u16strlcpy(&mut info.title, &keyswitch.name);
+                    u16strlcpy(&mut info.short_title, &keyswitch.name);
+                    info.keyswitch_min = keyswitch.note as i32;
+                    info.keyswitch_max = keyswitch.note as i32;
+                    info.key_remapped = keyswitch.note as i32;
+                    info.unit_id = kNoParentUnitId;
+
+                    kResultOk
+                }
+                None => kInvalidArgument,
+            }
+        })
+    }
+}
+
 impl<P: Vst3Plugin> IProcessContextRequirements for Wrapper<P> {
     unsafe fn get_process_context_requirements(&self) -> u32 {
         IProcessContextRequirementsFlags::kNeedProjectTimeMusic
@@ -1931,7 +2287,12 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
                 info.id = unit_id;
                 info.parent_unit_id = unit_info.parent_id;
                 u16strlcpy(&mut info.name, &unit_info.name);
-                info.program_list_id = kNoProgramListId;
+                // The factory presets or the note names are exposed as a program list on the
+                // root unit
+                info.program_list_id = match self.inner.program_list_id() {
+                    Some(program_list_id) if unit_id == kRootUnitId => program_list_id,
+                    _ => kNoProgramListId,
+                };
 
                 kResultOk
             }
@@ -1940,50 +2301,145 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
     }
 
     unsafe fn get_program_list_count(&self) -> i32 {
-        // TODO: Do we want program lists? Probably not, CLAP doesn't even support them.
-        0
+        // Program lists are only used for the plugin's factory presets and note names
+        match self.inner.program_list_id() {
+            Some(_) => 1,
+            None => 0,
+        }
     }
 
-    unsafe fn get_program_list_info(
-        &self,
-        _list_index: i32,
-        _info: *mut ProgramListInfo,
-    ) -> tresult {
-        kInvalidArgument
+    unsafe fn get_program_list_info(&self, list_index: i32, info: *mut ProgramListInfo) -> tresult {
+        check_null_ptr!(info);
+
+        let program_list_id = match self.inner.program_list_id() {
+            Some(program_list_id) if list_index == 0 => program_list_id,
+            _ => return kInvalidArgument,
+        };
+
+        *info = mem::zeroed();
+
+        let info = &mut *info;
+        info.id = program_list_id;
+        if program_list_id == VST3_FACTORY_PRESETS_PROGRAM_LIST_ID {
+            u16strlcpy(&mut info.name, "Factory Presets");
+            info.program_count = self.inner.factory_presets.len() as i32;
+        } else {
+            u16strlcpy(&mut info.name, "Note Names");
+            info.program_count = 1;
+        }
+
+        kResultOk
     }
 
-    unsafe fn get_program_name(
-        &self,
-        _list_id: i32,
-        _program_index: i32,
-        _name: *mut u16,
-    ) -> tresult {
-        kInvalidArgument
+    unsafe fn get_program_name(&self, list_id: i32, program_index: i32, name: *mut u16) -> tresult {
+        check_null_ptr!(name);
+
+        if Some(list_id) != self.inner.program_list_id() || program_index < 0 {
+            return kInvalidArgument;
+        }
+
+        // The note names program list only contains a single program
+        let program_name = if list_id == VST3_NOTE_NAMES_PROGRAM_LIST_ID {
+            (program_index == 0).then_some("Default")
+        } else {
+            self.inner
+                .factory_presets
+                .get(program_index as usize)
+                .map(|preset| preset.name.as_str())
+        };
+        match program_name {
+            Some(program_name) => {
+                u16strlcpy(&mut *(name as *mut [TChar; 128]), program_name);
+
+                kResultOk
+            }
+            None => kInvalidArgument,
+        }
     }
 
     unsafe fn get_program_info(
         &self,
-        _list_id: i32,
-        _program_index: i32,
-        _attribute_id: *const u8,
-        _attribute_value: *mut u16,
+        list_id: i32,
+        program_index: i32,
+        attribute_id: *const u8,
+        attribute_value: *mut u16,
     ) -> tresult {
-        kInvalidArgument
+        check_null_ptr!(attribute_id, attribute_value);
+
+        if list_id != VST3_FACTORY_PRESETS_PROGRAM_LIST_ID || program_index < 0 {
+            return kInvalidArgument;
+        }
+
+        let preset = match self.inner.factory_presets.get(program_index as usize) {
+            Some(preset) => preset,
+            None => return kInvalidArgument,
+        };
+
+        // These are the attribute IDs from `Steinberg::Vst::PresetAttributes`. The preset's
+        // category is exposed as the musical instrument attribute.
+        let value = match CStr::from_ptr(attribute_id as *const c_char).to_bytes() {
+            b"Name" => preset.name.as_str(),
+            b"MusicalInstrument" => match &preset.category {
+                Some(category) => category.as_str(),
+                None => return kResultFalse,
+            },
+            _ => return kResultFalse,
+        };
+        u16strlcpy(&mut *(attribute_value as *mut [TChar; 128]), value);
+
+        kResultOk
     }
 
-    unsafe fn has_program_pitch_names(&self, _id: i32, _index: i32) -> tresult {
-        // TODO: Support note names once someone requests it
-        kInvalidArgument
+    unsafe fn has_program_pitch_names(&self, list_id: i32, program_index: i32) -> tresult {
+        // VST3 only has pitch names for programs, so the same names are used for all programs in
+        // the root unit's program list. Program pitch names don't have a channel, so we'll use the
+        // names for the first channel.
+        if P::MIDI_INPUT < MidiConfig::Basic
+            || Some(list_id) != self.inner.program_list_id()
+            || program_index < 0
+        {
+            return kInvalidArgument;
+        }
+
+        let has_pitch_names = self
+            .inner
+            .with_note_names(|note_names| note_names.iter().any(|name| name.matches_channel(0)));
+        if has_pitch_names {
+            kResultOk
+        } else {
+            kResultFalse
+        }
     }
 
     unsafe fn get_program_pitch_name(
         &self,
-        _id: i32,
-        _index: i32,
-        _pitch: i16,
-        _name: *mut u16,
+        list_id: i32,
+        program_index: i32,
+        pitch: i16,
+        name: *mut u16,
     ) -> tresult {
-        kInvalidArgument
+        check_null_ptr!(name);
+
+        if P::MIDI_INPUT < MidiConfig::Basic
+            || Some(list_id) != self.inner.program_list_id()
+            || program_index < 0
+        {
+            return kInvalidArgument;
+        }
+
+        self.inner.with_note_names(|note_names| {
+            match note_names
+                .iter()
+                .find(|note_name| note_name.note as i16 == pitch && note_name.matches_channel(0))
+            {
+                Some(note_name) => {
+                    u16strlcpy(&mut *(name as *mut [TChar; 128]), &note_name.name);
+
+                    kResultOk
+                }
+                None => kResultFalse,
+            }
+        })
     }
 
     unsafe fn get_selected_unit(&self) -> i32 {
@@ -2016,3 +2472,27 @@ impl<P: Vst3Plugin> IUnitInfo for Wrapper<P> {
         kInvalidArgument
     }
 }
+
+impl<P: Vst3Plugin> IInfoListener for Wrapper<P> {
+    unsafe fn set_channel_context_infos(&self, list: SharedVstPtr<dyn IAttributeList>) -> tresult {
+        check_null_ptr!(list);
+
+        let list = list.upgrade().unwrap();
+        {
+            let mut track_info = self.inner.track_info.lock();
+            channel_context::update_track_info(
+                track_info.get_or_insert_with(Default::default),
+                &*list,
+            );
+        }
+
+        // This is called from the UI thread, so the editor can be notified immediately
+        if self.inner.plug_view.read().is_some() {
+            if let Some(editor) = self.inner.editor.borrow().as_ref() {
+                editor.lock().track_info_changed();
+            }
+        }
+
+        kResultOk
+    }
+}